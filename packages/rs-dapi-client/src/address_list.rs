@@ -2,22 +2,89 @@
 
 use chrono::Utc;
 use dapi_grpc::tonic::transport::Uri;
-use rand::{rngs::SmallRng, seq::IteratorRandom, SeedableRng};
 use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 
+use crate::address_selector::{AddressSelector, RandomSelector};
+use crate::circuit_breaker::{CircuitBreaker, CircuitPermit, CircuitState};
+use crate::request_settings::AppliedRequestSettings;
+
 const DEFAULT_BASE_BAN_PERIOD: Duration = Duration::from_secs(60);
 
+/// Weight of the newest sample in exponentially weighted moving averages of [AddressStats].
+const EWMA_ALPHA: f64 = 0.2;
+
+/// Health statistics of a DAPI address, updated after every request sent to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressStats {
+    /// Number of requests the node responded to.
+    pub success_count: u64,
+    /// Number of requests that failed because of the node, like timeouts or unavailability.
+    pub failure_count: u64,
+    /// Number of failures since the last successful request.
+    pub consecutive_failures: u64,
+    /// Exponentially weighted moving average of response time; `None` until the node responds.
+    pub ewma_latency: Option<Duration>,
+    /// Exponentially weighted moving average of the success rate, between 0.0 and 1.0.
+    pub ewma_success_rate: f64,
+    /// Time of the last successful request.
+    pub last_success: Option<chrono::DateTime<Utc>>,
+    /// Time of the last failed request.
+    pub last_failure: Option<chrono::DateTime<Utc>>,
+}
+
+impl Default for AddressStats {
+    fn default() -> Self {
+        Self {
+            success_count: 0,
+            failure_count: 0,
+            consecutive_failures: 0,
+            ewma_latency: None,
+            // new nodes are assumed healthy until proven otherwise
+            ewma_success_rate: 1.0,
+            last_success: None,
+            last_failure: None,
+        }
+    }
+}
+
+impl AddressStats {
+    /// Record a response received after `latency`.
+    pub fn record_success(&mut self, latency: Duration) {
+        self.success_count += 1;
+        self.consecutive_failures = 0;
+        self.ewma_latency = Some(match self.ewma_latency {
+            Some(ewma) => ewma.mul_f64(1.0 - EWMA_ALPHA) + latency.mul_f64(EWMA_ALPHA),
+            None => latency,
+        });
+        self.ewma_success_rate = self.ewma_success_rate * (1.0 - EWMA_ALPHA) + EWMA_ALPHA;
+        self.last_success = Some(Utc::now());
+    }
+
+    /// Record a failure of the node.
+    pub fn record_failure(&mut self) {
+        self.failure_count += 1;
+        self.consecutive_failures += 1;
+        self.ewma_success_rate *= 1.0 - EWMA_ALPHA;
+        self.last_failure = Some(Utc::now());
+    }
+}
+
 /// DAPI address.
-#[derive(Debug, Clone, Eq)]
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "mocks", derive(serde::Serialize, serde::Deserialize))]
 pub struct Address {
     ban_count: usize,
     banned_until: Option<chrono::DateTime<Utc>>,
     #[cfg_attr(feature = "mocks", serde(with = "http_serde::uri"))]
     uri: Uri,
+    #[cfg_attr(feature = "mocks", serde(skip))]
+    stats: AddressStats,
+    #[cfg_attr(feature = "mocks", serde(skip))]
+    circuit: CircuitBreaker,
 }
 
 impl PartialEq<Self> for Address {
@@ -26,6 +93,8 @@ impl PartialEq<Self> for Address {
     }
 }
 
+impl Eq for Address {}
+
 impl PartialEq<Uri> for Address {
     fn eq(&self, other: &Uri) -> bool {
         self.uri == *other
@@ -44,6 +113,8 @@ impl From<Uri> for Address {
             ban_count: 0,
             banned_until: None,
             uri,
+            stats: AddressStats::default(),
+            circuit: CircuitBreaker::default(),
         }
     }
 }
@@ -73,6 +144,16 @@ impl Address {
     pub fn uri(&self) -> &Uri {
         &self.uri
     }
+
+    /// Get health statistics of a node.
+    pub fn stats(&self) -> &AddressStats {
+        &self.stats
+    }
+
+    /// Get state of the circuit breaker of a node.
+    pub fn circuit_state(&self) -> CircuitState {
+        self.circuit.state()
+    }
 }
 
 /// [AddressList] errors
@@ -89,6 +170,11 @@ pub enum AddressListError {
 pub struct AddressList {
     addresses: HashSet<Address>,
     base_ban_period: Duration,
+    selector: Arc<dyn AddressSelector>,
+    /// Addresses kept by [AddressList::replace_uris()], see [AddressList::set_fallback_uris()]
+    fallback: HashSet<Uri>,
+    /// Addresses set by the last [AddressList::replace_uris()]
+    replaced: HashSet<Uri>,
 }
 
 impl Default for AddressList {
@@ -114,35 +200,97 @@ impl AddressList {
         AddressList {
             addresses: HashSet::new(),
             base_ban_period,
+            selector: Arc::new(RandomSelector),
+            fallback: HashSet::new(),
+            replaced: HashSet::new(),
         }
     }
 
-    /// Bans address
-    pub(crate) fn ban_address(&mut self, address: &Address) -> Result<(), AddressListError> {
-        if !self.addresses.remove(address) {
+    /// Use provided strategy to select addresses in [AddressList::get_live_address].
+    ///
+    /// Defaults to [RandomSelector].
+    pub fn with_selector<S: AddressSelector + 'static>(mut self, selector: S) -> Self {
+        self.selector = Arc::new(selector);
+        self
+    }
+
+    /// Update the stored copy of the address with `update`.
+    fn update_address<F: FnOnce(&mut Address)>(
+        &mut self,
+        address: &Address,
+        update: F,
+    ) -> Result<(), AddressListError> {
+        let Some(mut stored) = self.addresses.take(address) else {
             return Err(AddressListError::AddressNotFound(address.uri.clone()));
         };
 
-        let mut banned_address = address.clone();
-        banned_address.ban(&self.base_ban_period);
-
-        self.addresses.insert(banned_address);
+        update(&mut stored);
+        self.addresses.insert(stored);
 
         Ok(())
     }
 
+    /// Bans address
+    pub(crate) fn ban_address(&mut self, address: &Address) -> Result<(), AddressListError> {
+        let base_ban_period = self.base_ban_period;
+        self.update_address(address, |address| address.ban(&base_ban_period))
+    }
+
     /// Clears address' ban record
     pub(crate) fn unban_address(&mut self, address: &Address) -> Result<(), AddressListError> {
-        if !self.addresses.remove(address) {
-            return Err(AddressListError::AddressNotFound(address.uri.clone()));
-        };
+        self.update_address(address, Address::unban)
+    }
 
-        let mut unbanned_address = address.clone();
-        unbanned_address.unban();
+    /// Starts a request to the address; if its circuit is not closed, the request becomes the probe.
+    ///
+    /// Returns `None` if the circuit doesn't allow the request.
+    pub(crate) fn acquire_circuit(
+        &mut self,
+        address: &Address,
+        settings: &AppliedRequestSettings,
+    ) -> Result<Option<CircuitPermit>, AddressListError> {
+        let mut permit = None;
+        self.update_address(address, |address| {
+            permit = address.circuit.acquire(settings.circuit_open_duration);
+        })?;
+
+        Ok(permit)
+    }
 
-        self.addresses.insert(unbanned_address);
+    /// Gives up a request to the address that was not sent, releasing its circuit probe.
+    pub(crate) fn release_circuit(
+        &mut self,
+        address: &Address,
+        permit: CircuitPermit,
+    ) -> Result<(), AddressListError> {
+        self.update_address(address, |address| address.circuit.release(permit))
+    }
 
-        Ok(())
+    /// Records a response from the address received after `latency`.
+    pub(crate) fn record_success(
+        &mut self,
+        address: &Address,
+        latency: Duration,
+    ) -> Result<(), AddressListError> {
+        self.update_address(address, |address| {
+            address.stats.record_success(latency);
+            address.circuit.record_success();
+        })
+    }
+
+    /// Records a failure of the address.
+    pub(crate) fn record_failure(
+        &mut self,
+        address: &Address,
+        settings: &AppliedRequestSettings,
+    ) -> Result<(), AddressListError> {
+        self.update_address(address, |address| {
+            address.stats.record_failure();
+            address.circuit.record_failure(
+                settings.address_failure_threshold,
+                settings.circuit_open_duration,
+            );
+        })
     }
 
     /// Adds a node [Address] to [AddressList]
@@ -160,30 +308,96 @@ impl AddressList {
         self.addresses.insert(uri.into())
     }
 
-    /// Randomly select a not banned address.
+    /// Replace addresses in the list with `uris`.
+    ///
+    /// Addresses already in the list keep their ban status and health statistics; addresses not
+    /// present in `uris` are removed, unless they are [fallback](AddressList::set_fallback_uris())
+    /// addresses.
+    pub fn replace_uris<I: IntoIterator<Item = Uri>>(&mut self, uris: I) {
+        let uris: HashSet<Uri> = uris.into_iter().collect();
+
+        self.addresses
+            .retain(|address| uris.contains(&address.uri) || self.fallback.contains(&address.uri));
+        for uri in uris.iter().cloned() {
+            self.add_uri(uri);
+        }
+        self.replaced = uris;
+    }
+
+    /// Add fallback addresses, like seed addresses used to discover other ones.
+    ///
+    /// Fallback addresses are never removed by [AddressList::replace_uris()], and are selected
+    /// only when no other address is live, unless they are also present in the replaced addresses.
+    pub fn set_fallback_uris<I: IntoIterator<Item = Uri>>(&mut self, uris: I) {
+        for uri in uris {
+            self.add_uri(uri.clone());
+            self.fallback.insert(uri);
+        }
+    }
+
+    /// Select a not banned address with circuit that is not open, using configured [AddressSelector].
     pub fn get_live_address(&self) -> Option<&Address> {
-        let mut rng = SmallRng::from_entropy();
+        self.selector.select(&self.live())
+    }
+
+    /// Select a live address that is not in `excluded`, using configured [AddressSelector].
+    ///
+    /// Falls back to any live address if all of them are excluded.
+    pub fn get_live_address_excluding(&self, excluded: &HashSet<Address>) -> Option<&Address> {
+        let live = self.live();
+        let candidates: Vec<&Address> = live
+            .iter()
+            .filter(|address| !excluded.contains(**address))
+            .copied()
+            .collect();
+
+        if candidates.is_empty() {
+            self.selector.select(&live)
+        } else {
+            self.selector.select(&candidates)
+        }
+    }
 
-        self.unbanned().into_iter().choose(&mut rng)
+    /// Get all addresses, both banned and not banned, together with their health statistics.
+    pub fn addresses(&self) -> impl Iterator<Item = &Address> {
+        self.addresses.iter()
     }
 
-    /// Get all addresses that are not banned.
-    fn unbanned(&self) -> Vec<&Address> {
+    /// Get all addresses that are not banned and whose circuit allows requests.
+    ///
+    /// Fallback addresses are only returned when no other address is live.
+    fn live(&self) -> Vec<&Address> {
         let now = chrono::Utc::now();
 
-        self.addresses
+        let live: Vec<&Address> = self
+            .addresses
             .iter()
             .filter(|addr| {
                 addr.banned_until
                     .map(|banned_until| banned_until < now)
                     .unwrap_or(true)
             })
-            .collect()
+            .filter(|addr| addr.circuit.is_available())
+            .collect();
+
+        let preferred: Vec<&Address> = live
+            .iter()
+            .filter(|addr| !self.fallback.contains(&addr.uri) || self.replaced.contains(&addr.uri))
+            .copied()
+            .collect();
+
+        if preferred.is_empty() {
+            live
+        } else {
+            preferred
+        }
     }
 
-    /// Get number of available, not banned addresses.
+    /// Get number of available addresses, that are not banned and whose circuit allows requests.
+    ///
+    /// Fallback addresses are only counted when no other address is live.
     pub fn available(&self) -> usize {
-        self.unbanned().len()
+        self.live().len()
     }
 
     /// Get number of all addresses, both banned and not banned.
@@ -0,0 +1,101 @@
+//! Strategies of selecting DAPI addresses for requests.
+
+use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::Address;
+
+/// Latency assumed for addresses that didn't respond yet, if no address responded yet.
+const DEFAULT_LATENCY: Duration = Duration::from_millis(100);
+/// Lowest success rate taken into account, so that failing nodes still get a chance to recover.
+const MIN_SUCCESS_RATE: f64 = 0.01;
+
+/// Strategy of selecting an address for a request from the [AddressList](crate::AddressList).
+pub trait AddressSelector: Debug + Send + Sync {
+    /// Select one of `addresses`, which are all live (not banned).
+    ///
+    /// Returns `None` if `addresses` is empty.
+    fn select<'a>(&self, addresses: &[&'a Address]) -> Option<&'a Address>;
+}
+
+/// Select a random address; this is the default strategy.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RandomSelector;
+
+impl AddressSelector for RandomSelector {
+    fn select<'a>(&self, addresses: &[&'a Address]) -> Option<&'a Address> {
+        let mut rng = SmallRng::from_entropy();
+
+        addresses.choose(&mut rng).copied()
+    }
+}
+
+/// Select addresses one after another, in the order of their URIs.
+#[derive(Debug, Default)]
+pub struct RoundRobinSelector {
+    next: AtomicUsize,
+}
+
+impl RoundRobinSelector {
+    /// Create a new round-robin selector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AddressSelector for RoundRobinSelector {
+    fn select<'a>(&self, addresses: &[&'a Address]) -> Option<&'a Address> {
+        if addresses.is_empty() {
+            return None;
+        }
+
+        // addresses are stored in a hash set, so we need some stable order
+        let mut sorted = addresses.to_vec();
+        sorted.sort_by_cached_key(|address| address.uri().to_string());
+
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % sorted.len();
+
+        Some(sorted[index])
+    }
+}
+
+/// Select addresses randomly, preferring fast and reliable ones.
+///
+/// Probability of selecting an address is proportional to its success rate divided by its latency,
+/// both being exponentially weighted moving averages from [AddressStats](crate::AddressStats).
+/// Addresses that didn't respond yet are assumed to be as fast as the fastest known one, so that
+/// they get a chance to be measured.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WeightedSelector;
+
+impl WeightedSelector {
+    /// Create a new weighted selector.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl AddressSelector for WeightedSelector {
+    fn select<'a>(&self, addresses: &[&'a Address]) -> Option<&'a Address> {
+        let fastest = addresses
+            .iter()
+            .filter_map(|address| address.stats().ewma_latency)
+            .min()
+            .unwrap_or(DEFAULT_LATENCY);
+
+        let weight = |address: &&'a Address| {
+            let stats = address.stats();
+            let latency = stats.ewma_latency.unwrap_or(fastest);
+            // avoid division by zero for extremely fast (or mocked) nodes
+            let latency_secs = latency.as_secs_f64().max(f64::EPSILON);
+
+            stats.ewma_success_rate.max(MIN_SUCCESS_RATE) / latency_secs
+        };
+
+        let mut rng = SmallRng::from_entropy();
+
+        addresses.choose_weighted(&mut rng, weight).ok().copied()
+    }
+}
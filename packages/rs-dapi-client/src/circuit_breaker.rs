@@ -0,0 +1,141 @@
+//! Circuit breakers that stop sending requests to failing DAPI nodes.
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// State of a circuit breaker.
+///
+/// Each [Address](crate::Address) has its own circuit breaker, and [DapiClient](crate::DapiClient)
+/// has a global one shared by all requests. A circuit opens after
+/// [RequestSettings::address_failure_threshold](crate::RequestSettings::address_failure_threshold)
+/// (or [RequestSettings::global_failure_threshold](crate::RequestSettings::global_failure_threshold))
+/// consecutive node failures. While open, requests are not sent at all. After
+/// [RequestSettings::circuit_open_duration](crate::RequestSettings::circuit_open_duration), the
+/// circuit becomes half-open and lets a single probe request through: if it succeeds, the circuit
+/// closes, otherwise it opens again.
+///
+/// Circuit breakers are disabled unless these thresholds are set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent normally.
+    Closed,
+    /// Requests are rejected without being sent.
+    Open,
+    /// A single probe request is allowed to check if the node recovered.
+    HalfOpen,
+}
+
+/// Permission to send a request, granted by [CircuitBreaker::acquire()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CircuitPermit {
+    /// The circuit is closed, and the request is sent normally.
+    Closed,
+    /// The request is the probe of a half-open circuit. Its outcome must be recorded, or the probe
+    /// [released](CircuitBreaker::release()) if the request was not sent.
+    Probe,
+}
+
+/// Circuit breaker state machine.
+#[derive(Debug, Clone)]
+pub(crate) struct CircuitBreaker {
+    state: CircuitState,
+    consecutive_failures: usize,
+    /// Until when requests are rejected; in half-open state, it's the deadline of the probe request,
+    /// after which another probe is allowed.
+    open_until: Option<Instant>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            open_until: None,
+        }
+    }
+}
+
+impl CircuitBreaker {
+    /// Current state of the circuit.
+    ///
+    /// Open circuit is reported as half-open once the open duration elapsed, as the next request
+    /// will be a probe.
+    pub(crate) fn state(&self) -> CircuitState {
+        match self.state {
+            CircuitState::Open if self.is_available() => CircuitState::HalfOpen,
+            state => state,
+        }
+    }
+
+    /// Check if a request can be sent now.
+    pub(crate) fn is_available(&self) -> bool {
+        match self.state {
+            CircuitState::Closed => true,
+            CircuitState::Open | CircuitState::HalfOpen => self
+                .open_until
+                .map(|open_until| open_until <= Instant::now())
+                .unwrap_or(true),
+        }
+    }
+
+    /// Start a request; returns `None` if the circuit doesn't allow it.
+    ///
+    /// When the circuit is not closed, the request becomes the probe, and other requests are
+    /// rejected until it completes, is released or `open_duration` elapses.
+    pub(crate) fn acquire(&mut self, open_duration: Duration) -> Option<CircuitPermit> {
+        if !self.is_available() {
+            return None;
+        }
+
+        if self.state == CircuitState::Closed {
+            return Some(CircuitPermit::Closed);
+        }
+
+        self.state = CircuitState::HalfOpen;
+        self.open_until = Some(Instant::now() + open_duration);
+
+        Some(CircuitPermit::Probe)
+    }
+
+    /// Give up a request that was not sent, like a cancelled one, without recording its outcome.
+    ///
+    /// If it was the probe, another request can probe the node right away.
+    pub(crate) fn release(&mut self, permit: CircuitPermit) {
+        if permit == CircuitPermit::Probe && self.state == CircuitState::HalfOpen {
+            self.open_until = None;
+        }
+    }
+
+    /// Record a response from the node, closing the circuit.
+    pub(crate) fn record_success(&mut self) {
+        if self.state != CircuitState::Closed {
+            tracing::debug!("circuit closed");
+        }
+
+        self.state = CircuitState::Closed;
+        self.consecutive_failures = 0;
+        self.open_until = None;
+    }
+
+    /// Record a failure of the node.
+    ///
+    /// The circuit opens for `open_duration` after `threshold` consecutive failures, or when the
+    /// probe request failed; `threshold` of 0 never opens it.
+    pub(crate) fn record_failure(&mut self, threshold: usize, open_duration: Duration) {
+        self.consecutive_failures += 1;
+
+        let probe_failed = self.state == CircuitState::HalfOpen;
+        let threshold_reached = threshold > 0 && self.consecutive_failures >= threshold;
+
+        if probe_failed || (self.state == CircuitState::Closed && threshold_reached) {
+            tracing::debug!(
+                consecutive_failures = self.consecutive_failures,
+                "circuit opened for {} secs",
+                open_duration.as_secs_f32()
+            );
+
+            self.state = CircuitState::Open;
+            self.open_until = Some(Instant::now() + open_duration);
+        }
+    }
+}
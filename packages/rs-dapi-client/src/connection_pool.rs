@@ -36,6 +36,20 @@ impl ConnectionPool {
     }
 }
 
+impl ConnectionPool {
+    /// Change capacity of the pool, evicting least recently used items if it shrinks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    pub fn resize(&self, capacity: usize) {
+        self.inner
+            .lock()
+            .expect("must lock")
+            .resize(capacity.try_into().expect("must be non-zero"));
+    }
+}
+
 impl Default for ConnectionPool {
     fn default() -> Self {
         Self::new(50)
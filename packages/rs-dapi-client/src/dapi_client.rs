@@ -3,16 +3,23 @@
 use backon::{ExponentialBuilder, Retryable};
 use dapi_grpc::mock::Mockable;
 use dapi_grpc::tonic::async_trait;
+use dapi_grpc::tonic::transport::Uri;
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::collections::HashSet;
 use std::fmt::Debug;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
 use tracing::Instrument;
 
 use crate::address_list::AddressListError;
+use crate::circuit_breaker::{CircuitBreaker, CircuitPermit};
 use crate::connection_pool::ConnectionPool;
+use crate::multi_node::{AcceptAny, QuorumTracker, ResponseVerifier, VerifiedRequestError};
+use crate::rate_limiter::RateLimiter;
+use crate::request_settings::AppliedRequestSettings;
 use crate::{
     transport::{TransportClient, TransportRequest},
-    Address, AddressList, CanRetry, RequestSettings,
+    Address, AddressList, CanRetry, CircuitState, RequestSettings,
 };
 
 /// General DAPI request error type.
@@ -31,6 +38,17 @@ pub enum DapiClientError<TE: Mockable> {
     /// [AddressListError] errors
     #[error("address list error: {0}")]
     AddressList(AddressListError),
+    /// Not enough matching responses were received to reach the quorum.
+    #[error("no quorum: {matching} of {required} required responses match")]
+    NoQuorum {
+        /// Highest number of matching responses received
+        matching: usize,
+        /// Number of matching responses required
+        required: usize,
+    },
+    /// Global circuit breaker is open after too many node failures; see [CircuitState].
+    #[error("circuit breaker is open, requests are suspended")]
+    CircuitOpen,
 
     #[cfg(feature = "mocks")]
     #[error("mock error: {0}")]
@@ -45,6 +63,8 @@ impl<TE: CanRetry + Mockable> CanRetry for DapiClientError<TE> {
             NoAvailableAddresses => false,
             Transport(transport_error, _) => transport_error.is_node_failure(),
             AddressList(_) => false,
+            NoQuorum { .. } => false,
+            CircuitOpen => false,
             #[cfg(feature = "mocks")]
             Mock(_) => false,
         }
@@ -94,29 +114,278 @@ pub struct DapiClient {
     address_list: Arc<RwLock<AddressList>>,
     settings: RequestSettings,
     pool: ConnectionPool,
+    /// Global circuit breaker, shared by clones of the client
+    circuit_breaker: Arc<Mutex<CircuitBreaker>>,
+    /// Token bucket used to enforce [RequestSettings::rate_limit], shared by clones of the client
+    rate_limiter: Arc<RateLimiter>,
     #[cfg(feature = "dump")]
     pub(crate) dump_dir: Option<std::path::PathBuf>,
+    #[cfg(feature = "dump")]
+    pub(crate) session_recorder: Option<crate::session::SessionRecorder>,
 }
 
 impl DapiClient {
     /// Initialize new [DapiClient] and optionally override default settings.
-    pub fn new(address_list: AddressList, settings: RequestSettings) -> Self {
-        // multiply by 3 as we need to store core and platform addresses, and we want some spare capacity just in case
-        let address_count = 3 * address_list.len();
+    ///
+    /// Provided addresses are kept as a fallback when addresses are replaced with
+    /// [DapiClient::update_addresses()].
+    pub fn new(mut address_list: AddressList, settings: RequestSettings) -> Self {
+        let seeds: Vec<Uri> = address_list
+            .addresses()
+            .map(|address| address.uri().clone())
+            .collect();
+        address_list.set_fallback_uris(seeds);
+
+        let pool = ConnectionPool::new(Self::pool_capacity(&address_list));
 
         Self {
             address_list: Arc::new(RwLock::new(address_list)),
             settings,
-            pool: ConnectionPool::new(address_count),
+            pool,
+            circuit_breaker: Arc::new(Mutex::new(CircuitBreaker::default())),
+            rate_limiter: Arc::new(RateLimiter::default()),
             #[cfg(feature = "dump")]
             dump_dir: None,
+            #[cfg(feature = "dump")]
+            session_recorder: None,
         }
     }
+
+    /// Replace DAPI addresses used by the client, for example with ones discovered from the
+    /// masternode list; see [AddressList::replace_uris()].
+    ///
+    /// Addresses the client was created with are kept as a fallback, used only when none of the new
+    /// addresses is live. Connection pool is resized to fit the new list.
+    pub fn update_addresses<I: IntoIterator<Item = Uri>>(&self, uris: I) {
+        let mut address_list = self
+            .address_list
+            .write()
+            .expect("can't get address list for write");
+
+        address_list.replace_uris(uris);
+        self.pool.resize(Self::pool_capacity(&address_list));
+    }
+
+    /// Capacity of the connection pool for the address list.
+    fn pool_capacity(address_list: &AddressList) -> usize {
+        // multiply by 3 as we need to store core and platform addresses, and we want some spare capacity just in case
+        (3 * address_list.len()).max(1)
+    }
+
+    /// Get all DAPI addresses with their ban status and health statistics, for diagnostics.
+    pub fn addresses(&self) -> Vec<Address> {
+        self.address_list
+            .read()
+            .expect("can't get address list for read")
+            .addresses()
+            .cloned()
+            .collect()
+    }
+
+    /// Get state of the global circuit breaker.
+    ///
+    /// See [Address::circuit_state()] for circuit breakers of individual addresses.
+    pub fn circuit_state(&self) -> CircuitState {
+        self.circuit_breaker
+            .lock()
+            .expect("can't get circuit breaker lock")
+            .state()
+    }
+
+    /// Record a failure of the node that happened outside of request execution, like a broken
+    /// stream, banning the address if configured.
+    pub(crate) fn record_node_failure(&self, address: &Address, settings: &AppliedRequestSettings) {
+        let mut address_list = self
+            .address_list
+            .write()
+            .expect("can't get address list for write");
+
+        record_address_stats(address_list.record_failure(address, settings));
+        if settings.ban_failed_address {
+            record_address_stats(address_list.ban_address(address));
+        }
+        drop(address_list);
+
+        self.record_circuit(true, settings);
+    }
+
+    /// Ban the node that sent invalid data, regardless of [RequestSettings::ban_failed_address].
+    ///
+    /// Invalid data is a failure of this node only, so the global circuit breaker is not affected.
+    pub(crate) fn ban_node(&self, address: &Address, settings: &AppliedRequestSettings) {
+        let mut address_list = self
+            .address_list
+            .write()
+            .expect("can't get address list for write");
+
+        record_address_stats(address_list.record_failure(address, settings));
+        record_address_stats(address_list.ban_address(address));
+    }
+
+    /// Join settings of different sources to get final version of the settings for execution of `R`.
+    ///
+    /// Requests that are not [idempotent](TransportRequest::IDEMPOTENT) are never sent to more than
+    /// one node at a time.
+    pub(crate) fn apply_settings<R: TransportRequest>(
+        &self,
+        settings: RequestSettings,
+    ) -> AppliedRequestSettings {
+        let mut applied = self
+            .settings
+            .override_by(R::SETTINGS_OVERRIDES)
+            .override_by(settings)
+            .finalize();
+
+        if !R::IDEMPOTENT {
+            applied.fan_out = 1;
+            applied.quorum = 1;
+        }
+
+        applied
+    }
+
+    /// Select a live address, avoiding `used_addresses` if possible, and start a request to it,
+    /// subject to the global circuit breaker and the one of the address.
+    fn select_address<TE: Mockable>(
+        &self,
+        used_addresses: &HashSet<Address>,
+        settings: &AppliedRequestSettings,
+    ) -> Result<(Address, CircuitProbes<'_>), DapiClientError<TE>> {
+        loop {
+            let address = self
+                .address_list
+                .read()
+                .expect("can't get address list for read")
+                .get_live_address_excluding(used_addresses)
+                .cloned()
+                .ok_or(DapiClientError::NoAvailableAddresses)?;
+
+            // An address that is no longer available is not live anymore, so another one is
+            // selected next time
+            if let Some(probes) = self.acquire_circuits(&address, settings)? {
+                return Ok((address, probes));
+            }
+        }
+    }
+
+    /// Start a request to `address`, subject to the global circuit breaker and the one of the
+    /// address.
+    ///
+    /// Returns `None` if the address is no longer available, as another request took the probe of
+    /// its circuit, or it was removed from the list since it was selected.
+    fn acquire_circuits<TE: Mockable>(
+        &self,
+        address: &Address,
+        settings: &AppliedRequestSettings,
+    ) -> Result<Option<CircuitProbes<'_>>, DapiClientError<TE>> {
+        let global = self
+            .circuit_breaker
+            .lock()
+            .expect("can't get circuit breaker lock")
+            .acquire(settings.circuit_open_duration)
+            .ok_or(DapiClientError::CircuitOpen)?;
+
+        let mut probes = CircuitProbes {
+            client: self,
+            global: Some(global),
+            address: None,
+        };
+
+        let acquired = self
+            .address_list
+            .write()
+            .expect("can't get address list for write")
+            .acquire_circuit(address, settings);
+        let permit = match acquired {
+            Ok(Some(permit)) => permit,
+            Ok(None) => {
+                tracing::trace!(%address, "probe of the address taken by another request");
+                return Ok(None);
+            }
+            Err(error) => {
+                tracing::trace!(?error, "address removed from the list");
+                return Ok(None);
+            }
+        };
+        probes.address = Some((address.clone(), permit));
+
+        Ok(Some(probes))
+    }
+
+    /// Record result of a request in the global circuit breaker.
+    fn record_circuit(&self, node_failure: bool, settings: &AppliedRequestSettings) {
+        let mut circuit_breaker = self
+            .circuit_breaker
+            .lock()
+            .expect("can't get circuit breaker lock");
+
+        if node_failure {
+            circuit_breaker.record_failure(
+                settings.global_failure_threshold,
+                settings.circuit_open_duration,
+            );
+        } else {
+            circuit_breaker.record_success();
+        }
+    }
+}
+
+/// Circuit breaker permits of a request in flight.
+///
+/// Probes of half-open circuits are released on drop, unless the outcome of the request was
+/// recorded, so that a request cancelled or failed before it was sent doesn't block its circuits.
+struct CircuitProbes<'a> {
+    client: &'a DapiClient,
+    global: Option<CircuitPermit>,
+    address: Option<(Address, CircuitPermit)>,
+}
+
+impl CircuitProbes<'_> {
+    /// Mark the probes as resolved, as the outcome of the request is recorded by the caller.
+    fn resolve(mut self) {
+        self.global = None;
+        self.address = None;
+    }
+}
+
+impl Drop for CircuitProbes<'_> {
+    fn drop(&mut self) {
+        if let Some(permit) = self.global.take() {
+            self.client
+                .circuit_breaker
+                .lock()
+                .expect("can't get circuit breaker lock")
+                .release(permit);
+        }
+
+        if let Some((address, permit)) = self.address.take() {
+            record_address_stats(
+                self.client
+                    .address_list
+                    .write()
+                    .expect("can't get address list for write")
+                    .release_circuit(&address, permit),
+            );
+        }
+    }
+}
+
+/// Log failure to update address statistics.
+///
+/// Statistics are best-effort, so failure to update them (e.g. because the address was removed
+/// from the list in the meantime) doesn't fail the request.
+fn record_address_stats(result: Result<(), AddressListError>) {
+    if let Err(error) = result {
+        tracing::debug!(?error, "unable to update address statistics");
+    }
 }
 
 #[async_trait]
 impl DapiRequestExecutor for DapiClient {
     /// Execute the [DapiRequest](crate::DapiRequest).
+    ///
+    /// Responses are not verified; see [DapiClient::execute_verified()] for hedged and quorum
+    /// reads of verified responses.
     async fn execute<R>(
         &self,
         request: R,
@@ -127,13 +396,155 @@ impl DapiRequestExecutor for DapiClient {
         R::Response: Mockable,
         <R::Client as TransportClient>::Error: Mockable,
     {
-        // Join settings of different sources to get final version of the settings for this execution:
-        let applied_settings = self
-            .settings
-            .override_by(R::SETTINGS_OVERRIDES)
-            .override_by(settings)
-            .finalize();
+        self.execute_verified(request, settings, AcceptAny)
+            .await
+            .map_err(DapiClientError::from)
+    }
+}
+
+impl DapiClient {
+    /// Execute the [DapiRequest](crate::DapiRequest), verifying each response with `verifier`.
+    ///
+    /// The request is sent to [RequestSettings::fan_out] nodes, waiting
+    /// [RequestSettings::hedge_delay] before sending it to each next node. Each node is retried
+    /// independently, and responses that fail verification are discarded, so a single slow or
+    /// misbehaving node doesn't fail the request.
+    ///
+    /// Returns the first response of [RequestSettings::quorum] verified responses that
+    /// [match](ResponseVerifier::matches()). Outstanding requests are cancelled then. If no response
+    /// was verified, the last error is returned; otherwise it fails with
+    /// [DapiClientError::NoQuorum].
+    pub async fn execute_verified<R, V>(
+        &self,
+        request: R,
+        settings: RequestSettings,
+        verifier: V,
+    ) -> Result<V::Output, VerifiedRequestError<<R::Client as TransportClient>::Error, V::Error>>
+    where
+        R: TransportRequest + Mockable,
+        R::Response: Mockable,
+        <R::Client as TransportClient>::Error: Mockable,
+        V: ResponseVerifier<R>,
+    {
+        let applied_settings = self.apply_settings::<R>(settings);
 
+        // Addresses already used by this request, so that each node gets it only once
+        let used_addresses = Mutex::new(HashSet::new());
+
+        // Responses of all nodes to this request are recorded in the same session round
+        #[cfg(feature = "dump")]
+        let round = self
+            .session_recorder
+            .as_ref()
+            .map(crate::session::SessionRecorder::next_round);
+
+        let mut pending = (0..applied_settings.fan_out)
+            .map(|index| {
+                let request = request.clone();
+                let delay = applied_settings
+                    .hedge_delay
+                    .map(|delay| delay * index as u32)
+                    .filter(|delay| !delay.is_zero());
+                let used_addresses = &used_addresses;
+                let verifier = &verifier;
+
+                async move {
+                    if let Some(delay) = delay {
+                        tokio::time::sleep(delay).await;
+                    }
+
+                    #[cfg(feature = "dump")]
+                    let dump_request = request.clone();
+
+                    let result = self
+                        .execute_on_node(request, applied_settings, used_addresses)
+                        .await;
+
+                    let (result, address) = match result {
+                        Ok((response, address)) => (Ok(response), Some(address)),
+                        Err(error) => (Err(error), None),
+                    };
+
+                    // Dump request and response to disk if dump_dir is set:
+                    #[cfg(feature = "dump")]
+                    Self::dump_request_response(&dump_request, &result, self.dump_dir.clone());
+                    #[cfg(feature = "dump")]
+                    if let (Some(recorder), Some(round)) = (&self.session_recorder, round) {
+                        let node = match (&address, &result) {
+                            (Some(address), _) => Some(address),
+                            (None, Err(DapiClientError::Transport(_, address))) => Some(address),
+                            _ => None,
+                        };
+                        recorder.record_node(&dump_request, &result, node, round);
+                    }
+
+                    let response = result.map_err(VerifiedRequestError::Dapi)?;
+                    let address = address.expect("address of successful request");
+
+                    // Invalid response is a failure of this node only, not of the network
+                    verifier.verify(response).map_err(|error| {
+                        record_address_stats(
+                            self.address_list
+                                .write()
+                                .expect("can't get address list for write")
+                                .record_failure(&address, &applied_settings),
+                        );
+
+                        VerifiedRequestError::Verification(error, address)
+                    })
+                }
+            })
+            .collect::<FuturesUnordered<_>>();
+
+        let mut quorum = QuorumTracker::new(applied_settings.quorum);
+        let mut last_error = None;
+
+        while let Some(result) = pending.next().await {
+            match result {
+                Ok(output) => {
+                    if let Some(output) =
+                        quorum.add(output, |output, other| verifier.matches(output, other))
+                    {
+                        return Ok(output);
+                    }
+                }
+                Err(error) => {
+                    if applied_settings.fan_out > 1 {
+                        tracing::debug!(?error, "one of requests sent to many nodes failed");
+                    }
+                    last_error = Some(error);
+                }
+            }
+
+            if !quorum.reachable(pending.len()) {
+                break;
+            }
+        }
+
+        match (quorum.matching(), last_error) {
+            (0, Some(error)) => Err(error),
+            (matching, _) => Err(VerifiedRequestError::Dapi(DapiClientError::NoQuorum {
+                matching,
+                required: applied_settings.quorum,
+            })),
+        }
+    }
+
+    /// Execute the request on a single node, retrying on other nodes on failure.
+    ///
+    /// Addresses in `used_addresses` are avoided if possible; the selected address is added to it.
+    /// Each attempt is subject to circuit breakers and [RequestSettings::rate_limit].
+    pub(crate) async fn execute_on_node<R>(
+        &self,
+        request: R,
+        applied_settings: AppliedRequestSettings,
+        used_addresses: &Mutex<HashSet<Address>>,
+    ) -> Result<(R::Response, Address), DapiClientError<<R::Client as TransportClient>::Error>>
+    where
+        R: TransportRequest + Mockable,
+        R::Response: Mockable,
+        <R::Client as TransportClient>::Error: Mockable,
+    {
         // Setup retry policy:
         let retry_settings = ExponentialBuilder::default()
             .with_max_times(applied_settings.retries)
@@ -142,31 +553,28 @@ impl DapiRequestExecutor for DapiClient {
             .with_min_delay(Duration::from_secs(0))
             .with_max_delay(Duration::from_secs(0));
 
-        // Save dump dir for later use, as self is moved into routine
-        #[cfg(feature = "dump")]
-        let dump_dir = self.dump_dir.clone();
-        #[cfg(feature = "dump")]
-        let dump_request = request.clone();
-
         // Setup DAPI request execution routine future. It's a closure that will be called
         // more once to build new future on each retry.
         let routine = move || {
             // Try to get an address to initialize transport on:
 
-            let address_list = self
-                .address_list
-                .read()
-                .expect("can't get address list for read");
+            let mut used_addresses = used_addresses
+                .lock()
+                .expect("can't get used addresses lock");
 
-            let address_result = address_list.get_live_address().cloned().ok_or(
-                DapiClientError::<<R::Client as TransportClient>::Error>::NoAvailableAddresses,
+            let address_result = self.select_address::<<R::Client as TransportClient>::Error>(
+                &used_addresses,
+                &applied_settings,
             );
 
-            drop(address_list);
+            if let Ok((address, _)) = &address_result {
+                used_addresses.insert(address.clone());
+            }
+            drop(used_addresses);
 
             let _span = tracing::trace_span!(
                 "execute request",
-                address = ?address_result,
+                address = ?address_result.as_ref().map(|(address, _)| address),
                 settings = ?applied_settings,
                 method = request.method_name(),
             )
@@ -187,15 +595,20 @@ impl DapiRequestExecutor for DapiClient {
             async move {
                 // It stays wrapped in `Result` since we want to return
                 // `impl Future<Output = Result<...>`, not a `Result` itself.
-                let address = address_result?;
+                let (address, probes) = address_result?;
                 let pool = self.pool.clone();
 
+                if let Some(rate_limit) = applied_settings.rate_limit {
+                    self.rate_limiter.acquire(rate_limit).await;
+                }
+
                 let mut transport_client = R::Client::with_uri_and_settings(
                     address.uri().clone(),
                     &applied_settings,
                     &pool,
                 );
 
+                let started = std::time::Instant::now();
                 let response = transport_request
                     .execute_transport(&mut transport_client, &applied_settings)
                     .await
@@ -206,15 +619,28 @@ impl DapiRequestExecutor for DapiClient {
                         )
                     });
 
+                let latency = started.elapsed();
+
+                // Probes are resolved by recording the outcome below
+                probes.resolve();
+                self.record_circuit(
+                    response
+                        .as_ref()
+                        .is_err_and(|error| error.is_node_failure()),
+                    &applied_settings,
+                );
+
                 match &response {
                     Ok(_) => {
+                        let mut address_list = self
+                            .address_list
+                            .write()
+                            .expect("can't get address list for write");
+
+                        record_address_stats(address_list.record_success(&address, latency));
+
                         // Unban the address if it was banned and node responded successfully this time
                         if address.is_banned() {
-                            let mut address_list = self
-                                .address_list
-                                .write()
-                                .expect("can't get address list for write");
-
                             address_list.unban_address(&address)
                                 .map_err(DapiClientError::<<R::Client as TransportClient>::Error>::AddressList)?;
                         }
@@ -222,23 +648,30 @@ impl DapiRequestExecutor for DapiClient {
                         tracing::trace!(?response, "received {} response", response_name);
                     }
                     Err(error) => {
+                        let mut address_list = self
+                            .address_list
+                            .write()
+                            .expect("can't get address list for write");
+
                         if error.is_node_failure() {
-                            if applied_settings.ban_failed_address {
-                                let mut address_list = self
-                                    .address_list
-                                    .write()
-                                    .expect("can't get address list for write");
+                            record_address_stats(
+                                address_list.record_failure(&address, &applied_settings),
+                            );
 
+                            if applied_settings.ban_failed_address {
                                 address_list.ban_address(&address)
                                     .map_err(DapiClientError::<<R::Client as TransportClient>::Error>::AddressList)?;
                             }
                         } else {
+                            // node responded, just not with what we wanted
+                            record_address_stats(address_list.record_success(&address, latency));
+
                             tracing::trace!(?error, "received error");
                         }
                     }
                 };
 
-                response
+                response.map(|response| (response, address))
             }
         };
 
@@ -263,10 +696,6 @@ impl DapiRequestExecutor for DapiClient {
             }
         }
 
-        // Dump request and response to disk if dump_dir is set:
-        #[cfg(feature = "dump")]
-        Self::dump_request_response(&dump_request, &result, dump_dir);
-
         result
     }
 }
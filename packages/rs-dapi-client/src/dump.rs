@@ -4,8 +4,10 @@ use dapi_grpc::mock::Mockable;
 
 use crate::{
     mock::{Key, MockResult},
+    session::SessionRecorder,
+    stream::SerializedStream,
     transport::TransportRequest,
-    DapiClient,
+    DapiClient, DapiStreamRequest, StreamResult,
 };
 use std::{any::type_name, path::PathBuf};
 
@@ -40,6 +42,44 @@ impl<T: TransportRequest> DumpData<T> {
     }
 }
 
+impl<T: DapiStreamRequest> DumpData<T> {
+    /// Create new dump data of a stream, with all messages received from it.
+    pub(crate) fn new_stream(request: &T, messages: &SerializedStream) -> Self {
+        let request = request
+            .mock_serialize()
+            .expect("unable to serialize request");
+        let messages = messages
+            .mock_serialize()
+            .expect("unable to serialize stream messages");
+
+        Self {
+            serialized_request: request,
+            serialized_response: messages,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Return deserialized request and messages of a stream
+    pub fn deserialize_stream(&self) -> (T, Vec<StreamResult<T>>) {
+        let req = T::mock_deserialize(&self.serialized_request).unwrap_or_else(|| {
+            panic!(
+                "unable to deserialize mock data of type {}",
+                type_name::<T>()
+            )
+        });
+        let messages = SerializedStream::mock_deserialize(&self.serialized_response)
+            .unwrap_or_else(|| {
+                panic!(
+                    "unable to deserialize stream messages of type {}",
+                    type_name::<T::Message>()
+                )
+            })
+            .deserialize::<T>();
+
+        (req, messages.into())
+    }
+}
+
 impl<T: TransportRequest> dapi_grpc::mock::Mockable for DumpData<T>
 where
     T: Mockable,
@@ -169,6 +209,8 @@ impl DapiClient {
     ///
     /// Each request and response pair will be saved to a JSON file in `dump_dir`.
     /// Data is saved as [DumpData] structure.
+    /// Streams are saved together with all received messages when dropped, see
+    /// [DumpData::deserialize_stream()].
     /// Any errors are logged on `warn` level and ignored.
     ///
     /// Dump file name is generated by [DumpData::filename()].
@@ -181,6 +223,39 @@ impl DapiClient {
         self
     }
 
+    /// Record all requests and responses, in the order they were executed, with `recorder`.
+    ///
+    /// Recorded session can be saved with [SessionRecorder::save()] and replayed with
+    /// [ReplayDapiClient](crate::session::ReplayDapiClient).
+    pub fn record_session(mut self, recorder: Option<SessionRecorder>) -> Self {
+        self.session_recorder = recorder;
+
+        self
+    }
+
+    /// Save dump of a stream request and all messages received from the stream to disk.
+    ///
+    /// Any errors are logged on `warn` level and ignored.
+    pub(crate) fn dump_stream<R: DapiStreamRequest>(
+        request: &R,
+        messages: &SerializedStream,
+        dump_dir: &std::path::Path,
+    ) {
+        let data = DumpData::new_stream(request, messages);
+
+        // Construct file name
+        let filename = match data.filename() {
+            Ok(f) => f,
+            Err(e) => return tracing::warn!("unable to create dump file name: {}", e),
+        };
+
+        let file = dump_dir.join(filename);
+
+        if let Err(e) = data.save(&file) {
+            tracing::warn!("unable to write dump file {:?}: {}", dump_dir, e);
+        }
+    }
+
     /// Save dump of request and response to disk.
     ///
     /// Any errors are logged on `warn` level and ignored.
@@ -197,7 +272,21 @@ impl DapiClient {
             None => return,
         };
 
-        let data = DumpData::new(request, response);
+        // Streams are not serializable; they are dumped by DapiStream with all received messages
+        let (Some(serialized_request), Some(serialized_response)) =
+            (request.mock_serialize(), response.mock_serialize())
+        else {
+            return tracing::trace!(
+                "skipping dump of {} response that can't be serialized",
+                type_name::<R::Response>()
+            );
+        };
+
+        let data = DumpData::<R> {
+            serialized_request,
+            serialized_response,
+            phantom: std::marker::PhantomData,
+        };
 
         // Construct file name
         let filename = match data.filename() {
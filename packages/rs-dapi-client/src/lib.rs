@@ -3,24 +3,37 @@
 #![deny(missing_docs)]
 
 mod address_list;
+mod address_selector;
+mod circuit_breaker;
 mod connection_pool;
 mod dapi_client;
 #[cfg(feature = "dump")]
 pub mod dump;
 #[cfg(feature = "mocks")]
 pub mod mock;
+mod multi_node;
+mod rate_limiter;
 mod request_settings;
+#[cfg(feature = "mocks")]
+pub mod session;
+mod stream;
 pub mod transport;
 
 pub use address_list::Address;
 pub use address_list::AddressList;
+pub use address_list::AddressStats;
+pub use address_selector::{AddressSelector, RandomSelector, RoundRobinSelector, WeightedSelector};
+pub use circuit_breaker::CircuitState;
 pub use dapi_client::DapiRequestExecutor;
 pub use dapi_client::{DapiClient, DapiClientError};
 use dapi_grpc::mock::Mockable;
 #[cfg(feature = "dump")]
 pub use dump::DumpData;
 use futures::{future::BoxFuture, FutureExt};
+pub use multi_node::{AcceptAny, ResponseVerifier, VerifiedRequestError};
+pub use rate_limiter::RateLimit;
 pub use request_settings::RequestSettings;
+pub use stream::{DapiStream, DapiStreamExecutor, DapiStreamRequest, StreamResult};
 
 /// A DAPI request could be executed with an initialized [DapiClient].
 ///
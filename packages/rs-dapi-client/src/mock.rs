@@ -12,8 +12,10 @@
 //! See `tests/mock_dapi_client.rs` for an example.
 
 use crate::{
+    stream::SerializedStream,
     transport::{TransportClient, TransportRequest},
-    DapiClientError, DapiRequestExecutor, RequestSettings,
+    DapiClientError, DapiRequestExecutor, DapiStream, DapiStreamExecutor, DapiStreamRequest,
+    RequestSettings, StreamResult,
 };
 use dapi_grpc::mock::Mockable;
 use dapi_grpc::tonic::async_trait;
@@ -68,6 +70,31 @@ impl MockDapiClient {
         Ok(self)
     }
 
+    /// Add a new expectation for a streaming request.
+    ///
+    /// Stream opened for the request returns `messages` in order, and then ends.
+    pub fn expect_stream<R>(
+        &mut self,
+        request: &R,
+        messages: &[StreamResult<R>],
+    ) -> Result<&mut Self, MockError>
+    where
+        R: DapiStreamRequest,
+    {
+        let key = self
+            .expectations
+            .add(request, &SerializedStream::new::<R>(messages))?;
+
+        tracing::trace!(
+            %key,
+            request_type = std::any::type_name::<R>(),
+            message_type = std::any::type_name::<R::Message>(),
+            "mock added stream expectation"
+        );
+
+        Ok(self)
+    }
+
     /// Load expectation from file.
     ///
     /// The file must contain JSON structure.
@@ -101,6 +128,27 @@ impl MockDapiClient {
         })?;
         Ok((request, response))
     }
+
+    /// Load expectation of a streaming request from file.
+    ///
+    /// See [MockDapiClient::load()] for details.
+    #[cfg(feature = "dump")]
+    pub fn load_stream<T: DapiStreamRequest, P: AsRef<std::path::Path>>(
+        &mut self,
+        file: P,
+    ) -> Result<(T, Vec<StreamResult<T>>), std::io::Error> {
+        use crate::DumpData;
+
+        let (request, messages) = DumpData::<T>::load(file)?.deserialize_stream();
+
+        self.expect_stream(&request, &messages).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("unable to add expectation: {}", e),
+            )
+        })?;
+        Ok((request, messages))
+    }
 }
 
 #[async_trait]
@@ -136,6 +184,36 @@ impl DapiRequestExecutor for MockDapiClient {
     }
 }
 
+#[async_trait]
+impl DapiStreamExecutor for MockDapiClient {
+    async fn stream<R>(
+        &self,
+        request: R,
+        _settings: RequestSettings,
+    ) -> Result<DapiStream<R>, DapiClientError<<R::Client as TransportClient>::Error>>
+    where
+        R: DapiStreamRequest,
+    {
+        let (key, messages) = self.expectations.get::<_, SerializedStream>(&request);
+
+        tracing::trace!(
+            %key,
+            request_type = std::any::type_name::<R>(),
+            message_type = std::any::type_name::<R::Message>(),
+            "mock stream"
+        );
+
+        match messages {
+            Some(messages) => Ok(DapiStream::from_messages(messages.deserialize::<R>())),
+            None => Err(MockError::MockExpectationNotFound(format!(
+                "unexpected mock stream request with key {}, use MockDapiClient::expect_stream(): {:?}",
+                key, request
+            ))
+            .into()),
+        }
+    }
+}
+
 #[derive(Eq, Hash, PartialEq, PartialOrd, Ord, Clone, Debug)]
 /// Unique identifier of some serializable object (e.g. request) that can be used as a key in a hashmap.
 pub struct Key([u8; 32]);
@@ -196,6 +274,10 @@ pub enum MockError {
     #[error("expectation already defined for request: {0}")]
     /// Expectation already defined for request
     MockExpectationConflict(String),
+
+    #[error("replayed session doesn't match requests: {0}")]
+    /// Requests executed by [ReplayDapiClient](crate::session::ReplayDapiClient) don't match the recorded session
+    SessionMismatch(String),
 }
 
 #[derive(Debug)]
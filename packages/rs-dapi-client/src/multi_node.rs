@@ -0,0 +1,121 @@
+//! Requests sent to many DAPI nodes at once: hedged and quorum reads.
+//!
+//! With [RequestSettings::fan_out](crate::RequestSettings::fan_out) greater than 1, the same
+//! request is sent to that many nodes, either all at once or one after another every
+//! [RequestSettings::hedge_delay](crate::RequestSettings::hedge_delay) (hedging). Each response is
+//! checked with a [ResponseVerifier], so that a node returning invalid data doesn't fail the whole
+//! request, and the request completes as soon as
+//! [RequestSettings::quorum](crate::RequestSettings::quorum) verified responses match.
+use std::convert::Infallible;
+use std::fmt::Debug;
+
+use dapi_grpc::mock::Mockable;
+
+use crate::transport::TransportRequest;
+use crate::{Address, DapiClientError};
+
+/// Verification of responses received from DAPI nodes, used by
+/// [DapiClient::execute_verified()](crate::DapiClient::execute_verified()).
+pub trait ResponseVerifier<R: TransportRequest>: Send + Sync {
+    /// Verified response.
+    type Output: Send;
+    /// Verification error.
+    type Error: Debug + Send;
+
+    /// Verify response received from a single node.
+    fn verify(&self, response: R::Response) -> Result<Self::Output, Self::Error>;
+
+    /// Check if two verified responses match, so that they count towards the same quorum.
+    fn matches(&self, output: &Self::Output, other: &Self::Output) -> bool;
+}
+
+/// Verifier that accepts any response.
+///
+/// Unverified responses can't be compared, so all of them match each other; quorum only requires
+/// that many nodes to respond successfully.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AcceptAny;
+
+impl<R: TransportRequest> ResponseVerifier<R> for AcceptAny {
+    type Output = R::Response;
+    type Error = Infallible;
+
+    fn verify(&self, response: R::Response) -> Result<Self::Output, Self::Error> {
+        Ok(response)
+    }
+
+    fn matches(&self, _output: &Self::Output, _other: &Self::Output) -> bool {
+        true
+    }
+}
+
+/// Error of a request executed with [DapiClient::execute_verified()](crate::DapiClient::execute_verified()).
+#[derive(Debug, thiserror::Error)]
+pub enum VerifiedRequestError<TE: Mockable, VE: Debug> {
+    /// Request failed
+    #[error("{0}")]
+    Dapi(DapiClientError<TE>),
+    /// Response received from the node failed verification
+    #[error("response from {1} failed verification: {0:?}")]
+    Verification(VE, Address),
+}
+
+impl<TE: Mockable> From<VerifiedRequestError<TE, Infallible>> for DapiClientError<TE> {
+    fn from(value: VerifiedRequestError<TE, Infallible>) -> Self {
+        match value {
+            VerifiedRequestError::Dapi(error) => error,
+            VerifiedRequestError::Verification(error, _) => match error {},
+        }
+    }
+}
+
+/// Groups of matching responses, received so far.
+pub(crate) struct QuorumTracker<O> {
+    required: usize,
+    /// First response of each group and the number of responses in the group
+    groups: Vec<(O, usize)>,
+}
+
+impl<O> QuorumTracker<O> {
+    pub(crate) fn new(required: usize) -> Self {
+        Self {
+            required,
+            groups: Vec::new(),
+        }
+    }
+
+    /// Add a verified response, grouped with responses it `matches`; returns the first response of
+    /// the group that reached the quorum.
+    pub(crate) fn add<F: Fn(&O, &O) -> bool>(&mut self, output: O, matches: F) -> Option<O> {
+        let index = match self.groups.iter().position(|(o, _)| matches(o, &output)) {
+            Some(index) => {
+                self.groups[index].1 += 1;
+                index
+            }
+            None => {
+                self.groups.push((output, 1));
+                self.groups.len() - 1
+            }
+        };
+
+        if self.groups[index].1 >= self.required {
+            Some(self.groups.swap_remove(index).0)
+        } else {
+            None
+        }
+    }
+
+    /// Highest number of matching responses.
+    pub(crate) fn matching(&self) -> usize {
+        self.groups
+            .iter()
+            .map(|(_, count)| *count)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Check if the quorum can still be reached with `pending` more responses.
+    pub(crate) fn reachable(&self, pending: usize) -> bool {
+        self.matching() + pending >= self.required
+    }
+}
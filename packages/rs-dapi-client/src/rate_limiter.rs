@@ -0,0 +1,87 @@
+//! Token bucket limiting the rate of requests sent to DAPI.
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Limit of the rate of requests sent by [DapiClient](crate::DapiClient), configured with
+/// [RequestSettings::rate_limit](crate::RequestSettings::rate_limit).
+///
+/// Every attempt to send a request, including retries and requests sent to many nodes, takes one
+/// token from a bucket shared by all requests of the client. The bucket holds up to `burst` tokens
+/// and is refilled with `requests_per_second` tokens per second. When it's empty, requests wait
+/// for a token instead of being sent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    requests_per_second: f64,
+    burst: u32,
+}
+
+impl RateLimit {
+    /// Create a new rate limit of `requests_per_second` on average, with up to `burst` requests
+    /// sent at once after a period of inactivity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests_per_second` is not positive, as no request could ever be sent.
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        assert!(
+            requests_per_second > 0.0,
+            "requests per second must be positive, got {}",
+            requests_per_second
+        );
+
+        Self {
+            requests_per_second,
+            burst,
+        }
+    }
+
+    /// Number of requests allowed per second on average.
+    pub fn requests_per_second(&self) -> f64 {
+        self.requests_per_second
+    }
+
+    /// Number of requests that can be sent at once after a period of inactivity.
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+}
+
+/// Token bucket shared by all requests of a [DapiClient](crate::DapiClient).
+#[derive(Debug, Default)]
+pub(crate) struct RateLimiter {
+    /// Available tokens and time of the last refill; `None` until the first request.
+    bucket: Mutex<Option<(f64, Instant)>>,
+}
+
+impl RateLimiter {
+    /// Wait until a request can be sent according to `limit`.
+    pub(crate) async fn acquire(&self, limit: RateLimit) {
+        while let Some(wait) = self.try_acquire(limit) {
+            tracing::trace!("rate limit reached, waiting {} secs", wait.as_secs_f32());
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token if available; otherwise, return time to wait for the next one.
+    fn try_acquire(&self, limit: RateLimit) -> Option<Duration> {
+        let capacity = limit.burst.max(1) as f64;
+        let now = Instant::now();
+
+        let mut bucket = self.bucket.lock().expect("can't get rate limiter lock");
+        let (tokens, updated) = bucket.get_or_insert((capacity, now));
+
+        let elapsed = now.saturating_duration_since(*updated).as_secs_f64();
+        *tokens = (*tokens + elapsed * limit.requests_per_second).min(capacity);
+        *updated = now;
+
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            None
+        } else {
+            let wait = (1.0 - *tokens) / limit.requests_per_second;
+            Some(Duration::try_from_secs_f64(wait).unwrap_or(Duration::MAX))
+        }
+    }
+}
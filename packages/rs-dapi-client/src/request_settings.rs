@@ -2,11 +2,19 @@
 
 use std::time::Duration;
 
+use crate::RateLimit;
+
 /// Default low-level client timeout
 const DEFAULT_CONNECT_TIMEOUT: Option<Duration> = None;
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
 const DEFAULT_RETRIES: usize = 5;
 const DEFAULT_BAN_FAILED_ADDRESS: bool = true;
+const DEFAULT_FAN_OUT: usize = 1;
+const DEFAULT_QUORUM: usize = 1;
+/// Circuit breakers are opt-in, so `0` disables them by default
+const DEFAULT_ADDRESS_FAILURE_THRESHOLD: usize = 0;
+const DEFAULT_GLOBAL_FAILURE_THRESHOLD: usize = 0;
+const DEFAULT_CIRCUIT_OPEN_DURATION: Duration = Duration::from_secs(10);
 
 /// DAPI request settings.
 ///
@@ -25,6 +33,35 @@ pub struct RequestSettings {
     pub retries: Option<usize>,
     /// Ban DAPI address if node not responded or responded with error.
     pub ban_failed_address: Option<bool>,
+    /// Number of DAPI nodes the request is sent to; defaults to 1.
+    ///
+    /// The first successful response is used, unless [RequestSettings::quorum] requires more.
+    pub fan_out: Option<usize>,
+    /// Delay between sending the request to consecutive nodes when [RequestSettings::fan_out] is
+    /// greater than 1 (hedged requests); `None` sends the request to all nodes at once.
+    pub hedge_delay: Option<Duration>,
+    /// Number of matching responses required; defaults to 1.
+    ///
+    /// See [DapiClient::execute_verified()](crate::DapiClient::execute_verified()) for details on
+    /// how responses are matched.
+    /// [RequestSettings::fan_out] is increased to the quorum if it's lower.
+    pub quorum: Option<usize>,
+    /// Number of consecutive node failures that open the circuit of a DAPI address; defaults to `0`,
+    /// which disables circuit breaking of addresses.
+    ///
+    /// The address is not used until the circuit closes. See [CircuitState](crate::CircuitState)
+    /// for details.
+    pub address_failure_threshold: Option<usize>,
+    /// Number of consecutive node failures, on any address, that open the global circuit of the
+    /// [crate::DapiClient]; defaults to `0`, which disables the global circuit breaker.
+    ///
+    /// While the global circuit is open, requests fail with
+    /// [DapiClientError::CircuitOpen](crate::DapiClientError::CircuitOpen) without being sent.
+    pub global_failure_threshold: Option<usize>,
+    /// Time an open circuit rejects requests before a probe request is allowed; defaults to 10 seconds.
+    pub circuit_open_duration: Option<Duration>,
+    /// Limit of the rate of requests sent by the [crate::DapiClient]; unlimited by default.
+    pub rate_limit: Option<RateLimit>,
 }
 
 impl RequestSettings {
@@ -36,6 +73,13 @@ impl RequestSettings {
             timeout: None,
             retries: None,
             ban_failed_address: None,
+            fan_out: None,
+            hedge_delay: None,
+            quorum: None,
+            address_failure_threshold: None,
+            global_failure_threshold: None,
+            circuit_open_duration: None,
+            rate_limit: None,
         }
     }
 
@@ -48,11 +92,24 @@ impl RequestSettings {
             timeout: rhs.timeout.or(self.timeout),
             retries: rhs.retries.or(self.retries),
             ban_failed_address: rhs.ban_failed_address.or(self.ban_failed_address),
+            fan_out: rhs.fan_out.or(self.fan_out),
+            hedge_delay: rhs.hedge_delay.or(self.hedge_delay),
+            quorum: rhs.quorum.or(self.quorum),
+            address_failure_threshold: rhs
+                .address_failure_threshold
+                .or(self.address_failure_threshold),
+            global_failure_threshold: rhs
+                .global_failure_threshold
+                .or(self.global_failure_threshold),
+            circuit_open_duration: rhs.circuit_open_duration.or(self.circuit_open_duration),
+            rate_limit: rhs.rate_limit.or(self.rate_limit),
         }
     }
 
     /// Fill in settings defaults.
     pub fn finalize(self) -> AppliedRequestSettings {
+        let quorum = self.quorum.unwrap_or(DEFAULT_QUORUM).max(1);
+
         AppliedRequestSettings {
             connect_timeout: self.connect_timeout.or(DEFAULT_CONNECT_TIMEOUT),
             timeout: self.timeout.unwrap_or(DEFAULT_TIMEOUT),
@@ -60,6 +117,19 @@ impl RequestSettings {
             ban_failed_address: self
                 .ban_failed_address
                 .unwrap_or(DEFAULT_BAN_FAILED_ADDRESS),
+            fan_out: self.fan_out.unwrap_or(DEFAULT_FAN_OUT).max(quorum),
+            hedge_delay: self.hedge_delay,
+            quorum,
+            address_failure_threshold: self
+                .address_failure_threshold
+                .unwrap_or(DEFAULT_ADDRESS_FAILURE_THRESHOLD),
+            global_failure_threshold: self
+                .global_failure_threshold
+                .unwrap_or(DEFAULT_GLOBAL_FAILURE_THRESHOLD),
+            circuit_open_duration: self
+                .circuit_open_duration
+                .unwrap_or(DEFAULT_CIRCUIT_OPEN_DURATION),
+            rate_limit: self.rate_limit,
         }
     }
 }
@@ -75,4 +145,18 @@ pub struct AppliedRequestSettings {
     pub retries: usize,
     /// Ban DAPI address if node not responded or responded with error.
     pub ban_failed_address: bool,
+    /// Number of DAPI nodes the request is sent to.
+    pub fan_out: usize,
+    /// Delay between sending the request to consecutive nodes.
+    pub hedge_delay: Option<Duration>,
+    /// Number of matching responses required.
+    pub quorum: usize,
+    /// Number of consecutive node failures that open the circuit of a DAPI address.
+    pub address_failure_threshold: usize,
+    /// Number of consecutive node failures that open the global circuit.
+    pub global_failure_threshold: usize,
+    /// Time an open circuit rejects requests before a probe request is allowed.
+    pub circuit_open_duration: Duration,
+    /// Limit of the rate of requests.
+    pub rate_limit: Option<RateLimit>,
 }
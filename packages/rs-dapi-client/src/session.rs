@@ -0,0 +1,391 @@
+//! Recording and replay of whole DAPI sessions.
+//!
+//! [MockDapiClient](crate::mock::MockDapiClient) serves responses by request, so it can't replay a
+//! session in which the same request gets different responses over time, like polling for a state
+//! transition result. [SessionRecorder] keeps the order of requests: it records every request and
+//! response executed by a [DapiClient](crate::DapiClient) (see
+//! [DapiClient::record_session()](crate::DapiClient::record_session())) and saves them to a session
+//! log. [ReplayDapiClient] serves responses from the log in the recorded order.
+//!
+//! Session log is a text file with one JSON-encoded [SessionEntry] per line.
+//!
+//! ## Matching requests
+//!
+//! By default, each request is served with the response of the first not yet consumed entry of the
+//! same request, so the order of different requests executed concurrently doesn't matter, while
+//! repeated requests get their responses in the recorded order. Use
+//! [ReplayDapiClient::with_strict_order()] to require that requests arrive exactly in the recorded
+//! order.
+//!
+//! Requests that don't match any entry fail with [MockError::MockExpectationNotFound], and are
+//! reported together with entries that were never consumed by [ReplayDapiClient::verify()].
+//!
+//! ## Fan-out
+//!
+//! When a request is sent to more than one node (see
+//! [RequestSettings::fan_out](crate::RequestSettings::fan_out)), the recorder stores a separate
+//! entry for the response of each node, with the address of the [node](SessionEntry::node) and the
+//! same [round](SessionEntry::round). A replayed request consumes all entries of the round at once,
+//! and is served with the first successful response among them, or with the first response if all
+//! of them failed. Entries without a round are served one at a time.
+use std::any::type_name;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use dapi_grpc::mock::Mockable;
+use dapi_grpc::tonic::async_trait;
+
+use crate::mock::{Key, MockError, MockResult};
+use crate::transport::TransportRequest;
+use crate::{Address, DapiRequestExecutor, RequestSettings};
+
+/// Single request and response of a recorded session.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SessionEntry {
+    /// Type of the request, like `GetIdentityRequest`
+    pub request_type: String,
+    /// Unique identifier of the request, see [Key]
+    pub key: String,
+    /// Request serialized with [Mockable::mock_serialize()], hex-encoded
+    pub request: String,
+    /// Result of the request serialized with [Mockable::mock_serialize()], hex-encoded
+    pub response: String,
+    /// URI of the node that returned the response, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// Execution of the request this response belongs to; responses of all nodes that were sent
+    /// the same request share the round
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub round: Option<u64>,
+}
+
+impl SessionEntry {
+    /// Create a new entry from a request and its result.
+    pub fn new<R: TransportRequest>(
+        request: &R,
+        response: &MockResult<R>,
+    ) -> Result<Self, std::io::Error>
+    where
+        R: Mockable,
+        R::Response: Mockable,
+    {
+        let key = Key::try_new(request)?;
+        let request_data = request.mock_serialize().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unable to serialize {} request", type_name::<R>()),
+            )
+        })?;
+        let response_data = response.mock_serialize().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "unable to serialize {} response",
+                    type_name::<R::Response>()
+                ),
+            )
+        })?;
+
+        Ok(Self {
+            request_type: request_type::<R>(),
+            key: key.to_string(),
+            request: hex::encode(request_data),
+            response: hex::encode(response_data),
+            node: None,
+            round: None,
+        })
+    }
+
+    /// Set the node that returned the response and the round of the request, see [SessionEntry::round].
+    pub fn with_node(mut self, node: Option<&Address>, round: u64) -> Self {
+        self.node = node.map(|address| address.uri().to_string());
+        self.round = Some(round);
+        self
+    }
+
+    /// Deserialize the recorded result of the request.
+    pub fn response<R: TransportRequest>(&self) -> Option<MockResult<R>>
+    where
+        R::Response: Mockable,
+    {
+        let data = hex::decode(&self.response).ok()?;
+        MockResult::<R>::mock_deserialize(&data)
+    }
+}
+
+/// Return request type (R) name without module prefix
+fn request_type<R>() -> String {
+    let name = type_name::<R>();
+    name.split(':').last().unwrap_or(name).to_string()
+}
+
+/// Save session entries to a session log file.
+pub fn save_session<P: AsRef<Path>>(
+    entries: &[SessionEntry],
+    file: P,
+) -> Result<(), std::io::Error> {
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(file)?);
+    for entry in entries {
+        serde_json::to_writer(&mut writer, entry)?;
+        writer.write_all(b"\n")?;
+    }
+
+    writer.flush()
+}
+
+/// Load session entries from a session log file created with [save_session()].
+pub fn load_session<P: AsRef<Path>>(file: P) -> Result<Vec<SessionEntry>, std::io::Error> {
+    let reader = std::io::BufReader::new(std::fs::File::open(file)?);
+
+    reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|line| Ok(serde_json::from_str(&line?)?))
+        .collect()
+}
+
+/// Records requests and responses in the order they were executed.
+///
+/// Recorder is cheap to clone; clones share recorded entries.
+#[derive(Debug, Clone, Default)]
+pub struct SessionRecorder {
+    entries: Arc<Mutex<Vec<SessionEntry>>>,
+    rounds: Arc<AtomicU64>,
+}
+
+impl SessionRecorder {
+    /// Create a new, empty recorder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a request and its result to the session.
+    ///
+    /// Errors are logged on `warn` level and ignored.
+    pub fn record<R: TransportRequest>(&self, request: &R, response: &MockResult<R>)
+    where
+        R: Mockable,
+        R::Response: Mockable,
+    {
+        match SessionEntry::new(request, response) {
+            Ok(entry) => self.push(entry),
+            Err(e) => tracing::warn!("unable to record {} request: {}", type_name::<R>(), e),
+        }
+    }
+
+    /// Start a new execution of a request that can be sent to many nodes.
+    ///
+    /// Returns the round to pass to [SessionRecorder::record_node()].
+    pub fn next_round(&self) -> u64 {
+        self.rounds.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Append a request and the result returned by `node` to the session.
+    ///
+    /// Errors are logged on `warn` level and ignored.
+    pub fn record_node<R: TransportRequest>(
+        &self,
+        request: &R,
+        response: &MockResult<R>,
+        node: Option<&Address>,
+        round: u64,
+    ) where
+        R: Mockable,
+        R::Response: Mockable,
+    {
+        match SessionEntry::new(request, response) {
+            Ok(entry) => self.push(entry.with_node(node, round)),
+            Err(e) => tracing::warn!("unable to record {} request: {}", type_name::<R>(), e),
+        }
+    }
+
+    fn push(&self, entry: SessionEntry) {
+        self.entries
+            .lock()
+            .expect("session recorder lock poisoned")
+            .push(entry)
+    }
+
+    /// Entries recorded so far.
+    pub fn entries(&self) -> Vec<SessionEntry> {
+        self.entries
+            .lock()
+            .expect("session recorder lock poisoned")
+            .clone()
+    }
+
+    /// Save recorded entries to a session log file.
+    pub fn save<P: AsRef<Path>>(&self, file: P) -> Result<(), std::io::Error> {
+        save_session(&self.entries(), file)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ReplayState {
+    consumed: Vec<bool>,
+    unmatched: Vec<String>,
+}
+
+/// DAPI client that serves responses from a recorded session.
+///
+/// See [module documentation](self) for details.
+#[derive(Debug)]
+pub struct ReplayDapiClient {
+    entries: Vec<SessionEntry>,
+    strict_order: bool,
+    state: Mutex<ReplayState>,
+}
+
+impl ReplayDapiClient {
+    /// Create a client that replays provided session entries.
+    pub fn new(entries: Vec<SessionEntry>) -> Self {
+        let state = ReplayState {
+            consumed: vec![false; entries.len()],
+            unmatched: Vec::new(),
+        };
+
+        Self {
+            entries,
+            strict_order: false,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Create a client that replays a session log file created with [SessionRecorder::save()].
+    pub fn load<P: AsRef<Path>>(file: P) -> Result<Self, std::io::Error> {
+        Ok(Self::new(load_session(file)?))
+    }
+
+    /// Require requests to arrive exactly in the recorded order.
+    pub fn with_strict_order(mut self, strict_order: bool) -> Self {
+        self.strict_order = strict_order;
+        self
+    }
+
+    /// Descriptions of requests that didn't match any entry of the session.
+    pub fn unmatched_requests(&self) -> Vec<String> {
+        self.state
+            .lock()
+            .expect("replay state lock poisoned")
+            .unmatched
+            .clone()
+    }
+
+    /// Entries of the session that were not served yet.
+    pub fn unconsumed_entries(&self) -> Vec<SessionEntry> {
+        let state = self.state.lock().expect("replay state lock poisoned");
+
+        self.entries
+            .iter()
+            .zip(state.consumed.iter())
+            .filter(|(_, consumed)| !**consumed)
+            .map(|(entry, _)| entry.clone())
+            .collect()
+    }
+
+    /// Check that all requests matched the session and all entries of the session were served.
+    pub fn verify(&self) -> Result<(), MockError> {
+        let unmatched = self.unmatched_requests();
+        let unconsumed = self.unconsumed_entries();
+        if unmatched.is_empty() && unconsumed.is_empty() {
+            return Ok(());
+        }
+
+        let unconsumed = unconsumed
+            .iter()
+            .map(|entry| match &entry.node {
+                Some(node) => format!("{} {} from {}", entry.request_type, entry.key, node),
+                None => format!("{} {}", entry.request_type, entry.key),
+            })
+            .collect::<Vec<_>>();
+
+        Err(MockError::SessionMismatch(format!(
+            "unmatched requests: [{}], unconsumed entries: [{}]",
+            unmatched.join(", "),
+            unconsumed.join(", ")
+        )))
+    }
+
+    /// Find entries of the round that should serve the request, and mark them as consumed.
+    fn next_entries(&self, request_type: &str, key: &str) -> Vec<&SessionEntry> {
+        let mut state = self.state.lock().expect("replay state lock poisoned");
+
+        let found = match self.strict_order {
+            true => state
+                .consumed
+                .iter()
+                .position(|consumed| !consumed)
+                .filter(|index| self.entries[*index].key == key),
+            false => self
+                .entries
+                .iter()
+                .zip(state.consumed.iter())
+                .position(|(entry, consumed)| !consumed && entry.key == key),
+        };
+
+        let Some(index) = found else {
+            state.unmatched.push(format!("{} {}", request_type, key));
+            return Vec::new();
+        };
+
+        let round = self.entries[index].round;
+        let mut entries = Vec::new();
+        for (i, entry) in self.entries.iter().enumerate().skip(index) {
+            let same_round =
+                i == index || (round.is_some() && entry.round == round && entry.key == key);
+            if same_round && !state.consumed[i] {
+                state.consumed[i] = true;
+                entries.push(entry);
+            }
+        }
+
+        entries
+    }
+}
+
+#[async_trait]
+impl DapiRequestExecutor for ReplayDapiClient {
+    async fn execute<R: TransportRequest>(
+        &self,
+        request: R,
+        _settings: RequestSettings,
+    ) -> MockResult<R>
+    where
+        R: Mockable,
+        R::Response: Mockable,
+    {
+        let key = Key::new(&request).to_string();
+        let request_type = request_type::<R>();
+
+        let entries = self.next_entries(&request_type, &key);
+        if entries.is_empty() {
+            return Err(MockError::MockExpectationNotFound(format!(
+                "request {} with key {} not found in replayed session: {:?}",
+                request_type, key, request
+            ))
+            .into());
+        };
+
+        tracing::trace!(%key, %request_type, nodes = entries.len(), "replaying recorded response");
+
+        let mut responses = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let Some(response) = entry.response::<R>() else {
+                return Err(MockError::MockExpectationNotFound(format!(
+                    "unable to deserialize recorded response to {} request with key {}",
+                    request_type, key
+                ))
+                .into());
+            };
+            responses.push(response);
+        }
+
+        // like DapiClient with fan-out, prefer any successful response over errors
+        match responses.iter().position(|response| response.is_ok()) {
+            Some(index) => responses.swap_remove(index),
+            None => responses.swap_remove(0),
+        }
+    }
+}
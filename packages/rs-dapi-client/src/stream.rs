@@ -0,0 +1,393 @@
+//! Streaming DAPI requests, like `subscribeTo*` endpoints of the Core service.
+//!
+//! Streams opened with [DapiStreamExecutor::stream()] use the same address management, banning
+//! and retries as unary requests. When the stream fails, [DapiStream] reconnects to another live
+//! address and resumes the stream after the last received message, so that long-running
+//! subscriptions survive failures of individual nodes.
+use std::collections::HashSet;
+#[cfg(feature = "mocks")]
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::Mutex;
+
+use dapi_grpc::mock::Mockable;
+use dapi_grpc::tonic::async_trait;
+use futures::future::BoxFuture;
+
+use crate::request_settings::AppliedRequestSettings;
+use crate::transport::{TransportClient, TransportRequest};
+use crate::{Address, CanRetry, DapiClient, DapiClientError, RequestSettings};
+
+/// Result of receiving a message from a [DapiStream].
+pub type StreamResult<R> = Result<
+    <R as DapiStreamRequest>::Message,
+    DapiClientError<<<R as TransportRequest>::Client as TransportClient>::Error>,
+>;
+
+/// Streaming DAPI request.
+///
+/// Transport response of a streaming request is a stream of [DapiStreamRequest::Message]s.
+pub trait DapiStreamRequest: TransportRequest {
+    /// Message received from the stream.
+    type Message: Mockable + Send + Debug;
+
+    /// Receive the next message from the stream returned by the transport; `None` means the stream
+    /// ended.
+    fn next_message(
+        stream: &mut Self::Response,
+    ) -> BoxFuture<'_, Result<Option<Self::Message>, <Self::Client as TransportClient>::Error>>;
+
+    /// Update the request, so that sending it again resumes the stream after `message`.
+    ///
+    /// Returns `false` if all requested messages were received, and the stream is finished.
+    fn resume_after(&mut self, message: &Self::Message) -> bool;
+
+    /// Whether the stream is a subscription that never ends, so a stream closed by the node is
+    /// reconnected instead of being finished.
+    fn is_unbounded(&self) -> bool;
+}
+
+/// DAPI client executor of streaming requests.
+#[async_trait]
+pub trait DapiStreamExecutor {
+    /// Open a stream for the [DapiStreamRequest].
+    async fn stream<R>(
+        &self,
+        request: R,
+        settings: RequestSettings,
+    ) -> Result<DapiStream<R>, DapiClientError<<R::Client as TransportClient>::Error>>
+    where
+        R: DapiStreamRequest;
+}
+
+/// Stream of messages received from DAPI, returned by [DapiStreamExecutor::stream()].
+///
+/// Failed streams are reconnected to another live address and resumed with
+/// [DapiStreamRequest::resume_after()]; so are [unbounded](DapiStreamRequest::is_unbounded())
+/// streams closed by the node. A node that fails before sending any message is treated like a node
+/// that failed a unary request: its failure is recorded, and the address is banned if
+/// [RequestSettings::ban_failed_address] is set. Up to [RequestSettings::retries] reconnects are
+/// done in a row; the counter is reset by every received message.
+///
+/// Nodes that send invalid data can be banned with [DapiStream::ban_current_node()].
+pub struct DapiStream<R: DapiStreamRequest> {
+    source: StreamSource<R>,
+}
+
+enum StreamSource<R: DapiStreamRequest> {
+    Dapi(Box<LiveStream<R>>),
+    #[cfg(feature = "mocks")]
+    Mock(VecDeque<StreamResult<R>>),
+}
+
+impl<R: DapiStreamRequest> DapiStream<R> {
+    /// Create a stream that returns `messages`, used by mocks.
+    #[cfg(feature = "mocks")]
+    pub(crate) fn from_messages(messages: VecDeque<StreamResult<R>>) -> Self {
+        Self {
+            source: StreamSource::Mock(messages),
+        }
+    }
+
+    /// Receive the next message; returns `None` when the stream ended.
+    pub async fn message(
+        &mut self,
+    ) -> Result<Option<R::Message>, DapiClientError<<R::Client as TransportClient>::Error>> {
+        match &mut self.source {
+            StreamSource::Dapi(stream) => stream.message().await,
+            #[cfg(feature = "mocks")]
+            StreamSource::Mock(messages) => messages.pop_front().transpose(),
+        }
+    }
+
+    /// Ban the node that sent the last received message, as it turned out to be invalid, and
+    /// reconnect to another node on the next [DapiStream::message()].
+    ///
+    /// The stream resumes from before the invalid message, regardless of
+    /// [RequestSettings::ban_failed_address]. Mock streams are not affected.
+    pub fn ban_current_node(&mut self) {
+        match &mut self.source {
+            StreamSource::Dapi(stream) => stream.ban_current_node(),
+            #[cfg(feature = "mocks")]
+            StreamSource::Mock(_) => {}
+        }
+    }
+}
+
+type TransportError<R> = <<R as TransportRequest>::Client as TransportClient>::Error;
+
+/// Stream received from the currently connected node.
+struct Connection<R: DapiStreamRequest> {
+    stream: R::Response,
+    address: Address,
+    /// Whether any message was received on this connection
+    received: bool,
+}
+
+/// Stream received from DAPI nodes.
+struct LiveStream<R: DapiStreamRequest> {
+    client: DapiClient,
+    /// Request resuming the stream after the last received message
+    request: R,
+    /// Request resuming the stream before the last received message
+    previous_request: Option<R>,
+    /// Address of the node that sent the last received message
+    last_sender: Option<Address>,
+    settings: AppliedRequestSettings,
+    /// Addresses already used by this stream, avoided on reconnect
+    used_addresses: Mutex<HashSet<Address>>,
+    connection: Option<Connection<R>>,
+    /// Number of reconnects since the last received message
+    reconnects: usize,
+    finished: bool,
+    #[cfg(feature = "dump")]
+    dump: Option<StreamDump<R>>,
+}
+
+impl<R: DapiStreamRequest> LiveStream<R> {
+    async fn connect(&mut self) -> Result<Connection<R>, DapiClientError<TransportError<R>>> {
+        let (stream, address) = self
+            .client
+            .execute_on_node(self.request.clone(), self.settings, &self.used_addresses)
+            .await?;
+
+        tracing::debug!(%address, method = self.request.method_name(), "stream connected");
+
+        Ok(Connection {
+            stream,
+            address,
+            received: false,
+        })
+    }
+
+    async fn message(&mut self) -> Result<Option<R::Message>, DapiClientError<TransportError<R>>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let result = self.receive().await;
+
+        #[cfg(feature = "dump")]
+        if let Some(dump) = &mut self.dump {
+            match &result {
+                Ok(Some(message)) => dump.messages.push_message::<R>(message),
+                Ok(None) => {}
+                Err(error) => dump.messages.push_error(error),
+            }
+        }
+
+        result
+    }
+
+    async fn receive(&mut self) -> Result<Option<R::Message>, DapiClientError<TransportError<R>>> {
+        loop {
+            let mut connection = match self.connection.take() {
+                Some(connection) => connection,
+                None => self.connect().await?,
+            };
+
+            match R::next_message(&mut connection.stream).await {
+                Ok(Some(message)) => {
+                    self.previous_request = Some(self.request.clone());
+                    self.finished = !self.request.resume_after(&message);
+                    self.last_sender = Some(connection.address.clone());
+                    self.reconnects = 0;
+                    connection.received = true;
+                    self.connection = Some(connection);
+
+                    tracing::trace!(?message, "received stream message");
+
+                    return Ok(Some(message));
+                }
+                Ok(None) => {
+                    if !self.request.is_unbounded() || self.reconnects >= self.settings.retries {
+                        self.finished = true;
+
+                        return Ok(None);
+                    }
+
+                    self.reconnect(&connection);
+                    tracing::warn!(address = %connection.address, "stream closed, reconnecting");
+                }
+                Err(error) => {
+                    let error = DapiClientError::Transport(error, connection.address.clone());
+                    if !error.is_node_failure() || self.reconnects >= self.settings.retries {
+                        return Err(error);
+                    }
+
+                    self.reconnect(&connection);
+                    tracing::warn!(?error, "stream failed, reconnecting");
+                }
+            }
+        }
+    }
+
+    /// Prepare to reconnect after `connection` failed or was closed.
+    fn reconnect(&mut self, connection: &Connection<R>) {
+        // A stream that already delivered messages can be closed by the node for reasons like
+        // request timeout, so only nodes that failed to deliver anything are treated as failed.
+        if !connection.received {
+            self.client
+                .record_node_failure(&connection.address, &self.settings);
+        }
+
+        self.reconnects += 1;
+    }
+
+    /// Ban the sender of the last message, and resume the stream from before that message.
+    fn ban_current_node(&mut self) {
+        let Some(address) = self.last_sender.take() else {
+            return;
+        };
+
+        tracing::warn!(%address, "banning node that sent invalid stream message");
+        self.client.ban_node(&address, &self.settings);
+
+        if self
+            .connection
+            .as_ref()
+            .is_some_and(|connection| connection.address == address)
+        {
+            self.connection = None;
+        }
+        if let Some(request) = self.previous_request.take() {
+            self.request = request;
+        }
+        self.finished = false;
+    }
+}
+
+#[async_trait]
+impl DapiStreamExecutor for DapiClient {
+    async fn stream<R>(
+        &self,
+        request: R,
+        settings: RequestSettings,
+    ) -> Result<DapiStream<R>, DapiClientError<<R::Client as TransportClient>::Error>>
+    where
+        R: DapiStreamRequest,
+    {
+        let mut stream = LiveStream {
+            client: self.clone(),
+            settings: self.apply_settings::<R>(settings),
+            previous_request: None,
+            last_sender: None,
+            used_addresses: Mutex::new(HashSet::new()),
+            connection: None,
+            reconnects: 0,
+            finished: false,
+            #[cfg(feature = "dump")]
+            dump: self.dump_dir.clone().map(|dump_dir| StreamDump {
+                dump_dir,
+                request: request.clone(),
+                messages: SerializedStream::default(),
+            }),
+            request,
+        };
+
+        match stream.connect().await {
+            Ok(connection) => stream.connection = Some(connection),
+            Err(error) => {
+                #[cfg(feature = "dump")]
+                if let Some(dump) = &mut stream.dump {
+                    dump.messages.push_error(&error);
+                }
+
+                return Err(error);
+            }
+        }
+
+        Ok(DapiStream {
+            source: StreamSource::Dapi(Box::new(stream)),
+        })
+    }
+}
+
+/// Messages received from a stream, saved to disk when the stream is dropped.
+#[cfg(feature = "dump")]
+struct StreamDump<R: DapiStreamRequest> {
+    dump_dir: std::path::PathBuf,
+    /// Request that opened the stream
+    request: R,
+    messages: SerializedStream,
+}
+
+#[cfg(feature = "dump")]
+impl<R: DapiStreamRequest> Drop for LiveStream<R> {
+    fn drop(&mut self) {
+        if let Some(dump) = &self.dump {
+            DapiClient::dump_stream(&dump.request, &dump.messages, &dump.dump_dir);
+        }
+    }
+}
+
+/// Serialized message or error received from a stream.
+#[cfg(feature = "mocks")]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+enum SerializedStreamItem {
+    Message(Vec<u8>),
+    Error(Vec<u8>),
+}
+
+/// Serialized messages of a stream, used by mocks and dumps.
+#[cfg(feature = "mocks")]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SerializedStream(Vec<SerializedStreamItem>);
+
+#[cfg(feature = "mocks")]
+impl SerializedStream {
+    /// Serialize results of a stream.
+    pub(crate) fn new<R: DapiStreamRequest>(results: &[StreamResult<R>]) -> Self {
+        let mut stream = Self::default();
+        for result in results {
+            match result {
+                Ok(message) => stream.push_message::<R>(message),
+                Err(error) => stream.push_error(error),
+            }
+        }
+
+        stream
+    }
+
+    fn push_message<R: DapiStreamRequest>(&mut self, message: &R::Message) {
+        self.0.push(SerializedStreamItem::Message(
+            message
+                .mock_serialize()
+                .expect("unable to serialize stream message"),
+        ));
+    }
+
+    fn push_error<E: Mockable>(&mut self, error: &E) {
+        self.0.push(SerializedStreamItem::Error(
+            error
+                .mock_serialize()
+                .expect("unable to serialize stream error"),
+        ));
+    }
+
+    /// Deserialize results of a stream.
+    pub(crate) fn deserialize<R: DapiStreamRequest>(&self) -> VecDeque<StreamResult<R>> {
+        self.0
+            .iter()
+            .map(|item| match item {
+                SerializedStreamItem::Message(data) => Ok(R::Message::mock_deserialize(data)
+                    .expect("unable to deserialize stream message")),
+                SerializedStreamItem::Error(data) => Err(DapiClientError::mock_deserialize(data)
+                    .expect("unable to deserialize stream error")),
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "mocks")]
+impl Mockable for SerializedStream {
+    fn mock_serialize(&self) -> Option<Vec<u8>> {
+        Some(serde_json::to_vec(&self.0).expect("unable to serialize stream"))
+    }
+
+    fn mock_deserialize(data: &[u8]) -> Option<Self> {
+        Some(Self(
+            serde_json::from_slice(data).expect("unable to deserialize stream"),
+        ))
+    }
+}
@@ -24,6 +24,12 @@ pub trait TransportRequest: Clone + Send + Sync + Debug + Mockable {
     /// Settings that will override [DapiClient](crate::DapiClient)'s ones each time the request is executed.
     const SETTINGS_OVERRIDES: RequestSettings;
 
+    /// Whether executing the request more than once has the same effect as executing it once.
+    ///
+    /// Requests that are not idempotent, like broadcasts, are never sent to many nodes at a time,
+    /// regardless of [RequestSettings::fan_out] and [RequestSettings::quorum].
+    const IDEMPOTENT: bool = true;
+
     /// gRPC request name
     fn request_name(&self) -> &'static str {
         any::type_name::<Self>()
@@ -4,7 +4,7 @@ use std::time::Duration;
 
 use super::{CanRetry, TransportClient, TransportRequest};
 use crate::connection_pool::{ConnectionPool, PoolPrefix};
-use crate::{request_settings::AppliedRequestSettings, RequestSettings};
+use crate::{request_settings::AppliedRequestSettings, DapiStreamRequest, RequestSettings};
 use dapi_grpc::core::v0::core_client::CoreClient;
 use dapi_grpc::core::v0::{self as core_proto};
 use dapi_grpc::platform::v0::{self as platform_proto, platform_client::PlatformClient};
@@ -12,6 +12,7 @@ use dapi_grpc::tonic::transport::Uri;
 use dapi_grpc::tonic::Streaming;
 use dapi_grpc::tonic::{transport::Channel, IntoRequest};
 use futures::{future::BoxFuture, FutureExt, TryFutureExt};
+use sha2::{Digest, Sha256};
 
 /// Platform Client using gRPC transport.
 pub type PlatformGrpcClient = PlatformClient<Channel>;
@@ -95,8 +96,14 @@ impl CanRetry for dapi_grpc::tonic::Status {
 
 /// A shortcut to link between gRPC request type, response type, client and its
 /// method in order to represent it in a form of types and data.
+///
+/// Requests prefixed with `non_idempotent` are never sent to many nodes at a time, see
+/// [TransportRequest::IDEMPOTENT].
 macro_rules! impl_transport_request_grpc {
-    ($request:ty, $response:ty, $client:ty, $settings:expr, $($method:tt)+) => {
+    (non_idempotent $request:ty, $response:ty, $client:ty, $settings:expr, $($method:tt)+) => {
+        impl_transport_request_grpc!(@impl false, $request, $response, $client, $settings, $($method)+);
+    };
+    (@impl $idempotent:expr, $request:ty, $response:ty, $client:ty, $settings:expr, $($method:tt)+) => {
         impl TransportRequest for $request {
             type Client = $client;
 
@@ -104,6 +111,8 @@ macro_rules! impl_transport_request_grpc {
 
             const SETTINGS_OVERRIDES: RequestSettings = $settings;
 
+            const IDEMPOTENT: bool = $idempotent;
+
             fn method_name(&self) -> &'static str {
                 stringify!($($method)+)
             }
@@ -127,6 +136,9 @@ macro_rules! impl_transport_request_grpc {
             }
         }
     };
+    ($request:ty, $response:ty, $client:ty, $settings:expr, $($method:tt)+) => {
+        impl_transport_request_grpc!(@impl true, $request, $response, $client, $settings, $($method)+);
+    };
 }
 
 // Link to each platform gRPC request what client and method to use:
@@ -174,7 +186,7 @@ impl_transport_request_grpc!(
 );
 
 impl_transport_request_grpc!(
-    platform_proto::BroadcastStateTransitionRequest,
+    non_idempotent platform_proto::BroadcastStateTransitionRequest,
     platform_proto::BroadcastStateTransitionResponse,
     PlatformGrpcClient,
     RequestSettings::default(),
@@ -192,6 +204,14 @@ impl_transport_request_grpc!(
     wait_for_state_transition_result
 );
 
+impl_transport_request_grpc!(
+    platform_proto::GetProofsRequest,
+    platform_proto::GetProofsResponse,
+    PlatformGrpcClient,
+    RequestSettings::default(),
+    get_proofs
+);
+
 impl_transport_request_grpc!(
     platform_proto::GetIdentityByPublicKeyHashRequest,
     platform_proto::GetIdentityByPublicKeyHashResponse,
@@ -369,7 +389,7 @@ impl_transport_request_grpc!(
 );
 
 impl_transport_request_grpc!(
-    core_proto::BroadcastTransactionRequest,
+    non_idempotent core_proto::BroadcastTransactionRequest,
     core_proto::BroadcastTransactionResponse,
     CoreGrpcClient,
     RequestSettings::default(),
@@ -386,3 +406,198 @@ impl_transport_request_grpc!(
     },
     subscribe_to_transactions_with_proofs
 );
+
+impl_transport_request_grpc!(
+    core_proto::MasternodeListRequest,
+    Streaming<core_proto::MasternodeListResponse>,
+    CoreGrpcClient,
+    RequestSettings {
+        timeout: Some(STREAMING_TIMEOUT),
+        ..RequestSettings::default()
+    },
+    subscribe_to_masternode_list
+);
+
+impl_transport_request_grpc!(
+    core_proto::BlockHeadersWithChainLocksRequest,
+    Streaming<core_proto::BlockHeadersWithChainLocksResponse>,
+    CoreGrpcClient,
+    RequestSettings {
+        timeout: Some(STREAMING_TIMEOUT),
+        ..RequestSettings::default()
+    },
+    subscribe_to_block_headers_with_chain_locks
+);
+
+/// Size of a serialized block header, which is also the prefix of a serialized merkle block.
+const BLOCK_HEADER_SIZE: usize = 80;
+
+/// Receive the next message from a gRPC stream.
+fn next_grpc_message<T: Send>(
+    stream: &mut Streaming<T>,
+) -> BoxFuture<'_, Result<Option<T>, dapi_grpc::tonic::Status>> {
+    stream.message().boxed()
+}
+
+/// Hash of the block with `header`, in the byte order used by `from_block_hash` (reversed, as
+/// displayed by Dash Core); `None` if the header is too short.
+fn block_hash(header: &[u8]) -> Option<Vec<u8>> {
+    let header = header.get(..BLOCK_HEADER_SIZE)?;
+    let mut hash = Sha256::digest(Sha256::digest(header)).to_vec();
+    hash.reverse();
+
+    Some(hash)
+}
+
+/// Number of requested blocks covered by `received` consecutive blocks, the first of them with
+/// `first_hash`, in a stream requested from the `from_hash` block.
+///
+/// After the stream is resumed from the last received block, further messages on the same
+/// connection continue after that block, so it's covered as well.
+fn covered_blocks(from_hash: Option<&Vec<u8>>, first_hash: Option<&Vec<u8>>, received: u32) -> u32 {
+    match from_hash {
+        // blocks follow the one we resume from, which was covered by previous messages
+        Some(from_hash) if first_hash != Some(from_hash) => received + 1,
+        _ => received,
+    }
+}
+
+impl DapiStreamRequest for core_proto::BlockHeadersWithChainLocksRequest {
+    type Message = core_proto::BlockHeadersWithChainLocksResponse;
+
+    fn next_message(
+        stream: &mut Self::Response,
+    ) -> BoxFuture<'_, Result<Option<Self::Message>, <Self::Client as TransportClient>::Error>>
+    {
+        next_grpc_message(stream)
+    }
+
+    /// Resume from the block after the last received header, and request only the remaining
+    /// headers; when all of them were received, the stream is finished.
+    ///
+    /// If the stream started from a block hash, height of received headers is unknown, so the
+    /// stream resumes from the last received header, which is received again.
+    fn resume_after(&mut self, message: &Self::Message) -> bool {
+        use core_proto::block_headers_with_chain_locks_request::FromBlock;
+        use core_proto::block_headers_with_chain_locks_response::Responses;
+
+        let Some(Responses::BlockHeaders(block_headers)) = &message.responses else {
+            return true;
+        };
+        let received = block_headers.headers.len() as u32;
+        if received == 0 {
+            return true;
+        }
+
+        let (from_block, covered) = match &self.from_block {
+            Some(FromBlock::FromBlockHeight(height)) => {
+                (FromBlock::FromBlockHeight(height + received), received)
+            }
+            from_block => {
+                let Some(hash) = block_headers.headers.last().and_then(|h| block_hash(h)) else {
+                    return true;
+                };
+                let from_hash = match from_block {
+                    Some(FromBlock::FromBlockHash(from_hash)) => Some(from_hash),
+                    _ => None,
+                };
+                let first_hash = block_headers.headers.first().and_then(|h| block_hash(h));
+
+                (
+                    FromBlock::FromBlockHash(hash),
+                    covered_blocks(from_hash, first_hash.as_ref(), received),
+                )
+            }
+        };
+
+        // 0 means no limit
+        if self.count > 0 {
+            let remaining = self.count.saturating_sub(covered);
+            if remaining == 0 {
+                return false;
+            }
+
+            self.count = match from_block {
+                FromBlock::FromBlockHeight(_) => remaining,
+                // the last received header is received again
+                FromBlock::FromBlockHash(_) => remaining + 1,
+            };
+        }
+        self.from_block = Some(from_block);
+
+        true
+    }
+
+    fn is_unbounded(&self) -> bool {
+        self.count == 0
+    }
+}
+
+impl DapiStreamRequest for core_proto::TransactionsWithProofsRequest {
+    type Message = core_proto::TransactionsWithProofsResponse;
+
+    fn next_message(
+        stream: &mut Self::Response,
+    ) -> BoxFuture<'_, Result<Option<Self::Message>, <Self::Client as TransportClient>::Error>>
+    {
+        next_grpc_message(stream)
+    }
+
+    /// Resume from the block of the last received merkle block, which is received again, and
+    /// request only the remaining blocks.
+    ///
+    /// Historical blocks are followed by their transactions, so the stream is finished only when
+    /// the node closes it.
+    fn resume_after(&mut self, message: &Self::Message) -> bool {
+        use core_proto::transactions_with_proofs_request::FromBlock;
+        use core_proto::transactions_with_proofs_response::Responses;
+
+        let Some(Responses::RawMerkleBlock(merkle_block)) = &message.responses else {
+            return true;
+        };
+        let Some(hash) = block_hash(merkle_block) else {
+            return true;
+        };
+
+        // 0 means no limit
+        if self.count > 0 {
+            let from_hash = match &self.from_block {
+                Some(FromBlock::FromBlockHash(from_hash)) => Some(from_hash),
+                _ => None,
+            };
+            let covered = covered_blocks(from_hash, Some(&hash), 1);
+
+            // the last received block is received again
+            self.count = self.count.saturating_sub(covered) + 1;
+        }
+        self.from_block = Some(FromBlock::FromBlockHash(hash));
+
+        true
+    }
+
+    fn is_unbounded(&self) -> bool {
+        self.count == 0
+    }
+}
+
+impl DapiStreamRequest for core_proto::MasternodeListRequest {
+    type Message = core_proto::MasternodeListResponse;
+
+    fn next_message(
+        stream: &mut Self::Response,
+    ) -> BoxFuture<'_, Result<Option<Self::Message>, <Self::Client as TransportClient>::Error>>
+    {
+        next_grpc_message(stream)
+    }
+
+    /// Masternode list stream starts with the full list on every connection, so the request
+    /// doesn't change.
+    fn resume_after(&mut self, _message: &Self::Message) -> bool {
+        true
+    }
+
+    /// The stream sends changes of the masternode list as they happen, and never ends.
+    fn is_unbounded(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,52 @@
+use dapi_grpc::tonic::transport::Uri;
+use rs_dapi_client::AddressList;
+
+/// Given an address list, when I replace its addresses, then removed addresses are dropped and
+/// new ones are added.
+#[test]
+fn test_address_list_replace_uris() {
+    let mut list = AddressList::from("http://127.0.0.1:1443,http://127.0.0.2:1443");
+
+    list.replace_uris([
+        Uri::from_static("http://127.0.0.2:1443"),
+        Uri::from_static("http://127.0.0.3:1443"),
+    ]);
+
+    let mut uris: Vec<String> = list
+        .addresses()
+        .map(|address| address.uri().to_string())
+        .collect();
+    uris.sort();
+
+    assert_eq!(
+        uris,
+        vec!["http://127.0.0.2:1443/", "http://127.0.0.3:1443/"]
+    );
+    assert_eq!(list.available(), 2);
+}
+
+/// Given an address list with fallback seed addresses, when I replace its addresses, then seeds are
+/// kept, but used only when no replaced address is live.
+#[test]
+fn test_address_list_keeps_fallback_uris() {
+    let mut list = AddressList::new();
+    list.set_fallback_uris([Uri::from_static("http://127.0.0.1:1443")]);
+    assert_eq!(list.available(), 1);
+
+    list.replace_uris([Uri::from_static("http://127.0.0.2:1443")]);
+    assert_eq!(list.len(), 2);
+    assert_eq!(list.available(), 1);
+    assert_eq!(
+        list.get_live_address()
+            .map(|address| address.uri().to_string()),
+        Some("http://127.0.0.2:1443/".to_string())
+    );
+
+    list.replace_uris(Vec::<Uri>::new());
+    assert_eq!(list.len(), 1);
+    assert_eq!(
+        list.get_live_address()
+            .map(|address| address.uri().to_string()),
+        Some("http://127.0.0.1:1443/".to_string())
+    );
+}
@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use dapi_grpc::tonic::transport::Uri;
+use rs_dapi_client::{
+    Address, AddressList, AddressSelector, AddressStats, RandomSelector, RoundRobinSelector,
+    WeightedSelector,
+};
+
+fn addresses() -> Vec<Address> {
+    [
+        "http://127.0.0.1:1443",
+        "http://127.0.0.2:1443",
+        "http://127.0.0.3:1443",
+    ]
+    .into_iter()
+    .map(|uri| Address::from(Uri::from_static(uri)))
+    .collect()
+}
+
+/// Given a list of addresses, when I select with round-robin strategy, then each address is
+/// selected once per round.
+#[test]
+fn test_round_robin_selects_all_addresses() {
+    let list = addresses()
+        .into_iter()
+        .fold(AddressList::new(), |mut list, address| {
+            list.add(address);
+            list
+        })
+        .with_selector(RoundRobinSelector::new());
+
+    let first_round: HashSet<Uri> = (0..3)
+        .map(|_| list.get_live_address().expect("address").uri().clone())
+        .collect();
+    assert_eq!(first_round.len(), 3);
+
+    let first = list.get_live_address().expect("address").uri().clone();
+    list.get_live_address();
+    list.get_live_address();
+    assert_eq!(list.get_live_address().expect("address").uri(), &first);
+}
+
+/// When there are no addresses, then no strategy selects anything.
+#[test]
+fn test_selectors_empty_list() {
+    let selectors: Vec<Box<dyn AddressSelector>> = vec![
+        Box::new(RandomSelector),
+        Box::new(RoundRobinSelector::new()),
+        Box::new(WeightedSelector::new()),
+    ];
+
+    for selector in selectors {
+        assert!(selector.select(&[]).is_none(), "{:?}", selector);
+    }
+}
+
+/// Given addresses without statistics, when I select with weighted strategy, then some address
+/// is selected.
+#[test]
+fn test_weighted_selects_unmeasured_addresses() {
+    let addresses = addresses();
+    let refs: Vec<&Address> = addresses.iter().collect();
+
+    let selected = WeightedSelector::new().select(&refs).expect("address");
+
+    assert!(addresses.contains(selected));
+}
+
+/// Given address statistics, when I record successes and failures, then averages are updated.
+#[test]
+fn test_address_stats_ewma() {
+    let mut stats = AddressStats::default();
+    assert_eq!(stats.ewma_latency, None);
+    assert_eq!(stats.ewma_success_rate, 1.0);
+
+    stats.record_success(Duration::from_millis(100));
+    assert_eq!(stats.ewma_latency, Some(Duration::from_millis(100)));
+
+    stats.record_success(Duration::from_millis(200));
+    assert_eq!(stats.ewma_latency, Some(Duration::from_millis(120)));
+
+    stats.record_failure();
+    stats.record_failure();
+    assert_eq!(stats.success_count, 2);
+    assert_eq!(stats.failure_count, 2);
+    assert_eq!(stats.consecutive_failures, 2);
+    assert!(stats.ewma_success_rate < 1.0);
+
+    stats.record_success(Duration::from_millis(120));
+    assert_eq!(stats.consecutive_failures, 0);
+}
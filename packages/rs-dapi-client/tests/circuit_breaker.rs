@@ -0,0 +1,270 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use dapi_grpc::platform::v0::GetIdentityRequest;
+use rs_dapi_client::{
+    Address, AddressList, AddressSelector, CircuitState, DapiClient, DapiClientError,
+    DapiRequestExecutor, RateLimit, RequestSettings,
+};
+
+/// Addresses where nothing listens, so that every request fails with a node failure.
+const UNREACHABLE_ADDRESSES: &str = "http://127.0.0.1:1,http://127.0.0.1:2";
+
+/// Given default settings, when I finalize them, then circuit breakers are disabled and requests
+/// are not rate limited.
+#[test]
+fn test_circuit_breaker_default_settings() {
+    let settings = RequestSettings::default().finalize();
+
+    assert_eq!(settings.address_failure_threshold, 0);
+    assert_eq!(settings.global_failure_threshold, 0);
+    assert_eq!(settings.circuit_open_duration, Duration::from_secs(10));
+    assert_eq!(settings.rate_limit, None);
+}
+
+/// Given client and call settings, when I override them, then the most specific settings win.
+#[test]
+fn test_rate_limit_settings_override() {
+    let client_settings = RequestSettings {
+        rate_limit: Some(RateLimit::new(10.0, 5)),
+        global_failure_threshold: Some(50),
+        ..RequestSettings::default()
+    };
+    let call_settings = RequestSettings {
+        rate_limit: Some(RateLimit::new(1.0, 1)),
+        ..RequestSettings::default()
+    };
+
+    let settings = client_settings.override_by(call_settings).finalize();
+
+    assert_eq!(settings.rate_limit, Some(RateLimit::new(1.0, 1)));
+    assert_eq!(settings.global_failure_threshold, 50);
+}
+
+/// Given nodes that don't respond, when failures reach the thresholds, then circuits of both
+/// addresses and the global circuit open, and further requests fail without being sent, as no
+/// address is available.
+#[tokio::test]
+async fn test_circuit_opens_after_failures() {
+    let settings = RequestSettings {
+        retries: Some(1),
+        ban_failed_address: Some(false),
+        address_failure_threshold: Some(1),
+        global_failure_threshold: Some(2),
+        circuit_open_duration: Some(Duration::from_secs(60)),
+        ..RequestSettings::default()
+    };
+    let client = DapiClient::new(AddressList::from(UNREACHABLE_ADDRESSES), settings);
+
+    let error = client
+        .execute(GetIdentityRequest::default(), RequestSettings::default())
+        .await
+        .expect_err("node failure");
+    assert!(matches!(error, DapiClientError::Transport(..)), "{error:?}");
+
+    assert!(client
+        .addresses()
+        .iter()
+        .all(|address| address.circuit_state() == CircuitState::Open));
+    assert_eq!(client.circuit_state(), CircuitState::Open);
+
+    let error = client
+        .execute(GetIdentityRequest::default(), RequestSettings::default())
+        .await
+        .expect_err("circuits open");
+    assert!(
+        matches!(error, DapiClientError::NoAvailableAddresses),
+        "{error:?}"
+    );
+}
+
+/// Given nodes that don't respond and circuits of addresses disabled, when failures reach the global
+/// threshold, then the global circuit opens and further requests fail without being sent.
+#[tokio::test]
+async fn test_global_circuit_opens_after_failures() {
+    let settings = RequestSettings {
+        retries: Some(1),
+        ban_failed_address: Some(false),
+        address_failure_threshold: Some(0),
+        global_failure_threshold: Some(2),
+        circuit_open_duration: Some(Duration::from_secs(60)),
+        ..RequestSettings::default()
+    };
+    let client = DapiClient::new(AddressList::from(UNREACHABLE_ADDRESSES), settings);
+
+    client
+        .execute(GetIdentityRequest::default(), RequestSettings::default())
+        .await
+        .expect_err("node failure");
+
+    assert!(client
+        .addresses()
+        .iter()
+        .all(|address| address.circuit_state() == CircuitState::Closed));
+    assert_eq!(client.circuit_state(), CircuitState::Open);
+
+    let error = client
+        .execute(GetIdentityRequest::default(), RequestSettings::default())
+        .await
+        .expect_err("circuit open");
+    assert!(matches!(error, DapiClientError::CircuitOpen), "{error:?}");
+}
+
+/// Given a half-open global circuit, when the probe request is cancelled before it's sent, then the
+/// probe is released and the next request is let through.
+#[tokio::test]
+async fn test_cancelled_probe_is_released() {
+    let settings = RequestSettings {
+        retries: Some(0),
+        ban_failed_address: Some(false),
+        address_failure_threshold: Some(0),
+        global_failure_threshold: Some(1),
+        circuit_open_duration: Some(Duration::from_millis(200)),
+        // only the first request gets a token, others wait for the limiter until cancelled
+        rate_limit: Some(RateLimit::new(0.001, 1)),
+        ..RequestSettings::default()
+    };
+    let client = DapiClient::new(AddressList::from("http://127.0.0.1:1"), settings);
+
+    client
+        .execute(GetIdentityRequest::default(), RequestSettings::default())
+        .await
+        .expect_err("node failure");
+    assert_eq!(client.circuit_state(), CircuitState::Open);
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+
+    for _ in 0..2 {
+        tokio::time::timeout(
+            Duration::from_millis(50),
+            client.execute(GetIdentityRequest::default(), RequestSettings::default()),
+        )
+        .await
+        .expect_err("probe waits for the rate limiter");
+    }
+}
+
+/// Selector that always prefers the first address, so that concurrent requests race for it.
+#[derive(Debug)]
+struct FirstSelector;
+
+impl AddressSelector for FirstSelector {
+    fn select<'a>(&self, addresses: &[&'a Address]) -> Option<&'a Address> {
+        addresses
+            .iter()
+            .min_by_key(|address| address.uri().to_string())
+            .copied()
+    }
+}
+
+/// Given a half-open circuit of one address and another live address, when concurrent requests
+/// race for the probe of the half-open address, then requests that lose it are sent to the other
+/// address instead of failing.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_lost_probe_selects_another_address() {
+    let settings = RequestSettings {
+        retries: Some(0),
+        ban_failed_address: Some(false),
+        address_failure_threshold: Some(1),
+        global_failure_threshold: Some(0),
+        circuit_open_duration: Some(Duration::from_millis(200)),
+        // only the first request gets a token, others wait for the limiter until cancelled
+        rate_limit: Some(RateLimit::new(0.001, 1)),
+        ..RequestSettings::default()
+    };
+    let address_list = AddressList::from(UNREACHABLE_ADDRESSES).with_selector(FirstSelector);
+    let client = Arc::new(DapiClient::new(address_list, settings));
+
+    client
+        .execute(GetIdentityRequest::default(), RequestSettings::default())
+        .await
+        .expect_err("node failure");
+    let states: Vec<_> = client
+        .addresses()
+        .iter()
+        .map(Address::circuit_state)
+        .collect();
+    assert!(states.contains(&CircuitState::Open), "{states:?}");
+    assert!(states.contains(&CircuitState::Closed), "{states:?}");
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+
+    let requests: Vec<_> = (0..16)
+        .map(|_| {
+            let client = Arc::clone(&client);
+            tokio::spawn(async move {
+                tokio::time::timeout(
+                    Duration::from_millis(100),
+                    client.execute(GetIdentityRequest::default(), RequestSettings::default()),
+                )
+                .await
+            })
+        })
+        .collect();
+
+    for request in requests {
+        let result = request.await.expect("request task panicked");
+        assert!(
+            result.is_err(),
+            "request should wait for the rate limiter: {result:?}"
+        );
+    }
+}
+
+/// Given default settings, when requests to nodes that don't respond fail many times, then no
+/// circuit opens and every request is sent and retried.
+#[tokio::test]
+async fn test_circuits_stay_closed_by_default() {
+    let settings = RequestSettings {
+        retries: Some(2),
+        ban_failed_address: Some(false),
+        ..RequestSettings::default()
+    };
+    let client = DapiClient::new(AddressList::from(UNREACHABLE_ADDRESSES), settings);
+
+    for _ in 0..10 {
+        let error = client
+            .execute(GetIdentityRequest::default(), RequestSettings::default())
+            .await
+            .expect_err("node failure");
+        assert!(matches!(error, DapiClientError::Transport(..)), "{error:?}");
+    }
+
+    assert!(client
+        .addresses()
+        .iter()
+        .all(|address| address.circuit_state() == CircuitState::Closed));
+    assert_eq!(client.circuit_state(), CircuitState::Closed);
+}
+
+/// When I create a rate limit that allows no requests, then it's rejected.
+#[test]
+#[should_panic(expected = "requests per second must be positive")]
+fn test_rate_limit_rejects_non_positive_rate() {
+    RateLimit::new(0.0, 1);
+}
+
+/// Given a rate limit of 10 requests per second without burst, when a request is retried twice,
+/// then retries wait for the limiter.
+#[tokio::test]
+async fn test_rate_limit_delays_retries() {
+    let settings = RequestSettings {
+        retries: Some(2),
+        ban_failed_address: Some(false),
+        address_failure_threshold: Some(0),
+        global_failure_threshold: Some(0),
+        rate_limit: Some(RateLimit::new(10.0, 1)),
+        ..RequestSettings::default()
+    };
+    let client = DapiClient::new(AddressList::from(UNREACHABLE_ADDRESSES), settings);
+
+    let started = std::time::Instant::now();
+    client
+        .execute(GetIdentityRequest::default(), RequestSettings::default())
+        .await
+        .expect_err("node failure");
+
+    // first attempt uses the initial token, each retry waits 100 ms for a new one
+    assert!(started.elapsed() >= Duration::from_millis(180));
+    assert_eq!(client.circuit_state(), CircuitState::Closed);
+}
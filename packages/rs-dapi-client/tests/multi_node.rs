@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use dapi_grpc::platform::v0::{BroadcastStateTransitionRequest, GetIdentityRequest};
+use rs_dapi_client::{
+    transport::TransportRequest, AddressList, DapiClient, DapiRequestExecutor, RequestSettings,
+};
+
+/// Addresses where nothing listens, so that every request fails with a node failure.
+const UNREACHABLE_ADDRESSES: &str = "http://127.0.0.1:1,http://127.0.0.1:2";
+
+/// Given settings requiring a quorum higher than fan-out, when I finalize them, then the request is
+/// sent to enough nodes to reach the quorum.
+#[test]
+fn test_quorum_increases_fan_out() {
+    let settings = RequestSettings {
+        fan_out: Some(2),
+        quorum: Some(3),
+        ..RequestSettings::default()
+    }
+    .finalize();
+
+    assert_eq!(settings.fan_out, 3);
+    assert_eq!(settings.quorum, 3);
+}
+
+/// Given default settings, when I finalize them, then the request is sent to a single node.
+#[test]
+fn test_single_node_by_default() {
+    let settings = RequestSettings::default().finalize();
+
+    assert_eq!(settings.fan_out, 1);
+    assert_eq!(settings.quorum, 1);
+    assert_eq!(settings.hedge_delay, None);
+}
+
+/// Given hedged request settings, when I override them, then the most specific settings win.
+#[test]
+fn test_hedging_settings_override() {
+    let client_settings = RequestSettings {
+        fan_out: Some(3),
+        hedge_delay: Some(Duration::from_millis(200)),
+        ..RequestSettings::default()
+    };
+    let call_settings = RequestSettings {
+        hedge_delay: Some(Duration::from_millis(50)),
+        ..RequestSettings::default()
+    };
+
+    let settings = client_settings.override_by(call_settings).finalize();
+
+    assert_eq!(settings.fan_out, 3);
+    assert_eq!(settings.hedge_delay, Some(Duration::from_millis(50)));
+}
+
+/// Send `request` with fan-out to all unreachable nodes, and return the number of nodes it was sent to.
+async fn nodes_used<R: TransportRequest>(request: R) -> u64 {
+    let settings = RequestSettings {
+        retries: Some(0),
+        ban_failed_address: Some(false),
+        fan_out: Some(2),
+        ..RequestSettings::default()
+    };
+    let client = DapiClient::new(AddressList::from(UNREACHABLE_ADDRESSES), settings);
+
+    client
+        .execute(request, RequestSettings::default())
+        .await
+        .expect_err("nodes are unreachable");
+
+    client
+        .addresses()
+        .iter()
+        .map(|address| address.stats().failure_count)
+        .sum()
+}
+
+/// Given fan-out to many nodes, when I broadcast a state transition, then it's sent to a single node,
+/// while a read request is sent to all of them.
+#[tokio::test]
+async fn test_non_idempotent_requests_are_not_fanned_out() {
+    assert_eq!(nodes_used(GetIdentityRequest::default()).await, 2);
+    assert_eq!(
+        nodes_used(BroadcastStateTransitionRequest::default()).await,
+        1
+    );
+}
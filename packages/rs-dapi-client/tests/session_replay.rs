@@ -0,0 +1,166 @@
+#[cfg(feature = "mocks")]
+use {
+    dapi_grpc::platform::v0::{
+        get_identity_request::GetIdentityRequestV0,
+        get_identity_response::{self, GetIdentityResponseV0},
+        GetIdentityRequest, GetIdentityResponse, ResponseMetadata,
+    },
+    dapi_grpc::tonic::transport::Uri,
+    rs_dapi_client::{
+        mock::MockError,
+        session::{ReplayDapiClient, SessionRecorder},
+        Address, DapiClientError, DapiRequest, RequestSettings,
+    },
+};
+
+#[cfg(feature = "mocks")]
+fn request(id: u8) -> GetIdentityRequest {
+    GetIdentityRequestV0 {
+        id: vec![id; 32],
+        prove: true,
+    }
+    .into()
+}
+
+#[cfg(feature = "mocks")]
+fn response(height: u64) -> GetIdentityResponse {
+    GetIdentityResponse {
+        version: Some(get_identity_response::Version::V0(GetIdentityResponseV0 {
+            result: None,
+            metadata: Some(ResponseMetadata {
+                height,
+                ..Default::default()
+            }),
+        })),
+    }
+}
+
+/// Given a recorded session where the same request got different responses,
+/// when I replay it, then responses are served in the recorded order.
+#[tokio::test]
+#[cfg(feature = "mocks")]
+async fn test_session_replay_repeated_requests() {
+    let recorder = SessionRecorder::new();
+    recorder.record(&request(1), &Ok(response(10)));
+    recorder.record(&request(2), &Ok(response(20)));
+    recorder.record(&request(1), &Ok(response(11)));
+
+    let file = std::env::temp_dir().join(format!("session-replay-{}.jsonl", std::process::id()));
+    recorder.save(&file).expect("save session");
+    let replay = ReplayDapiClient::load(&file).expect("load session");
+    std::fs::remove_file(&file).expect("remove session file");
+
+    let settings = RequestSettings::default();
+    assert_eq!(
+        request(1).execute(&replay, settings).await.unwrap(),
+        response(10)
+    );
+    assert_eq!(
+        request(1).execute(&replay, settings).await.unwrap(),
+        response(11)
+    );
+    assert_eq!(
+        request(2).execute(&replay, settings).await.unwrap(),
+        response(20)
+    );
+
+    replay.verify().expect("whole session replayed");
+}
+
+/// Given a recorded session, when I replay it with strict order and send requests in different order,
+/// then out-of-order requests fail.
+#[tokio::test]
+#[cfg(feature = "mocks")]
+async fn test_session_replay_strict_order() {
+    let recorder = SessionRecorder::new();
+    recorder.record(&request(1), &Ok(response(10)));
+    recorder.record(&request(2), &Ok(response(20)));
+
+    let replay = ReplayDapiClient::new(recorder.entries()).with_strict_order(true);
+    let settings = RequestSettings::default();
+
+    let result = request(2).execute(&replay, settings).await;
+    assert!(matches!(
+        result,
+        Err(DapiClientError::Mock(MockError::MockExpectationNotFound(_)))
+    ));
+
+    assert_eq!(
+        request(1).execute(&replay, settings).await.unwrap(),
+        response(10)
+    );
+    assert_eq!(
+        request(2).execute(&replay, settings).await.unwrap(),
+        response(20)
+    );
+
+    assert_eq!(replay.unmatched_requests().len(), 1);
+    assert!(replay.unconsumed_entries().is_empty());
+}
+
+/// Given a recorded session, when I replay only part of it and send an unknown request,
+/// then verification reports both the unmatched request and unconsumed entries.
+#[tokio::test]
+#[cfg(feature = "mocks")]
+async fn test_session_replay_verify_reports_mismatches() {
+    let recorder = SessionRecorder::new();
+    recorder.record(&request(1), &Ok(response(10)));
+    recorder.record(&request(2), &Ok(response(20)));
+
+    let replay = ReplayDapiClient::new(recorder.entries());
+    let settings = RequestSettings::default();
+
+    request(1).execute(&replay, settings).await.unwrap();
+    request(3).execute(&replay, settings).await.unwrap_err();
+
+    let unconsumed = replay.unconsumed_entries();
+    assert_eq!(unconsumed.len(), 1);
+    assert_eq!(unconsumed[0].request_type, "GetIdentityRequest");
+
+    assert!(matches!(
+        replay.verify(),
+        Err(MockError::SessionMismatch(_))
+    ));
+}
+
+/// Given a session recorded with fan-out, where two nodes responded to the same request in one round,
+/// when I replay it, then one request consumes the whole round and gets the successful response.
+#[tokio::test]
+#[cfg(feature = "mocks")]
+async fn test_session_replay_fan_out_round() {
+    let node_1 = Address::from(Uri::from_static("http://127.0.0.1:1"));
+    let node_2 = Address::from(Uri::from_static("http://127.0.0.1:2"));
+
+    let recorder = SessionRecorder::new();
+    let round = recorder.next_round();
+    recorder.record_node(
+        &request(1),
+        &Err(DapiClientError::NoAvailableAddresses),
+        Some(&node_1),
+        round,
+    );
+    recorder.record_node(&request(1), &Ok(response(10)), Some(&node_2), round);
+    let round = recorder.next_round();
+    recorder.record_node(&request(1), &Ok(response(11)), Some(&node_1), round);
+
+    let entries = recorder.entries();
+    assert_eq!(entries[0].node.as_deref(), Some("http://127.0.0.1:1/"));
+    assert_eq!(entries[1].node.as_deref(), Some("http://127.0.0.1:2/"));
+    assert_eq!(entries[0].round, entries[1].round);
+    assert_ne!(entries[1].round, entries[2].round);
+
+    let replay = ReplayDapiClient::new(entries);
+    let settings = RequestSettings::default();
+
+    assert_eq!(
+        request(1).execute(&replay, settings).await.unwrap(),
+        response(10)
+    );
+    assert_eq!(replay.unconsumed_entries().len(), 1);
+    assert_eq!(
+        request(1).execute(&replay, settings).await.unwrap(),
+        response(11)
+    );
+
+    replay.verify().expect("whole session replayed");
+}
@@ -0,0 +1,289 @@
+use dapi_grpc::core::v0::{
+    block_headers_with_chain_locks_request::FromBlock as HeadersFromBlock,
+    block_headers_with_chain_locks_response::Responses as HeadersResponses,
+    transactions_with_proofs_request::FromBlock as TransactionsFromBlock,
+    transactions_with_proofs_response::Responses as TransactionsResponses, BlockHeaders,
+    BlockHeadersWithChainLocksRequest, BlockHeadersWithChainLocksResponse, MasternodeListRequest,
+    MasternodeListResponse, TransactionsWithProofsRequest, TransactionsWithProofsResponse,
+};
+use rs_dapi_client::DapiStreamRequest;
+use sha2::{Digest, Sha256};
+#[cfg(feature = "mocks")]
+use {
+    dapi_grpc::tonic::Status,
+    rs_dapi_client::{
+        mock::MockDapiClient, Address, DapiClientError, DapiStreamExecutor, RequestSettings,
+    },
+};
+
+fn headers_response(count: usize) -> BlockHeadersWithChainLocksResponse {
+    BlockHeadersWithChainLocksResponse {
+        responses: Some(HeadersResponses::BlockHeaders(BlockHeaders {
+            headers: vec![vec![0u8; 80]; count],
+        })),
+    }
+}
+
+/// Block headers with distinct content, one per item of `range`.
+fn headers_range(range: std::ops::Range<u8>) -> BlockHeadersWithChainLocksResponse {
+    BlockHeadersWithChainLocksResponse {
+        responses: Some(HeadersResponses::BlockHeaders(BlockHeaders {
+            headers: range.map(|i| vec![i; 80]).collect(),
+        })),
+    }
+}
+
+/// Merkle block with distinct content.
+fn merkle_block(i: u8) -> TransactionsWithProofsResponse {
+    TransactionsWithProofsResponse {
+        responses: Some(TransactionsResponses::RawMerkleBlock(vec![i; 84])),
+    }
+}
+
+/// Block hash of a header or merkle block, as displayed by Dash Core.
+fn block_hash(data: &[u8]) -> Vec<u8> {
+    let mut hash = Sha256::digest(Sha256::digest(&data[..80])).to_vec();
+    hash.reverse();
+
+    hash
+}
+
+/// Given a block headers stream started from a height, when headers are received, then the stream
+/// resumes from the next height and requests only the remaining headers.
+#[test]
+fn test_block_headers_resume_from_height() {
+    let mut request = BlockHeadersWithChainLocksRequest {
+        from_block: Some(HeadersFromBlock::FromBlockHeight(100)),
+        count: 10,
+    };
+
+    request.resume_after(&headers_response(3));
+    assert_eq!(
+        request.from_block,
+        Some(HeadersFromBlock::FromBlockHeight(103))
+    );
+    assert_eq!(request.count, 7);
+
+    // chain locks don't change the position
+    request.resume_after(&BlockHeadersWithChainLocksResponse {
+        responses: Some(HeadersResponses::ChainLock(vec![1u8; 132])),
+    });
+    assert_eq!(
+        request.from_block,
+        Some(HeadersFromBlock::FromBlockHeight(103))
+    );
+}
+
+/// Given a block headers stream started from a block hash, when headers are received, then the
+/// stream resumes from the hash of the last received header.
+#[test]
+fn test_block_headers_resume_from_hash() {
+    let mut request = BlockHeadersWithChainLocksRequest {
+        from_block: Some(HeadersFromBlock::FromBlockHash(vec![0u8; 32])),
+        count: 0,
+    };
+
+    request.resume_after(&headers_response(2));
+
+    let Some(HeadersFromBlock::FromBlockHash(hash)) = &request.from_block else {
+        panic!("expected block hash, got {:?}", request.from_block);
+    };
+    assert_eq!(hash.len(), 32);
+    assert_ne!(hash, &vec![0u8; 32]);
+    // 0 means no limit
+    assert_eq!(request.count, 0);
+}
+
+/// Given a transactions stream started from a height, when a merkle block is received, then the
+/// stream resumes from that block.
+#[test]
+fn test_transactions_resume_from_merkle_block() {
+    let mut request = TransactionsWithProofsRequest {
+        from_block: Some(TransactionsFromBlock::FromBlockHeight(100)),
+        ..Default::default()
+    };
+
+    request.resume_after(&TransactionsWithProofsResponse {
+        responses: Some(TransactionsResponses::RawMerkleBlock(vec![0u8; 84])),
+    });
+
+    assert!(matches!(
+        request.from_block,
+        Some(TransactionsFromBlock::FromBlockHash(ref hash)) if hash.len() == 32
+    ));
+}
+
+/// Given a stream of 10 block headers from a height, when it fails after 4 headers and is resumed on
+/// another node, then only the remaining headers are requested, and the stream finishes after them.
+#[test]
+fn test_block_headers_resume_from_height_after_failure() {
+    let mut request = BlockHeadersWithChainLocksRequest {
+        from_block: Some(HeadersFromBlock::FromBlockHeight(100)),
+        count: 10,
+    };
+
+    assert!(request.resume_after(&headers_range(0..3)));
+    assert!(request.resume_after(&headers_range(3..4)));
+
+    // stream fails; the updated request is sent to another node
+    assert_eq!(
+        request,
+        BlockHeadersWithChainLocksRequest {
+            from_block: Some(HeadersFromBlock::FromBlockHeight(104)),
+            count: 6,
+        }
+    );
+    assert!(!request.is_unbounded());
+
+    assert!(!request.resume_after(&headers_range(4..10)));
+}
+
+/// Given a stream of 5 block headers from a block hash, when it fails after 3 headers and is resumed
+/// on another node, then it resumes from the last received header, which is sent again, and the
+/// stream finishes after the remaining headers without requesting more.
+#[test]
+fn test_block_headers_resume_from_hash_after_failure() {
+    let mut request = BlockHeadersWithChainLocksRequest {
+        from_block: Some(HeadersFromBlock::FromBlockHash(block_hash(&[0u8; 80]))),
+        count: 5,
+    };
+
+    assert!(request.resume_after(&headers_range(0..2)));
+    assert!(request.resume_after(&headers_range(2..3)));
+
+    // stream fails; the updated request is sent to another node
+    assert_eq!(
+        request,
+        BlockHeadersWithChainLocksRequest {
+            from_block: Some(HeadersFromBlock::FromBlockHash(block_hash(&[2u8; 80]))),
+            count: 3,
+        }
+    );
+
+    assert!(request.resume_after(&headers_range(2..4)));
+    assert!(!request.resume_after(&headers_range(4..5)));
+}
+
+/// Given a stream of transactions in 3 blocks from a height, when it fails after 2 merkle blocks and
+/// is resumed on another node, then it resumes from the last received block with the remaining
+/// count, and it's finished when the node closes it.
+#[test]
+fn test_transactions_resume_after_failure() {
+    let mut request = TransactionsWithProofsRequest {
+        from_block: Some(TransactionsFromBlock::FromBlockHeight(100)),
+        count: 3,
+        ..Default::default()
+    };
+
+    assert!(request.resume_after(&TransactionsWithProofsResponse {
+        responses: Some(TransactionsResponses::RawTransactions(Default::default())),
+    }));
+    assert_eq!(request.count, 3);
+    assert!(request.resume_after(&merkle_block(0)));
+    assert!(request.resume_after(&merkle_block(1)));
+
+    // stream fails; the updated request is sent to another node
+    assert_eq!(
+        request.from_block,
+        Some(TransactionsFromBlock::FromBlockHash(block_hash(&[1u8; 84])))
+    );
+    assert_eq!(request.count, 2);
+    assert!(!request.is_unbounded());
+
+    // the last received block is sent again
+    assert!(request.resume_after(&merkle_block(1)));
+    assert_eq!(request.count, 2);
+    assert!(request.resume_after(&merkle_block(2)));
+    assert_eq!(request.count, 1);
+}
+
+/// Given a subscription to new transactions, when it fails and is resumed on another node, then it
+/// stays unbounded.
+#[test]
+fn test_transactions_subscription_resume_after_failure() {
+    let mut request = TransactionsWithProofsRequest {
+        from_block: Some(TransactionsFromBlock::FromBlockHeight(100)),
+        count: 0,
+        ..Default::default()
+    };
+
+    assert!(request.resume_after(&merkle_block(0)));
+    assert!(request.resume_after(&merkle_block(1)));
+
+    assert_eq!(request.count, 0);
+    assert!(request.is_unbounded());
+}
+
+/// Given a masternode list stream, when a message is received, then the request doesn't change,
+/// as the full list is sent on every connection, and it never ends.
+#[test]
+fn test_masternode_list_resume() {
+    let mut request = MasternodeListRequest {};
+    assert!(request.resume_after(&MasternodeListResponse {
+        masternode_list_diff: vec![1, 2, 3],
+    }));
+
+    assert_eq!(request, MasternodeListRequest {});
+    // subscription is reconnected when the node closes it
+    assert!(request.is_unbounded());
+}
+
+/// Given a mock stream expectation, when I open the stream, then messages and errors are returned
+/// in order, and then the stream ends.
+#[tokio::test]
+#[cfg(feature = "mocks")]
+async fn test_mock_stream() {
+    let mut dapi = MockDapiClient::new();
+    let request = BlockHeadersWithChainLocksRequest {
+        from_block: Some(HeadersFromBlock::FromBlockHeight(1)),
+        count: 0,
+    };
+    let address = Address::from(dapi_grpc::tonic::transport::Uri::from_static(
+        "http://127.0.0.1:3000",
+    ));
+
+    dapi.expect_stream(
+        &request,
+        &[
+            Ok(headers_response(1)),
+            Ok(headers_response(2)),
+            Err(DapiClientError::Transport(
+                Status::unavailable("node down"),
+                address,
+            )),
+        ],
+    )
+    .expect("expectation added");
+
+    let mut stream = dapi
+        .stream(request, RequestSettings::default())
+        .await
+        .expect("stream opened");
+
+    assert_eq!(
+        stream.message().await.expect("first message"),
+        Some(headers_response(1))
+    );
+    assert_eq!(
+        stream.message().await.expect("second message"),
+        Some(headers_response(2))
+    );
+    assert!(matches!(
+        stream.message().await,
+        Err(DapiClientError::Transport(status, _)) if status.code() == dapi_grpc::tonic::Code::Unavailable
+    ));
+    assert_eq!(stream.message().await.expect("end of stream"), None);
+}
+
+/// Given no stream expectation, when I open a stream, then mock error is returned.
+#[tokio::test]
+#[cfg(feature = "mocks")]
+async fn test_mock_stream_not_expected() {
+    let dapi = MockDapiClient::new();
+
+    let result = dapi
+        .stream(MasternodeListRequest {}, RequestSettings::default())
+        .await;
+
+    assert!(matches!(result, Err(DapiClientError::Mock(_))));
+}
@@ -1,7 +1,60 @@
 mod v0;
 
+use crate::state_transition::identity_credit_transfer_transition::IdentityCreditTransferTransition;
+#[cfg(feature = "state-transition-signing")]
+use platform_version::version::FeatureVersion;
 pub use v0::*;
 
-use crate::state_transition::identity_credit_transfer_transition::IdentityCreditTransferTransition;
+#[cfg(feature = "state-transition-signing")]
+use crate::identity::signer::Signer;
+#[cfg(feature = "state-transition-signing")]
+use crate::identity::Identity;
+#[cfg(feature = "state-transition-signing")]
+use crate::identity::IdentityPublicKey;
+#[cfg(feature = "state-transition-signing")]
+use crate::prelude::{Identifier, IdentityNonce, UserFeeIncrease};
+#[cfg(feature = "state-transition-signing")]
+use crate::state_transition::identity_credit_transfer_transition::v0::IdentityCreditTransferTransitionV0;
+#[cfg(feature = "state-transition-signing")]
+use crate::state_transition::StateTransition;
+#[cfg(feature = "state-transition-signing")]
+use crate::version::PlatformVersion;
+#[cfg(feature = "state-transition-signing")]
+use crate::ProtocolError;
 
-impl IdentityCreditTransferTransitionMethodsV0 for IdentityCreditTransferTransition {}
+impl IdentityCreditTransferTransitionMethodsV0 for IdentityCreditTransferTransition {
+    #[cfg(feature = "state-transition-signing")]
+    fn try_from_identity<S: Signer>(
+        identity: &Identity,
+        transfer_key_to_use: Option<&IdentityPublicKey>,
+        recipient_id: Identifier,
+        amount: u64,
+        user_fee_increase: UserFeeIncrease,
+        signer: S,
+        nonce: IdentityNonce,
+        platform_version: &PlatformVersion,
+        version: Option<FeatureVersion>,
+    ) -> Result<StateTransition, ProtocolError> {
+        match version.unwrap_or(
+            platform_version
+                .dpp
+                .state_transition_conversion_versions
+                .identity_to_identity_transfer_transition,
+        ) {
+            0 => Ok(IdentityCreditTransferTransitionV0::try_from_identity(
+                identity,
+                transfer_key_to_use,
+                recipient_id,
+                amount,
+                user_fee_increase,
+                signer,
+                nonce,
+                platform_version,
+                version,
+            )?),
+            v => Err(ProtocolError::UnknownVersionError(format!(
+                "Unknown IdentityCreditTransferTransition version for try_from_identity {v}"
+            ))),
+        }
+    }
+}
@@ -1,6 +1,33 @@
+#[cfg(feature = "state-transition-signing")]
+use crate::identity::signer::Signer;
+#[cfg(feature = "state-transition-signing")]
+use crate::identity::Identity;
+#[cfg(feature = "state-transition-signing")]
+use crate::identity::IdentityPublicKey;
+#[cfg(feature = "state-transition-signing")]
+use crate::prelude::{Identifier, IdentityNonce, UserFeeIncrease};
+#[cfg(feature = "state-transition-signing")]
+use crate::state_transition::StateTransition;
 use crate::state_transition::StateTransitionType;
+#[cfg(feature = "state-transition-signing")]
+use crate::ProtocolError;
+#[cfg(feature = "state-transition-signing")]
+use platform_version::version::{FeatureVersion, PlatformVersion};
 
 pub trait IdentityCreditTransferTransitionMethodsV0 {
+    #[cfg(feature = "state-transition-signing")]
+    fn try_from_identity<S: Signer>(
+        identity: &Identity,
+        transfer_key_to_use: Option<&IdentityPublicKey>,
+        recipient_id: Identifier,
+        amount: u64,
+        user_fee_increase: UserFeeIncrease,
+        signer: S,
+        nonce: IdentityNonce,
+        platform_version: &PlatformVersion,
+        version: Option<FeatureVersion>,
+    ) -> Result<StateTransition, ProtocolError>;
+
     /// Get State Transition Type
     fn get_type() -> StateTransitionType {
         StateTransitionType::IdentityCreditTransfer
@@ -1409,6 +1409,7 @@ impl FromProof<platform::GetContestedResourceVoteStateRequest> for Contenders {
             contenders,
             abstain_vote_tally: contested_resource_vote_state.abstaining_vote_tally,
             lock_vote_tally: contested_resource_vote_state.locked_vote_tally,
+            winner: contested_resource_vote_state.winner,
         };
         Ok((response.into_option(), mtd.clone(), proof.clone()))
     }
@@ -5,6 +5,7 @@
 //! In this case, the [FromProof](crate::FromProof) trait is implemented for dedicated object type
 //! defined in this module.
 
+use dpp::block::block_info::BlockInfo;
 use dpp::data_contract::document_type::DocumentType;
 use dpp::fee::Credits;
 use dpp::platform_value::Value;
@@ -13,6 +14,7 @@ use dpp::version::PlatformVersion;
 pub use dpp::version::ProtocolVersionVoteCount;
 use dpp::voting::contender_structs::{Contender, ContenderWithSerializedDocument};
 use dpp::voting::vote_choices::resource_vote_choice::ResourceVoteChoice;
+use dpp::voting::vote_info_storage::contested_document_vote_poll_winner_info::ContestedDocumentVotePollWinnerInfo;
 use dpp::voting::vote_polls::contested_document_resource_vote_poll::ContestedDocumentResourceVotePoll;
 use dpp::voting::vote_polls::VotePoll;
 use dpp::voting::votes::resource_vote::ResourceVote;
@@ -66,7 +68,7 @@ pub type DataContracts = RetrievedObjects<Identifier, DataContract>;
 ///
 /// Mapping between the contenders identity IDs and their info.
 /// If a contender is not found, it is represented as `None`.
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq)]
 #[cfg_attr(
     feature = "mocks",
     derive(Encode, Decode, PlatformSerialize, PlatformDeserialize,),
@@ -79,6 +81,8 @@ pub struct Contenders {
     pub abstain_vote_tally: Option<u32>,
     /// Tally of lock votes.
     pub lock_vote_tally: Option<u32>,
+    /// Result of the vote poll and the block in which it was decided; `None` while voting is in progress.
+    pub winner: Option<(ContestedDocumentVotePollWinnerInfo, BlockInfo)>,
 }
 
 impl Contenders {
@@ -112,6 +116,7 @@ impl FromIterator<(Identifier, Option<ContenderWithSerializedDocument>)> for Con
             ),
             abstain_vote_tally: None,
             lock_vote_tally: None,
+            winner: None,
         }
     }
 }
@@ -126,7 +131,7 @@ impl FromIterator<(Identifier, Option<ContenderWithSerializedDocument>)> for Con
 pub struct Voter(pub Identifier);
 
 /// Multiple voters.
-#[derive(Debug, Clone, derive_more::From, Default)]
+#[derive(Debug, Clone, PartialEq, derive_more::From, Default)]
 #[cfg_attr(
     feature = "mocks",
     derive(Encode, Decode, PlatformSerialize, PlatformDeserialize,),
@@ -168,7 +173,7 @@ pub struct KeysInPath {
 }
 
 /// The total credits on Platform.
-#[derive(Debug, derive_more::From, Clone, Copy)]
+#[derive(Debug, derive_more::From, Clone, Copy, PartialEq)]
 #[cfg_attr(
     feature = "mocks",
     derive(Encode, Decode, PlatformSerialize, PlatformDeserialize),
@@ -252,7 +257,7 @@ impl PlatformVersionedDecode for ContestedResource {
 }
 
 /// Contested resources
-#[derive(derive_more::From, Clone, Debug, Default)]
+#[derive(derive_more::From, Clone, Debug, Default, PartialEq)]
 pub struct ContestedResources(pub Vec<ContestedResource>);
 
 #[cfg(feature = "mocks")]
@@ -305,7 +310,7 @@ pub struct ContestedVote(ContestedDocumentResourceVotePoll, ResourceVoteChoice);
 pub type ResourceVotesByIdentity = RetrievedObjects<Identifier, ResourceVote>;
 
 /// Prefunded specialized balance.
-#[derive(Debug, derive_more::From, Copy, Clone)]
+#[derive(Debug, derive_more::From, Copy, Clone, PartialEq)]
 #[cfg_attr(
     feature = "mocks",
     derive(Encode, Decode, PlatformSerialize, PlatformDeserialize),
@@ -326,7 +331,7 @@ impl From<&PrefundedSpecializedBalance> for Credits {
 }
 
 /// Contested document resource vote polls grouped by timestamp.
-#[derive(Clone, Debug, Default, derive_more::From)]
+#[derive(Clone, Debug, Default, derive_more::From, PartialEq)]
 #[cfg_attr(
     feature = "mocks",
     derive(Encode, Decode, PlatformSerialize, PlatformDeserialize),
@@ -392,11 +397,11 @@ impl IntoIterator for VotePollsGroupedByTimestamp {
 }
 
 /// An identity nonce
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct IdentityNonceFetcher(pub IdentityNonce);
 
 /// An identity contract nonce
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct IdentityContractNonceFetcher(pub IdentityNonce);
 
 /// Public keys belonging to some identity.
@@ -422,7 +427,7 @@ pub type ExtendedEpochInfos = RetrievedObjects<EpochIndex, ExtendedEpochInfo>;
 pub type ProtocolVersionUpgrades = RetrievedObjects<ProtocolVersion, ProtocolVersionVoteCount>;
 
 /// Vote of a masternode for a protocol version.
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 #[cfg_attr(feature = "mocks", derive(serde::Serialize, serde::Deserialize))]
 pub struct MasternodeProtocolVote {
     /// ProTxHash of the masternode
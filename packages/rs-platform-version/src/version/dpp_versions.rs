@@ -119,6 +119,7 @@ pub struct StateTransitionConversionVersions {
     pub identity_to_identity_create_transition: FeatureVersion,
     pub identity_to_identity_top_up_transition: FeatureVersion,
     pub identity_to_identity_withdrawal_transition: FeatureVersion,
+    pub identity_to_identity_transfer_transition: FeatureVersion,
     pub identity_to_identity_create_transition_with_signer: FeatureVersion,
 }
 
@@ -1102,6 +1102,7 @@ pub const TEST_PLATFORM_V2: PlatformVersion = PlatformVersion {
             identity_to_identity_create_transition: 0,
             identity_to_identity_top_up_transition: 0,
             identity_to_identity_withdrawal_transition: 0,
+            identity_to_identity_transfer_transition: 0,
             identity_to_identity_create_transition_with_signer: 0,
         },
         state_transition_method_versions: StateTransitionMethodVersions {
@@ -0,0 +1,245 @@
+//! Blocking (synchronous) API of the SDK.
+//!
+//! [Sdk](crate::Sdk) is async. This module wraps it in a [blocking::Sdk](Sdk) that owns a Tokio runtime
+//! and runs every operation to completion on it, for use in code that is not async, like FFI
+//! bindings, simple command-line tools or [ContextProvider](crate::platform::ContextProvider)
+//! implementations.
+//!
+//! Available only when the `blocking` feature is enabled.
+//!
+//! ## Cancellation
+//!
+//! Blocking calls return [Error::Cancelled] as soon as the SDK is shut down with [Sdk::shutdown()]
+//! (or with [Sdk::shutdown()](crate::Sdk::shutdown()) of the wrapped async SDK), also when
+//! the shutdown is requested from another thread while the call is in progress.
+//!
+//! ## Panics
+//!
+//! Methods of this module must not be called from within an async runtime; they return
+//! [Error::Generic] in that case. The last clone of [Sdk] must not be dropped from within an async
+//! runtime either, as dropping a runtime in an async context panics.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use dash_sdk::blocking;
+//! use dash_sdk::SdkBuilder;
+//! use dpp::prelude::{Identifier, Identity};
+//! use rs_dapi_client::AddressList;
+//!
+//! let addresses = AddressList::from("https://127.0.0.1:1443");
+//! let sdk = blocking::Sdk::new(SdkBuilder::new(addresses)).expect("build SDK");
+//! let identity: Result<Option<Identity>, _> = sdk.fetch(Identifier::random());
+//! ```
+use std::future::Future;
+use std::sync::Arc;
+
+use dpp::dashcore::PrivateKey;
+use dpp::data_contract::document_type::DocumentType;
+use dpp::data_contract::DataContract;
+use dpp::document::Document;
+use dpp::identity::signer::Signer;
+use dpp::identity::IdentityPublicKey;
+use dpp::prelude::{AssetLockProof, Identifier, Identity};
+use dpp::voting::votes::Vote;
+use drive_proof_verifier::FromProof;
+use rs_dapi_client::transport::TransportRequest;
+use tokio::runtime::Runtime;
+
+use crate::mock::MockResponse;
+use crate::platform::transition::put_contract::PutContract;
+use crate::platform::transition::put_document::PutDocument;
+use crate::platform::transition::put_identity::PutIdentity;
+use crate::platform::transition::put_settings::PutSettings;
+use crate::platform::transition::vote::PutVote;
+use crate::platform::{Fetch, FetchMany, Query};
+use crate::{Error, SdkBuilder};
+
+/// Blocking wrapper of [Sdk](crate::Sdk).
+///
+/// It's cheap to clone; clones share the SDK and the runtime.
+#[derive(Clone)]
+pub struct Sdk {
+    sdk: crate::Sdk,
+    runtime: Arc<Runtime>,
+}
+
+impl Sdk {
+    /// Build the SDK with a new multi-threaded runtime.
+    pub fn new(builder: SdkBuilder) -> Result<Self, Error> {
+        let runtime = new_runtime()?;
+        // some SDK components need the runtime context when created
+        let sdk = {
+            let _guard = runtime.enter();
+            builder.build()?
+        };
+
+        Ok(Self::with_runtime(sdk, Arc::new(runtime)))
+    }
+
+    /// Wrap an existing async SDK, creating a new multi-threaded runtime.
+    pub fn from_async(sdk: crate::Sdk) -> Result<Self, Error> {
+        Ok(Self::with_runtime(sdk, Arc::new(new_runtime()?)))
+    }
+
+    /// Wrap an existing async SDK, running operations on provided runtime.
+    pub fn with_runtime(sdk: crate::Sdk, runtime: Arc<Runtime>) -> Self {
+        Self { sdk, runtime }
+    }
+
+    /// Wrapped async SDK.
+    pub fn as_async(&self) -> &crate::Sdk {
+        &self.sdk
+    }
+
+    /// Runtime used to run operations.
+    pub fn runtime(&self) -> &Arc<Runtime> {
+        &self.runtime
+    }
+
+    /// Run a future to completion, unless the SDK is shut down first.
+    ///
+    /// Use it to call async SDK APIs not covered by this module, like:
+    ///
+    /// ```rust
+    /// # use dash_sdk::{blocking, SdkBuilder};
+    /// # let sdk = blocking::Sdk::new(SdkBuilder::new_mock()).unwrap();
+    /// use dash_sdk::platform::Fetch;
+    /// use dpp::prelude::{Identifier, Identity};
+    ///
+    /// let identity = sdk.block_on(Identity::fetch(sdk.as_async(), Identifier::random()));
+    /// ```
+    pub fn block_on<F, T>(&self, future: F) -> Result<T, Error>
+    where
+        F: Future<Output = Result<T, Error>>,
+    {
+        if tokio::runtime::Handle::try_current().is_ok() {
+            return Err(Error::Generic(
+                "blocking SDK can't be used from within an async runtime; use async SDK instead"
+                    .to_string(),
+            ));
+        }
+
+        self.runtime.block_on(async {
+            tokio::select! {
+                biased;
+                _ = self.sdk.cancelled() => Err(Error::Cancelled("SDK shut down".to_string())),
+                result = future => result,
+            }
+        })
+    }
+
+    /// Request shutdown of the SDK; pending and future blocking calls return [Error::Cancelled].
+    pub fn shutdown(&self) {
+        self.sdk.shutdown();
+    }
+
+    /// Fetch single object from Platform; see [Fetch::fetch()].
+    pub fn fetch<O: Fetch>(
+        &self,
+        query: impl Query<<O as Fetch>::Request>,
+    ) -> Result<Option<O>, Error> {
+        self.block_on(O::fetch(&self.sdk, query))
+    }
+
+    /// Fetch multiple objects from Platform; see [FetchMany::fetch_many()].
+    pub fn fetch_many<O, K, R>(
+        &self,
+        query: impl Query<<O as FetchMany<K, R>>::Request>,
+    ) -> Result<R, Error>
+    where
+        O: FetchMany<K, R>,
+        K: Ord,
+        R: FromIterator<(K, Option<O>)>
+            + MockResponse
+            + FromProof<
+                <O as FetchMany<K, R>>::Request,
+                Request = <O as FetchMany<K, R>>::Request,
+                Response = <<O as FetchMany<K, R>>::Request as TransportRequest>::Response,
+            > + Send
+            + Default
+            + PartialEq,
+    {
+        self.block_on(O::fetch_many(&self.sdk, query))
+    }
+
+    /// Put a data contract to Platform and wait for the proof; see
+    /// [PutContract::put_to_platform_and_wait_for_response()].
+    pub fn put_contract<S: Signer>(
+        &self,
+        data_contract: &DataContract,
+        identity_public_key: IdentityPublicKey,
+        signer: &S,
+    ) -> Result<DataContract, Error> {
+        self.block_on(data_contract.put_to_platform_and_wait_for_response(
+            &self.sdk,
+            identity_public_key,
+            signer,
+        ))
+    }
+
+    /// Put a document to Platform and wait for the proof; see
+    /// [PutDocument::put_to_platform_and_wait_for_response()].
+    pub fn put_document<S: Signer>(
+        &self,
+        document: &Document,
+        document_type: DocumentType,
+        document_state_transition_entropy: [u8; 32],
+        identity_public_key: IdentityPublicKey,
+        data_contract: Arc<DataContract>,
+        signer: &S,
+    ) -> Result<Document, Error> {
+        self.block_on(document.put_to_platform_and_wait_for_response(
+            &self.sdk,
+            document_type,
+            document_state_transition_entropy,
+            identity_public_key,
+            data_contract,
+            signer,
+        ))
+    }
+
+    /// Put an identity to Platform and wait for the proof; see
+    /// [PutIdentity::put_to_platform_and_wait_for_response()].
+    pub fn put_identity<S: Signer>(
+        &self,
+        identity: &Identity,
+        asset_lock_proof: AssetLockProof,
+        asset_lock_proof_private_key: &PrivateKey,
+        signer: &S,
+    ) -> Result<Identity, Error> {
+        self.block_on(identity.put_to_platform_and_wait_for_response(
+            &self.sdk,
+            asset_lock_proof,
+            asset_lock_proof_private_key,
+            signer,
+        ))
+    }
+
+    /// Put a masternode vote to Platform and wait for the proof; see
+    /// [PutVote::put_to_platform_and_wait_for_response()].
+    pub fn put_vote<S: Signer>(
+        &self,
+        vote: &Vote,
+        voter_pro_tx_hash: Identifier,
+        voting_public_key: &IdentityPublicKey,
+        signer: &S,
+        settings: Option<PutSettings>,
+    ) -> Result<Vote, Error> {
+        self.block_on(vote.put_to_platform_and_wait_for_response(
+            voter_pro_tx_hash,
+            voting_public_key,
+            &self.sdk,
+            signer,
+            settings,
+        ))
+    }
+}
+
+fn new_runtime() -> Result<Runtime, Error> {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .thread_name("dash-sdk-blocking")
+        .build()
+        .map_err(|e| Error::Generic(format!("unable to create runtime: {}", e)))
+}
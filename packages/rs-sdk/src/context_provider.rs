@@ -0,0 +1,14 @@
+//! [ContextProvider](drive_proof_verifier::ContextProvider) implementations provided by the SDK.
+//!
+//! Context providers supply the SDK with information required to verify proofs, like data contracts and
+//! quorum public keys.
+//!
+//! * [PersistentContextProvider] - caches information returned by another context provider on disk,
+//!   so that it survives process restarts.
+//! * [SpvContextProvider] - derives quorum public keys from a chain-lock validated quorum list synced
+//!   from DAPI, without a Dash Core node.
+mod persistent;
+mod spv;
+
+pub use persistent::PersistentContextProvider;
+pub use spv::{SpvCheckpoint, SpvContextProvider};
@@ -0,0 +1,524 @@
+//! Context provider that persists data contracts and quorum public keys on disk.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use dpp::data_contract::accessors::v0::DataContractV0Getters;
+use dpp::prelude::{CoreBlockHeight, DataContract, Identifier};
+use dpp::serialization::{
+    PlatformDeserializableWithPotentialValidationFromVersionedStructure,
+    PlatformSerializableWithPlatformVersion,
+};
+use dpp::version::PlatformVersion;
+use drive_proof_verifier::error::ContextProviderError;
+use drive_proof_verifier::ContextProvider;
+use hex::ToHex;
+
+use crate::Error;
+
+const DATA_CONTRACT_FILE_PREFIX: &str = "data_contract-";
+const QUORUM_PUBLIC_KEY_FILE_PREFIX: &str = "quorum_pubkey-";
+const FILE_EXTENSION: &str = "bin";
+/// Data contracts can be updated, so they are refreshed after some time by default
+const DEFAULT_DATA_CONTRACT_TTL: Duration = Duration::from_secs(3600);
+
+/// Context provider that caches data contracts and quorum public keys on disk.
+///
+/// All requests are first served from memory, then from files stored in the cache directory.
+/// On a cache miss, the request is delegated to the wrapped context provider, and the result is
+/// persisted, so that it is available after a restart of the process.
+///
+/// ## Expiration
+///
+/// Cached items can expire after configurable time-to-live, see
+/// [with_data_contract_ttl()](PersistentContextProvider::with_data_contract_ttl()) and
+/// [with_quorum_public_key_ttl()](PersistentContextProvider::with_quorum_public_key_ttl()).
+/// Data contracts expire after one hour by default, so that updates made by other clients are
+/// eventually picked up; quorum public keys never change and don't expire by default.
+/// Expired items are refreshed using the wrapped provider; if it fails, the expired item is used.
+///
+/// Data contracts are only replaced with versions that are not older than the cached one.
+/// When a data contract is updated (eg. with
+/// [UpdateContract](crate::platform::transition::update_contract::UpdateContract)), the new version
+/// should be stored with [PersistentContextProvider::put_data_contract()].
+///
+/// Data contracts are stored in the format defined by the platform version of the provider;
+/// files that cannot be read with this version (eg. after a protocol upgrade) are discarded.
+///
+/// ## Warm start
+///
+/// [PersistentContextProvider::warm_start()] loads all cached items into memory at once, so that
+/// short-lived processes don't pay the cost of reading files one by one.
+///
+/// ## Example
+///
+/// ```rust
+/// use dash_sdk::context_provider::PersistentContextProvider;
+/// use dash_sdk::platform::dpp::version::PlatformVersion;
+/// use dash_sdk::platform::MockContextProvider;
+/// use std::time::Duration;
+///
+/// let dir = std::env::temp_dir().join("dash-sdk-context-cache");
+/// let provider = PersistentContextProvider::new(MockContextProvider::new(), dir, PlatformVersion::latest())
+///     .expect("cache directory should be created")
+///     .with_data_contract_ttl(Some(Duration::from_secs(3600)));
+/// provider.warm_start().expect("cache should be loaded");
+/// ```
+pub struct PersistentContextProvider<P: ContextProvider> {
+    /// Context provider used on cache misses
+    inner: P,
+    /// Directory where cached items are stored
+    dir: PathBuf,
+    /// Platform version used to serialize data contracts
+    platform_version: &'static PlatformVersion,
+    /// Time after which cached data contracts are refreshed; `None` means they never expire
+    data_contract_ttl: Option<Duration>,
+    /// Time after which cached quorum public keys are refreshed; `None` means they never expire
+    quorum_public_key_ttl: Option<Duration>,
+
+    data_contracts: RwLock<HashMap<Identifier, CacheEntry<Arc<DataContract>>>>,
+    quorum_public_keys: RwLock<HashMap<(u32, [u8; 32]), CacheEntry<[u8; 48]>>>,
+}
+
+#[derive(Clone)]
+struct CacheEntry<T> {
+    value: T,
+    stored_at: SystemTime,
+}
+
+impl<T> CacheEntry<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            stored_at: SystemTime::now(),
+        }
+    }
+
+    fn is_expired(&self, ttl: Option<Duration>) -> bool {
+        match (ttl, self.stored_at.elapsed()) {
+            (Some(ttl), Ok(age)) => age > ttl,
+            // stored in the future; clock was moved back
+            (Some(_), Err(_)) => false,
+            (None, _) => false,
+        }
+    }
+}
+
+impl<P: ContextProvider> PersistentContextProvider<P> {
+    /// Create new persistent context provider.
+    ///
+    /// ## Parameters
+    ///
+    /// - `inner`: context provider used to retrieve items that are not cached.
+    /// - `dir`: directory where cached items are stored; it is created if it does not exist.
+    /// - `platform_version`: platform version used to serialize data contracts.
+    pub fn new<D: Into<PathBuf>>(
+        inner: P,
+        dir: D,
+        platform_version: &'static PlatformVersion,
+    ) -> Result<Self, Error> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|e| {
+            Error::Generic(format!(
+                "unable to create context cache directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+
+        Ok(Self {
+            inner,
+            dir,
+            platform_version,
+            data_contract_ttl: Some(DEFAULT_DATA_CONTRACT_TTL),
+            quorum_public_key_ttl: None,
+            data_contracts: RwLock::new(HashMap::new()),
+            quorum_public_keys: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Set time after which cached data contracts are refreshed.
+    ///
+    /// Defaults to one hour; `None` means that data contracts never expire.
+    pub fn with_data_contract_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.data_contract_ttl = ttl;
+        self
+    }
+
+    /// Set time after which cached quorum public keys are refreshed.
+    ///
+    /// `None` (default) means that quorum public keys never expire.
+    pub fn with_quorum_public_key_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.quorum_public_key_ttl = ttl;
+        self
+    }
+
+    /// Load all cached, not expired items from disk into memory.
+    ///
+    /// Files that cannot be read are removed. Returns number of loaded items.
+    pub fn warm_start(&self) -> Result<usize, Error> {
+        let entries = fs::read_dir(&self.dir).map_err(|e| {
+            Error::Generic(format!(
+                "unable to read context cache directory {}: {}",
+                self.dir.display(),
+                e
+            ))
+        })?;
+
+        let mut loaded = 0;
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let Some(name) = file_name
+                .to_str()
+                .and_then(|name| name.strip_suffix(&format!(".{}", FILE_EXTENSION)))
+            else {
+                continue;
+            };
+
+            if let Some(id) = name.strip_prefix(DATA_CONTRACT_FILE_PREFIX) {
+                let Some(id) = hex::decode(id)
+                    .ok()
+                    .and_then(|id| Identifier::from_bytes(&id).ok())
+                else {
+                    continue;
+                };
+                if let Some(entry) = self.read_data_contract(&id) {
+                    if !entry.is_expired(self.data_contract_ttl) {
+                        self.data_contracts
+                            .write()
+                            .expect("data contracts cache lock poisoned")
+                            .insert(id, entry);
+                        loaded += 1;
+                    }
+                }
+            } else if let Some(key) = name.strip_prefix(QUORUM_PUBLIC_KEY_FILE_PREFIX) {
+                let Some((quorum_type, quorum_hash)) = parse_quorum_file_name(key) else {
+                    continue;
+                };
+                if let Some(entry) = self.read_quorum_public_key(quorum_type, quorum_hash) {
+                    if !entry.is_expired(self.quorum_public_key_ttl) {
+                        self.quorum_public_keys
+                            .write()
+                            .expect("quorum public keys cache lock poisoned")
+                            .insert((quorum_type, quorum_hash), entry);
+                        loaded += 1;
+                    }
+                }
+            }
+        }
+
+        tracing::debug!(loaded, dir = ?self.dir, "context cache loaded from disk");
+
+        Ok(loaded)
+    }
+
+    /// Store a data contract in the cache.
+    ///
+    /// The data contract is ignored if a newer version of it is already cached.
+    pub fn put_data_contract(&self, data_contract: DataContract) -> Result<(), Error> {
+        let id = data_contract.id();
+        if let Some(cached) = self.cached_data_contract(&id) {
+            if cached.value.version() > data_contract.version() {
+                tracing::debug!(
+                    %id,
+                    cached_version = cached.value.version(),
+                    version = data_contract.version(),
+                    "ignoring data contract older than the cached one"
+                );
+                return Ok(());
+            }
+        }
+
+        let bytes =
+            data_contract.serialize_to_bytes_with_platform_version(self.platform_version)?;
+        write_atomically(&self.data_contract_path(&id), &bytes)
+            .map_err(|e| Error::Generic(format!("unable to store data contract {}: {}", id, e)))?;
+
+        self.data_contracts
+            .write()
+            .expect("data contracts cache lock poisoned")
+            .insert(id, CacheEntry::new(Arc::new(data_contract)));
+
+        Ok(())
+    }
+
+    /// Remove a data contract from the cache.
+    ///
+    /// It will be retrieved using the wrapped provider next time it is requested.
+    pub fn invalidate_data_contract(&self, id: &Identifier) {
+        self.data_contracts
+            .write()
+            .expect("data contracts cache lock poisoned")
+            .remove(id);
+        remove_file(&self.data_contract_path(id));
+    }
+
+    /// Remove all cached items, both from memory and disk.
+    pub fn clear(&self) -> Result<(), Error> {
+        self.data_contracts
+            .write()
+            .expect("data contracts cache lock poisoned")
+            .clear();
+        self.quorum_public_keys
+            .write()
+            .expect("quorum public keys cache lock poisoned")
+            .clear();
+
+        let entries = fs::read_dir(&self.dir).map_err(|e| {
+            Error::Generic(format!(
+                "unable to read context cache directory {}: {}",
+                self.dir.display(),
+                e
+            ))
+        })?;
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with(DATA_CONTRACT_FILE_PREFIX)
+                || name.starts_with(QUORUM_PUBLIC_KEY_FILE_PREFIX)
+            {
+                remove_file(&entry.path());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return the wrapped context provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    fn data_contract_path(&self, id: &Identifier) -> PathBuf {
+        self.dir.join(format!(
+            "{}{}.{}",
+            DATA_CONTRACT_FILE_PREFIX,
+            id.encode_hex::<String>(),
+            FILE_EXTENSION
+        ))
+    }
+
+    fn quorum_public_key_path(&self, quorum_type: u32, quorum_hash: &[u8; 32]) -> PathBuf {
+        self.dir.join(format!(
+            "{}{}-{}.{}",
+            QUORUM_PUBLIC_KEY_FILE_PREFIX,
+            quorum_type,
+            quorum_hash.encode_hex::<String>(),
+            FILE_EXTENSION
+        ))
+    }
+
+    /// Get data contract from memory or disk, regardless of its expiration.
+    fn cached_data_contract(&self, id: &Identifier) -> Option<CacheEntry<Arc<DataContract>>> {
+        if let Some(entry) = self
+            .data_contracts
+            .read()
+            .expect("data contracts cache lock poisoned")
+            .get(id)
+        {
+            return Some(entry.clone());
+        }
+
+        let entry = self.read_data_contract(id)?;
+        self.data_contracts
+            .write()
+            .expect("data contracts cache lock poisoned")
+            .insert(*id, entry.clone());
+
+        Some(entry)
+    }
+
+    fn read_data_contract(&self, id: &Identifier) -> Option<CacheEntry<Arc<DataContract>>> {
+        let path = self.data_contract_path(id);
+        let (bytes, stored_at) = read_file(&path)?;
+
+        match DataContract::versioned_deserialize(&bytes, false, self.platform_version) {
+            Ok(data_contract) if data_contract.id() == *id => Some(CacheEntry {
+                value: Arc::new(data_contract),
+                stored_at,
+            }),
+            Ok(_) => {
+                tracing::warn!(path = ?path, "cached data contract has unexpected id, removing");
+                remove_file(&path);
+                None
+            }
+            Err(e) => {
+                tracing::warn!(path = ?path, error = ?e, "unable to decode cached data contract, removing");
+                remove_file(&path);
+                None
+            }
+        }
+    }
+
+    /// Get quorum public key from memory or disk, regardless of its expiration.
+    fn cached_quorum_public_key(
+        &self,
+        quorum_type: u32,
+        quorum_hash: [u8; 32],
+    ) -> Option<CacheEntry<[u8; 48]>> {
+        if let Some(entry) = self
+            .quorum_public_keys
+            .read()
+            .expect("quorum public keys cache lock poisoned")
+            .get(&(quorum_type, quorum_hash))
+        {
+            return Some(entry.clone());
+        }
+
+        let entry = self.read_quorum_public_key(quorum_type, quorum_hash)?;
+        self.quorum_public_keys
+            .write()
+            .expect("quorum public keys cache lock poisoned")
+            .insert((quorum_type, quorum_hash), entry.clone());
+
+        Some(entry)
+    }
+
+    fn read_quorum_public_key(
+        &self,
+        quorum_type: u32,
+        quorum_hash: [u8; 32],
+    ) -> Option<CacheEntry<[u8; 48]>> {
+        let path = self.quorum_public_key_path(quorum_type, &quorum_hash);
+        let (bytes, stored_at) = read_file(&path)?;
+
+        match <[u8; 48]>::try_from(bytes.as_slice()) {
+            Ok(value) => Some(CacheEntry { value, stored_at }),
+            Err(_) => {
+                tracing::warn!(path = ?path, "cached quorum public key has invalid length, removing");
+                remove_file(&path);
+                None
+            }
+        }
+    }
+
+    fn store_quorum_public_key(&self, quorum_type: u32, quorum_hash: [u8; 32], key: [u8; 48]) {
+        let path = self.quorum_public_key_path(quorum_type, &quorum_hash);
+        if let Err(e) = write_atomically(&path, &key) {
+            tracing::warn!(path = ?path, error = ?e, "unable to store quorum public key");
+        }
+
+        self.quorum_public_keys
+            .write()
+            .expect("quorum public keys cache lock poisoned")
+            .insert((quorum_type, quorum_hash), CacheEntry::new(key));
+    }
+}
+
+impl<P: ContextProvider> ContextProvider for PersistentContextProvider<P> {
+    fn get_quorum_public_key(
+        &self,
+        quorum_type: u32,
+        quorum_hash: [u8; 32],
+        core_chain_locked_height: u32,
+    ) -> Result<[u8; 48], ContextProviderError> {
+        let cached = self.cached_quorum_public_key(quorum_type, quorum_hash);
+        if let Some(ref entry) = cached {
+            if !entry.is_expired(self.quorum_public_key_ttl) {
+                return Ok(entry.value);
+            }
+        }
+
+        match self
+            .inner
+            .get_quorum_public_key(quorum_type, quorum_hash, core_chain_locked_height)
+        {
+            Ok(key) => {
+                self.store_quorum_public_key(quorum_type, quorum_hash, key);
+                Ok(key)
+            }
+            Err(e) => match cached {
+                Some(entry) => {
+                    tracing::warn!(error = ?e, quorum_type, "unable to refresh quorum public key, using expired one");
+                    Ok(entry.value)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    fn get_data_contract(
+        &self,
+        id: &Identifier,
+    ) -> Result<Option<Arc<DataContract>>, ContextProviderError> {
+        let cached = self.cached_data_contract(id);
+        if let Some(ref entry) = cached {
+            if !entry.is_expired(self.data_contract_ttl) {
+                return Ok(Some(Arc::clone(&entry.value)));
+            }
+        }
+
+        match self.inner.get_data_contract(id) {
+            Ok(Some(data_contract)) => {
+                if let Err(e) = self.put_data_contract(data_contract.as_ref().clone()) {
+                    tracing::warn!(%id, error = ?e, "unable to store data contract");
+                }
+                // put_data_contract() keeps the newest version
+                Ok(self
+                    .cached_data_contract(id)
+                    .map(|entry| entry.value)
+                    .or(Some(data_contract)))
+            }
+            Ok(None) => Ok(cached.map(|entry| entry.value)),
+            Err(e) => match cached {
+                Some(entry) => {
+                    tracing::warn!(%id, error = ?e, "unable to refresh data contract, using expired one");
+                    Ok(Some(entry.value))
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    fn get_platform_activation_height(&self) -> Result<CoreBlockHeight, ContextProviderError> {
+        self.inner.get_platform_activation_height()
+    }
+}
+
+/// Parse `<quorum_type>-<hex quorum_hash>` part of quorum public key file name.
+fn parse_quorum_file_name(name: &str) -> Option<(u32, [u8; 32])> {
+    let (quorum_type, quorum_hash) = name.split_once('-')?;
+    let quorum_type = quorum_type.parse().ok()?;
+    let quorum_hash = hex::decode(quorum_hash).ok()?.try_into().ok()?;
+
+    Some((quorum_type, quorum_hash))
+}
+
+/// Read file content together with its modification time.
+///
+/// Any errors other than missing file are logged on `warn` level and ignored.
+fn read_file(path: &Path) -> Option<(Vec<u8>, SystemTime)> {
+    let read = || -> io::Result<(Vec<u8>, SystemTime)> {
+        let modified = fs::metadata(path)?.modified()?;
+        Ok((fs::read(path)?, modified))
+    };
+
+    match read() {
+        Ok(result) => Some(result),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => {
+            tracing::warn!(path = ?path, error = ?e, "unable to read context cache file");
+            None
+        }
+    }
+}
+
+/// Write file content so that readers never see partially written files.
+fn write_atomically(path: &Path, data: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data)?;
+    fs::rename(&tmp, path)
+}
+
+/// Remove a file; errors other than missing file are logged on `warn` level and ignored.
+fn remove_file(path: &Path) {
+    if let Err(e) = fs::remove_file(path) {
+        if e.kind() != io::ErrorKind::NotFound {
+            tracing::warn!(path = ?path, error = ?e, "unable to remove context cache file");
+        }
+    }
+}
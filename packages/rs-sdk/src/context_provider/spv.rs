@@ -0,0 +1,883 @@
+//! Context provider that maintains a local, chain-lock validated quorum list.
+//!
+//! [SpvContextProvider] follows the Core chain using the DAPI core gRPC service only:
+//!
+//! * `subscribeToBlockHeadersWithChainLocks` provides block headers and chain locks,
+//! * `subscribeToMasternodeList` provides masternode list diffs, including quorum commitments.
+//!
+//! Each masternode list diff is checked against the header of the block it was built for: the coinbase
+//! transaction must be included in the block (partial merkle tree proof), and the `merkleRootQuorums`
+//! committed in the coinbase must match the resulting quorum list. Chain locks are verified with the
+//! chain lock quorum selected from the quorum list, as described in
+//! [DIP-8](https://github.com/dashpay/dips/blob/master/dip-0008.md).
+//!
+//! Trust is anchored in an [SpvCheckpoint]: a block known to be final and the chain lock quorums active
+//! at that block. Headers are followed from the checkpoint; each header must connect to the previous one
+//! and satisfy its proof of work. A chain lock is accepted only when it is signed by a trusted quorum:
+//! one of the checkpoint quorums or a quorum from an already verified list. Quorum lists committed in
+//! blocks at or below an accepted chain lock become verified, and quorum public keys are only served
+//! from verified lists.
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+
+use arc_swap::ArcSwapAny;
+use dapi_grpc::core::v0::{
+    block_headers_with_chain_locks_request, block_headers_with_chain_locks_response,
+    BlockHeadersWithChainLocksRequest, MasternodeListRequest,
+};
+use dashcore_rpc::json::QuorumType;
+use dpp::bls_signatures;
+use dpp::dashcore::block::Header;
+use dpp::dashcore::consensus::deserialize;
+use dpp::dashcore::hashes::{sha256d, Hash, HashEngine};
+use dpp::dashcore::{ChainLock, Network, QuorumSigningRequestId};
+use dpp::prelude::{CoreBlockHeight, DataContract, Identifier};
+use drive_proof_verifier::error::ContextProviderError;
+use drive_proof_verifier::ContextProvider;
+use pollster::FutureExt;
+use rs_dapi_client::{DapiStream, DapiStreamExecutor, RequestSettings};
+
+use crate::core::wire::{merkle_root, MasternodeListDiff, QuorumCommitment};
+use crate::platform::Fetch;
+use crate::{Error, Sdk};
+
+/// Number of blocks between the block used to select the chain lock quorum and the chain locked block.
+const CHAIN_LOCK_SIGN_OFFSET: CoreBlockHeight = 8;
+/// Prefix of the chain lock signing request id.
+const CHAIN_LOCK_REQUEST_ID_PREFIX: &str = "clsig";
+/// Default number of quorum lists kept to verify chain locks.
+const DEFAULT_QUORUM_LIST_HISTORY: usize = 16;
+
+/// Trusted point of the Core chain that [SpvContextProvider] starts from.
+///
+/// It should be recent enough for its chain lock quorums to still sign chain locks when the sync starts;
+/// chain lock quorums of mainnet are replaced in about two days.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpvCheckpoint {
+    /// Height of the checkpoint block
+    pub height: CoreBlockHeight,
+    /// Header of the checkpoint block
+    pub header: Header,
+    /// Active chain lock quorums at the checkpoint block, as quorum hash in Core wire byte order and
+    /// quorum public key
+    pub chain_lock_quorums: Vec<([u8; 32], [u8; 48])>,
+}
+
+/// Context provider that derives quorum public keys from a locally maintained quorum list.
+///
+/// The list is fed by [SpvContextProvider::sync()], which subscribes to block headers, chain locks and
+/// masternode list diffs using DAPI, so no Dash Core node is needed. See [module docs](self) for
+/// details about validation.
+///
+/// Data contracts are fetched from Platform using the [Sdk] configured with
+/// [SpvContextProvider::set_sdk()].
+///
+/// The provider is cheap to clone; clones share the same state, so one clone can be passed to the [Sdk]
+/// while another one runs [SpvContextProvider::sync()].
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use dash_sdk::context_provider::{SpvCheckpoint, SpvContextProvider};
+/// use dash_sdk::dpp::dashcore::Network;
+/// use dash_sdk::SdkBuilder;
+///
+/// # fn checkpoint() -> SpvCheckpoint { unimplemented!() }
+/// # tokio_test::block_on(async {
+/// let provider = SpvContextProvider::new(Network::Testnet, checkpoint())
+///     .with_platform_activation_height(1066900);
+/// let sdk = SdkBuilder::new_testnet()
+///     .with_context_provider(provider.clone())
+///     .build()
+///     .expect("sdk");
+/// provider.set_sdk(Some(sdk.clone()));
+///
+/// let syncing = provider.clone();
+/// let sync_sdk = sdk.clone();
+/// tokio::spawn(async move { syncing.sync(&sync_sdk).await });
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct SpvContextProvider {
+    inner: Arc<SpvContextProviderInner>,
+}
+
+struct SpvContextProviderInner {
+    /// [Sdk] used to fetch data contracts
+    sdk: ArcSwapAny<Arc<Option<Sdk>>>,
+    /// LLMQ type used to sign chain locks
+    chain_lock_quorum_type: u8,
+    /// Core height of Platform activation (`mn_rr` fork), if known
+    platform_activation_height: Option<CoreBlockHeight>,
+    /// Max number of quorum lists to keep
+    quorum_list_history: usize,
+    state: RwLock<SpvState>,
+    data_contracts: RwLock<HashMap<Identifier, Arc<DataContract>>>,
+}
+
+/// Header of a block on the followed chain.
+#[derive(Debug, Clone, Copy)]
+struct StoredHeader {
+    block_hash: [u8; 32],
+    merkle_root: [u8; 32],
+}
+
+/// Quorum list at some block.
+#[derive(Debug, Clone)]
+struct QuorumList {
+    block_hash: [u8; 32],
+    quorums: BTreeMap<(u8, [u8; 32]), QuorumCommitment>,
+    /// Block of this list is at or below a verified chain lock
+    verified: bool,
+}
+
+#[derive(Debug)]
+struct SpvState {
+    headers: BTreeMap<CoreBlockHeight, StoredHeader>,
+    heights: HashMap<[u8; 32], CoreBlockHeight>,
+    /// Height of the latest verified chain lock, or of the checkpoint
+    chain_locked_height: CoreBlockHeight,
+    /// Latest chain lock that could not be verified yet
+    pending_chain_lock: Option<ChainLock>,
+    /// Masternode list diffs received before the headers of their blocks
+    pending_diffs: VecDeque<MasternodeListDiff>,
+    /// Quorum lists, by height of their block
+    lists: BTreeMap<CoreBlockHeight, QuorumList>,
+    /// Chain lock quorums of the checkpoint, trusted until a quorum list is verified
+    checkpoint_quorums: HashMap<(u8, [u8; 32]), [u8; 48]>,
+    /// Public keys of quorums from verified lists, by LLMQ type and quorum hash in wire byte order
+    quorum_public_keys: HashMap<(u8, [u8; 32]), [u8; 48]>,
+}
+
+impl std::fmt::Debug for SpvContextProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpvContextProvider")
+            .field("chain_locked_height", &self.chain_locked_height())
+            .finish_non_exhaustive()
+    }
+}
+
+impl SpvContextProvider {
+    /// Create new SPV context provider for the given network, following the chain from `checkpoint`.
+    ///
+    /// Chain lock quorum type is derived from the `network`; use
+    /// [SpvContextProvider::with_chain_lock_quorum_type()] for networks with custom LLMQ configuration.
+    pub fn new(network: Network, checkpoint: SpvCheckpoint) -> Self {
+        let chain_lock_quorum_type = match network {
+            Network::Dash => QuorumType::Llmq400_60,
+            Network::Testnet => QuorumType::Llmq50_60,
+            Network::Devnet => QuorumType::LlmqDevnetPlatform,
+            Network::Regtest => QuorumType::LlmqTest,
+            _ => QuorumType::Llmq50_60,
+        };
+
+        Self {
+            inner: Arc::new(SpvContextProviderInner {
+                sdk: ArcSwapAny::new(Arc::new(None)),
+                chain_lock_quorum_type: chain_lock_quorum_type as u8,
+                platform_activation_height: None,
+                quorum_list_history: DEFAULT_QUORUM_LIST_HISTORY,
+                state: RwLock::new(SpvState::new(&checkpoint, chain_lock_quorum_type as u8)),
+                data_contracts: RwLock::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Set LLMQ type used to sign chain locks.
+    ///
+    /// Must be called before the provider is cloned or synced.
+    pub fn with_chain_lock_quorum_type(mut self, quorum_type: QuorumType) -> Self {
+        let inner = self.inner_mut();
+        inner.chain_lock_quorum_type = quorum_type as u8;
+        let state = inner.state.get_mut().expect("spv state lock poisoned");
+        state.checkpoint_quorums = state
+            .checkpoint_quorums
+            .drain()
+            .map(|((_, quorum_hash), public_key)| ((quorum_type as u8, quorum_hash), public_key))
+            .collect();
+        self
+    }
+
+    /// Set Core height at which Platform was activated.
+    ///
+    /// It cannot be derived from block headers, so it has to be configured to serve
+    /// [ContextProvider::get_platform_activation_height()].
+    ///
+    /// Must be called before the provider is cloned or synced.
+    pub fn with_platform_activation_height(mut self, height: CoreBlockHeight) -> Self {
+        self.inner_mut().platform_activation_height = Some(height);
+        self
+    }
+
+    /// Set number of quorum lists kept to verify chain locks; defaults to 16.
+    ///
+    /// Must be called before the provider is cloned or synced.
+    pub fn with_quorum_list_history(mut self, lists: usize) -> Self {
+        self.inner_mut().quorum_list_history = lists.max(1);
+        self
+    }
+
+    fn inner_mut(&mut self) -> &mut SpvContextProviderInner {
+        Arc::get_mut(&mut self.inner)
+            .expect("SpvContextProvider must be configured before it is cloned")
+    }
+
+    /// Set the Sdk to use when fetching data contracts from Platform.
+    pub fn set_sdk(&self, sdk: Option<Sdk>) {
+        self.inner.sdk.store(Arc::new(sdk));
+    }
+
+    /// Height of the latest verified chain lock, or of the checkpoint if no chain lock was verified yet.
+    pub fn chain_locked_height(&self) -> CoreBlockHeight {
+        self.read_state().chain_locked_height
+    }
+
+    /// Follow the Core chain until the `sdk` is shut down.
+    ///
+    /// Headers are requested starting after the latest known header, which is the checkpoint block
+    /// when `sync` is called for the first time.
+    ///
+    /// Invalid data, like a chain lock with invalid signature, is skipped; the node that delivered it is
+    /// banned, and the stream continues on another node, so that a single misbehaving node does not
+    /// stop the sync.
+    ///
+    /// Returns an error when any of the streams fails; the state collected so far is kept, so `sync` can
+    /// be called again to resume.
+    pub async fn sync(&self, sdk: &Sdk) -> Result<(), Error> {
+        let mut headers = self.subscribe_to_block_headers(sdk).await?;
+        let mut masternode_list = sdk
+            .stream(MasternodeListRequest {}, RequestSettings::default())
+            .await?;
+
+        loop {
+            tokio::select! {
+                biased;
+                _ = sdk.cancelled() => return Ok(()),
+                message = headers.message() => {
+                    let response = message?
+                        .ok_or_else(|| Error::DapiClientError("block headers stream closed".to_string()))?;
+
+                    let result = match response.responses {
+                        Some(block_headers_with_chain_locks_response::Responses::BlockHeaders(block_headers)) => {
+                            self.process_block_headers(&block_headers.headers)
+                        }
+                        Some(block_headers_with_chain_locks_response::Responses::ChainLock(chain_lock)) => {
+                            self.process_chain_lock(&chain_lock)
+                        }
+                        None => Ok(()),
+                    };
+                    if let Err(error) = result {
+                        tracing::warn!(?error, "invalid block headers or chain lock received, banning node");
+                        headers.ban_current_node();
+                    }
+                },
+                message = masternode_list.message() => {
+                    let response = message?
+                        .ok_or_else(|| Error::DapiClientError("masternode list stream closed".to_string()))?;
+
+                    if let Err(error) = self.process_masternode_list_diff(&response.masternode_list_diff) {
+                        tracing::warn!(?error, "invalid masternode list diff received, banning node");
+                        masternode_list.ban_current_node();
+                    }
+                },
+            }
+        }
+    }
+
+    /// Subscribe to block headers and chain locks following the latest known header.
+    async fn subscribe_to_block_headers(
+        &self,
+        sdk: &Sdk,
+    ) -> Result<DapiStream<BlockHeadersWithChainLocksRequest>, Error> {
+        let from_height = self
+            .read_state()
+            .headers
+            .last_key_value()
+            .map(|(height, _)| height + 1)
+            .expect("checkpoint header is always known");
+
+        let request = BlockHeadersWithChainLocksRequest {
+            from_block: Some(
+                block_headers_with_chain_locks_request::FromBlock::FromBlockHeight(from_height),
+            ),
+            // keep streaming new headers
+            count: 0,
+        };
+
+        Ok(sdk.stream(request, RequestSettings::default()).await?)
+    }
+
+    /// Process consecutive serialized block headers.
+    ///
+    /// Each header must connect to an already known one, starting with the checkpoint block, and satisfy
+    /// its proof of work. A header connecting below the tip replaces the headers above it, unless they
+    /// are chain locked.
+    pub fn process_block_headers(&self, headers: &[Vec<u8>]) -> Result<(), ContextProviderError> {
+        let mut state = self.write_state();
+
+        for raw in headers {
+            let header: Header = deserialize(raw).map_err(|e| {
+                ContextProviderError::Generic(format!("cannot decode block header: {}", e))
+            })?;
+            state.add_header(&header)?;
+        }
+
+        self.advance(&mut state)
+    }
+
+    /// Process a serialized chain lock.
+    ///
+    /// Chain locks that cannot be verified yet, because the header or quorum list is missing, are kept
+    /// until more data arrives; only the latest received one is kept. A chain lock with invalid
+    /// signature is discarded and an error is returned.
+    pub fn process_chain_lock(&self, chain_lock: &[u8]) -> Result<(), ContextProviderError> {
+        let chain_lock: ChainLock = deserialize(chain_lock).map_err(|e| {
+            ContextProviderError::Generic(format!("cannot decode chain lock: {}", e))
+        })?;
+
+        let mut state = self.write_state();
+        if chain_lock.block_height > state.chain_locked_height {
+            state.pending_chain_lock = Some(chain_lock);
+        }
+
+        self.advance(&mut state)
+    }
+
+    /// Process a serialized masternode list diff.
+    ///
+    /// The diff is applied once the header of its block is known.
+    pub fn process_masternode_list_diff(&self, diff: &[u8]) -> Result<(), ContextProviderError> {
+        let diff = MasternodeListDiff::decode(diff)?;
+
+        let mut state = self.write_state();
+        state.pending_diffs.push_back(diff);
+
+        self.advance(&mut state)
+    }
+
+    /// Check if the block with `block_hash` was received.
+    pub(crate) fn is_block_known(&self, block_hash: &[u8; 32]) -> bool {
+        self.read_state().heights.contains_key(block_hash)
+    }
+
+    /// Check that a masternode list diff was built for a block of the followed chain, and that its
+    /// coinbase, which commits to the masternode list, is included in that block.
+    ///
+    /// Fails if the header of the block is not known yet.
+    pub(crate) fn verify_masternode_list_block(
+        &self,
+        diff: &MasternodeListDiff,
+    ) -> Result<(), ContextProviderError> {
+        let state = self.read_state();
+        let height = state
+            .heights
+            .get(&diff.block_hash)
+            .copied()
+            .ok_or_else(|| {
+                ContextProviderError::InvalidQuorum(format!(
+                    "block {} of masternode list diff is not known",
+                    hex::encode(diff.block_hash)
+                ))
+            })?;
+
+        state.verify_coinbase(height, diff)
+    }
+
+    /// Apply pending masternode list diffs and chain locks, as long as possible.
+    fn advance(&self, state: &mut SpvState) -> Result<(), ContextProviderError> {
+        let result = self.apply_pending(state);
+
+        if state.lists.len() > self.inner.quorum_list_history {
+            while state.lists.len() > self.inner.quorum_list_history {
+                state.lists.pop_first();
+            }
+            state.rebuild_public_keys();
+        }
+
+        result
+    }
+
+    fn apply_pending(&self, state: &mut SpvState) -> Result<(), ContextProviderError> {
+        while let Some(diff) = state.pending_diffs.front() {
+            let Some(height) = state.heights.get(&diff.block_hash).copied() else {
+                break;
+            };
+            let diff = state.pending_diffs.pop_front().expect("diff exists");
+            state.apply_diff(height, diff)?;
+        }
+
+        if let Some(chain_lock) = state.pending_chain_lock.take() {
+            if !state.apply_chain_lock(self.inner.chain_lock_quorum_type, &chain_lock)? {
+                state.pending_chain_lock = Some(chain_lock);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_state(&self) -> std::sync::RwLockReadGuard<'_, SpvState> {
+        self.inner.state.read().expect("spv state lock poisoned")
+    }
+
+    fn write_state(&self) -> std::sync::RwLockWriteGuard<'_, SpvState> {
+        self.inner.state.write().expect("spv state lock poisoned")
+    }
+}
+
+impl SpvState {
+    fn new(checkpoint: &SpvCheckpoint, chain_lock_quorum_type: u8) -> Self {
+        let block_hash = checkpoint.header.block_hash().to_byte_array();
+
+        Self {
+            headers: BTreeMap::from([(
+                checkpoint.height,
+                StoredHeader {
+                    block_hash,
+                    merkle_root: checkpoint.header.merkle_root.to_byte_array(),
+                },
+            )]),
+            heights: HashMap::from([(block_hash, checkpoint.height)]),
+            chain_locked_height: checkpoint.height,
+            pending_chain_lock: None,
+            pending_diffs: VecDeque::new(),
+            lists: BTreeMap::new(),
+            checkpoint_quorums: checkpoint
+                .chain_lock_quorums
+                .iter()
+                .map(|(quorum_hash, public_key)| {
+                    ((chain_lock_quorum_type, *quorum_hash), *public_key)
+                })
+                .collect(),
+            quorum_public_keys: HashMap::new(),
+        }
+    }
+
+    fn add_header(&mut self, header: &Header) -> Result<(), ContextProviderError> {
+        let block_hash = header.block_hash().to_byte_array();
+        let prev_hash = header.prev_blockhash.to_byte_array();
+
+        let Some(height) = self
+            .heights
+            .get(&prev_hash)
+            .map(|prev_height| prev_height + 1)
+        else {
+            return Err(ContextProviderError::Generic(format!(
+                "block header {} does not connect to known headers",
+                hex::encode(block_hash)
+            )));
+        };
+
+        header.validate_pow(header.target()).map_err(|e| {
+            ContextProviderError::Generic(format!(
+                "invalid proof of work of block header {} at height {}: {}",
+                hex::encode(block_hash),
+                height,
+                e
+            ))
+        })?;
+
+        match self.headers.get(&height) {
+            Some(existing) if existing.block_hash == block_hash => return Ok(()),
+            Some(_) => {
+                // reorg
+                if height <= self.chain_locked_height {
+                    return Err(ContextProviderError::Generic(format!(
+                        "block header {} at height {} conflicts with chain locked block",
+                        hex::encode(block_hash),
+                        height
+                    )));
+                }
+                let orphaned = self.headers.split_off(&height);
+                for header in orphaned.values() {
+                    self.heights.remove(&header.block_hash);
+                }
+                self.lists.retain(|list_height, _| *list_height < height);
+            }
+            None => {}
+        }
+
+        self.headers.insert(
+            height,
+            StoredHeader {
+                block_hash,
+                merkle_root: header.merkle_root.to_byte_array(),
+            },
+        );
+        self.heights.insert(block_hash, height);
+
+        Ok(())
+    }
+
+    /// Check that the coinbase of the masternode list diff is included in the known block at `height`.
+    fn verify_coinbase(
+        &self,
+        height: CoreBlockHeight,
+        diff: &MasternodeListDiff,
+    ) -> Result<(), ContextProviderError> {
+        let header = self.headers.get(&height).copied().expect("header is known");
+
+        if diff.coinbase.height != height {
+            return Err(ContextProviderError::InvalidQuorum(format!(
+                "masternode list diff coinbase height {} does not match block height {}",
+                diff.coinbase.height, height
+            )));
+        }
+
+        let mut matches = Vec::new();
+        let mut indexes = Vec::new();
+        let root = diff
+            .coinbase_merkle_tree
+            .extract_matches(&mut matches, &mut indexes)
+            .map_err(|e| {
+                ContextProviderError::InvalidQuorum(format!(
+                    "invalid coinbase merkle proof in masternode list diff: {}",
+                    e
+                ))
+            })?;
+        let coinbase_included = root.to_byte_array() == header.merkle_root
+            && matches
+                .iter()
+                .zip(indexes.iter())
+                .any(|(txid, index)| *index == 0 && txid.to_byte_array() == diff.coinbase.txid);
+        if !coinbase_included {
+            return Err(ContextProviderError::InvalidQuorum(format!(
+                "coinbase of masternode list diff is not included in block {}",
+                hex::encode(diff.block_hash)
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Verify masternode list diff against the header of its block and add the resulting quorum list.
+    fn apply_diff(
+        &mut self,
+        height: CoreBlockHeight,
+        diff: MasternodeListDiff,
+    ) -> Result<(), ContextProviderError> {
+        self.verify_coinbase(height, &diff)?;
+
+        let mut quorums = if diff.base_block_hash == [0u8; 32] {
+            BTreeMap::new()
+        } else {
+            self.lists
+                .values()
+                .rev()
+                .find(|list| list.block_hash == diff.base_block_hash)
+                .map(|list| list.quorums.clone())
+                .ok_or_else(|| {
+                    ContextProviderError::InvalidQuorum(format!(
+                        "unknown base block {} of masternode list diff",
+                        hex::encode(diff.base_block_hash)
+                    ))
+                })?
+        };
+
+        for key in &diff.deleted_quorums {
+            quorums.remove(key);
+        }
+        for quorum in diff.new_quorums {
+            quorums.insert((quorum.llmq_type, quorum.quorum_hash), quorum);
+        }
+
+        if let Some(expected) = diff.coinbase.merkle_root_quorums {
+            let mut hashes: Vec<[u8; 32]> = quorums.values().map(|q| q.commitment_hash).collect();
+            hashes.sort_unstable();
+            let actual = merkle_root(hashes).unwrap_or_default();
+            if actual != expected {
+                return Err(ContextProviderError::InvalidQuorum(format!(
+                    "quorum list merkle root {} does not match coinbase commitment {} at height {}",
+                    hex::encode(actual),
+                    hex::encode(expected),
+                    height
+                )));
+            }
+        }
+
+        let verified = height <= self.chain_locked_height;
+        let list = QuorumList {
+            block_hash: diff.block_hash,
+            quorums,
+            verified,
+        };
+        if verified {
+            self.store_public_keys(&list);
+        }
+        self.lists.insert(height, list);
+
+        Ok(())
+    }
+
+    /// Try to verify a chain lock.
+    ///
+    /// The chain lock must be signed by a trusted quorum, from a verified quorum list or from the
+    /// checkpoint. Returns `false` when there is not enough data to verify it yet.
+    fn apply_chain_lock(
+        &mut self,
+        quorum_type: u8,
+        chain_lock: &ChainLock,
+    ) -> Result<bool, ContextProviderError> {
+        let height = chain_lock.block_height;
+        let Some(header) = self.headers.get(&height) else {
+            return Ok(false);
+        };
+        if header.block_hash != chain_lock.block_hash.to_byte_array() {
+            // we can be on a fork that is not yet reorganized; wait for more headers
+            tracing::debug!(
+                height,
+                chain_lock_block_hash = hex::encode(chain_lock.block_hash.to_byte_array()),
+                header_block_hash = hex::encode(header.block_hash),
+                "chain locked block does not match known header"
+            );
+            return Ok(false);
+        }
+
+        let request_id = chain_lock_request_id(height);
+        let trusted = self.trusted_quorums();
+
+        // Chain lock quorum is selected using the list from `SIGN_OFFSET` blocks before the locked block;
+        // we fall back to a more recent list, as quorums rarely change between them, and to the trusted
+        // quorums when no list is known yet.
+        let signing_list = self
+            .lists
+            .range(..=height.saturating_sub(CHAIN_LOCK_SIGN_OFFSET))
+            .next_back()
+            .map(|(_, list)| list);
+        let fallback_list = self
+            .lists
+            .range(..=height)
+            .next_back()
+            .map(|(_, list)| list);
+
+        let list_quorums = |list: &'_ QuorumList| -> Vec<([u8; 32], [u8; 48])> {
+            list.quorums
+                .values()
+                .filter(|quorum| quorum.llmq_type == quorum_type)
+                .map(|quorum| (quorum.quorum_hash, quorum.public_key))
+                .collect()
+        };
+        let trusted_quorums: Vec<([u8; 32], [u8; 48])> = trusted
+            .iter()
+            .filter(|((llmq_type, _), _)| *llmq_type == quorum_type)
+            .map(|((_, quorum_hash), public_key)| (*quorum_hash, *public_key))
+            .collect();
+        // Signature made by a quorum selected from the signing list, or from the trusted quorums when
+        // no list is known, is conclusive; selection from other sets can pick a wrong quorum.
+        let candidates = [
+            signing_list.map(|list| (list_quorums(list), true)),
+            fallback_list.map(|list| (list_quorums(list), false)),
+            Some((trusted_quorums, fallback_list.is_none())),
+        ];
+
+        let mut verified = false;
+        let mut invalid_signature = false;
+        for (quorums, conclusive) in candidates.into_iter().flatten() {
+            let Some((quorum_hash, public_key)) =
+                select_chain_lock_quorum(quorum_type, &request_id, quorums)
+            else {
+                continue;
+            };
+            // only quorums with trusted public keys can sign chain locks
+            if trusted.get(&(quorum_type, quorum_hash)) != Some(&public_key) {
+                continue;
+            }
+
+            if verify_chain_lock_signature(
+                chain_lock,
+                quorum_type,
+                &request_id,
+                &quorum_hash,
+                &public_key,
+            )? {
+                verified = true;
+                break;
+            }
+            invalid_signature |= conclusive;
+        }
+
+        if !verified {
+            if invalid_signature {
+                return Err(ContextProviderError::InvalidQuorum(format!(
+                    "invalid chain lock signature for block {} at height {}",
+                    hex::encode(chain_lock.block_hash.to_byte_array()),
+                    height
+                )));
+            }
+            return Ok(false);
+        }
+
+        self.chain_locked_height = height;
+        let newly_verified: Vec<CoreBlockHeight> = self
+            .lists
+            .range(..=height)
+            .filter(|(_, list)| !list.verified)
+            .map(|(height, _)| *height)
+            .collect();
+        for list_height in newly_verified {
+            let list = self.lists.get_mut(&list_height).expect("list exists");
+            list.verified = true;
+            let list = list.clone();
+            self.store_public_keys(&list);
+        }
+
+        tracing::debug!(height, "chain lock verified");
+
+        Ok(true)
+    }
+
+    /// Quorums trusted to sign chain locks: quorums of verified lists, or checkpoint quorums before
+    /// any list is verified.
+    fn trusted_quorums(&self) -> HashMap<(u8, [u8; 32]), [u8; 48]> {
+        if self.quorum_public_keys.is_empty() {
+            self.checkpoint_quorums.clone()
+        } else {
+            self.quorum_public_keys.clone()
+        }
+    }
+
+    fn store_public_keys(&mut self, list: &QuorumList) {
+        for (key, quorum) in &list.quorums {
+            self.quorum_public_keys.insert(*key, quorum.public_key);
+        }
+    }
+
+    /// Rebuild public keys of verified quorums after old lists were removed.
+    fn rebuild_public_keys(&mut self) {
+        self.quorum_public_keys.clear();
+        let verified: Vec<QuorumList> = self
+            .lists
+            .values()
+            .filter(|list| list.verified)
+            .cloned()
+            .collect();
+        for list in &verified {
+            self.store_public_keys(list);
+        }
+    }
+}
+
+/// Signing request id of the chain lock at `height`.
+fn chain_lock_request_id(height: CoreBlockHeight) -> QuorumSigningRequestId {
+    let mut engine = QuorumSigningRequestId::engine();
+    engine.input(&[CHAIN_LOCK_REQUEST_ID_PREFIX.len() as u8]);
+    engine.input(CHAIN_LOCK_REQUEST_ID_PREFIX.as_bytes());
+    engine.input(height.to_le_bytes().as_slice());
+
+    QuorumSigningRequestId::from_engine(engine)
+}
+
+/// Select the quorum expected to sign the request from `quorums` of `quorum_type`, as described in DIP-8.
+///
+/// Returns `None` if `quorums` is empty.
+fn select_chain_lock_quorum(
+    quorum_type: u8,
+    request_id: &QuorumSigningRequestId,
+    quorums: Vec<([u8; 32], [u8; 48])>,
+) -> Option<([u8; 32], [u8; 48])> {
+    quorums.into_iter().min_by_key(|(quorum_hash, _)| {
+        let mut engine = sha256d::Hash::engine();
+        engine.input(&[quorum_type]);
+        engine.input(quorum_hash);
+        engine.input(request_id.as_byte_array());
+        sha256d::Hash::from_engine(engine).to_byte_array()
+    })
+}
+
+/// Verify chain lock signature made by the quorum.
+fn verify_chain_lock_signature(
+    chain_lock: &ChainLock,
+    quorum_type: u8,
+    request_id: &QuorumSigningRequestId,
+    quorum_hash: &[u8; 32],
+    public_key: &[u8; 48],
+) -> Result<bool, ContextProviderError> {
+    let mut engine = sha256d::Hash::engine();
+    engine.input(&[quorum_type]);
+    engine.input(quorum_hash);
+    engine.input(request_id.as_byte_array());
+    engine.input(chain_lock.block_hash.as_byte_array());
+    let message_digest = sha256d::Hash::from_engine(engine);
+
+    let public_key = bls_signatures::PublicKey::from_bytes(public_key)
+        .map_err(|e| ContextProviderError::InvalidQuorum(e.to_string()))?;
+    let Ok(signature) = bls_signatures::Signature::from_bytes(chain_lock.signature.as_bytes())
+    else {
+        return Ok(false);
+    };
+
+    Ok(public_key.verify(&signature, message_digest.as_byte_array()))
+}
+
+impl ContextProvider for SpvContextProvider {
+    /// Quorum hash is expected in the byte order used by Platform, which is reversed when compared to
+    /// Core wire format.
+    fn get_quorum_public_key(
+        &self,
+        quorum_type: u32,
+        quorum_hash: [u8; 32],
+        core_chain_locked_height: u32,
+    ) -> Result<[u8; 48], ContextProviderError> {
+        let llmq_type = u8::try_from(quorum_type).map_err(|_| {
+            ContextProviderError::InvalidQuorum(format!("invalid quorum type {}", quorum_type))
+        })?;
+        let mut wire_hash = quorum_hash;
+        wire_hash.reverse();
+
+        let state = self.read_state();
+        if let Some(key) = state.quorum_public_keys.get(&(llmq_type, wire_hash)) {
+            return Ok(*key);
+        }
+
+        Err(ContextProviderError::InvalidQuorum(format!(
+            "quorum {} of type {} not found in chain locked quorum lists; requested at core height {}, synced up to {}",
+            hex::encode(quorum_hash),
+            quorum_type,
+            core_chain_locked_height,
+            state.chain_locked_height
+        )))
+    }
+
+    fn get_data_contract(
+        &self,
+        data_contract_id: &Identifier,
+    ) -> Result<Option<Arc<DataContract>>, ContextProviderError> {
+        if let Some(contract) = self
+            .inner
+            .data_contracts
+            .read()
+            .expect("data contracts lock poisoned")
+            .get(data_contract_id)
+        {
+            return Ok(Some(Arc::clone(contract)));
+        }
+
+        let sdk_guard = self.inner.sdk.load();
+        let Some(sdk) = sdk_guard.as_ref() else {
+            tracing::warn!("data contract cache miss and no sdk provided, skipping fetch");
+            return Ok(None);
+        };
+
+        let data_contract = DataContract::fetch(sdk, *data_contract_id)
+            .block_on()
+            .map_err(|e| ContextProviderError::DataContractFailure(e.to_string()))?
+            .map(Arc::new);
+
+        if let Some(contract) = &data_contract {
+            self.inner
+                .data_contracts
+                .write()
+                .expect("data contracts lock poisoned")
+                .insert(*data_contract_id, Arc::clone(contract));
+        }
+
+        Ok(data_contract)
+    }
+
+    fn get_platform_activation_height(&self) -> Result<CoreBlockHeight, ContextProviderError> {
+        self.inner.platform_activation_height.ok_or_else(|| {
+            ContextProviderError::ActivationForkError(
+                "platform activation height not configured".to_string(),
+            )
+        })
+    }
+}
@@ -0,0 +1,296 @@
+//! Discovery of DAPI addresses from the masternode list.
+//!
+//! [Sdk] is created with a static list of seed addresses. [AddressDiscovery] uses them to subscribe to
+//! the masternode list with DAPI `subscribeToMasternodeList`, and keeps the DAPI addresses of the
+//! [Sdk] in sync with the list: evonodes added to the list are added, while removed and PoSe-banned
+//! ones are dropped.
+//!
+//! DAPI is served by evonodes only, on the IP address of the masternode and its Platform HTTP port.
+//!
+//! Each received list is checked against the `merkleRootMNList` committed in the coinbase transaction
+//! of its block, so a node can't alter single entries of the list; a node that sends an invalid list
+//! is banned, and the list is received from another node. To also make sure the coinbase
+//! belongs to a block of the Core chain, configure an [SpvContextProvider] with
+//! [AddressDiscovery::with_spv_context_provider()]. Seed addresses are kept as a fallback, see
+//! [Sdk::update_addresses()].
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use dapi_grpc::core::v0::MasternodeListRequest;
+use drive_proof_verifier::error::ContextProviderError;
+use rs_dapi_client::{DapiStream, DapiStreamExecutor, RequestSettings};
+use tokio::time::Instant;
+
+use crate::context_provider::SpvContextProvider;
+use crate::core::wire::{merkle_root, MasternodeEntry, MasternodeListDiff};
+use crate::sdk::Uri;
+use crate::{Error, Sdk};
+
+/// Default interval between full refreshes of the masternode list.
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+/// Default delay before subscribing again after the masternode list stream failed.
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Keeps DAPI addresses of the [Sdk] in sync with the masternode list.
+///
+/// Use [AddressDiscovery::discover()] to update addresses once, or [AddressDiscovery::run()] to keep
+/// them updated until the [Sdk] is shut down.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use dash_sdk::core::address_discovery::AddressDiscovery;
+/// use dash_sdk::SdkBuilder;
+/// use rs_dapi_client::AddressList;
+///
+/// # tokio_test::block_on(async {
+/// let seeds = AddressList::from("https://127.0.0.1:1443");
+/// let sdk = SdkBuilder::new(seeds).build().expect("sdk");
+///
+/// let discovery_sdk = sdk.clone();
+/// tokio::spawn(async move { AddressDiscovery::new().run(&discovery_sdk).await });
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct AddressDiscovery {
+    refresh_interval: Duration,
+    retry_delay: Duration,
+    /// Provider of verified block headers, used to verify the masternode list
+    spv: Option<SpvContextProvider>,
+    /// Current masternode list, by ProRegTx hash; iterated in the order used by `merkleRootMNList`
+    masternodes: BTreeMap<[u8; 32], MasternodeEntry>,
+}
+
+impl Default for AddressDiscovery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AddressDiscovery {
+    /// Create a new address discovery with default settings.
+    pub fn new() -> Self {
+        Self {
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            spv: None,
+            masternodes: BTreeMap::new(),
+        }
+    }
+
+    /// Interval between full refreshes of the masternode list; defaults to [DEFAULT_REFRESH_INTERVAL].
+    ///
+    /// Changes of the list are received as they happen, but the subscription is restarted
+    /// periodically to receive the full list again.
+    pub fn with_refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    /// Delay before subscribing again after the masternode list stream failed; defaults to
+    /// [DEFAULT_RETRY_DELAY].
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Accept only masternode lists of blocks known to the SPV context provider.
+    ///
+    /// The provider must be [synced](SpvContextProvider::sync()) separately; lists received before
+    /// the header of their block are rejected, and the subscription is retried.
+    pub fn with_spv_context_provider(mut self, provider: SpvContextProvider) -> Self {
+        self.spv = Some(provider);
+        self
+    }
+
+    /// Fetch the masternode list once and update DAPI addresses of the [Sdk].
+    ///
+    /// Returns discovered addresses.
+    pub async fn discover(&mut self, sdk: &Sdk) -> Result<Vec<Uri>, Error> {
+        let mut stream = sdk
+            .stream(MasternodeListRequest {}, RequestSettings::default())
+            .await?;
+
+        // first message contains the full list
+        loop {
+            let response = stream.message().await?.ok_or_else(|| {
+                Error::DapiClientError("masternode list stream closed".to_string())
+            })?;
+
+            if self.process_message(&mut stream, &response.masternode_list_diff)? {
+                break;
+            }
+        }
+        self.update_sdk(sdk)?;
+
+        Ok(self.addresses())
+    }
+
+    /// Keep DAPI addresses of the [Sdk] in sync with the masternode list until the [Sdk] is shut down.
+    ///
+    /// Failures of the masternode list stream are logged, and the subscription is retried after
+    /// [retry delay](AddressDiscovery::with_retry_delay()).
+    pub async fn run(&mut self, sdk: &Sdk) -> Result<(), Error> {
+        loop {
+            if let Err(e) = self.follow(sdk).await {
+                tracing::warn!(error = ?e, "masternode list discovery failed, retrying");
+
+                tokio::select! {
+                    biased;
+                    _ = sdk.cancelled() => return Ok(()),
+                    _ = tokio::time::sleep(self.retry_delay) => {},
+                }
+            }
+
+            if sdk.cancel_token.is_cancelled() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Follow masternode list changes until the refresh interval elapses or the [Sdk] is shut down.
+    async fn follow(&mut self, sdk: &Sdk) -> Result<(), Error> {
+        let deadline = Instant::now() + self.refresh_interval;
+
+        let mut stream = sdk
+            .stream(MasternodeListRequest {}, RequestSettings::default())
+            .await?;
+
+        loop {
+            tokio::select! {
+                biased;
+                _ = sdk.cancelled() => return Ok(()),
+                _ = tokio::time::sleep_until(deadline) => return Ok(()),
+                message = stream.message() => {
+                    let response = message?
+                        .ok_or_else(|| Error::DapiClientError("masternode list stream closed".to_string()))?;
+
+                    if self.process_message(&mut stream, &response.masternode_list_diff)? {
+                        self.update_sdk(sdk)?;
+                    }
+                },
+            }
+        }
+    }
+
+    /// Process a message of the masternode list stream, banning the node that sent an invalid list.
+    ///
+    /// Returns `true` if the list was updated, or an error if the list can't be verified yet, as the
+    /// [SPV context provider](AddressDiscovery::with_spv_context_provider()) doesn't know its block.
+    fn process_message(
+        &mut self,
+        stream: &mut DapiStream<MasternodeListRequest>,
+        data: &[u8],
+    ) -> Result<bool, Error> {
+        let result = match MasternodeListDiff::decode(data) {
+            Ok(diff) => {
+                if let Some(spv) = &self.spv {
+                    // not the fault of the node, the subscription is retried later
+                    if !spv.is_block_known(&diff.block_hash) {
+                        return Err(ContextProviderError::InvalidQuorum(format!(
+                            "block {} of masternode list diff is not known yet",
+                            hex::encode(diff.block_hash)
+                        ))
+                        .into());
+                    }
+                }
+
+                self.apply_diff(diff)
+            }
+            Err(error) => Err(error.into()),
+        };
+
+        match result {
+            Ok(()) => Ok(true),
+            Err(error) => {
+                tracing::warn!(?error, "invalid masternode list received, banning node");
+                stream.ban_current_node();
+
+                Ok(false)
+            }
+        }
+    }
+
+    /// Apply a serialized masternode list diff, as returned by `subscribeToMasternodeList`.
+    ///
+    /// A diff with empty base block hash contains the full list and replaces the current one.
+    ///
+    /// The resulting list must match `merkleRootMNList` of the coinbase transaction of the diff, and
+    /// the coinbase must be included in a known block if an
+    /// [SPV context provider](AddressDiscovery::with_spv_context_provider()) is configured.
+    /// Otherwise, an error is returned and the current list is kept.
+    pub fn process_masternode_list_diff(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.apply_diff(MasternodeListDiff::decode(data)?)
+    }
+
+    /// Apply a decoded masternode list diff, see [AddressDiscovery::process_masternode_list_diff()].
+    fn apply_diff(&mut self, diff: MasternodeListDiff) -> Result<(), Error> {
+        if let Some(spv) = &self.spv {
+            spv.verify_masternode_list_block(&diff)?;
+        }
+
+        let mut masternodes = if diff.base_block_hash == [0u8; 32] {
+            BTreeMap::new()
+        } else {
+            self.masternodes.clone()
+        };
+        for pro_reg_tx_hash in &diff.deleted_masternodes {
+            masternodes.remove(pro_reg_tx_hash);
+        }
+        for entry in diff.masternodes {
+            masternodes.insert(entry.pro_reg_tx_hash, entry);
+        }
+
+        let hashes = masternodes.values().map(|entry| entry.entry_hash).collect();
+        let root = merkle_root(hashes).unwrap_or_default();
+        if root != diff.coinbase.merkle_root_mn_list {
+            return Err(ContextProviderError::InvalidQuorum(format!(
+                "masternode list merkle root {} does not match coinbase commitment {} at height {}",
+                hex::encode(root),
+                hex::encode(diff.coinbase.merkle_root_mn_list),
+                diff.coinbase.height
+            ))
+            .into());
+        }
+
+        self.masternodes = masternodes;
+
+        Ok(())
+    }
+
+    /// DAPI addresses of valid evonodes in the current masternode list.
+    pub fn addresses(&self) -> Vec<Uri> {
+        self.masternodes
+            .values()
+            .filter(|entry| entry.is_valid && !entry.service.ip().is_unspecified())
+            .filter_map(|entry| {
+                let port = entry.platform_http_port?;
+                let mut address = entry.service;
+                address.set_port(port);
+
+                Uri::builder()
+                    .scheme("https")
+                    .authority(address.to_string())
+                    .path_and_query("/")
+                    .build()
+                    .inspect_err(|e| tracing::debug!(?address, error = ?e, "invalid DAPI address"))
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Replace DAPI addresses of the [Sdk] with discovered ones.
+    ///
+    /// Addresses are kept unchanged if no evonodes were discovered, so that a broken list doesn't
+    /// leave the [Sdk] without any address.
+    fn update_sdk(&self, sdk: &Sdk) -> Result<(), Error> {
+        let addresses = self.addresses();
+        if addresses.is_empty() {
+            tracing::warn!("no evonodes found in the masternode list, keeping DAPI addresses");
+            return Ok(());
+        }
+
+        tracing::debug!(count = addresses.len(), "updating DAPI addresses");
+        sdk.update_addresses(addresses)
+    }
+}
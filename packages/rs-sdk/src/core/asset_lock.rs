@@ -0,0 +1,176 @@
+//! Asset lock transactions used to fund identities.
+use dpp::balances::credits::Duffs;
+use dpp::dashcore::blockdata::script::{Builder, PushBytesBuf};
+use dpp::dashcore::consensus::encode::serialize;
+use dpp::dashcore::hashes::Hash;
+use dpp::dashcore::secp256k1::{Message, Secp256k1};
+use dpp::dashcore::sighash::{EcdsaSighashType, SighashCache};
+use dpp::dashcore::transaction::special_transaction::asset_lock::AssetLockPayload;
+use dpp::dashcore::transaction::special_transaction::TransactionPayload;
+use dpp::dashcore::{OutPoint, PrivateKey, PublicKey, ScriptBuf, Transaction, TxIn, TxOut};
+
+use crate::Error;
+
+/// Version of special transactions.
+const SPECIAL_TRANSACTION_VERSION: u16 = 3;
+/// Version of the asset lock payload.
+const ASSET_LOCK_PAYLOAD_VERSION: u8 = 1;
+/// Max size of a P2PKH input script: DER signature with sighash type and compressed public key, with pushes.
+const P2PKH_SCRIPT_SIG_MAX_SIZE: usize = 1 + 73 + 1 + 33;
+/// Outputs with lower value are not relayed by Dash Core; they are added to the fee instead.
+pub const DUST_THRESHOLD: Duffs = 546;
+/// Default fee rate, in duffs per byte.
+pub const DEFAULT_FEE_PER_BYTE: Duffs = 1;
+
+/// Unspent transaction output that can be spent with the funding private key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Utxo {
+    /// Reference to the output
+    pub out_point: OutPoint,
+    /// The output; its script must be P2PKH of the funding key
+    pub tx_out: TxOut,
+}
+
+/// Builds and signs an asset lock transaction.
+///
+/// The transaction spends all `utxos` with `funding_key`, locks `amount` duffs in a credit output
+/// paying to `asset_lock_public_key`, and returns the change, if not dust, to the funding key address.
+pub fn build_asset_lock_transaction(
+    utxos: &[Utxo],
+    funding_key: &PrivateKey,
+    asset_lock_public_key: &PublicKey,
+    amount: Duffs,
+    fee_per_byte: Duffs,
+) -> Result<Transaction, Error> {
+    if utxos.is_empty() {
+        return Err(Error::Generic(
+            "at least one utxo is required to fund asset lock".to_string(),
+        ));
+    }
+
+    let secp = Secp256k1::new();
+    let funding_public_key = funding_key.public_key(&secp);
+    let funding_script = ScriptBuf::new_p2pkh(&funding_public_key.pubkey_hash());
+
+    if let Some(utxo) = utxos
+        .iter()
+        .find(|utxo| utxo.tx_out.script_pubkey != funding_script)
+    {
+        return Err(Error::Generic(format!(
+            "utxo {} can't be spent with the funding key",
+            utxo.out_point
+        )));
+    }
+
+    let input_value = utxos
+        .iter()
+        .try_fold(0u64, |sum, utxo| sum.checked_add(utxo.tx_out.value))
+        .ok_or_else(|| Error::Generic("utxo values overflow".to_string()))?;
+
+    let mut transaction = Transaction {
+        version: SPECIAL_TRANSACTION_VERSION,
+        lock_time: 0,
+        input: utxos
+            .iter()
+            .map(|utxo| TxIn {
+                previous_output: utxo.out_point,
+                script_sig: ScriptBuf::new(),
+                sequence: u32::MAX,
+                witness: Default::default(),
+            })
+            .collect(),
+        output: vec![TxOut {
+            value: amount,
+            script_pubkey: ScriptBuf::new_op_return(&[]),
+        }],
+        special_transaction_payload: Some(TransactionPayload::AssetLockPayloadType(
+            AssetLockPayload {
+                version: ASSET_LOCK_PAYLOAD_VERSION,
+                credit_outputs: vec![TxOut {
+                    value: amount,
+                    script_pubkey: ScriptBuf::new_p2pkh(&asset_lock_public_key.pubkey_hash()),
+                }],
+            },
+        )),
+    };
+
+    let change_output = TxOut {
+        value: 0,
+        script_pubkey: funding_script.clone(),
+    };
+    let fee_with_change = estimated_size(&transaction, Some(&change_output)) as u64 * fee_per_byte;
+    let fee_without_change = estimated_size(&transaction, None) as u64 * fee_per_byte;
+
+    let change = input_value
+        .checked_sub(amount)
+        .and_then(|rest| rest.checked_sub(fee_with_change));
+    match change {
+        Some(change) if change >= DUST_THRESHOLD => transaction.output.push(TxOut {
+            value: change,
+            ..change_output
+        }),
+        _ if input_value >= amount.saturating_add(fee_without_change) => {}
+        _ => {
+            return Err(Error::Generic(format!(
+                "insufficient funds: utxos hold {} duffs, asset lock requires {} duffs and fee {} duffs",
+                input_value, amount, fee_without_change
+            )))
+        }
+    }
+
+    sign_p2pkh_inputs(&mut transaction, &funding_script, funding_key)?;
+
+    Ok(transaction)
+}
+
+/// Estimated size of the signed transaction, with optional additional output.
+fn estimated_size(transaction: &Transaction, additional_output: Option<&TxOut>) -> usize {
+    let outputs_size = additional_output
+        .map(|output| serialize(output).len())
+        .unwrap_or_default();
+
+    serialize(transaction).len()
+        + transaction.input.len() * P2PKH_SCRIPT_SIG_MAX_SIZE
+        + outputs_size
+}
+
+/// Sign all inputs of the transaction, spending P2PKH outputs with `script_pubkey`.
+fn sign_p2pkh_inputs(
+    transaction: &mut Transaction,
+    script_pubkey: &ScriptBuf,
+    private_key: &PrivateKey,
+) -> Result<(), Error> {
+    let secp = Secp256k1::new();
+    let public_key = private_key.public_key(&secp);
+    let sighash_type = EcdsaSighashType::All;
+
+    let sighash_cache = SighashCache::new(&*transaction);
+    let script_sigs = (0..transaction.input.len())
+        .map(|index| {
+            let sighash = sighash_cache
+                .legacy_signature_hash(index, script_pubkey, sighash_type.to_u32())
+                .map_err(|e| Error::Generic(format!("cannot calculate signature hash: {}", e)))?;
+            let message = Message::from_slice(sighash.as_byte_array())
+                .map_err(|e| Error::Generic(format!("invalid signature hash: {}", e)))?;
+
+            let mut signature = secp
+                .sign_ecdsa(&message, &private_key.inner)
+                .serialize_der()
+                .to_vec();
+            signature.push(sighash_type.to_u32() as u8);
+            let signature = PushBytesBuf::try_from(signature)
+                .map_err(|e| Error::Generic(format!("invalid signature: {}", e)))?;
+
+            Ok(Builder::new()
+                .push_slice(signature)
+                .push_key(&public_key)
+                .into_script())
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    for (input, script_sig) in transaction.input.iter_mut().zip(script_sigs) {
+        input.script_sig = script_sig;
+    }
+
+    Ok(())
+}
@@ -1,4 +1,7 @@
 //! Dash Core SDK implementation.
 //!
 //! TODO: This is work in progress.
+pub mod address_discovery;
+pub mod asset_lock;
 mod transaction;
+pub(crate) mod wire;
@@ -1,12 +1,14 @@
 use crate::{Error, Sdk};
 use bip37_bloom_filter::{BloomFilter, BloomFilterData};
 use dapi_grpc::core::v0::{
-    transactions_with_proofs_request, transactions_with_proofs_response, GetTransactionRequest,
+    transactions_with_proofs_request, transactions_with_proofs_response,
+    BroadcastTransactionRequest, BroadcastTransactionResponse, GetTransactionRequest,
     GetTransactionResponse, TransactionsWithProofsRequest, TransactionsWithProofsResponse,
 };
 use dapi_grpc::platform::v0::{
     get_epochs_info_request, get_epochs_info_response, GetEpochsInfoRequest, GetEpochsInfoResponse,
 };
+use dpp::dashcore::consensus::encode::serialize;
 use dpp::dashcore::consensus::Decodable;
 use dpp::dashcore::{Address, InstantLock, MerkleBlock, OutPoint, Transaction, Txid};
 use dpp::identity::state_transition::asset_lock_proof::chain::ChainAssetLockProof;
@@ -26,10 +28,25 @@ impl Sdk {
     ) -> Result<dapi_grpc::tonic::Streaming<TransactionsWithProofsResponse>, Error> {
         let address_bytes = address.as_unchecked().payload_to_vec();
 
+        self.start_transactions_stream(from_block_hash, &[address_bytes])
+            .await
+    }
+
+    /// Starts the stream to listen for transactions matching any of the bloom filter `elements`,
+    /// like public key hashes of outputs or transaction ids, and their instant send lock messages
+    pub async fn start_transactions_stream(
+        &self,
+        from_block_hash: Vec<u8>,
+        elements: &[Vec<u8>],
+    ) -> Result<dapi_grpc::tonic::Streaming<TransactionsWithProofsResponse>, Error> {
         // create the bloom filter
-        let bloom_filter = BloomFilter::builder(1, 0.001)
-            .expect("this FP rate allows up to 10000 items")
-            .add_element(&address_bytes)
+        let bloom_filter = elements
+            .iter()
+            .fold(
+                BloomFilter::builder(elements.len().max(1) as _, 0.001)
+                    .expect("this FP rate allows up to 10000 items"),
+                |builder, element| builder.add_element(element),
+            )
             .build();
 
         let bloom_filter_proto = {
@@ -164,95 +181,7 @@ impl Sdk {
                             "merkle block contains the transaction, obtaining core chain locked height"
                         );
 
-                        // TODO: This a temporary implementation until we have headers stream running in background
-                        //  so we can always get actual height and chain locks
-
-                        // Wait until the block is chainlocked
-                        let mut core_chain_locked_height;
-                        loop {
-                            let GetTransactionResponse {
-                                height,
-                                is_chain_locked,
-                                ..
-                            } = self
-                                .execute(
-                                    GetTransactionRequest {
-                                        id: transaction_id.to_string(),
-                                    },
-                                    RequestSettings::default(),
-                                )
-                                .await?;
-
-                            core_chain_locked_height = height;
-
-                            if is_chain_locked {
-                                break;
-                            }
-
-                            tracing::trace!("the transaction is on height {} but not chainlocked. try again in 1 sec", height);
-
-                            sleep(Duration::from_secs(1)).await;
-                        }
-
-                        tracing::debug!(
-                            "the transaction is chainlocked on height {}, waiting platform for reaching the same core height",
-                            core_chain_locked_height
-                        );
-
-                        // Wait until platform chain is on the block's chain locked height
-                        loop {
-                            let request = GetEpochsInfoRequest {
-                                version: Some(get_epochs_info_request::Version::V0(
-                                    get_epochs_info_request::GetEpochsInfoRequestV0 {
-                                        start_epoch: Some(0),
-                                        count: 1,
-                                        ..Default::default()
-                                    },
-                                )),
-                            };
-
-                            let GetEpochsInfoResponse {
-                                version:
-                                    Some(get_epochs_info_response::Version::V0(
-                                        get_epochs_info_response::GetEpochsInfoResponseV0 {
-                                            metadata: Some(metadata),
-                                            ..
-                                        },
-                                    )),
-                            } = self.execute(request, RequestSettings::default()).await?
-                            else {
-                                return Err(Error::DapiClientError(String::from(
-                                    "missing V0 `metadata` field",
-                                )));
-                            };
-
-                            if metadata.core_chain_locked_height >= core_chain_locked_height {
-                                break;
-                            }
-
-                            tracing::trace!(
-                                "platform chain locked core height {} but we need {}. try again in 1 sec",
-                                metadata.core_chain_locked_height,
-                                core_chain_locked_height,
-                            );
-
-                            sleep(Duration::from_secs(1)).await;
-                        }
-
-                        let asset_lock_proof = AssetLockProof::Chain(ChainAssetLockProof {
-                            core_chain_locked_height,
-                            out_point: OutPoint {
-                                txid: transaction.txid(),
-                                vout: 0,
-                            },
-                        });
-
-                        tracing::debug!(
-                                ?asset_lock_proof,
-                                "merkle block contains the broadcasted transaction, returning chain asset lock proof"
-                            );
-
-                        return Ok(asset_lock_proof);
+                        return self.wait_for_chain_asset_lock_proof(transaction).await;
                     }
                     Some(transactions_with_proofs_response::Responses::RawTransactions(_)) => {
                         tracing::trace!("received transaction(s), ignoring")
@@ -272,4 +201,124 @@ impl Sdk {
             None => stream_processing.await,
         }
     }
+
+    /// Waits until the asset lock transaction is chain locked and Platform reaches the chain locked
+    /// core height, and returns a chain asset lock proof.
+    ///
+    /// Use it when an instant lock for the transaction is not available.
+    pub async fn wait_for_chain_asset_lock_proof(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<AssetLockProof, Error> {
+        let transaction_id = transaction.txid();
+
+        // TODO: This a temporary implementation until we have headers stream running in background
+        //  so we can always get actual height and chain locks
+
+        // Wait until the block is chainlocked
+        let mut core_chain_locked_height;
+        loop {
+            let GetTransactionResponse {
+                height,
+                is_chain_locked,
+                ..
+            } = self
+                .execute(
+                    GetTransactionRequest {
+                        id: transaction_id.to_string(),
+                    },
+                    RequestSettings::default(),
+                )
+                .await?;
+
+            core_chain_locked_height = height;
+
+            if is_chain_locked {
+                break;
+            }
+
+            tracing::trace!(
+                "the transaction is on height {} but not chainlocked. try again in 1 sec",
+                height
+            );
+
+            sleep(Duration::from_secs(1)).await;
+        }
+
+        tracing::debug!(
+            "the transaction is chainlocked on height {}, waiting platform for reaching the same core height",
+            core_chain_locked_height
+        );
+
+        // Wait until platform chain is on the block's chain locked height
+        loop {
+            let request = GetEpochsInfoRequest {
+                version: Some(get_epochs_info_request::Version::V0(
+                    get_epochs_info_request::GetEpochsInfoRequestV0 {
+                        start_epoch: Some(0),
+                        count: 1,
+                        ..Default::default()
+                    },
+                )),
+            };
+
+            let GetEpochsInfoResponse {
+                version:
+                    Some(get_epochs_info_response::Version::V0(
+                        get_epochs_info_response::GetEpochsInfoResponseV0 {
+                            metadata: Some(metadata),
+                            ..
+                        },
+                    )),
+            } = self.execute(request, RequestSettings::default()).await?
+            else {
+                return Err(Error::DapiClientError(String::from(
+                    "missing V0 `metadata` field",
+                )));
+            };
+
+            if metadata.core_chain_locked_height >= core_chain_locked_height {
+                break;
+            }
+
+            tracing::trace!(
+                "platform chain locked core height {} but we need {}. try again in 1 sec",
+                metadata.core_chain_locked_height,
+                core_chain_locked_height,
+            );
+
+            sleep(Duration::from_secs(1)).await;
+        }
+
+        let asset_lock_proof = AssetLockProof::Chain(ChainAssetLockProof {
+            core_chain_locked_height,
+            out_point: OutPoint {
+                txid: transaction.txid(),
+                vout: 0,
+            },
+        });
+
+        tracing::debug!(
+            ?asset_lock_proof,
+            "transaction is chain locked, returning chain asset lock proof"
+        );
+
+        Ok(asset_lock_proof)
+    }
+
+    /// Broadcasts a transaction to the Dash Core network.
+    pub async fn broadcast_transaction(&self, transaction: &Transaction) -> Result<Txid, Error> {
+        let request = BroadcastTransactionRequest {
+            transaction: serialize(transaction),
+            allow_high_fees: false,
+            bypass_limits: false,
+        };
+
+        let BroadcastTransactionResponse { transaction_id } =
+            self.execute(request, RequestSettings::default()).await?;
+
+        tracing::debug!(transaction_id, "transaction broadcast");
+
+        Ok(transaction.txid())
+    }
 }
@@ -0,0 +1,384 @@
+//! Decoding of Dash Core wire structures received from DAPI.
+//!
+//! `dashcore` does not expose the simplified masternode list structures, so the masternode list diff
+//! (`MNLISTDIFF`) is decoded here. Layout follows the one produced by DAPI `subscribeToMasternodeList`:
+//!
+//! ```text
+//! baseBlockHash, blockHash, cbTxMerkleTree, cbTx, nVersion,
+//! deletedMNs, mnList, deletedQuorums, newQuorums[, quorumsCLSigs]
+//! ```
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+
+use dpp::dashcore::consensus::deserialize_partial;
+use dpp::dashcore::hashes::{sha256d, Hash};
+use dpp::dashcore::merkle_tree::PartialMerkleTree;
+use drive_proof_verifier::error::ContextProviderError;
+
+/// Special transaction type of the coinbase transaction.
+const TRANSACTION_TYPE_COINBASE: u16 = 5;
+/// Version of a masternode list entry that includes masternode type.
+const MN_ENTRY_BASIC_BLS_VERSION: u16 = 2;
+/// Evo masternode type.
+const MN_TYPE_EVO: u16 = 1;
+/// Quorum commitment versions that contain quorum index.
+const INDEXED_QUORUM_VERSIONS: [u16; 2] = [2, 4];
+
+/// Quorum commitment included in a masternode list diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct QuorumCommitment {
+    /// LLMQ type
+    pub llmq_type: u8,
+    /// Quorum hash, in wire byte order
+    pub quorum_hash: [u8; 32],
+    /// Quorum public key
+    pub public_key: [u8; 48],
+    /// Double-SHA256 of the serialized commitment, used to compute `merkleRootQuorums`
+    pub commitment_hash: [u8; 32],
+}
+
+/// Coinbase transaction of the block a masternode list diff was built for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CoinbaseTransaction {
+    /// Transaction id, in wire byte order
+    pub txid: [u8; 32],
+    /// Block height declared in the coinbase payload
+    pub height: u32,
+    /// Merkle root of all masternode list entries, see [MasternodeEntry::entry_hash]
+    pub merkle_root_mn_list: [u8; 32],
+    /// Merkle root of all active quorum commitments; `None` for coinbase payloads older than v2
+    pub merkle_root_quorums: Option<[u8; 32]>,
+}
+
+/// Masternode list entry included in a masternode list diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MasternodeEntry {
+    /// ProRegTx hash identifying the masternode, in wire byte order
+    pub pro_reg_tx_hash: [u8; 32],
+    /// Core P2P service address
+    pub service: SocketAddr,
+    /// Masternode is not PoSe-banned
+    pub is_valid: bool,
+    /// Platform HTTP (DAPI) port; `None` for regular masternodes, which don't run Platform
+    pub platform_http_port: Option<u16>,
+    /// Double-SHA256 of the serialized entry without its version, used to compute `merkleRootMNList`
+    pub entry_hash: [u8; 32],
+}
+
+/// Decoded masternode list diff.
+#[derive(Debug, Clone)]
+pub(crate) struct MasternodeListDiff {
+    /// Block hash of the list the diff applies to; all zeros for a full list
+    pub base_block_hash: [u8; 32],
+    /// Block hash of the list after applying the diff
+    pub block_hash: [u8; 32],
+    /// Partial merkle tree proving that `coinbase` is included in the block
+    pub coinbase_merkle_tree: PartialMerkleTree,
+    /// Coinbase transaction of the block
+    pub coinbase: CoinbaseTransaction,
+    /// ProRegTx hashes of masternodes removed from the list
+    pub deleted_masternodes: Vec<[u8; 32]>,
+    /// Masternodes added to the list or changed
+    pub masternodes: Vec<MasternodeEntry>,
+    /// Quorums removed from the list, as `(llmq_type, quorum_hash)`
+    pub deleted_quorums: Vec<(u8, [u8; 32])>,
+    /// Quorums added to the list
+    pub new_quorums: Vec<QuorumCommitment>,
+}
+
+impl MasternodeListDiff {
+    /// Decode a masternode list diff.
+    pub fn decode(data: &[u8]) -> Result<Self, ContextProviderError> {
+        let mut reader = Reader::new(data);
+
+        let base_block_hash = reader.read_array()?;
+        let block_hash = reader.read_array()?;
+
+        let (coinbase_merkle_tree, consumed) =
+            deserialize_partial::<PartialMerkleTree>(reader.remaining())
+                .map_err(|e| invalid(format!("cannot decode coinbase merkle tree: {}", e)))?;
+        reader.skip(consumed)?;
+
+        let coinbase = read_coinbase_transaction(&mut reader)?;
+
+        let _version = reader.read_u16()?;
+
+        let count = reader.read_compact_size()?;
+        let mut deleted_masternodes = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            deleted_masternodes.push(reader.read_array()?);
+        }
+
+        let count = reader.read_compact_size()?;
+        let mut masternodes = Vec::with_capacity(count.min(4096));
+        for _ in 0..count {
+            masternodes.push(read_masternode_entry(&mut reader)?);
+        }
+
+        let count = reader.read_compact_size()?;
+        let mut deleted_quorums = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            deleted_quorums.push((reader.read_u8()?, reader.read_array()?));
+        }
+
+        let count = reader.read_compact_size()?;
+        let mut new_quorums = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            new_quorums.push(read_quorum_commitment(&mut reader)?);
+        }
+
+        // Trailing `quorumsCLSigs` are not used; chain locks are received separately.
+
+        Ok(Self {
+            base_block_hash,
+            block_hash,
+            coinbase_merkle_tree,
+            coinbase,
+            deleted_masternodes,
+            masternodes,
+            deleted_quorums,
+            new_quorums,
+        })
+    }
+}
+
+/// Compute a Bitcoin-style merkle root of `hashes`, duplicating the last hash on odd levels.
+///
+/// Returns `None` if `hashes` is empty.
+pub(crate) fn merkle_root(mut hashes: Vec<[u8; 32]>) -> Option<[u8; 32]> {
+    if hashes.is_empty() {
+        return None;
+    }
+
+    while hashes.len() > 1 {
+        if hashes.len() % 2 == 1 {
+            hashes.push(*hashes.last().expect("not empty"));
+        }
+        hashes = hashes
+            .chunks(2)
+            .map(|pair| {
+                let mut data = [0u8; 64];
+                data[..32].copy_from_slice(&pair[0]);
+                data[32..].copy_from_slice(&pair[1]);
+                sha256d::Hash::hash(&data).to_byte_array()
+            })
+            .collect();
+    }
+
+    hashes.pop()
+}
+
+fn read_coinbase_transaction(
+    reader: &mut Reader<'_>,
+) -> Result<CoinbaseTransaction, ContextProviderError> {
+    let start = reader.position();
+
+    let version = reader.read_u16()?;
+    let tx_type = reader.read_u16()?;
+
+    let inputs = reader.read_compact_size()?;
+    for _ in 0..inputs {
+        // previous outpoint
+        reader.skip(36)?;
+        // script
+        reader.read_var_bytes()?;
+        // sequence
+        reader.skip(4)?;
+    }
+
+    let outputs = reader.read_compact_size()?;
+    for _ in 0..outputs {
+        // value
+        reader.skip(8)?;
+        // script
+        reader.read_var_bytes()?;
+    }
+
+    // lock time
+    reader.skip(4)?;
+
+    if version < 3 || tx_type != TRANSACTION_TYPE_COINBASE {
+        return Err(invalid(format!(
+            "expected coinbase special transaction, got version {} type {}",
+            version, tx_type
+        )));
+    }
+
+    let payload = reader.read_var_bytes()?;
+    let txid = sha256d::Hash::hash(&reader.data[start..reader.position()]).to_byte_array();
+
+    let mut payload = Reader::new(payload);
+    let payload_version = payload.read_u16()?;
+    let height = payload.read_u32()?;
+    let merkle_root_mn_list = payload.read_array()?;
+    let merkle_root_quorums = if payload_version >= 2 {
+        Some(payload.read_array()?)
+    } else {
+        None
+    };
+
+    Ok(CoinbaseTransaction {
+        txid,
+        height,
+        merkle_root_mn_list,
+        merkle_root_quorums,
+    })
+}
+
+fn read_masternode_entry(reader: &mut Reader<'_>) -> Result<MasternodeEntry, ContextProviderError> {
+    let version = reader.read_u16()?;
+    if version > MN_ENTRY_BASIC_BLS_VERSION {
+        return Err(invalid(format!(
+            "unsupported masternode list entry version {}",
+            version
+        )));
+    }
+
+    // version is not included in the hash of the entry
+    let start = reader.position();
+
+    let pro_reg_tx_hash = reader.read_array()?;
+    // confirmedHash
+    reader.skip(32)?;
+    let ip = Ipv6Addr::from(reader.read_array::<16>()?);
+    // port is big-endian, as in Core network address
+    let port = u16::from_be_bytes(reader.read_array()?);
+    // pubKeyOperator, keyIDVoting
+    reader.skip(48 + 20)?;
+    let is_valid = reader.read_u8()? != 0;
+
+    let mut platform_http_port = None;
+    if version == MN_ENTRY_BASIC_BLS_VERSION {
+        let mn_type = reader.read_u16()?;
+        if mn_type == MN_TYPE_EVO {
+            platform_http_port = Some(reader.read_u16()?);
+            // platformNodeID
+            reader.skip(20)?;
+        }
+    }
+
+    let entry_hash = sha256d::Hash::hash(&reader.data[start..reader.position()]).to_byte_array();
+
+    let ip = match ip.to_ipv4_mapped() {
+        Some(ipv4) => IpAddr::V4(ipv4),
+        None => IpAddr::V6(ip),
+    };
+
+    Ok(MasternodeEntry {
+        pro_reg_tx_hash,
+        service: SocketAddr::new(ip, port),
+        is_valid,
+        platform_http_port,
+        entry_hash,
+    })
+}
+
+fn read_quorum_commitment(
+    reader: &mut Reader<'_>,
+) -> Result<QuorumCommitment, ContextProviderError> {
+    let start = reader.position();
+
+    let version = reader.read_u16()?;
+    let llmq_type = reader.read_u8()?;
+    let quorum_hash = reader.read_array()?;
+    if INDEXED_QUORUM_VERSIONS.contains(&version) {
+        // quorumIndex
+        reader.skip(2)?;
+    }
+
+    // signers and validMembers bitsets
+    for _ in 0..2 {
+        let bits = reader.read_compact_size()?;
+        reader.skip(bits.div_ceil(8))?;
+    }
+
+    let public_key = reader.read_array()?;
+    // quorumVvecHash, quorumSig, membersSig
+    reader.skip(32 + 96 + 96)?;
+
+    let commitment_hash =
+        sha256d::Hash::hash(&reader.data[start..reader.position()]).to_byte_array();
+
+    Ok(QuorumCommitment {
+        llmq_type,
+        quorum_hash,
+        public_key,
+        commitment_hash,
+    })
+}
+
+fn invalid(message: String) -> ContextProviderError {
+    ContextProviderError::InvalidQuorum(format!("invalid masternode list diff: {}", message))
+}
+
+/// Minimal cursor over Dash Core consensus-encoded data.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn position(&self) -> usize {
+        self.position
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        &self.data[self.position..]
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], ContextProviderError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| {
+                invalid(format!(
+                    "unexpected end of data at offset {}, {} more bytes needed",
+                    self.position, len
+                ))
+            })?;
+
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), ContextProviderError> {
+        self.read_bytes(len).map(|_| ())
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], ContextProviderError> {
+        Ok(self.read_bytes(N)?.try_into().expect("length checked"))
+    }
+
+    fn read_u8(&mut self) -> Result<u8, ContextProviderError> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, ContextProviderError> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, ContextProviderError> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_compact_size(&mut self) -> Result<usize, ContextProviderError> {
+        let value = match self.read_u8()? {
+            0xfd => u16::from_le_bytes(self.read_array()?) as u64,
+            0xfe => u32::from_le_bytes(self.read_array()?) as u64,
+            0xff => u64::from_le_bytes(self.read_array()?),
+            value => value as u64,
+        };
+
+        usize::try_from(value).map_err(|_| invalid(format!("compact size {} too large", value)))
+    }
+
+    fn read_var_bytes(&mut self) -> Result<&'a [u8], ContextProviderError> {
+        let len = self.read_compact_size()?;
+        self.read_bytes(len)
+    }
+}
@@ -3,9 +3,14 @@ use std::fmt::Debug;
 use std::time::Duration;
 
 use dapi_grpc::mock::Mockable;
+use dapi_grpc::platform::v0::StateTransitionBroadcastError;
+use dapi_grpc::tonic::Status;
+use dpp::consensus::codes::ErrorWithCode;
+use dpp::consensus::ConsensusError;
+use dpp::serialization::PlatformDeserializable;
 use dpp::version::PlatformVersionError;
 use dpp::ProtocolError;
-use rs_dapi_client::DapiClientError;
+use rs_dapi_client::{DapiClientError, VerifiedRequestError};
 
 pub use drive_proof_verifier::error::ContextProviderError;
 
@@ -64,17 +69,127 @@ pub enum Error {
     #[error("Context provider error: {0}")]
     ContextProviderError(#[from] ContextProviderError),
 
+    /// State transition failed local validation; it was not broadcast to Platform
+    #[error("Invalid state transition: {0:?}")]
+    InvalidStateTransition(Vec<ConsensusError>),
+
+    /// State transition was rejected by Platform because of a consensus error
+    #[error("State transition rejected with consensus error {code}: {error}")]
+    StateTransitionConsensusError {
+        /// Error code reported by Platform
+        code: u32,
+        /// Consensus error decoded from the response
+        error: ConsensusError,
+    },
+
     /// Operation cancelled - cancel token was triggered, timeout, etc.
     #[error("Operation cancelled: {0}")]
     Cancelled(String),
 }
 
+impl Error {
+    /// Convert error of a gRPC request to DAPI.
+    ///
+    /// Consensus errors that Platform attaches to the gRPC status metadata are decoded into
+    /// [Error::StateTransitionConsensusError]; other errors are converted as usual.
+    pub(crate) fn from_grpc_error(error: DapiClientError<Status>) -> Self {
+        if let DapiClientError::Transport(status, _) = &error {
+            if let Some(error) = consensus_error_from_status(status) {
+                return error;
+            }
+        }
+
+        error.into()
+    }
+}
+
+impl From<StateTransitionBroadcastError> for Error {
+    /// Convert state transition execution error returned by `waitForStateTransitionResult`.
+    fn from(value: StateTransitionBroadcastError) -> Self {
+        match decode_consensus_error(&value.data) {
+            Some(error) => Self::StateTransitionConsensusError {
+                code: value.code,
+                error,
+            },
+            None => Self::DapiClientError(format!(
+                "state transition broadcast error {}: {}",
+                value.code, value.message
+            )),
+        }
+    }
+}
+
+/// gRPC status metadata key containing CBOR-encoded error details
+const DRIVE_ERROR_DATA_KEY: &str = "drive-error-data-bin";
+/// gRPC status metadata key containing Platform error code
+const DRIVE_ERROR_CODE_KEY: &str = "code";
+/// Key of serialized consensus error in error details
+const SERIALIZED_ERROR_KEY: &str = "serializedError";
+
+/// Decode consensus error attached to the gRPC status metadata.
+fn consensus_error_from_status(status: &Status) -> Option<Error> {
+    let data = status
+        .metadata()
+        .get_bin(DRIVE_ERROR_DATA_KEY)?
+        .to_bytes()
+        .ok()?;
+    let error = decode_consensus_error(&data)?;
+
+    let code = status
+        .metadata()
+        .get(DRIVE_ERROR_CODE_KEY)
+        .and_then(|code| code.to_str().ok())
+        .and_then(|code| code.parse().ok())
+        .unwrap_or_else(|| error.code());
+
+    Some(Error::StateTransitionConsensusError { code, error })
+}
+
+/// Decode consensus error from CBOR-encoded error details.
+///
+/// Details can be either the error data map, or metadata map containing CBOR-encoded error data
+/// (as returned by `waitForStateTransitionResult`).
+fn decode_consensus_error(data: &[u8]) -> Option<ConsensusError> {
+    let value: ciborium::Value = ciborium::de::from_reader(data).ok()?;
+    let serialized = find_serialized_error(&value)?;
+
+    ConsensusError::deserialize_from_bytes(&serialized)
+        .inspect_err(|e| tracing::warn!(error = ?e, "unable to deserialize consensus error"))
+        .ok()
+}
+
+fn find_serialized_error(value: &ciborium::Value) -> Option<Vec<u8>> {
+    value
+        .as_map()?
+        .iter()
+        .find_map(|(key, value)| match (key.as_text()?, value) {
+            (SERIALIZED_ERROR_KEY, ciborium::Value::Bytes(bytes)) => Some(bytes.clone()),
+            (DRIVE_ERROR_DATA_KEY, ciborium::Value::Bytes(bytes)) => {
+                ciborium::de::from_reader::<ciborium::Value, _>(bytes.as_slice())
+                    .ok()
+                    .as_ref()
+                    .and_then(find_serialized_error)
+            }
+            ("data", value) => find_serialized_error(value),
+            _ => None,
+        })
+}
+
 impl<T: Debug + Mockable> From<DapiClientError<T>> for Error {
     fn from(value: DapiClientError<T>) -> Self {
         Self::DapiClientError(format!("{:?}", value))
     }
 }
 
+impl<T: Debug + Mockable> From<VerifiedRequestError<T, drive_proof_verifier::Error>> for Error {
+    fn from(value: VerifiedRequestError<T, drive_proof_verifier::Error>) -> Self {
+        match value {
+            VerifiedRequestError::Dapi(error) => error.into(),
+            VerifiedRequestError::Verification(error, _) => Self::Proof(error),
+        }
+    }
+}
+
 impl From<PlatformVersionError> for Error {
     fn from(value: PlatformVersionError) -> Self {
         Self::Protocol(value.into())
@@ -61,6 +61,9 @@
 // #![warn(missing_docs)]
 #![allow(rustdoc::private_intra_doc_links)]
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod context_provider;
 pub mod core;
 #[cfg(feature = "mocks")]
 mod core_client;
@@ -8,17 +8,18 @@ use crate::{
 use arc_swap::ArcSwapOption;
 use dapi_grpc::platform::v0::{Proof, ResponseMetadata};
 use dapi_grpc::{
+    core::v0 as core_proto,
     mock::Mockable,
     platform::v0::{self as proto},
 };
 use dpp::dashcore::Network;
 use dpp::version::PlatformVersion;
 use drive_proof_verifier::{error::ContextProviderError, ContextProvider, FromProof};
-use rs_dapi_client::mock::MockError;
+use rs_dapi_client::mock::{MockError, MockResult};
 use rs_dapi_client::{
     mock::{Key, MockDapiClient},
     transport::TransportRequest,
-    DapiClient, DumpData,
+    DapiClient, DapiStreamRequest, DumpData, StreamResult,
 };
 use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
 use tokio::sync::Mutex;
@@ -201,6 +202,22 @@ impl MockDashPlatformSdk {
                     self.load_expectation::<proto::GetTotalCreditsInPlatformRequest>(filename)
                         .await?
                 }
+                "BlockHeadersWithChainLocksRequest" => {
+                    self.load_stream_expectation::<core_proto::BlockHeadersWithChainLocksRequest>(
+                        filename,
+                    )
+                    .await?
+                }
+                "TransactionsWithProofsRequest" => {
+                    self.load_stream_expectation::<core_proto::TransactionsWithProofsRequest>(
+                        filename,
+                    )
+                    .await?
+                }
+                "MasternodeListRequest" => {
+                    self.load_stream_expectation::<core_proto::MasternodeListRequest>(filename)
+                        .await?
+                }
                 _ => {
                     return Err(Error::Config(format!(
                         "unknown request type {} in {}",
@@ -229,6 +246,57 @@ impl MockDashPlatformSdk {
         Ok(())
     }
 
+    async fn load_stream_expectation<T: DapiStreamRequest>(
+        &mut self,
+        path: &PathBuf,
+    ) -> Result<(), Error> {
+        let (request, messages) = DumpData::<T>::load(path)
+            .map_err(|e| {
+                Error::Config(format!(
+                    "cannot load mock expectations from {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?
+            .deserialize_stream();
+
+        self.dapi.lock().await.expect_stream(&request, &messages)?;
+        Ok(())
+    }
+
+    /// Expect a streaming request and return provided messages.
+    ///
+    /// Stream opened for `request` with
+    /// [DapiStreamExecutor::stream()](rs_dapi_client::DapiStreamExecutor::stream()) returns
+    /// `messages` in order, and then ends.
+    pub async fn expect_stream<R: DapiStreamRequest>(
+        &mut self,
+        request: &R,
+        messages: &[StreamResult<R>],
+    ) -> Result<&mut Self, Error> {
+        self.dapi.lock().await.expect_stream(request, messages)?;
+
+        Ok(self)
+    }
+
+    /// Expect a DAPI request and return provided response.
+    ///
+    /// Use it for requests that are not sent by [Fetch] or [FetchMany], like broadcasts of state
+    /// transitions.
+    pub async fn expect_request<R>(
+        &mut self,
+        request: &R,
+        response: &MockResult<R>,
+    ) -> Result<&mut Self, Error>
+    where
+        R: TransportRequest + Mockable,
+        R::Response: Mockable,
+    {
+        self.dapi.lock().await.expect(request, response)?;
+
+        Ok(self)
+    }
+
     /// Expect a [Fetch] request and return provided object.
     ///
     /// This method is used to define mock expectations for [Fetch] requests.
@@ -326,7 +394,7 @@ impl MockDashPlatformSdk {
         K: Ord,
         O: FetchMany<K, R>,
         Q: Query<<O as FetchMany<K, R>>::Request>,
-        R: FromIterator<(K, Option<O>)> + MockResponse + Send + Default,
+        R: FromIterator<(K, Option<O>)> + MockResponse + Send + Default + PartialEq,
     >(
         &mut self,
         query: Q,
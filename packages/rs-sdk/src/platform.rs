@@ -6,15 +6,19 @@
 // and while it will change the substance, the API structure will remain the same.
 
 pub mod block_info_from_metadata;
+pub mod dashpay;
 mod delegate;
 mod document_query;
+pub mod dpns;
 mod fetch;
 pub mod fetch_current_no_parameters;
 mod fetch_many;
 mod identities_contract_keys_query;
+mod pagination;
 mod query;
 pub mod transition;
 pub mod types;
+pub mod watch;
 
 pub use dapi_grpc::platform::v0::{self as proto};
 pub use dpp::{
@@ -31,5 +35,6 @@ pub use {
     document_query::DocumentQuery,
     fetch::Fetch,
     fetch_many::FetchMany,
-    query::{LimitQuery, Query, QueryStartInfo, DEFAULT_EPOCH_QUERY_LIMIT},
+    pagination::PaginatedQuery,
+    query::{LimitQuery, Query, QueryStartInfo, VoteQuery, DEFAULT_EPOCH_QUERY_LIMIT},
 };
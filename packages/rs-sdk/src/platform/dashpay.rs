@@ -0,0 +1,667 @@
+//! DashPay helpers.
+//!
+//! The DashPay data contract defines user profiles and contact requests. A contact request sent by
+//! an identity to another identity carries an extended public key the recipient uses to derive
+//! payment addresses of the sender; the key is encrypted with a key shared between the sender's
+//! ENCRYPTION key and the recipient's DECRYPTION key (see [encryption]).
+//!
+//! Contact requests are checked client-side against the rules of the DashPay data trigger:
+//! the recipient must exist and can't be the sender.
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use dpp::dashcore::secp256k1::{PublicKey, Secp256k1, SecretKey};
+use dpp::data_contract::accessors::v0::DataContractV0Getters;
+use dpp::data_contract::document_type::methods::DocumentTypeV0Methods;
+use dpp::data_contract::DataContract;
+use dpp::document::{Document, DocumentV0Getters, DocumentV0Setters};
+use dpp::identity::accessors::IdentityGettersV0;
+use dpp::identity::identity_public_key::accessors::v0::IdentityPublicKeyGettersV0;
+use dpp::identity::identity_public_key::contract_bounds::ContractBounds;
+use dpp::identity::signer::Signer;
+use dpp::identity::{Identity, IdentityPublicKey, KeyID, KeyType, Purpose};
+use dpp::platform_value::btreemap_extensions::BTreeValueMapHelper;
+use dpp::platform_value::string_encoding::Encoding;
+use dpp::platform_value::Value;
+use dpp::prelude::{Identifier, TimestampMillis};
+use dpp::system_data_contracts::dashpay_contract::v1::document_types::contact_request::properties::TO_USER_ID;
+use dpp::system_data_contracts::{load_system_data_contract, SystemDataContract};
+use dpp::util::entropy_generator::{DefaultEntropyGenerator, EntropyGenerator};
+use dpp::ProtocolError;
+use drive::query::{OrderClause, WhereClause, WhereOperator};
+
+use self::encryption::{
+    account_reference, decrypt, decrypt_account_label, encrypt, encrypt_account_label, shared_key,
+    ContactExtendedPublicKey, DashPayAccountKey,
+};
+use super::transition::document_batch::DocumentBatchBuilder;
+use super::transition::put_settings::PutSettings;
+use super::{DocumentQuery, Fetch, FetchMany};
+use crate::{Error, Sdk};
+
+pub mod encryption;
+
+/// Name of the profile document type.
+pub const PROFILE_DOCUMENT_TYPE: &str = "profile";
+/// Name of the contact request document type.
+pub const CONTACT_REQUEST_DOCUMENT_TYPE: &str = "contactRequest";
+/// Max number of contact requests returned by [Sdk::dashpay_contact_requests()].
+pub const MAX_CONTACT_REQUESTS: u32 = 100;
+
+const MAX_DISPLAY_NAME_LENGTH: usize = 25;
+const MAX_PUBLIC_MESSAGE_LENGTH: usize = 140;
+const MAX_AVATAR_URL_LENGTH: usize = 2048;
+
+/// DashPay profile of an identity.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    /// Name displayed to other users
+    pub display_name: Option<String>,
+    /// Public message
+    pub public_message: Option<String>,
+    /// URL of the avatar image
+    pub avatar_url: Option<String>,
+    /// SHA256 hash of the avatar image
+    pub avatar_hash: Option<[u8; 32]>,
+    /// dHash of the avatar image
+    pub avatar_fingerprint: Option<[u8; 8]>,
+}
+
+impl Profile {
+    /// Check the profile against the DashPay contract rules.
+    pub fn validate(&self) -> Result<(), Error> {
+        let fields = [
+            ("displayName", &self.display_name, MAX_DISPLAY_NAME_LENGTH),
+            (
+                "publicMessage",
+                &self.public_message,
+                MAX_PUBLIC_MESSAGE_LENGTH,
+            ),
+            ("avatarUrl", &self.avatar_url, MAX_AVATAR_URL_LENGTH),
+        ];
+
+        for (name, value, max_length) in fields {
+            if let Some(value) = value {
+                let length = value.chars().count();
+                if length == 0 || length > max_length {
+                    return Err(Error::Generic(format!(
+                        "profile {} must be 1 to {} characters long",
+                        name, max_length
+                    )));
+                }
+            }
+        }
+
+        if self.avatar_url.is_none()
+            && (self.avatar_hash.is_some() || self.avatar_fingerprint.is_some())
+        {
+            return Err(Error::Generic(
+                "profile avatar hash and fingerprint require avatar url".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Document properties of the profile.
+    pub fn to_properties(&self) -> BTreeMap<String, Value> {
+        let mut properties = BTreeMap::new();
+
+        if let Some(display_name) = &self.display_name {
+            properties.insert("displayName".to_string(), display_name.as_str().into());
+        }
+        if let Some(public_message) = &self.public_message {
+            properties.insert("publicMessage".to_string(), public_message.as_str().into());
+        }
+        if let Some(avatar_url) = &self.avatar_url {
+            properties.insert("avatarUrl".to_string(), avatar_url.as_str().into());
+        }
+        if let Some(avatar_hash) = self.avatar_hash {
+            properties.insert("avatarHash".to_string(), Value::Bytes32(avatar_hash));
+        }
+        if let Some(avatar_fingerprint) = self.avatar_fingerprint {
+            properties.insert(
+                "avatarFingerprint".to_string(),
+                Value::Bytes(avatar_fingerprint.to_vec()),
+            );
+        }
+
+        properties
+    }
+}
+
+impl TryFrom<&Document> for Profile {
+    type Error = Error;
+
+    fn try_from(document: &Document) -> Result<Self, Self::Error> {
+        let properties = document.properties();
+
+        Ok(Self {
+            display_name: properties
+                .get_optional_string("displayName")
+                .map_err(ProtocolError::ValueError)?,
+            public_message: properties
+                .get_optional_string("publicMessage")
+                .map_err(ProtocolError::ValueError)?,
+            avatar_url: properties
+                .get_optional_string("avatarUrl")
+                .map_err(ProtocolError::ValueError)?,
+            avatar_hash: properties
+                .get_optional_hash256_bytes("avatarHash")
+                .map_err(ProtocolError::ValueError)?,
+            avatar_fingerprint: properties
+                .get_optional_bytes("avatarFingerprint")
+                .map_err(ProtocolError::ValueError)?
+                .map(|bytes| {
+                    bytes.try_into().map_err(|_| {
+                        Error::Generic("avatar fingerprint must be 8 bytes".to_string())
+                    })
+                })
+                .transpose()?,
+        })
+    }
+}
+
+/// Contact request, as stored on Platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContactRequest {
+    /// Document id
+    pub id: Identifier,
+    /// Sender of the request
+    pub owner_id: Identifier,
+    /// Recipient of the request
+    pub to_user_id: Identifier,
+    /// Encrypted [ContactExtendedPublicKey]
+    pub encrypted_public_key: Vec<u8>,
+    /// Id of the sender's ENCRYPTION key
+    pub sender_key_index: KeyID,
+    /// Id of the recipient's DECRYPTION key
+    pub recipient_key_index: KeyID,
+    /// Reference to the sender's account, see [account_reference()]
+    pub account_reference: u32,
+    /// Encrypted label of the sender's account
+    pub encrypted_account_label: Option<Vec<u8>>,
+    /// Creation time
+    pub created_at: Option<TimestampMillis>,
+}
+
+impl TryFrom<&Document> for ContactRequest {
+    type Error = Error;
+
+    fn try_from(document: &Document) -> Result<Self, Self::Error> {
+        let properties = document.properties();
+
+        Ok(Self {
+            id: document.id(),
+            owner_id: document.owner_id(),
+            to_user_id: properties
+                .get_identifier(TO_USER_ID)
+                .map_err(ProtocolError::ValueError)?,
+            encrypted_public_key: properties
+                .get_bytes("encryptedPublicKey")
+                .map_err(ProtocolError::ValueError)?,
+            sender_key_index: properties
+                .get_integer("senderKeyIndex")
+                .map_err(ProtocolError::ValueError)?,
+            recipient_key_index: properties
+                .get_integer("recipientKeyIndex")
+                .map_err(ProtocolError::ValueError)?,
+            account_reference: properties
+                .get_integer("accountReference")
+                .map_err(ProtocolError::ValueError)?,
+            encrypted_account_label: properties
+                .get_optional_bytes("encryptedAccountLabel")
+                .map_err(ProtocolError::ValueError)?,
+            created_at: document.created_at(),
+        })
+    }
+}
+
+/// Decrypted data of a contact request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contact {
+    /// Extended public key of the sender's payment addresses for this contact
+    pub extended_public_key: ContactExtendedPublicKey,
+    /// Label of the sender's account
+    pub account_label: Option<String>,
+}
+
+/// Contact request with its decrypted data.
+#[derive(Debug)]
+pub struct DecryptedContactRequest {
+    /// Contact request
+    pub request: ContactRequest,
+    /// Decrypted data, or error if the request can't be decrypted
+    pub contact: Result<Contact, Error>,
+}
+
+/// Direction of contact requests to list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactRequestDirection {
+    /// Requests sent to the identity
+    Incoming,
+    /// Requests sent by the identity
+    Outgoing,
+}
+
+/// Contact request to send with [Sdk::send_dashpay_contact_request()].
+#[derive(Debug, Clone)]
+pub struct NewContactRequest {
+    /// Recipient of the request
+    pub recipient_id: Identifier,
+    /// Private key of the sender's ENCRYPTION key
+    pub sender_encryption_key: SecretKey,
+    /// Sender's DashPay account key, used to derive the contact's extended public key
+    pub account_key: DashPayAccountKey,
+    /// Account number of the sender
+    pub account: u32,
+    /// Label of the sender's account, visible only to the recipient
+    pub account_label: Option<String>,
+}
+
+impl Sdk {
+    /// Fetch the DashPay profile document of the identity.
+    pub async fn dashpay_profile(
+        &self,
+        identity_id: Identifier,
+    ) -> Result<Option<Document>, Error> {
+        let query = DocumentQuery::new(self.dashpay_data_contract()?, PROFILE_DOCUMENT_TYPE)?
+            .with_where(WhereClause {
+                field: "$ownerId".to_string(),
+                operator: WhereOperator::Equal,
+                value: Value::Identifier(identity_id.to_buffer()),
+            });
+
+        Document::fetch(self, query).await
+    }
+
+    /// Create the DashPay profile of the identity.
+    pub async fn create_dashpay_profile<S: Signer>(
+        &self,
+        identity_id: Identifier,
+        profile: &Profile,
+        identity_public_key: &IdentityPublicKey,
+        signer: &S,
+        settings: Option<PutSettings>,
+    ) -> Result<Document, Error> {
+        profile.validate()?;
+
+        let data_contract = self.dashpay_data_contract()?;
+        let (document, entropy) = create_document(
+            &data_contract,
+            PROFILE_DOCUMENT_TYPE,
+            identity_id,
+            Value::from(profile.to_properties()),
+            self,
+        )?;
+        let document_id = document.id();
+
+        DocumentBatchBuilder::new(data_contract, identity_id)
+            .create_document(document, PROFILE_DOCUMENT_TYPE, entropy)
+            .broadcast_and_wait(self, identity_public_key, signer, settings)
+            .await?
+            .remove(&document_id)
+            .flatten()
+            .ok_or_else(|| Error::InvalidProvedResponse("profile document not proved".to_string()))
+    }
+
+    /// Replace the DashPay profile stored in `document` with `profile`.
+    pub async fn update_dashpay_profile<S: Signer>(
+        &self,
+        mut document: Document,
+        profile: &Profile,
+        identity_public_key: &IdentityPublicKey,
+        signer: &S,
+        settings: Option<PutSettings>,
+    ) -> Result<Document, Error> {
+        profile.validate()?;
+
+        let document_id = document.id();
+        let owner_id = document.owner_id();
+        document.set_properties(profile.to_properties());
+
+        DocumentBatchBuilder::new(self.dashpay_data_contract()?, owner_id)
+            .replace_document(document, PROFILE_DOCUMENT_TYPE)
+            .broadcast_and_wait(self, identity_public_key, signer, settings)
+            .await?
+            .remove(&document_id)
+            .flatten()
+            .ok_or_else(|| Error::InvalidProvedResponse("profile document not proved".to_string()))
+    }
+
+    /// Delete the DashPay profile stored in `document`.
+    pub async fn delete_dashpay_profile<S: Signer>(
+        &self,
+        document: Document,
+        identity_public_key: &IdentityPublicKey,
+        signer: &S,
+        settings: Option<PutSettings>,
+    ) -> Result<(), Error> {
+        let owner_id = document.owner_id();
+
+        DocumentBatchBuilder::new(self.dashpay_data_contract()?, owner_id)
+            .delete_document(document, PROFILE_DOCUMENT_TYPE)
+            .broadcast_and_wait(self, identity_public_key, signer, settings)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Send a contact request from `sender` to another identity.
+    ///
+    /// Derives the contact's extended public key from the account key, and encrypts it with the key
+    /// shared by the sender's ENCRYPTION key and the recipient's DECRYPTION key.
+    /// The document is signed with `identity_public_key`.
+    pub async fn send_dashpay_contact_request<S: Signer>(
+        &self,
+        sender: &Identity,
+        request: &NewContactRequest,
+        identity_public_key: &IdentityPublicKey,
+        signer: &S,
+        settings: Option<PutSettings>,
+    ) -> Result<Document, Error> {
+        if request.recipient_id == sender.id() {
+            return Err(Error::Generic(format!(
+                "identity {} can't send contact request to itself",
+                sender.id()
+            )));
+        }
+
+        let data_contract = self.dashpay_data_contract()?;
+
+        let recipient = Identity::fetch(self, request.recipient_id)
+            .await?
+            .ok_or_else(|| {
+                Error::MissingDependency(
+                    "Identity".to_string(),
+                    request.recipient_id.to_string(Encoding::Base58),
+                )
+            })?;
+
+        let secp = Secp256k1::new();
+        let sender_public_key =
+            PublicKey::from_secret_key(&secp, &request.sender_encryption_key).serialize();
+        let sender_key = find_contact_key(sender, Purpose::ENCRYPTION, data_contract.id(), |key| {
+            key.data().as_slice() == sender_public_key.as_slice()
+        })
+        .ok_or_else(|| {
+            Error::Generic(format!(
+                "identity {} has no DashPay ENCRYPTION key matching the provided private key",
+                sender.id()
+            ))
+        })?;
+        let recipient_key =
+            find_contact_key(&recipient, Purpose::DECRYPTION, data_contract.id(), |_| {
+                true
+            })
+            .ok_or_else(|| {
+                Error::Generic(format!(
+                    "identity {} has no DashPay DECRYPTION key",
+                    recipient.id()
+                ))
+            })?;
+
+        let shared_key = shared_key(
+            &request.sender_encryption_key,
+            &contact_public_key(recipient_key)?,
+        );
+        let extended_public_key = request
+            .account_key
+            .contact_extended_public_key(sender.id(), recipient.id())?;
+
+        let mut properties = BTreeMap::from([
+            (
+                TO_USER_ID.to_string(),
+                Value::Identifier(recipient.id().to_buffer()),
+            ),
+            (
+                "encryptedPublicKey".to_string(),
+                Value::Bytes(encrypt(&shared_key, &extended_public_key.to_bytes())?),
+            ),
+            ("senderKeyIndex".to_string(), Value::U32(sender_key.id())),
+            (
+                "recipientKeyIndex".to_string(),
+                Value::U32(recipient_key.id()),
+            ),
+            (
+                "accountReference".to_string(),
+                Value::U32(account_reference(
+                    &request.sender_encryption_key,
+                    &extended_public_key,
+                    request.account,
+                )),
+            ),
+        ]);
+        if let Some(label) = &request.account_label {
+            properties.insert(
+                "encryptedAccountLabel".to_string(),
+                Value::Bytes(encrypt_account_label(&shared_key, label)?),
+            );
+        }
+
+        let (document, entropy) = create_document(
+            &data_contract,
+            CONTACT_REQUEST_DOCUMENT_TYPE,
+            sender.id(),
+            Value::from(properties),
+            self,
+        )?;
+        let document_id = document.id();
+
+        DocumentBatchBuilder::new(data_contract, sender.id())
+            .create_document(document, CONTACT_REQUEST_DOCUMENT_TYPE, entropy)
+            .broadcast_and_wait(self, identity_public_key, signer, settings)
+            .await?
+            .remove(&document_id)
+            .flatten()
+            .ok_or_else(|| {
+                Error::InvalidProvedResponse("contact request document not proved".to_string())
+            })
+    }
+
+    /// List contact requests sent to or by the identity, and decrypt them.
+    ///
+    /// `private_keys` contains private keys of the identity by key id: DECRYPTION keys to decrypt
+    /// incoming requests, ENCRYPTION keys to decrypt outgoing requests. Requests that can't be
+    /// decrypted are returned with an error in [DecryptedContactRequest::contact].
+    ///
+    /// Returns up to [MAX_CONTACT_REQUESTS] requests created after `created_after`, oldest first.
+    pub async fn dashpay_contact_requests(
+        &self,
+        identity: &Identity,
+        direction: ContactRequestDirection,
+        private_keys: &BTreeMap<KeyID, SecretKey>,
+        created_after: Option<TimestampMillis>,
+    ) -> Result<Vec<DecryptedContactRequest>, Error> {
+        let field = match direction {
+            ContactRequestDirection::Incoming => TO_USER_ID,
+            ContactRequestDirection::Outgoing => "$ownerId",
+        };
+
+        let mut query =
+            DocumentQuery::new(self.dashpay_data_contract()?, CONTACT_REQUEST_DOCUMENT_TYPE)?
+                .with_where(WhereClause {
+                    field: field.to_string(),
+                    operator: WhereOperator::Equal,
+                    value: Value::Identifier(identity.id().to_buffer()),
+                })
+                .with_order_by(OrderClause {
+                    field: "$createdAt".to_string(),
+                    ascending: true,
+                });
+        if let Some(created_after) = created_after {
+            query = query.with_where(WhereClause {
+                field: "$createdAt".to_string(),
+                operator: WhereOperator::GreaterThan,
+                value: Value::U64(created_after),
+            });
+        }
+        query.limit = MAX_CONTACT_REQUESTS;
+
+        let requests = Document::fetch_many(self, query)
+            .await?
+            .values()
+            .flatten()
+            .map(ContactRequest::try_from)
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        // fetch every counterparty once
+        let mut counterparties: BTreeMap<Identifier, Option<Identity>> = BTreeMap::new();
+        for request in &requests {
+            let counterparty_id = match direction {
+                ContactRequestDirection::Incoming => request.owner_id,
+                ContactRequestDirection::Outgoing => request.to_user_id,
+            };
+            if !counterparties.contains_key(&counterparty_id) {
+                let counterparty = Identity::fetch(self, counterparty_id).await?;
+                counterparties.insert(counterparty_id, counterparty);
+            }
+        }
+
+        let mut decrypted = requests
+            .into_iter()
+            .map(|request| {
+                let contact = decrypt_contact_request(
+                    &request,
+                    identity,
+                    direction,
+                    private_keys,
+                    &counterparties,
+                );
+                DecryptedContactRequest { request, contact }
+            })
+            .collect::<Vec<_>>();
+        decrypted.sort_by_key(|request| request.request.created_at);
+
+        Ok(decrypted)
+    }
+
+    fn dashpay_data_contract(&self) -> Result<Arc<DataContract>, Error> {
+        Ok(Arc::new(load_system_data_contract(
+            SystemDataContract::Dashpay,
+            self.version(),
+        )?))
+    }
+}
+
+/// Decrypt a contact request using the identity's private key and the counterparty's public key.
+fn decrypt_contact_request(
+    request: &ContactRequest,
+    identity: &Identity,
+    direction: ContactRequestDirection,
+    private_keys: &BTreeMap<KeyID, SecretKey>,
+    counterparties: &BTreeMap<Identifier, Option<Identity>>,
+) -> Result<Contact, Error> {
+    let (own_key_id, counterparty_id, counterparty_key_id) = match direction {
+        ContactRequestDirection::Incoming => (
+            request.recipient_key_index,
+            request.owner_id,
+            request.sender_key_index,
+        ),
+        ContactRequestDirection::Outgoing => (
+            request.sender_key_index,
+            request.to_user_id,
+            request.recipient_key_index,
+        ),
+    };
+
+    if !identity.public_keys().contains_key(&own_key_id) {
+        return Err(Error::Generic(format!(
+            "identity {} has no key {}",
+            identity.id(),
+            own_key_id
+        )));
+    }
+    let private_key = private_keys
+        .get(&own_key_id)
+        .ok_or_else(|| Error::Generic(format!("private key {} is not provided", own_key_id)))?;
+
+    let counterparty_key = counterparties
+        .get(&counterparty_id)
+        .and_then(|counterparty| counterparty.as_ref())
+        .and_then(|counterparty| counterparty.public_keys().get(&counterparty_key_id))
+        .ok_or_else(|| {
+            Error::Generic(format!(
+                "key {} of identity {} not found",
+                counterparty_key_id, counterparty_id
+            ))
+        })?;
+
+    let shared_key = shared_key(private_key, &contact_public_key(counterparty_key)?);
+
+    let extended_public_key = ContactExtendedPublicKey::from_bytes(&decrypt(
+        &shared_key,
+        &request.encrypted_public_key,
+    )?)?;
+    let account_label = request
+        .encrypted_account_label
+        .as_ref()
+        .map(|label| decrypt_account_label(&shared_key, label))
+        .transpose()?;
+
+    Ok(Contact {
+        extended_public_key,
+        account_label,
+    })
+}
+
+/// Find an enabled ECDSA key of the identity with given purpose, usable with the DashPay contract.
+///
+/// Keys bound to the DashPay contract are preferred over unbound keys.
+fn find_contact_key(
+    identity: &Identity,
+    purpose: Purpose,
+    dashpay_contract_id: Identifier,
+    filter: impl Fn(&IdentityPublicKey) -> bool,
+) -> Option<&IdentityPublicKey> {
+    let candidates = identity.public_keys().values().filter(|key| {
+        key.purpose() == purpose
+            && key.key_type() == KeyType::ECDSA_SECP256K1
+            && key.disabled_at().is_none()
+            && filter(key)
+    });
+
+    let mut unbound = None;
+    for key in candidates {
+        match key.contract_bounds() {
+            Some(ContractBounds::SingleContract { id }) if *id == dashpay_contract_id => {
+                return Some(key)
+            }
+            Some(ContractBounds::SingleContractDocumentType {
+                id,
+                document_type_name,
+            }) if *id == dashpay_contract_id
+                && document_type_name == CONTACT_REQUEST_DOCUMENT_TYPE =>
+            {
+                return Some(key)
+            }
+            None if unbound.is_none() => unbound = Some(key),
+            _ => {}
+        }
+    }
+
+    unbound
+}
+
+fn contact_public_key(key: &IdentityPublicKey) -> Result<PublicKey, Error> {
+    PublicKey::from_slice(key.data().as_slice())
+        .map_err(|e| Error::Generic(format!("invalid public key {}: {}", key.id(), e)))
+}
+
+fn create_document(
+    data_contract: &DataContract,
+    document_type_name: &str,
+    owner_id: Identifier,
+    data: Value,
+    sdk: &Sdk,
+) -> Result<(Document, [u8; 32]), Error> {
+    let entropy = DefaultEntropyGenerator
+        .generate()
+        .map_err(|e| Error::Generic(format!("can't generate entropy: {}", e)))?;
+    let document = data_contract
+        .document_type_for_name(document_type_name)
+        .map_err(ProtocolError::DataContractError)?
+        .create_document_from_data(data, owner_id, 0, 0, entropy, sdk.version())?;
+
+    Ok((document, entropy))
+}
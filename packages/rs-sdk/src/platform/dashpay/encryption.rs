@@ -0,0 +1,442 @@
+//! Key derivation and encryption of contact requests, as defined in
+//! [DIP-15](https://github.com/dashpay/dips/blob/master/dip-0015.md).
+//!
+//! Contact request data is encrypted with AES-256-CBC, using a key shared between the sender and
+//! the recipient: ECDH of the sender's ENCRYPTION key and the recipient's DECRYPTION key.
+//! Encrypted data is prefixed with a random 16-byte initialization vector.
+use aes::Aes256;
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::generic_array::GenericArray;
+use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use dpp::dashcore::hashes::hmac::{Hmac, HmacEngine};
+use dpp::dashcore::hashes::{hash160, sha256, sha512, Hash, HashEngine};
+use dpp::dashcore::secp256k1::ecdh::SharedSecret;
+use dpp::dashcore::secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+use dpp::prelude::Identifier;
+use dpp::util::entropy_generator::{DefaultEntropyGenerator, EntropyGenerator};
+
+use crate::Error;
+
+/// Size of the AES block and initialization vector.
+pub const AES_BLOCK_SIZE: usize = 16;
+/// Size of the serialized [ContactExtendedPublicKey].
+pub const EXTENDED_PUBLIC_KEY_SIZE: usize = 69;
+/// Max size of the account label, in bytes.
+pub const MAX_ACCOUNT_LABEL_SIZE: usize = 63;
+/// Labels are padded with spaces to at least this size, so that encrypted label is at least
+/// 48 bytes long, as required by the DashPay contract.
+const MIN_ACCOUNT_LABEL_SIZE: usize = AES_BLOCK_SIZE;
+/// Version of the account reference.
+const ACCOUNT_REFERENCE_VERSION: u32 = 0;
+
+/// Key shared by the sender and the recipient of a contact request.
+///
+/// It's computed as ECDH of one party's private key and the other party's public key.
+pub fn shared_key(private_key: &SecretKey, public_key: &PublicKey) -> [u8; 32] {
+    SharedSecret::new(public_key, private_key).secret_bytes()
+}
+
+/// Encrypt `data` with AES-256-CBC and PKCS#7 padding; the result is prefixed with a random IV.
+pub fn encrypt(key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, Error> {
+    let entropy = DefaultEntropyGenerator
+        .generate()
+        .map_err(|e| Error::Generic(format!("can't generate initialization vector: {}", e)))?;
+    let mut iv = [0u8; AES_BLOCK_SIZE];
+    iv.copy_from_slice(&entropy[..AES_BLOCK_SIZE]);
+
+    Ok(encrypt_with_iv(key, iv, data))
+}
+
+/// Encrypt `data` with AES-256-CBC and PKCS#7 padding, using provided IV.
+pub fn encrypt_with_iv(key: &[u8; 32], iv: [u8; AES_BLOCK_SIZE], data: &[u8]) -> Vec<u8> {
+    let ciphertext =
+        cbc::Encryptor::<Aes256>::new(GenericArray::from_slice(key), GenericArray::from_slice(&iv))
+            .encrypt_padded_vec_mut::<Pkcs7>(data);
+
+    let mut result = Vec::with_capacity(AES_BLOCK_SIZE + ciphertext.len());
+    result.extend_from_slice(&iv);
+    result.extend(ciphertext);
+
+    result
+}
+
+/// Decrypt data created with [encrypt()].
+pub fn decrypt(key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, Error> {
+    if data.len() < 2 * AES_BLOCK_SIZE || data.len() % AES_BLOCK_SIZE != 0 {
+        return Err(Error::Generic(format!(
+            "invalid encrypted data length {}",
+            data.len()
+        )));
+    }
+
+    let (iv, ciphertext) = data.split_at(AES_BLOCK_SIZE);
+
+    cbc::Decryptor::<Aes256>::new(GenericArray::from_slice(key), GenericArray::from_slice(iv))
+        .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+        .map_err(|_| Error::Generic("invalid padding of decrypted data; wrong key?".to_string()))
+}
+
+/// Encrypt the account label of a contact request.
+pub fn encrypt_account_label(key: &[u8; 32], label: &str) -> Result<Vec<u8>, Error> {
+    if label.len() > MAX_ACCOUNT_LABEL_SIZE {
+        return Err(Error::Generic(format!(
+            "account label can't be longer than {} bytes",
+            MAX_ACCOUNT_LABEL_SIZE
+        )));
+    }
+
+    encrypt(
+        key,
+        format!("{:<1$}", label, MIN_ACCOUNT_LABEL_SIZE).as_bytes(),
+    )
+}
+
+/// Decrypt the account label created with [encrypt_account_label()].
+pub fn decrypt_account_label(key: &[u8; 32], data: &[u8]) -> Result<String, Error> {
+    let label = String::from_utf8(decrypt(key, data)?)
+        .map_err(|e| Error::Generic(format!("account label is not valid UTF-8: {}", e)))?;
+
+    Ok(label.trim_end_matches(' ').to_string())
+}
+
+/// Extended public key the recipient of a contact request uses to derive the sender's payment
+/// addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContactExtendedPublicKey {
+    /// First 4 bytes of HASH160 of the parent public key
+    pub parent_fingerprint: [u8; 4],
+    /// Chain code
+    pub chain_code: [u8; 32],
+    /// Public key
+    pub public_key: PublicKey,
+}
+
+impl ContactExtendedPublicKey {
+    /// Serialize as parent fingerprint, chain code and compressed public key.
+    pub fn to_bytes(&self) -> [u8; EXTENDED_PUBLIC_KEY_SIZE] {
+        let mut bytes = [0u8; EXTENDED_PUBLIC_KEY_SIZE];
+        bytes[..4].copy_from_slice(&self.parent_fingerprint);
+        bytes[4..36].copy_from_slice(&self.chain_code);
+        bytes[36..].copy_from_slice(&self.public_key.serialize());
+
+        bytes
+    }
+
+    /// Deserialize extended public key created with [ContactExtendedPublicKey::to_bytes()].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != EXTENDED_PUBLIC_KEY_SIZE {
+            return Err(Error::Generic(format!(
+                "extended public key must be {} bytes, got {}",
+                EXTENDED_PUBLIC_KEY_SIZE,
+                bytes.len()
+            )));
+        }
+
+        let mut parent_fingerprint = [0u8; 4];
+        parent_fingerprint.copy_from_slice(&bytes[..4]);
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&bytes[4..36]);
+        let public_key = PublicKey::from_slice(&bytes[36..])
+            .map_err(|e| Error::Generic(format!("invalid public key: {}", e)))?;
+
+        Ok(Self {
+            parent_fingerprint,
+            chain_code,
+            public_key,
+        })
+    }
+}
+
+/// Extended private key of a DashPay account, at `m/9'/coin_type'/15'/account'`.
+///
+/// The SDK doesn't manage wallets, so the account key is derived by the caller.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct DashPayAccountKey {
+    /// Private key
+    pub private_key: SecretKey,
+    /// Chain code
+    pub chain_code: [u8; 32],
+}
+
+impl std::fmt::Debug for DashPayAccountKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DashPayAccountKey")
+            .field("chain_code", &hex::encode(self.chain_code))
+            .finish_non_exhaustive()
+    }
+}
+
+impl DashPayAccountKey {
+    /// Derive a non-hardened child key with a 256-bit index, as defined in
+    /// [DIP-14](https://github.com/dashpay/dips/blob/master/dip-0014.md).
+    pub fn derive_child(&self, index: &[u8; 32]) -> Result<Self, Error> {
+        let secp = Secp256k1::new();
+        let public_key = PublicKey::from_secret_key(&secp, &self.private_key);
+
+        let mut engine = HmacEngine::<sha512::Hash>::new(&self.chain_code);
+        engine.input(&public_key.serialize());
+        engine.input(index);
+        let hmac = Hmac::<sha512::Hash>::from_engine(engine).to_byte_array();
+
+        let mut tweak = [0u8; 32];
+        tweak.copy_from_slice(&hmac[..32]);
+        let tweak = Scalar::from_be_bytes(tweak)
+            .map_err(|_| Error::Generic("invalid derived key, use next index".to_string()))?;
+        let private_key = self
+            .private_key
+            .add_tweak(&tweak)
+            .map_err(|e| Error::Generic(format!("invalid derived key: {}", e)))?;
+
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&hmac[32..]);
+
+        Ok(Self {
+            private_key,
+            chain_code,
+        })
+    }
+
+    /// Extended public key of the contact, at `m/9'/coin_type'/15'/account'/(sender_id)/(recipient_id)`.
+    pub fn contact_extended_public_key(
+        &self,
+        sender_id: Identifier,
+        recipient_id: Identifier,
+    ) -> Result<ContactExtendedPublicKey, Error> {
+        let secp = Secp256k1::new();
+
+        let parent = self.derive_child(&sender_id.to_buffer())?;
+        let contact = parent.derive_child(&recipient_id.to_buffer())?;
+
+        let parent_public_key = PublicKey::from_secret_key(&secp, &parent.private_key);
+        let mut parent_fingerprint = [0u8; 4];
+        parent_fingerprint.copy_from_slice(
+            &hash160::Hash::hash(&parent_public_key.serialize()).to_byte_array()[..4],
+        );
+
+        Ok(ContactExtendedPublicKey {
+            parent_fingerprint,
+            chain_code: contact.chain_code,
+            public_key: PublicKey::from_secret_key(&secp, &contact.private_key),
+        })
+    }
+}
+
+/// Account reference of a contact request.
+///
+/// It allows the sender to find the account used for the contact without revealing the account
+/// number to others: 28 bits of HMAC-SHA256 of the extended public key, keyed with the sender's
+/// private key, are XOR-ed with the account number.
+pub fn account_reference(
+    sender_private_key: &SecretKey,
+    extended_public_key: &ContactExtendedPublicKey,
+    account: u32,
+) -> u32 {
+    let mut engine = HmacEngine::<sha256::Hash>::new(&sender_private_key.secret_bytes());
+    engine.input(&extended_public_key.to_bytes());
+    let hmac = Hmac::<sha256::Hash>::from_engine(engine).to_byte_array();
+
+    let ask28 = u32::from_le_bytes([hmac[0], hmac[1], hmac[2], hmac[3]]) >> 4;
+    let shortened_account_bits = account & 0x0FFF_FFFF;
+
+    (ACCOUNT_REFERENCE_VERSION << 28) | (ask28 ^ shortened_account_bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret_key(seed: u8) -> SecretKey {
+        SecretKey::from_slice(&[seed; 32]).expect("valid secret key")
+    }
+
+    fn account_key() -> DashPayAccountKey {
+        DashPayAccountKey {
+            private_key: secret_key(3),
+            chain_code: [4u8; 32],
+        }
+    }
+
+    fn xpub() -> ContactExtendedPublicKey {
+        account_key()
+            .contact_extended_public_key(Identifier::new([1u8; 32]), Identifier::new([2u8; 32]))
+            .expect("derive")
+    }
+
+    /// Shared key of `secret_key(1)` and `secret_key(2)`.
+    fn shared_key_1_2() -> [u8; 32] {
+        let secp = Secp256k1::new();
+        shared_key(
+            &secret_key(1),
+            &PublicKey::from_secret_key(&secp, &secret_key(2)),
+        )
+    }
+
+    /// Given sender and recipient keys, when both compute the shared key, then they get the same
+    /// key, equal to SHA256 of the compressed ECDH point.
+    #[test]
+    fn test_shared_key() {
+        let secp = Secp256k1::new();
+
+        assert_eq!(
+            shared_key_1_2(),
+            shared_key(
+                &secret_key(2),
+                &PublicKey::from_secret_key(&secp, &secret_key(1))
+            ),
+        );
+        assert_eq!(
+            hex::encode(shared_key_1_2()),
+            "b7c99dee100e6844572a8d9ee91975af09e602491d4ba32f6781261cd9c99173"
+        );
+    }
+
+    /// Given the AES-256 CBC vector from NIST SP 800-38A (F.2.5), when I encrypt its plaintext, then
+    /// I get its ciphertext, followed by the PKCS#7 padding block, and decrypt it back.
+    #[test]
+    fn test_aes_256_cbc_known_answer() {
+        let key: [u8; 32] =
+            hex::decode("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+                .expect("hex")
+                .try_into()
+                .expect("key size");
+        let iv: [u8; 16] = hex::decode("000102030405060708090a0b0c0d0e0f")
+            .expect("hex")
+            .try_into()
+            .expect("iv size");
+        let plaintext = hex::decode(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+             30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+        )
+        .expect("hex");
+
+        let encrypted = encrypt_with_iv(&key, iv, &plaintext);
+        assert_eq!(
+            hex::encode(&encrypted),
+            "000102030405060708090a0b0c0d0e0f\
+             f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d\
+             39f23369a9d9bacfa530e26304231461b2eb05e2c39be9fcda6c19078c6a9d1b\
+             3f461796d6b0d6b2e0c2a72b4d80e644"
+        );
+        assert_eq!(decrypt(&key, &encrypted).expect("decrypt"), plaintext);
+    }
+
+    /// Given data encrypted with a key, when I decrypt it with the same key, then I get the data
+    /// back; with another key, decryption fails.
+    #[test]
+    fn test_encryption_round_trip() {
+        let key = [7u8; 32];
+
+        for length in [0, 1, 15, 16, 17, 69] {
+            let data = vec![0xAB; length];
+            let encrypted = encrypt(&key, &data).expect("encrypt");
+
+            assert_eq!(encrypted.len(), 16 + (length / 16 + 1) * 16);
+            assert_eq!(decrypt(&key, &encrypted).expect("decrypt"), data);
+        }
+
+        let encrypted = encrypt_with_iv(&key, [9u8; 16], b"hello");
+        assert_eq!(&encrypted[..16], &[9u8; 16]);
+        assert!(decrypt(&[8u8; 32], &encrypted).is_err());
+        assert!(decrypt(&key, &encrypted[..16]).is_err());
+    }
+
+    /// Given a DashPay account key, when I derive a DIP-14 child with a 256-bit index, then I get
+    /// the key and chain code computed with an independent implementation of DIP-14 `CKDpriv`.
+    #[test]
+    fn test_dip14_derivation_known_answer() {
+        let child = account_key().derive_child(&[1u8; 32]).expect("derive");
+
+        assert_eq!(
+            hex::encode(child.private_key.secret_bytes()),
+            "fbf1da8fed2b053b0c378cc9c8011d6bebf05cba5b7432720ed43196bf73c6b0"
+        );
+        assert_eq!(
+            hex::encode(child.chain_code),
+            "4b48476f694704b91d9f9010fdc29fb919562a3f821ece8d08830f858fc55434"
+        );
+    }
+
+    /// Given a DashPay account key, when I derive the contact extended public key, then it matches
+    /// the known answer, depends on the order of identities, and serializes back and forth.
+    #[test]
+    fn test_contact_extended_public_key() {
+        let xpub = xpub();
+        assert_eq!(
+            hex::encode(xpub.to_bytes()),
+            "038cca68c4a05a2d07a79c61babf3c2d3e7bf2b1fb456d2fa8dda936c0d83010\
+             ddafa7dc027e24ab2954889a408b94fd33cc488936826b57920de7049071254a\
+             1833a9101f"
+        );
+        assert_ne!(
+            xpub,
+            account_key()
+                .contact_extended_public_key(Identifier::new([2u8; 32]), Identifier::new([1u8; 32]))
+                .expect("derive")
+        );
+        assert_eq!(
+            ContactExtendedPublicKey::from_bytes(&xpub.to_bytes()).expect("deserialize"),
+            xpub
+        );
+    }
+
+    /// Given the contact extended public key and the shared key, when I encrypt it as described in
+    /// DIP-15, then I get the known 96-byte answer and decrypt it back.
+    #[test]
+    fn test_dip15_encrypted_public_key_known_answer() {
+        let key = shared_key_1_2();
+
+        let encrypted = encrypt_with_iv(&key, [9u8; 16], &xpub().to_bytes());
+        assert_eq!(
+            hex::encode(&encrypted),
+            "09090909090909090909090909090909\
+             6ebb9014ae36c07a3705ed924af0a819835807ce5be96061f1a335af26c0c9cb\
+             ec88279353dd70b05fa97cf7d2ddee0fa3ea250480cbbd9c8368cc0f6cd1bc0b\
+             e5f26fca608a24f85a1e3f3010b31536"
+        );
+        assert_eq!(
+            ContactExtendedPublicKey::from_bytes(&decrypt(&key, &encrypted).expect("decrypt"))
+                .expect("deserialize"),
+            xpub()
+        );
+    }
+
+    /// Given a known encrypted account label, when I decrypt it, then I get the label without
+    /// padding; labels I encrypt are padded to the min length required by the contract.
+    #[test]
+    fn test_dip15_account_label_known_answer() {
+        let key = shared_key_1_2();
+        let encrypted = hex::decode(
+            "09090909090909090909090909090909\
+             ac7419f25135a1731da1198dd3253c6dc176706c150ceff0fb85bf05e0de9af5",
+        )
+        .expect("hex");
+
+        assert_eq!(
+            decrypt_account_label(&key, &encrypted).expect("decrypt"),
+            "Default"
+        );
+
+        let encrypted = encrypt_account_label(&key, "Main").expect("encrypt");
+        assert_eq!(encrypted.len(), 48);
+        assert_eq!(
+            decrypt_account_label(&key, &encrypted).expect("decrypt"),
+            "Main"
+        );
+        assert!(encrypt_account_label(&key, &"a".repeat(64)).is_err());
+    }
+
+    /// Given an account, when I compute the account reference, then it matches the known answer,
+    /// and the account number is masked so that it can be recovered by the sender.
+    #[test]
+    fn test_account_reference() {
+        let xpub = xpub();
+        let sender_key = secret_key(5);
+
+        let reference_0 = account_reference(&sender_key, &xpub, 0);
+        let reference_1 = account_reference(&sender_key, &xpub, 1);
+
+        assert_eq!(reference_1, 0x0baf48a6);
+        assert_eq!(reference_0 >> 28, 0, "version must be 0");
+        assert_eq!(reference_0 ^ reference_1, 1);
+    }
+}
@@ -0,0 +1,266 @@
+//! Dash Platform Name Service (DPNS) helpers.
+//!
+//! Registering a name takes two documents of the DPNS data contract:
+//!
+//! 1. `preorder` with a salted hash of the name, which reserves the name without revealing it,
+//! 2. `domain` with the name itself and the salt used in the preorder.
+//!
+//! [Sdk::register_dpns_name()] creates both documents, waiting until the preorder is executed before
+//! the domain is submitted. [Sdk::resolve_dpns_name()] and [Sdk::dpns_names_of_identity()] look up
+//! names using proved queries.
+//!
+//! Names are compared in their normalized form: lowercase, with `o`, `i` and `l` replaced
+//! with `0` and `1` (see [normalize_label()]). This is the conversion enforced by the DPNS
+//! data trigger, so names that look alike resolve to the same domain.
+use std::sync::Arc;
+
+use dpp::data_contract::accessors::v0::DataContractV0Getters;
+use dpp::data_contract::document_type::methods::DocumentTypeV0Methods;
+use dpp::data_contract::DataContract;
+use dpp::document::{Document, DocumentV0Getters};
+use dpp::identity::signer::Signer;
+use dpp::identity::IdentityPublicKey;
+use dpp::platform_value::btreemap_extensions::BTreeValueMapPathHelper;
+use dpp::platform_value::{platform_value, Value};
+use dpp::prelude::Identifier;
+use dpp::system_data_contracts::dpns_contract::v1::document_types::domain::properties::{
+    LABEL, NORMALIZED_LABEL, NORMALIZED_PARENT_DOMAIN_NAME, PARENT_DOMAIN_NAME,
+};
+use dpp::system_data_contracts::{load_system_data_contract, SystemDataContract};
+use dpp::util::entropy_generator::{DefaultEntropyGenerator, EntropyGenerator};
+use dpp::util::hash::hash_double;
+use dpp::util::strings::convert_to_homograph_safe_chars;
+use drive::query::{WhereClause, WhereOperator};
+
+use super::transition::document_batch::DocumentBatchBuilder;
+use super::transition::put_settings::PutSettings;
+use super::{DocumentQuery, Fetch, FetchMany};
+use crate::{Error, Sdk};
+
+/// Parent domain of names registered by users.
+pub const DASH_PARENT_DOMAIN: &str = "dash";
+/// Name of the preorder document type.
+pub const PREORDER_DOCUMENT_TYPE: &str = "preorder";
+/// Name of the domain document type.
+pub const DOMAIN_DOCUMENT_TYPE: &str = "domain";
+/// Max number of names returned by [Sdk::dpns_names_of_identity()].
+pub const MAX_NAMES_PER_IDENTITY: u32 = 100;
+
+const MIN_LABEL_LENGTH: usize = 3;
+const MAX_LABEL_LENGTH: usize = 63;
+const MAX_CONTESTED_LABEL_LENGTH: usize = 19;
+
+/// Convert a label to the homograph-safe form stored in `normalizedLabel`.
+pub fn normalize_label(label: &str) -> String {
+    convert_to_homograph_safe_chars(label)
+}
+
+/// Check that the label matches the DPNS contract rules: 3 to 63 alphanumeric characters or
+/// hyphens, starting and ending with an alphanumeric character.
+pub fn is_valid_label(label: &str) -> bool {
+    let bytes = label.as_bytes();
+
+    (MIN_LABEL_LENGTH..=MAX_LABEL_LENGTH).contains(&bytes.len())
+        && bytes
+            .iter()
+            .all(|c| c.is_ascii_alphanumeric() || *c == b'-')
+        && bytes[0] != b'-'
+        && bytes[bytes.len() - 1] != b'-'
+}
+
+/// Check whether registration of the label starts a masternode vote contest.
+///
+/// Labels which normalize to at most 19 characters from `a-z`, `0`, `1` and `-` are contested.
+pub fn is_contested_label(label: &str) -> bool {
+    let normalized = normalize_label(label);
+
+    (MIN_LABEL_LENGTH..=MAX_CONTESTED_LABEL_LENGTH).contains(&normalized.len())
+        && normalized
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c == '0' || c == '1' || c == '-')
+}
+
+/// Hash stored in the preorder document: double SHA-256 of the salt followed by the full domain name.
+pub fn salted_domain_hash(salt: &[u8; 32], label: &str, parent_domain_name: &str) -> [u8; 32] {
+    let full_domain_name = format!("{}.{}", normalize_label(label), parent_domain_name);
+
+    let mut buffer = Vec::with_capacity(salt.len() + full_domain_name.len());
+    buffer.extend_from_slice(salt);
+    buffer.extend_from_slice(full_domain_name.as_bytes());
+
+    hash_double(buffer)
+}
+
+/// Split a name into a label and a parent domain name.
+///
+/// Names without a parent domain, like `alice`, belong to [DASH_PARENT_DOMAIN].
+pub fn split_name(name: &str) -> (&str, &str) {
+    name.split_once('.').unwrap_or((name, DASH_PARENT_DOMAIN))
+}
+
+/// Full name of a domain document, like `Alice.dash`.
+pub fn name_of_domain(document: &Document) -> Result<String, Error> {
+    let properties = document.properties();
+    let label = properties
+        .get_str_at_path(LABEL)
+        .map_err(dpp::ProtocolError::ValueError)?;
+    let parent_domain_name = properties
+        .get_str_at_path(PARENT_DOMAIN_NAME)
+        .map_err(dpp::ProtocolError::ValueError)?;
+
+    Ok(format!("{}.{}", label, parent_domain_name))
+}
+
+impl Sdk {
+    /// Register a `label.dash` name for an identity.
+    ///
+    /// Submits a preorder document with a random salt, waits until it's executed, and then submits
+    /// the domain document pointing to `identity_id`. Returns the proved domain document.
+    ///
+    /// Registration of [contested](is_contested_label) names starts a masternode vote; the domain
+    /// is assigned when the vote ends.
+    pub async fn register_dpns_name<S: Signer>(
+        &self,
+        label: &str,
+        identity_id: Identifier,
+        identity_public_key: &IdentityPublicKey,
+        signer: &S,
+        settings: Option<PutSettings>,
+    ) -> Result<Document, Error> {
+        if !is_valid_label(label) {
+            return Err(Error::Generic(format!("invalid DPNS label: {}", label)));
+        }
+
+        let data_contract = self.dpns_data_contract()?;
+        let salt = generate_entropy()?;
+
+        let (preorder, preorder_entropy) = self.create_dpns_document(
+            &data_contract,
+            PREORDER_DOCUMENT_TYPE,
+            identity_id,
+            platform_value!({
+                "saltedDomainHash": Value::Bytes32(salted_domain_hash(&salt, label, DASH_PARENT_DOMAIN)),
+            }),
+        )?;
+        DocumentBatchBuilder::new(data_contract.clone(), identity_id)
+            .create_document(preorder, PREORDER_DOCUMENT_TYPE, preorder_entropy)
+            .broadcast_and_wait(self, identity_public_key, signer, settings)
+            .await?;
+
+        tracing::debug!(label, "DPNS preorder executed, registering domain");
+
+        let (domain, entropy) = self.create_dpns_document(
+            &data_contract,
+            DOMAIN_DOCUMENT_TYPE,
+            identity_id,
+            platform_value!({
+                "label": label,
+                "normalizedLabel": normalize_label(label),
+                "parentDomainName": DASH_PARENT_DOMAIN,
+                "normalizedParentDomainName": normalize_label(DASH_PARENT_DOMAIN),
+                "preorderSalt": Value::Bytes32(salt),
+                "records": {
+                    "identity": Value::Identifier(identity_id.to_buffer()),
+                },
+                "subdomainRules": {
+                    "allowSubdomains": false,
+                },
+            }),
+        )?;
+        let domain_id = domain.id();
+
+        let mut documents = DocumentBatchBuilder::new(data_contract, identity_id)
+            .create_document(domain, DOMAIN_DOCUMENT_TYPE, entropy)
+            .broadcast_and_wait(self, identity_public_key, signer, settings)
+            .await?;
+
+        documents
+            .remove(&domain_id)
+            .flatten()
+            .ok_or_else(|| Error::InvalidProvedResponse("domain document not proved".to_string()))
+    }
+
+    /// Resolve a name, like `alice.dash`, to the identity it points to.
+    ///
+    /// The name is normalized before lookup, so `Alice.dash` and `a1ice.dash` resolve to the same
+    /// identity. Returns `None` if the name is not registered.
+    pub async fn resolve_dpns_name(&self, name: &str) -> Result<Option<Identifier>, Error> {
+        let (label, parent_domain_name) = split_name(name);
+
+        let query = DocumentQuery::new(self.dpns_data_contract()?, DOMAIN_DOCUMENT_TYPE)?
+            .with_where(WhereClause {
+                field: NORMALIZED_PARENT_DOMAIN_NAME.to_string(),
+                operator: WhereOperator::Equal,
+                value: Value::Text(normalize_label(parent_domain_name)),
+            })
+            .with_where(WhereClause {
+                field: NORMALIZED_LABEL.to_string(),
+                operator: WhereOperator::Equal,
+                value: Value::Text(normalize_label(label)),
+            });
+
+        let Some(domain) = Document::fetch(self, query).await? else {
+            return Ok(None);
+        };
+
+        let identity_id = domain
+            .properties()
+            .get_optional_identifier_at_path("records.identity")
+            .map_err(dpp::ProtocolError::ValueError)?
+            .map(Identifier::from);
+
+        Ok(identity_id)
+    }
+
+    /// Names pointing to the identity, like `alice.dash`.
+    ///
+    /// Returns up to [MAX_NAMES_PER_IDENTITY] names.
+    pub async fn dpns_names_of_identity(
+        &self,
+        identity_id: Identifier,
+    ) -> Result<Vec<String>, Error> {
+        let mut query = DocumentQuery::new(self.dpns_data_contract()?, DOMAIN_DOCUMENT_TYPE)?
+            .with_where(WhereClause {
+                field: "records.identity".to_string(),
+                operator: WhereOperator::Equal,
+                value: Value::Identifier(identity_id.to_buffer()),
+            });
+        query.limit = MAX_NAMES_PER_IDENTITY;
+
+        Document::fetch_many(self, query)
+            .await?
+            .into_values()
+            .flatten()
+            .map(|domain| name_of_domain(&domain))
+            .collect()
+    }
+
+    fn dpns_data_contract(&self) -> Result<Arc<DataContract>, Error> {
+        Ok(Arc::new(load_system_data_contract(
+            SystemDataContract::DPNS,
+            self.version(),
+        )?))
+    }
+
+    fn create_dpns_document(
+        &self,
+        data_contract: &DataContract,
+        document_type_name: &str,
+        owner_id: Identifier,
+        data: Value,
+    ) -> Result<(Document, [u8; 32]), Error> {
+        let entropy = generate_entropy()?;
+        let document = data_contract
+            .document_type_for_name(document_type_name)
+            .map_err(dpp::ProtocolError::DataContractError)?
+            .create_document_from_data(data, owner_id, 0, 0, entropy, self.version())?;
+
+        Ok((document, entropy))
+    }
+}
+
+fn generate_entropy() -> Result<[u8; 32], Error> {
+    DefaultEntropyGenerator
+        .generate()
+        .map_err(|e| Error::Generic(format!("can't generate entropy: {}", e)))
+}
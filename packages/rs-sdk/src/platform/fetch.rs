@@ -5,7 +5,7 @@
 //!
 //! ## Traits
 //! - [Fetch]: An asynchronous trait that defines how to fetch data from Platform.
-//!   It requires the implementing type to also implement [Debug] and [FromProof]
+//!   It requires the implementing type to also implement [Debug], [PartialEq] and [FromProof]
 //!   traits. The associated [Fetch::Request]` type needs to implement [TransportRequest].
 
 use crate::mock::MockResponse;
@@ -52,7 +52,9 @@ pub trait Fetch
 where
     Self: Sized
         + Debug
+        + PartialEq
         + MockResponse
+        + Send
         + FromProof<
             <Self as Fetch>::Request,
             Request = <Self as Fetch>::Request,
@@ -119,23 +121,10 @@ where
         query: Q,
         settings: Option<RequestSettings>,
     ) -> Result<(Option<Self>, ResponseMetadata), Error> {
-        let request = query.query(sdk.prove())?;
+        let (object, response_metadata, _) =
+            Self::fetch_with_metadata_and_proof(sdk, query, settings).await?;
 
-        let response = request
-            .clone()
-            .execute(sdk, settings.unwrap_or_default())
-            .await?;
-
-        let object_type = std::any::type_name::<Self>().to_string();
-        tracing::trace!(request = ?request, response = ?response, object_type, "fetched object from platform");
-
-        let (object, response_metadata): (Option<Self>, ResponseMetadata) =
-            sdk.parse_proof_with_metadata(request, response).await?;
-
-        match object {
-            Some(item) => Ok((item.into(), response_metadata)),
-            None => Ok((None, response_metadata)),
-        }
+        Ok((object, response_metadata))
     }
 
     /// Fetch single object from Platform with metadata and underlying proof.
@@ -167,24 +156,16 @@ where
         query: Q,
         settings: Option<RequestSettings>,
     ) -> Result<(Option<Self>, ResponseMetadata, Proof), Error> {
-        let request = query.query(sdk.prove())?;
+        let request: <Self as Fetch>::Request = query.query(sdk.prove())?;
 
-        let response = request
-            .clone()
-            .execute(sdk, settings.unwrap_or_default())
+        let (object, response_metadata, proof): (Option<Self>, ResponseMetadata, Proof) = sdk
+            .execute_and_parse_proof(request.clone(), settings.unwrap_or_default())
             .await?;
 
         let object_type = std::any::type_name::<Self>().to_string();
-        tracing::trace!(request = ?request, response = ?response, object_type, "fetched object from platform");
-
-        let (object, response_metadata, proof): (Option<Self>, ResponseMetadata, Proof) = sdk
-            .parse_proof_with_metadata_and_proof(request, response)
-            .await?;
+        tracing::trace!(request = ?request, ?object, object_type, "fetched object from platform");
 
-        match object {
-            Some(item) => Ok((item.into(), response_metadata, proof)),
-            None => Ok((None, response_metadata, proof)),
-        }
+        Ok((object, response_metadata, proof))
     }
 
     /// Fetch single object from Platform.
@@ -13,7 +13,7 @@ use crate::{
 use dapi_grpc::platform::v0::{
     GetContestedResourceIdentityVotesRequest, GetContestedResourceVoteStateRequest,
     GetContestedResourceVotersForIdentityRequest, GetContestedResourcesRequest,
-    GetDataContractsRequest, GetDocumentsResponse, GetEpochsInfoRequest, GetIdentityKeysRequest,
+    GetDataContractsRequest, GetEpochsInfoRequest, GetIdentityKeysRequest,
     GetProtocolVersionUpgradeStateRequest, GetProtocolVersionUpgradeVoteStatusRequest,
     GetVotePollsByEndDateRequest,
 };
@@ -34,10 +34,11 @@ use drive_proof_verifier::types::{
     ResourceVotesByIdentity, VotePollsGroupedByTimestamp, Voter, Voters,
 };
 use drive_proof_verifier::{types::Documents, FromProof};
-use rs_dapi_client::{transport::TransportRequest, DapiRequest, RequestSettings};
-use std::collections::BTreeMap;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use rs_dapi_client::{transport::TransportRequest, RequestSettings};
 
-use super::LimitQuery;
+use super::{LimitQuery, PaginatedQuery};
 
 /// Fetch multiple objects from Platform.
 ///
@@ -46,7 +47,8 @@ use super::LimitQuery;
 ///
 /// You can also use convenience methods:
 /// * [FetchMany::fetch_many_by_identifiers()] - to fetch multiple objects by their identifiers,
-/// * [FetchMany::fetch_many_with_limit()] - to fetch not more than `limit` objects.
+/// * [FetchMany::fetch_many_with_limit()] - to fetch not more than `limit` objects,
+/// * [FetchMany::fetch_many_stream()] - to iterate over all matching objects, page by page.
 ///
 /// ## Generic Parameters
 ///
@@ -86,7 +88,8 @@ where
             Request = Self::Request,
             Response = <<Self as FetchMany<K, O>>::Request as TransportRequest>::Response,
         > + Send
-        + Default,
+        + Default
+        + PartialEq,
 {
     /// Type of request used to fetch multiple objects from Platform.
     ///
@@ -138,22 +141,16 @@ where
         sdk: &Sdk,
         query: Q,
     ) -> Result<O, Error> {
-        let request = query.query(sdk.prove())?;
+        let request: <Self as FetchMany<K, O>>::Request = query.query(sdk.prove())?;
 
-        let response = request
-            .clone()
-            .execute(sdk, RequestSettings::default())
+        let (object, _, _) = sdk
+            .execute_and_parse_proof::<_, O>(request.clone(), RequestSettings::default())
             .await?;
 
         let object_type = std::any::type_name::<Self>().to_string();
-        tracing::trace!(request = ?request, response = ?response, object_type, "fetched object from platform");
+        tracing::trace!(request = ?request, object_type, "fetched object from platform");
 
-        let object: O = sdk
-            .parse_proof::<<Self as FetchMany<K, O>>::Request, O>(request, response)
-            .await?
-            .unwrap_or_default();
-
-        Ok(object)
+        Ok(object.unwrap_or_default())
     }
 
     /// Fetch multiple objects from Platform by their identifiers.
@@ -206,6 +203,85 @@ where
 
         Self::fetch_many(sdk, limit_query).await
     }
+
+    /// Fetch all objects matching the query, page by page.
+    ///
+    /// Returns a [Stream] of verified items. Pages are fetched lazily, as the stream is consumed;
+    /// each page is requested with the query advanced past the last item of the previous page.
+    /// Page size is controlled by the limit set on the query itself.
+    ///
+    /// When fetching or verifying a page fails, the error is yielded and the stream ends.
+    ///
+    /// ## Parameters
+    ///
+    /// - `sdk`: An instance of [Sdk].
+    /// - `query`: A query implementing [PaginatedQuery] to specify the data to be retrieved.
+    /// - `max_items`: Maximum total number of items to yield; `None` means no limit.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use dash_sdk::{Sdk, platform::{FetchMany, LimitQuery}};
+    /// use dash_sdk::platform::types::epoch::EpochQuery;
+    /// use dpp::block::extended_epoch_info::ExtendedEpochInfo;
+    /// use futures::TryStreamExt;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let sdk = Sdk::new_mock();
+    /// let query = LimitQuery {
+    ///     query: EpochQuery::default(),
+    ///     start_info: None,
+    ///     limit: Some(10),
+    /// };
+    /// let epochs = ExtendedEpochInfo::fetch_many_stream(&sdk, query, Some(100));
+    /// let result: Result<Vec<_>, _> = epochs.try_collect().await;
+    /// # });
+    /// ```
+    fn fetch_many_stream<'a, Q>(
+        sdk: &'a Sdk,
+        query: Q,
+        max_items: Option<usize>,
+    ) -> BoxStream<'a, Result<Q::Item, Error>>
+    where
+        Self: Send + 'a,
+        K: 'a,
+        O: 'a,
+        Q: Query<<Self as FetchMany<K, O>>::Request> + PaginatedQuery<O> + 'a,
+        Q::Item: Send + 'a,
+    {
+        let pages = futures::stream::try_unfold(
+            (Some(query), max_items),
+            move |(query, remaining)| async move {
+                let mut query = match (query, remaining) {
+                    (Some(query), remaining) if remaining != Some(0) => query,
+                    _ => return Ok(None),
+                };
+
+                // don't fetch more than we are going to return
+                if let Some(remaining) = remaining {
+                    let remaining = u32::try_from(remaining).unwrap_or(u32::MAX);
+                    if query.page_limit().is_some_and(|limit| limit > remaining) {
+                        query = query.with_page_limit(remaining);
+                    }
+                }
+
+                let page = Self::fetch_many(sdk, query.clone()).await?;
+                let (mut items, next) = query.next_page(page, sdk.version())?;
+
+                let remaining = remaining.map(|remaining| {
+                    items.truncate(remaining);
+                    remaining - items.len()
+                });
+
+                Ok(Some((items, (next, remaining))))
+            },
+        );
+
+        pages
+            .map_ok(|items| futures::stream::iter(items.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
+    }
 }
 
 /// Fetch documents from Platform.
@@ -228,19 +304,16 @@ impl FetchMany<Identifier, Documents> for Document {
     ) -> Result<Documents, Error> {
         let document_query: DocumentQuery = query.query(sdk.prove())?;
 
-        let request = document_query.clone();
-        let response: GetDocumentsResponse =
-            request.execute(sdk, RequestSettings::default()).await?;
-
-        tracing::trace!(request=?document_query, response=?response, "fetch multiple documents");
+        let (documents, _, _) = sdk
+            .execute_and_parse_proof::<_, Documents>(
+                document_query.clone(),
+                RequestSettings::default(),
+            )
+            .await?;
 
-        // let object: Option<BTreeMap<K,Document>> = sdk
-        let documents: BTreeMap<Identifier, Option<Document>> = sdk
-            .parse_proof::<DocumentQuery, Documents>(document_query, response)
-            .await?
-            .unwrap_or_default();
+        tracing::trace!(request=?document_query, ?documents, "fetch multiple documents");
 
-        Ok(documents)
+        Ok(documents.unwrap_or_default())
     }
 }
 
@@ -0,0 +1,392 @@
+//! Pagination of queries returning multiple objects.
+//!
+//! Platform returns results of queries for multiple objects in pages of limited size.
+//! [PaginatedQuery] is implemented by queries that know how to continue after the last item of a page,
+//! and is used by [FetchMany::fetch_many_stream()](crate::platform::FetchMany::fetch_many_stream())
+//! to retrieve all matching objects.
+use dapi_grpc::platform::v0::get_documents_request::get_documents_request_v0::Start;
+use dpp::block::extended_epoch_info::v0::ExtendedEpochInfoV0Getters;
+use dpp::block::extended_epoch_info::ExtendedEpochInfo;
+use dpp::data_contract::accessors::v0::DataContractV0Getters;
+use dpp::document::document_methods::DocumentMethodsV0;
+use dpp::document::{Document, DocumentV0Getters};
+use dpp::prelude::{Identifier, TimestampMillis};
+use dpp::version::PlatformVersion;
+use dpp::voting::contender_structs::ContenderWithSerializedDocument;
+use dpp::voting::vote_polls::VotePoll;
+use dpp::voting::votes::resource_vote::ResourceVote;
+use dpp::ProtocolError;
+use drive::query::contested_resource_votes_given_by_identity_query::ContestedResourceVotesGivenByIdentityQuery;
+use drive::query::vote_poll_contestant_votes_query::ContestedDocumentVotePollVotesDriveQuery;
+use drive::query::vote_poll_vote_state_query::ContestedDocumentVotePollDriveQuery;
+use drive::query::vote_polls_by_document_type_query::VotePollsByDocumentTypeQuery;
+use drive::query::VotePollsByEndDateDriveQuery;
+use drive_proof_verifier::types::{
+    Contenders, ContestedResource, ContestedResources, Documents, ExtendedEpochInfos,
+    ResourceVotesByIdentity, VotePollsGroupedByTimestamp, Voter, Voters,
+};
+
+use super::types::epoch::EpochQuery;
+use super::{DocumentQuery, LimitQuery};
+use crate::Error;
+
+/// Query that can be executed page by page.
+///
+/// Implemented for queries that can be used with
+/// [FetchMany::fetch_many_stream()](crate::platform::FetchMany::fetch_many_stream()).
+///
+/// ## Generic Parameters
+///
+/// - `O`: The type of container returned by Platform for a single page
+pub trait PaginatedQuery<O>: Sized {
+    /// Single item yielded when iterating over results of the query.
+    type Item;
+
+    /// Maximum number of items returned in a single page, if set.
+    fn page_limit(&self) -> Option<u32>;
+
+    /// Return a copy of this query that returns at most `limit` items in a single page.
+    fn with_page_limit(self, limit: u32) -> Self;
+
+    /// Split a page returned by Platform into items, in the order defined by the query.
+    ///
+    /// Returns the items together with the query that retrieves the following page,
+    /// or `None` when this was the last page.
+    fn next_page(
+        self,
+        page: O,
+        platform_version: &PlatformVersion,
+    ) -> Result<(Vec<Self::Item>, Option<Self>), Error>;
+}
+
+/// Check if a page with `returned` items was the last one, given the page `limit`.
+///
+/// When the limit is not known, only an empty page is treated as the last one.
+fn is_last_page(returned: usize, limit: Option<u32>) -> bool {
+    returned == 0 || limit.is_some_and(|limit| returned < limit as usize)
+}
+
+/// Documents are yielded in the order defined by `order_by` clauses of the query;
+/// the next page starts after the last yielded document.
+impl PaginatedQuery<Documents> for DocumentQuery {
+    type Item = Document;
+
+    fn page_limit(&self) -> Option<u32> {
+        // limit of 0 means Platform default
+        (self.limit > 0).then_some(self.limit)
+    }
+
+    fn with_page_limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    fn next_page(
+        mut self,
+        page: Documents,
+        platform_version: &PlatformVersion,
+    ) -> Result<(Vec<Self::Item>, Option<Self>), Error> {
+        let document_type = self
+            .data_contract
+            .document_type_for_name(&self.document_type_name)
+            .map_err(ProtocolError::DataContractError)?;
+
+        // Platform returns documents in index order, but they are verified into a map indexed by ID,
+        // so we need to restore the order using serialized values of the fields we order by.
+        let mut documents = page
+            .into_values()
+            .flatten()
+            .map(|document| {
+                let key = self
+                    .order_by_clauses
+                    .iter()
+                    .map(|clause| {
+                        document.get_raw_for_document_type(
+                            &clause.field,
+                            document_type,
+                            None,
+                            platform_version,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, ProtocolError>>()?;
+                Ok((key, document))
+            })
+            .collect::<Result<Vec<_>, ProtocolError>>()?;
+
+        // ties are resolved by document ID, in the direction of the last order clause
+        let id_ascending = self
+            .order_by_clauses
+            .last()
+            .map(|clause| clause.ascending)
+            .unwrap_or(true);
+
+        documents.sort_by(|(key_a, doc_a), (key_b, doc_b)| {
+            self.order_by_clauses
+                .iter()
+                .zip(key_a.iter().zip(key_b.iter()))
+                .map(|(clause, (a, b))| match clause.ascending {
+                    true => a.cmp(b),
+                    false => b.cmp(a),
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| match id_ascending {
+                    true => doc_a.id().cmp(&doc_b.id()),
+                    false => doc_b.id().cmp(&doc_a.id()),
+                })
+        });
+
+        let documents: Vec<Document> = documents
+            .into_iter()
+            .map(|(_, document)| document)
+            .collect();
+
+        let next = match documents.last() {
+            Some(last) if !is_last_page(documents.len(), self.page_limit()) => {
+                self.start = Some(Start::StartAfter(last.id().to_vec()));
+                Some(self)
+            }
+            _ => None,
+        };
+
+        Ok((documents, next))
+    }
+}
+
+/// Epochs are yielded in the order defined by [EpochQuery::ascending].
+impl PaginatedQuery<ExtendedEpochInfos> for LimitQuery<EpochQuery> {
+    type Item = ExtendedEpochInfo;
+
+    fn page_limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    fn with_page_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    fn next_page(
+        mut self,
+        page: ExtendedEpochInfos,
+        _platform_version: &PlatformVersion,
+    ) -> Result<(Vec<Self::Item>, Option<Self>), Error> {
+        let mut epochs: Vec<ExtendedEpochInfo> = page.into_values().flatten().collect();
+        if !self.query.ascending {
+            epochs.reverse();
+        }
+
+        let next_start = epochs.last().and_then(|last| match self.query.ascending {
+            true => last.index().checked_add(1),
+            false => last.index().checked_sub(1),
+        });
+
+        let next = match next_start {
+            Some(start) if !is_last_page(epochs.len(), self.limit) => {
+                self.query.start = Some(start);
+                Some(self)
+            }
+            _ => None,
+        };
+
+        Ok((epochs, next))
+    }
+}
+
+/// Contested resources are yielded in the order returned by Platform.
+impl PaginatedQuery<ContestedResources> for VotePollsByDocumentTypeQuery {
+    type Item = ContestedResource;
+
+    fn page_limit(&self) -> Option<u32> {
+        self.limit.map(u32::from)
+    }
+
+    fn with_page_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit.min(u16::MAX as u32) as u16);
+        self
+    }
+
+    fn next_page(
+        mut self,
+        page: ContestedResources,
+        _platform_version: &PlatformVersion,
+    ) -> Result<(Vec<Self::Item>, Option<Self>), Error> {
+        let resources = page.0;
+
+        let next = match resources.last() {
+            Some(ContestedResource::Value(last))
+                if !is_last_page(resources.len(), self.page_limit()) =>
+            {
+                self.start_at_value = Some((last.clone(), false));
+                Some(self)
+            }
+            _ => None,
+        };
+
+        Ok((resources, next))
+    }
+}
+
+/// Contenders are yielded in ascending order of their identity IDs.
+///
+/// Note that vote tallies are not part of the yielded items; use
+/// [FetchMany::fetch_many()](crate::platform::FetchMany::fetch_many()) to retrieve them.
+impl PaginatedQuery<Contenders> for ContestedDocumentVotePollDriveQuery {
+    type Item = (Identifier, ContenderWithSerializedDocument);
+
+    fn page_limit(&self) -> Option<u32> {
+        self.limit.map(u32::from)
+    }
+
+    fn with_page_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit.min(u16::MAX as u32) as u16);
+        self
+    }
+
+    fn next_page(
+        mut self,
+        page: Contenders,
+        _platform_version: &PlatformVersion,
+    ) -> Result<(Vec<Self::Item>, Option<Self>), Error> {
+        let contenders: Vec<_> = page.contenders.into_iter().collect();
+
+        let next = match contenders.last() {
+            Some((last, _)) if !is_last_page(contenders.len(), self.page_limit()) => {
+                self.start_at = Some((last.to_buffer(), false));
+                Some(self)
+            }
+            _ => None,
+        };
+
+        Ok((contenders, next))
+    }
+}
+
+/// Voters are yielded in the order defined by `order_ascending` field of the query.
+impl PaginatedQuery<Voters> for ContestedDocumentVotePollVotesDriveQuery {
+    type Item = Voter;
+
+    fn page_limit(&self) -> Option<u32> {
+        self.limit.map(u32::from)
+    }
+
+    fn with_page_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit.min(u16::MAX as u32) as u16);
+        self
+    }
+
+    fn next_page(
+        mut self,
+        page: Voters,
+        _platform_version: &PlatformVersion,
+    ) -> Result<(Vec<Self::Item>, Option<Self>), Error> {
+        let mut voters: Vec<Voter> = page.0.into_iter().collect();
+        if !self.order_ascending {
+            voters.reverse();
+        }
+
+        let next = match voters.last() {
+            Some(last) if !is_last_page(voters.len(), self.page_limit()) => {
+                self.start_at = Some((last.0.to_buffer(), false));
+                Some(self)
+            }
+            _ => None,
+        };
+
+        Ok((voters, next))
+    }
+}
+
+/// Votes are yielded together with the ID of the vote poll, in the order defined by
+/// `order_ascending` field of the query.
+impl PaginatedQuery<ResourceVotesByIdentity> for ContestedResourceVotesGivenByIdentityQuery {
+    type Item = (Identifier, ResourceVote);
+
+    fn page_limit(&self) -> Option<u32> {
+        self.limit.map(u32::from)
+    }
+
+    fn with_page_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit.min(u16::MAX as u32) as u16);
+        self
+    }
+
+    fn next_page(
+        mut self,
+        page: ResourceVotesByIdentity,
+        _platform_version: &PlatformVersion,
+    ) -> Result<(Vec<Self::Item>, Option<Self>), Error> {
+        let mut votes: Vec<_> = page
+            .into_iter()
+            .filter_map(|(id, vote)| vote.map(|vote| (id, vote)))
+            .collect();
+        if !self.order_ascending {
+            votes.reverse();
+        }
+
+        let next = match votes.last() {
+            Some((last, _)) if !is_last_page(votes.len(), self.page_limit()) => {
+                self.start_at = Some((last.to_buffer(), false));
+                Some(self)
+            }
+            _ => None,
+        };
+
+        Ok((votes, next))
+    }
+}
+
+/// Vote polls are yielded together with their end date, in the order defined by
+/// `order_ascending` field of the query.
+///
+/// Page limit applies to vote polls, so the last end date of a full page can be incomplete.
+/// Vote polls ending at that time are not yielded; the next page starts at that end date instead.
+impl PaginatedQuery<VotePollsGroupedByTimestamp> for VotePollsByEndDateDriveQuery {
+    type Item = (TimestampMillis, VotePoll);
+
+    fn page_limit(&self) -> Option<u32> {
+        self.limit.map(u32::from)
+    }
+
+    fn with_page_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit.min(u16::MAX as u32) as u16);
+        self
+    }
+
+    fn next_page(
+        mut self,
+        page: VotePollsGroupedByTimestamp,
+        _platform_version: &PlatformVersion,
+    ) -> Result<(Vec<Self::Item>, Option<Self>), Error> {
+        let mut groups = page.sorted(self.order_ascending).0;
+        let returned = groups.iter().map(|(_, polls)| polls.len()).sum();
+
+        if is_last_page(returned, self.page_limit()) {
+            return Ok((flatten_vote_polls(groups), None));
+        }
+
+        if groups.len() < 2 {
+            return Err(Error::Generic(format!(
+                "more than {} vote polls end at the same time; increase the limit of the query",
+                returned
+            )));
+        }
+
+        // the last group can be incomplete; it will be retrieved again with the next page
+        let (last_end_date, _) = groups.pop().expect("at least two groups");
+        match self.order_ascending {
+            true => self.start_time = Some((last_end_date, true)),
+            false => self.end_time = Some((last_end_date, true)),
+        };
+
+        Ok((flatten_vote_polls(groups), Some(self)))
+    }
+}
+
+fn flatten_vote_polls(
+    groups: Vec<(TimestampMillis, Vec<VotePoll>)>,
+) -> Vec<(TimestampMillis, VotePoll)> {
+    groups
+        .into_iter()
+        .flat_map(|(timestamp, polls)| polls.into_iter().map(move |poll| (timestamp, poll)))
+        .collect()
+}
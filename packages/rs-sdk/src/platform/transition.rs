@@ -3,16 +3,27 @@ pub mod broadcast;
 pub(crate) mod broadcast_identity;
 pub mod broadcast_request;
 pub(crate) mod context;
+pub mod delete_document;
+pub mod document_batch;
+pub mod estimate_fee;
+pub mod identity_creator;
+pub mod offline;
 pub mod purchase_document;
 pub mod put_contract;
 pub mod put_document;
 pub mod put_identity;
 pub mod put_settings;
+pub mod replace_document;
 pub mod top_up_identity;
+pub mod tracker;
+pub mod transfer;
 pub mod transfer_document;
 mod txid;
+pub mod update_contract;
+pub mod update_identity;
 pub mod update_price_of_document;
 pub mod vote;
+pub mod vote_batch;
 pub mod withdraw_from_identity;
 
 pub use context::*;
@@ -1,6 +1,10 @@
 use super::broadcast_request::BroadcastRequestForStateTransition;
 use crate::platform::block_info_from_metadata::block_info_from_metadata;
 use crate::{Error, Sdk};
+use dapi_grpc::platform::v0::wait_for_state_transition_result_response::{
+    self, wait_for_state_transition_result_response_v0, WaitForStateTransitionResultResponseV0,
+};
+use dapi_grpc::platform::v0::WaitForStateTransitionResultResponse;
 use dapi_grpc::platform::VersionedGrpcResponse;
 use dpp::state_transition::proof_result::StateTransitionProofResult;
 use dpp::state_transition::StateTransition;
@@ -24,7 +28,10 @@ impl BroadcastStateTransition for StateTransition {
     async fn broadcast(&self, sdk: &Sdk) -> Result<(), Error> {
         let request = self.broadcast_request_for_state_transition()?;
 
-        request.execute(sdk, RequestSettings::default()).await?;
+        request
+            .execute(sdk, RequestSettings::default())
+            .await
+            .map_err(Error::from_grpc_error)?;
 
         // response is empty for a broadcast, result comes from the stream wait for state transition result
 
@@ -41,11 +48,17 @@ impl BroadcastStateTransition for StateTransition {
         request
             .clone()
             .execute(sdk, RequestSettings::default())
-            .await?;
+            .await
+            .map_err(Error::from_grpc_error)?;
 
         let request = self.wait_for_state_transition_result_request()?;
 
-        let response = request.execute(sdk, RequestSettings::default()).await?;
+        let response = request
+            .execute(sdk, RequestSettings::default())
+            .await
+            .map_err(Error::from_grpc_error)?;
+
+        check_broadcast_error(&response)?;
 
         let block_info = block_info_from_metadata(response.metadata()?)?;
         let proof = response.proof_owned()?;
@@ -66,3 +79,22 @@ impl BroadcastStateTransition for StateTransition {
         Ok(result)
     }
 }
+
+/// Return an error if Platform reported that execution of the state transition failed.
+///
+/// Consensus errors are returned as [Error::StateTransitionConsensusError].
+pub(crate) fn check_broadcast_error(
+    response: &WaitForStateTransitionResultResponse,
+) -> Result<(), Error> {
+    if let Some(wait_for_state_transition_result_response::Version::V0(
+        WaitForStateTransitionResultResponseV0 {
+            result: Some(wait_for_state_transition_result_response_v0::Result::Error(error)),
+            ..
+        },
+    )) = &response.version
+    {
+        return Err(error.clone().into());
+    }
+
+    Ok(())
+}
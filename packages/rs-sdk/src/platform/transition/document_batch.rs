@@ -0,0 +1,520 @@
+//! Builder of [DocumentsBatchTransition]s containing many document actions.
+//!
+//! Helpers like [PutDocument](super::put_document::PutDocument) wrap a single document action
+//! into its own batch, which costs one identity contract nonce and one round trip per document.
+//! [DocumentBatchBuilder] collects actions on documents of one data contract and submits them
+//! in as few signed batches as the protocol version allows.
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::platform::block_info_from_metadata::block_info_from_metadata;
+use crate::platform::transition::broadcast::check_broadcast_error;
+use crate::platform::transition::broadcast_request::BroadcastRequestForStateTransition;
+use crate::platform::transition::put_settings::PutSettings;
+use crate::platform::Identifier;
+use crate::{Error, Sdk};
+use dapi_grpc::platform::VersionedGrpcResponse;
+use dpp::consensus::basic::document::InvalidDocumentTransitionActionError;
+use dpp::data_contract::accessors::v0::DataContractV0Getters;
+use dpp::data_contract::document_type::accessors::DocumentTypeV0Getters;
+use dpp::data_contract::DataContract;
+use dpp::document::document_methods::DocumentMethodsV0;
+use dpp::document::{Document, DocumentV0Getters};
+use dpp::fee::Credits;
+use dpp::identity::signer::Signer;
+use dpp::identity::{IdentityPublicKey, SecurityLevel};
+use dpp::state_transition::documents_batch_transition::document_transition::{
+    DocumentCreateTransition, DocumentDeleteTransition, DocumentPurchaseTransition,
+    DocumentReplaceTransition, DocumentTransferTransition, DocumentTransition,
+};
+use dpp::state_transition::documents_batch_transition::{
+    DocumentsBatchTransition, DocumentsBatchTransitionV0,
+};
+use dpp::state_transition::proof_result::StateTransitionProofResult;
+use dpp::state_transition::StateTransition;
+use drive::drive::Drive;
+use rs_dapi_client::DapiRequest;
+
+/// Single document action collected by [DocumentBatchBuilder].
+#[derive(Debug, Clone)]
+pub enum DocumentBatchAction {
+    /// Create a new document
+    Create {
+        /// Document to create
+        document: Document,
+        /// Name of the document type
+        document_type_name: String,
+        /// Entropy used to generate the document id
+        entropy: [u8; 32],
+    },
+    /// Replace an existing document; the revision is incremented by the builder
+    Replace {
+        /// Updated document, carrying the revision currently stored on Platform
+        document: Document,
+        /// Name of the document type
+        document_type_name: String,
+    },
+    /// Delete an existing document
+    Delete {
+        /// Document to delete
+        document: Document,
+        /// Name of the document type
+        document_type_name: String,
+    },
+    /// Transfer a document to another identity; the revision is incremented by the builder
+    Transfer {
+        /// Document to transfer
+        document: Document,
+        /// Name of the document type
+        document_type_name: String,
+        /// Identity receiving the document
+        recipient_id: Identifier,
+    },
+    /// Purchase a document from another identity; the revision is incremented by the builder
+    Purchase {
+        /// Document to purchase
+        document: Document,
+        /// Name of the document type
+        document_type_name: String,
+        /// Price of the document
+        price: Credits,
+    },
+}
+
+impl DocumentBatchAction {
+    fn document(&self) -> &Document {
+        match self {
+            DocumentBatchAction::Create { document, .. }
+            | DocumentBatchAction::Replace { document, .. }
+            | DocumentBatchAction::Delete { document, .. }
+            | DocumentBatchAction::Transfer { document, .. }
+            | DocumentBatchAction::Purchase { document, .. } => document,
+        }
+    }
+
+    fn document_type_name(&self) -> &str {
+        match self {
+            DocumentBatchAction::Create {
+                document_type_name, ..
+            }
+            | DocumentBatchAction::Replace {
+                document_type_name, ..
+            }
+            | DocumentBatchAction::Delete {
+                document_type_name, ..
+            }
+            | DocumentBatchAction::Transfer {
+                document_type_name, ..
+            }
+            | DocumentBatchAction::Purchase {
+                document_type_name, ..
+            } => document_type_name,
+        }
+    }
+}
+
+/// Builder of a [DocumentsBatchTransition] carrying many document actions.
+///
+/// All actions must target document types of the same data contract and are signed by the
+/// `owner_id` identity. Each action gets its own identity contract nonce, allocated from the
+/// [Sdk] nonce cache.
+///
+/// Platform limits the number of actions in one batch transition to
+/// `max_transitions_in_documents_batch` of the protocol version, which is 1 in protocol version 1.
+/// Actions are split, in order, into as many batch transitions as needed, and the result of
+/// every batch is verified with its execution proof.
+///
+/// ## Example
+///
+/// ```rust, ignore
+/// let documents = DocumentBatchBuilder::new(data_contract, owner_id)
+///     .create_document(profile, "profile", entropy)
+///     .delete_document(old_note, "note")
+///     .broadcast_and_wait(&sdk, &identity_public_key, &signer, None)
+///     .await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct DocumentBatchBuilder {
+    data_contract: Arc<DataContract>,
+    owner_id: Identifier,
+    actions: Vec<DocumentBatchAction>,
+}
+
+impl DocumentBatchBuilder {
+    /// Create a new, empty batch of actions on documents of `data_contract` owned by `owner_id`.
+    pub fn new<C: Into<Arc<DataContract>>>(data_contract: C, owner_id: Identifier) -> Self {
+        Self {
+            data_contract: data_contract.into(),
+            owner_id,
+            actions: vec![],
+        }
+    }
+
+    /// Add an action to the batch.
+    pub fn with_action(mut self, action: DocumentBatchAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Add creation of a document to the batch.
+    pub fn create_document(
+        self,
+        document: Document,
+        document_type_name: &str,
+        entropy: [u8; 32],
+    ) -> Self {
+        self.with_action(DocumentBatchAction::Create {
+            document,
+            document_type_name: document_type_name.to_string(),
+            entropy,
+        })
+    }
+
+    /// Add replacement of a document to the batch.
+    pub fn replace_document(self, document: Document, document_type_name: &str) -> Self {
+        self.with_action(DocumentBatchAction::Replace {
+            document,
+            document_type_name: document_type_name.to_string(),
+        })
+    }
+
+    /// Add deletion of a document to the batch.
+    pub fn delete_document(self, document: Document, document_type_name: &str) -> Self {
+        self.with_action(DocumentBatchAction::Delete {
+            document,
+            document_type_name: document_type_name.to_string(),
+        })
+    }
+
+    /// Add transfer of a document to `recipient_id` to the batch.
+    pub fn transfer_document(
+        self,
+        document: Document,
+        document_type_name: &str,
+        recipient_id: Identifier,
+    ) -> Self {
+        self.with_action(DocumentBatchAction::Transfer {
+            document,
+            document_type_name: document_type_name.to_string(),
+            recipient_id,
+        })
+    }
+
+    /// Add purchase of a document for `price` to the batch.
+    pub fn purchase_document(
+        self,
+        document: Document,
+        document_type_name: &str,
+        price: Credits,
+    ) -> Self {
+        self.with_action(DocumentBatchAction::Purchase {
+            document,
+            document_type_name: document_type_name.to_string(),
+            price,
+        })
+    }
+
+    /// Actions collected so far.
+    pub fn actions(&self) -> &[DocumentBatchAction] {
+        &self.actions
+    }
+
+    /// Validate the actions, allocate identity contract nonces and sign the batch transitions.
+    ///
+    /// Returns batch transitions carrying at most `max_transitions_in_documents_batch` actions
+    /// each, in the order of actions. They are not broadcast. Nonces allocated here are considered
+    /// used by the [Sdk] nonce cache.
+    pub async fn sign<S: Signer>(
+        &self,
+        sdk: &Sdk,
+        identity_public_key: &IdentityPublicKey,
+        signer: &S,
+        settings: Option<PutSettings>,
+    ) -> Result<Vec<StateTransition>, Error> {
+        let platform_version = sdk.version();
+
+        if self.actions.is_empty() {
+            return Err(Error::Generic(
+                "document batch must contain at least one action".to_string(),
+            ));
+        }
+
+        // validate all actions before allocating any nonce
+        for action in &self.actions {
+            let document_type = self
+                .data_contract
+                .document_type_for_name(action.document_type_name())
+                .map_err(dpp::ProtocolError::DataContractError)?;
+
+            let owner_mismatch = !matches!(action, DocumentBatchAction::Purchase { .. })
+                && action.document().owner_id() != self.owner_id;
+            if owner_mismatch {
+                return Err(Error::Generic(format!(
+                    "document {} is not owned by batch owner {}",
+                    action.document().id(),
+                    self.owner_id
+                )));
+            }
+
+            match action {
+                DocumentBatchAction::Replace { .. } if !document_type.documents_mutable() => {
+                    return Err(Error::InvalidStateTransition(vec![
+                        InvalidDocumentTransitionActionError::new(format!(
+                            "{} is not mutable and can not be replaced",
+                            action.document_type_name()
+                        ))
+                        .into(),
+                    ]));
+                }
+                DocumentBatchAction::Delete { .. } if !document_type.documents_can_be_deleted() => {
+                    return Err(Error::InvalidStateTransition(vec![
+                        InvalidDocumentTransitionActionError::new(format!(
+                            "documents of type {} can not be deleted",
+                            action.document_type_name()
+                        ))
+                        .into(),
+                    ]));
+                }
+                _ => {}
+            }
+        }
+
+        let mut transitions: Vec<DocumentTransition> = Vec::with_capacity(self.actions.len());
+        for action in &self.actions {
+            let document_type = self
+                .data_contract
+                .document_type_for_name(action.document_type_name())
+                .map_err(dpp::ProtocolError::DataContractError)?;
+
+            // every transition in the batch is checked against its own identity contract nonce
+            let identity_contract_nonce = sdk
+                .get_identity_contract_nonce(self.owner_id, self.data_contract.id(), true, settings)
+                .await?;
+
+            let transition: DocumentTransition = match action {
+                DocumentBatchAction::Create {
+                    document, entropy, ..
+                } => DocumentCreateTransition::from_document(
+                    document.clone(),
+                    document_type,
+                    *entropy,
+                    identity_contract_nonce,
+                    platform_version,
+                    None,
+                    None,
+                )?
+                .into(),
+                DocumentBatchAction::Replace { document, .. } => {
+                    let mut document = document.clone();
+                    document.increment_revision()?;
+                    DocumentReplaceTransition::from_document(
+                        document,
+                        document_type,
+                        identity_contract_nonce,
+                        platform_version,
+                        None,
+                        None,
+                    )?
+                    .into()
+                }
+                DocumentBatchAction::Delete { document, .. } => {
+                    DocumentDeleteTransition::from_document(
+                        document.clone(),
+                        document_type,
+                        identity_contract_nonce,
+                        platform_version,
+                        None,
+                        None,
+                    )?
+                    .into()
+                }
+                DocumentBatchAction::Transfer {
+                    document,
+                    recipient_id,
+                    ..
+                } => {
+                    let mut document = document.clone();
+                    document.increment_revision()?;
+                    DocumentTransferTransition::from_document(
+                        document,
+                        document_type,
+                        identity_contract_nonce,
+                        *recipient_id,
+                        platform_version,
+                        None,
+                        None,
+                    )?
+                    .into()
+                }
+                DocumentBatchAction::Purchase {
+                    document, price, ..
+                } => {
+                    let mut document = document.clone();
+                    document.increment_revision()?;
+                    DocumentPurchaseTransition::from_document(
+                        document,
+                        document_type,
+                        *price,
+                        identity_contract_nonce,
+                        platform_version,
+                        None,
+                        None,
+                    )?
+                    .into()
+                }
+            };
+
+            transitions.push(transition);
+        }
+
+        let max_transitions = platform_version
+            .system_limits
+            .max_transitions_in_documents_batch
+            .max(1) as usize;
+        let user_fee_increase = settings
+            .unwrap_or_default()
+            .user_fee_increase
+            .unwrap_or_default();
+
+        transitions
+            .chunks(max_transitions)
+            .map(|transitions| -> Result<StateTransition, Error> {
+                let documents_batch_transition: DocumentsBatchTransition =
+                    DocumentsBatchTransitionV0 {
+                        owner_id: self.owner_id,
+                        transitions: transitions.to_vec(),
+                        user_fee_increase,
+                        signature_public_key_id: 0,
+                        signature: Default::default(),
+                    }
+                    .into();
+
+                let mut state_transition: StateTransition = documents_batch_transition.into();
+                state_transition.sign_external(
+                    identity_public_key,
+                    signer,
+                    Some(|_, _| Ok(SecurityLevel::HIGH)),
+                )?;
+
+                Ok(state_transition)
+            })
+            .collect()
+    }
+
+    /// Sign the batch transitions and broadcast them to Platform.
+    ///
+    /// Returns the broadcast state transitions, which can be passed to
+    /// [DocumentBatchBuilder::wait_for_response]. Transitions are broadcast without waiting for
+    /// each other; use [DocumentBatchBuilder::broadcast_and_wait] when actions depend on each other.
+    pub async fn broadcast<S: Signer>(
+        &self,
+        sdk: &Sdk,
+        identity_public_key: &IdentityPublicKey,
+        signer: &S,
+        settings: Option<PutSettings>,
+    ) -> Result<Vec<StateTransition>, Error> {
+        let state_transitions = self
+            .sign(sdk, identity_public_key, signer, settings)
+            .await?;
+
+        for state_transition in &state_transitions {
+            broadcast_batch(sdk, state_transition, settings).await?;
+        }
+
+        Ok(state_transitions)
+    }
+
+    /// Waits for the results of broadcast batch transitions and verifies them with their execution
+    /// proofs.
+    ///
+    /// Returns proved documents by their id; deleted documents map to `None`.
+    pub async fn wait_for_response(
+        &self,
+        sdk: &Sdk,
+        state_transitions: Vec<StateTransition>,
+        settings: Option<PutSettings>,
+    ) -> Result<BTreeMap<Identifier, Option<Document>>, Error> {
+        let mut documents = BTreeMap::new();
+        for state_transition in &state_transitions {
+            documents.extend(self.wait_for_batch(sdk, state_transition, settings).await?);
+        }
+
+        Ok(documents)
+    }
+
+    /// Sign the batch transitions, then broadcast them one by one, waiting for the verified result
+    /// of each before broadcasting the next one.
+    ///
+    /// When a batch transition is rejected, the error is returned and the remaining ones are not
+    /// broadcast.
+    pub async fn broadcast_and_wait<S: Signer>(
+        &self,
+        sdk: &Sdk,
+        identity_public_key: &IdentityPublicKey,
+        signer: &S,
+        settings: Option<PutSettings>,
+    ) -> Result<BTreeMap<Identifier, Option<Document>>, Error> {
+        let state_transitions = self
+            .sign(sdk, identity_public_key, signer, settings)
+            .await?;
+
+        let mut documents = BTreeMap::new();
+        for state_transition in &state_transitions {
+            broadcast_batch(sdk, state_transition, settings).await?;
+            documents.extend(self.wait_for_batch(sdk, state_transition, settings).await?);
+        }
+
+        Ok(documents)
+    }
+
+    /// Wait for the result of one batch transition and verify it with the execution proof.
+    async fn wait_for_batch(
+        &self,
+        sdk: &Sdk,
+        state_transition: &StateTransition,
+        settings: Option<PutSettings>,
+    ) -> Result<BTreeMap<Identifier, Option<Document>>, Error> {
+        let request = state_transition.wait_for_state_transition_result_request()?;
+
+        let response = request
+            .execute(sdk, settings.unwrap_or_default().request_settings)
+            .await
+            .map_err(Error::from_grpc_error)?;
+        check_broadcast_error(&response)?;
+
+        let block_info = block_info_from_metadata(response.metadata()?)?;
+
+        let proof = response.proof_owned()?;
+
+        let data_contract = self.data_contract.clone();
+        let (_, result) = Drive::verify_state_transition_was_executed_with_proof(
+            state_transition,
+            &block_info,
+            proof.grovedb_proof.as_slice(),
+            &|_| Ok(Some(data_contract.clone())),
+            sdk.version(),
+        )?;
+
+        match result {
+            StateTransitionProofResult::VerifiedDocuments(documents) => Ok(documents),
+            _ => Err(Error::DapiClientError("proved a non document".to_string())),
+        }
+    }
+}
+
+async fn broadcast_batch(
+    sdk: &Sdk,
+    state_transition: &StateTransition,
+    settings: Option<PutSettings>,
+) -> Result<(), Error> {
+    let request = state_transition.broadcast_request_for_state_transition()?;
+
+    request
+        .execute(sdk, settings.unwrap_or_default().request_settings)
+        .await
+        .map_err(Error::from_grpc_error)?;
+
+    // response is empty for a broadcast, result comes from the stream wait for state transition result
+
+    Ok(())
+}
@@ -0,0 +1,460 @@
+//! Estimate fees of state transitions before they are signed and broadcast.
+//!
+//! Platform calculates fees while applying the state transition to GroveDB. Drive can estimate these
+//! costs without applying them (`apply = false`), but only with its `server` feature, which requires
+//! the full GroveDB backend that is not available in the SDK. Instead, fees are estimated from the
+//! [FeeVersion](dpp::version::fee::FeeVersion) of the current platform version, applied to the items
+//! that Drive stores, sized with Drive's own constants (see [drive::drive::constants]):
+//!
+//! * storage fee is calculated from the number of bytes the state transition adds to Platform state;
+//!   documents are sized by serializing them the same way Drive stores them,
+//! * processing fee covers loading and hashing of the state transition, signature verification,
+//!   seeks and writes of the stored and replaced items, and data contract validation, calculated
+//!   with the same [ProtocolValidationOperation]s that Platform uses,
+//! * min fee is the balance Platform requires before it starts processing the state transition
+//!   (see [StateTransitionMinFees](dpp::version::fee::state_transition_min_fees::StateTransitionMinFees)).
+//!
+//! Estimates of replaced documents and updated data contracts don't take storage refunds into account,
+//! so they are upper bounds.
+use std::cmp::max;
+use std::mem::size_of;
+
+use dpp::bincode;
+use dpp::block::block_info::BlockInfo;
+use dpp::data_contract::accessors::v0::DataContractV0Getters;
+use dpp::data_contract::document_type::accessors::DocumentTypeV0Getters;
+use dpp::data_contract::document_type::methods::DocumentTypeV0Methods;
+use dpp::data_contract::document_type::DocumentTypeRef;
+use dpp::data_contract::DataContract;
+use dpp::document::serialization_traits::DocumentPlatformConversionMethodsV0;
+use dpp::document::Document;
+use dpp::fee::default_costs::KnownCostItem;
+use dpp::fee::fee_result::FeeResult;
+use dpp::fee::Credits;
+use dpp::identity::KeyID;
+use dpp::platform_value::string_encoding::Encoding;
+use dpp::prelude::Identifier;
+use dpp::serialization::PlatformSerializable;
+use dpp::state_transition::data_contract_create_transition::accessors::DataContractCreateTransitionAccessorsV0;
+use dpp::state_transition::data_contract_update_transition::accessors::DataContractUpdateTransitionAccessorsV0;
+use dpp::state_transition::documents_batch_transition::accessors::DocumentsBatchTransitionAccessorsV0;
+use dpp::state_transition::documents_batch_transition::document_create_transition::v0::v0_methods::DocumentCreateTransitionV0Methods;
+use dpp::state_transition::documents_batch_transition::document_create_transition::DocumentFromCreateTransition;
+use dpp::state_transition::documents_batch_transition::document_replace_transition::DocumentFromReplaceTransition;
+use dpp::state_transition::documents_batch_transition::document_transition::{
+    DocumentTransition, DocumentTransitionV0Methods,
+};
+use dpp::state_transition::identity_create_transition::accessors::IdentityCreateTransitionAccessorsV0;
+use dpp::state_transition::identity_update_transition::accessors::IdentityUpdateTransitionAccessorsV0;
+use dpp::state_transition::public_key_in_creation::IdentityPublicKeyInCreation;
+use dpp::state_transition::StateTransition;
+use dpp::validation::operations::ProtocolValidationOperation;
+use dpp::version::PlatformVersion;
+use dpp::ProtocolError;
+use drive::drive::constants::{
+    AVERAGE_BALANCE_SIZE, AVERAGE_CONTESTED_RESOURCE_ITEM_REFERENCE_SIZE, AVERAGE_KEY_SIZE,
+    CONTESTED_DOCUMENT_REFERENCE_SIZE, EMPTY_TREE_STORAGE_SIZE, OPTIMIZED_DOCUMENT_REFERENCE,
+    STORAGE_FLAGS_SIZE,
+};
+use drive_proof_verifier::error::ContextProviderError;
+use drive_proof_verifier::ContextProvider;
+
+use crate::{Error, Sdk};
+
+/// Size of identifiers used as keys, and of the hash GroveDB keeps for each stored item.
+const HASH_SIZE: u64 = 32;
+/// Size of a public key hash (RIPEMD160 of SHA256) that references an identity by its key.
+const KEY_HASH_SIZE: u64 = 20;
+/// Size of the key of an item stored in the identity tree, like revision or nonce.
+const IDENTITY_ITEM_KEY_SIZE: u64 = 1;
+/// Size of a stored `u64` value, like revision or nonce.
+const U64_SIZE: u64 = size_of::<u64>() as u64;
+/// Size of a hash block used to calculate hashing costs.
+const HASH_BLOCK_SIZE: usize = 64;
+
+/// Estimated fees of a state transition.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeEstimate {
+    /// Fee for storing new data in Platform state
+    pub storage_fee: Credits,
+    /// Fee for processing the state transition, including `user_fee_increase`
+    pub processing_fee: Credits,
+    /// Minimal balance required by Platform to process the state transition
+    pub min_fee: Credits,
+}
+
+impl FeeEstimate {
+    /// Total estimated fee: sum of storage and processing fees.
+    pub fn total_fee(&self) -> Credits {
+        self.storage_fee.saturating_add(self.processing_fee)
+    }
+
+    /// Balance that the identity must have to pay for the state transition.
+    ///
+    /// It's the greater of total estimated fee and min fee.
+    pub fn required_balance(&self) -> Credits {
+        max(self.total_fee(), self.min_fee)
+    }
+}
+
+impl From<FeeEstimate> for FeeResult {
+    fn from(value: FeeEstimate) -> Self {
+        FeeResult::default_with_fees(value.storage_fee, value.processing_fee)
+    }
+}
+
+/// Estimate fees of a state transition.
+pub trait EstimateFee {
+    /// Estimate fees of the state transition using the platform version of `sdk`.
+    ///
+    /// Data contracts of document transitions are retrieved from the context provider.
+    fn estimate_fee(&self, sdk: &Sdk) -> Result<FeeEstimate, Error>;
+}
+
+impl EstimateFee for StateTransition {
+    fn estimate_fee(&self, sdk: &Sdk) -> Result<FeeEstimate, Error> {
+        let platform_version = sdk.version();
+        let fee_version = &platform_version.fee_version;
+
+        let mut estimator = Estimator::new(platform_version);
+
+        let serialized_len = self.serialize_to_bytes()?.len();
+        estimator.processing(KnownCostItem::SingleSHA256(
+            serialized_len.div_ceil(HASH_BLOCK_SIZE),
+        ));
+        estimator.processing_fee = estimator.processing_fee.saturating_add(
+            fee_version.storage.non_storage_load_credit_per_byte * serialized_len as u64,
+        );
+
+        let min_fees = &fee_version.state_transition_min_fees;
+        let min_fee = match self {
+            StateTransition::DataContractCreate(st) => {
+                estimator.identity_signature();
+                estimator.data_contract(st.data_contract())?;
+                estimator.replace_nonce();
+                min_fees.contract_create
+            }
+            StateTransition::DataContractUpdate(st) => {
+                estimator.identity_signature();
+                estimator.data_contract(st.data_contract())?;
+                estimator.replace_nonce();
+                min_fees.contract_update
+            }
+            StateTransition::DocumentsBatch(st) => {
+                estimator.identity_signature();
+                let provider = sdk.context_provider().ok_or(ContextProviderError::Config(
+                    "Context provider not initialized".to_string(),
+                ))?;
+                for transition in st.transitions() {
+                    let contract_id = transition.data_contract_id();
+                    let data_contract =
+                        provider.get_data_contract(&contract_id)?.ok_or_else(|| {
+                            Error::MissingDependency(
+                                "DataContract".to_string(),
+                                contract_id.to_string(Encoding::Base58),
+                            )
+                        })?;
+                    estimator.document(&data_contract, self.owner_id(), transition)?;
+                    estimator.replace_nonce();
+                }
+                min_fees
+                    .document_batch_sub_transition
+                    .saturating_mul(st.transitions().len() as u64)
+            }
+            StateTransition::IdentityCreate(st) => {
+                // asset lock proof and each of the new keys are signed
+                estimator.processing(KnownCostItem::VerifySignatureEcdsaSecp256k1);
+                // identity tree with balance, revision and keys tree
+                estimator.insert_tree(HASH_SIZE);
+                estimator.insert(HASH_SIZE, AVERAGE_BALANCE_SIZE.into());
+                estimator.insert(IDENTITY_ITEM_KEY_SIZE, U64_SIZE);
+                estimator.insert_tree(IDENTITY_ITEM_KEY_SIZE);
+                for key in st.public_keys() {
+                    estimator.identity_key(key)?;
+                }
+                0
+            }
+            StateTransition::IdentityTopUp(_) => {
+                estimator.processing(KnownCostItem::VerifySignatureEcdsaSecp256k1);
+                estimator.processing(KnownCostItem::FetchIdentityBalanceProcessingCost);
+                estimator.replace(HASH_SIZE, AVERAGE_BALANCE_SIZE.into());
+                0
+            }
+            StateTransition::IdentityCreditWithdrawal(_) => {
+                estimator.identity_signature();
+                estimator.processing(KnownCostItem::FetchIdentityBalanceProcessingCost);
+                estimator.replace(HASH_SIZE, AVERAGE_BALANCE_SIZE.into());
+                estimator.replace_nonce();
+                // queued withdrawal document holds the same data as the state transition
+                estimator.insert(HASH_SIZE, serialized_len as u64);
+                min_fees.credit_withdrawal
+            }
+            StateTransition::IdentityUpdate(st) => {
+                estimator.identity_signature();
+                for key in st.public_keys_to_add() {
+                    estimator.identity_key(key)?;
+                }
+                for _ in st.public_key_ids_to_disable() {
+                    estimator.replace(size_of::<KeyID>() as u64, AVERAGE_KEY_SIZE.into());
+                }
+                estimator.replace(IDENTITY_ITEM_KEY_SIZE, U64_SIZE);
+                estimator.replace_nonce();
+                min_fees.identity_update
+            }
+            StateTransition::IdentityCreditTransfer(_) => {
+                estimator.identity_signature();
+                estimator.processing(KnownCostItem::FetchIdentityBalanceProcessingCost);
+                // balances of the sender and the recipient
+                estimator.replace(HASH_SIZE, AVERAGE_BALANCE_SIZE.into());
+                estimator.replace(HASH_SIZE, AVERAGE_BALANCE_SIZE.into());
+                estimator.replace_nonce();
+                min_fees.credit_transfer
+            }
+            StateTransition::MasternodeVote(_) => {
+                estimator.processing(KnownCostItem::VerifySignatureEcdsaHash160);
+                estimator.processing(KnownCostItem::FetchSingleIdentityKeyProcessingCost);
+                estimator.insert(
+                    HASH_SIZE,
+                    AVERAGE_CONTESTED_RESOURCE_ITEM_REFERENCE_SIZE.into(),
+                );
+                estimator.replace_nonce();
+                min_fees.masternode_vote
+            }
+        };
+
+        let mut fee_result =
+            FeeResult::default_with_fees(estimator.storage_fee, estimator.processing_fee);
+        fee_result.apply_user_fee_increase(self.user_fee_increase());
+
+        Ok(FeeEstimate {
+            storage_fee: fee_result.storage_fee,
+            processing_fee: fee_result.processing_fee,
+            min_fee,
+        })
+    }
+}
+
+/// Accumulates estimated fees of operations executed by Platform.
+struct Estimator<'a> {
+    platform_version: &'a PlatformVersion,
+    storage_fee: Credits,
+    processing_fee: Credits,
+}
+
+impl<'a> Estimator<'a> {
+    fn new(platform_version: &'a PlatformVersion) -> Self {
+        Self {
+            platform_version,
+            storage_fee: 0,
+            processing_fee: 0,
+        }
+    }
+
+    /// Add processing cost of a known operation.
+    fn processing(&mut self, item: KnownCostItem) {
+        self.processing_fee = self
+            .processing_fee
+            .saturating_add(item.lookup_cost(&self.platform_version.fee_version));
+    }
+
+    /// Add cost of fetching the identity key and verifying the signature.
+    fn identity_signature(&mut self) {
+        self.processing(KnownCostItem::FetchSingleIdentityKeyProcessingCost);
+        self.processing(KnownCostItem::VerifySignatureEcdsaSecp256k1);
+    }
+
+    /// Add cost of inserting a new item with `key_len` bytes long key and `value_len` bytes long value.
+    fn insert(&mut self, key_len: u64, value_len: u64) {
+        let storage = &self.platform_version.fee_version.storage;
+        let bytes = item_size(key_len, value_len);
+
+        self.storage_fee = self
+            .storage_fee
+            .saturating_add(bytes.saturating_mul(storage.storage_disk_usage_credit_per_byte));
+        self.processing_fee = self
+            .processing_fee
+            .saturating_add(bytes.saturating_mul(storage.storage_processing_credit_per_byte))
+            .saturating_add(storage.storage_seek_cost);
+    }
+
+    /// Add cost of inserting a new empty tree under `key_len` bytes long key.
+    fn insert_tree(&mut self, key_len: u64) {
+        self.insert(key_len, EMPTY_TREE_STORAGE_SIZE.into());
+    }
+
+    /// Add cost of replacing an existing item with a value of the same size.
+    ///
+    /// Replaced items don't need new storage, so only processing is paid.
+    fn replace(&mut self, key_len: u64, value_len: u64) {
+        let storage = &self.platform_version.fee_version.storage;
+        let bytes = item_size(key_len, value_len);
+
+        self.processing_fee = self
+            .processing_fee
+            .saturating_add(bytes.saturating_mul(storage.storage_processing_credit_per_byte))
+            .saturating_add(storage.storage_seek_cost);
+    }
+
+    /// Add cost of removing an existing item.
+    fn remove(&mut self) {
+        self.processing_fee = self
+            .processing_fee
+            .saturating_add(self.platform_version.fee_version.storage.storage_seek_cost);
+    }
+
+    /// Add cost of bumping the identity contract nonce.
+    fn replace_nonce(&mut self) {
+        self.replace(IDENTITY_ITEM_KEY_SIZE, U64_SIZE);
+    }
+
+    /// Add cost of verifying and storing a new identity key, and its key hash reference.
+    fn identity_key(&mut self, key: &IdentityPublicKeyInCreation) -> Result<(), Error> {
+        self.processing(KnownCostItem::VerifySignatureEcdsaSecp256k1);
+        self.insert(size_of::<KeyID>() as u64, encoded_len(key)?);
+        self.insert(KEY_HASH_SIZE, HASH_SIZE);
+
+        Ok(())
+    }
+
+    /// Add cost of validating and storing a data contract.
+    fn data_contract(
+        &mut self,
+        data_contract: &dpp::data_contract::serialized_version::DataContractInSerializationFormat,
+    ) -> Result<(), Error> {
+        let mut validation_operations = vec![];
+        let validated = DataContract::try_from_platform_versioned(
+            data_contract.clone(),
+            true,
+            &mut validation_operations,
+            self.platform_version,
+        )?;
+
+        for operation in validation_operations {
+            self.processing_fee = self
+                .processing_fee
+                .saturating_add(operation.processing_cost(self.platform_version));
+        }
+
+        // contract tree with the serialized contract, and a tree for each document type and index
+        self.insert_tree(HASH_SIZE);
+        self.insert(HASH_SIZE, encoded_len(data_contract)?);
+        for (name, document_type) in validated.document_types() {
+            self.insert_tree(name.len() as u64);
+            for index_name in document_type.indexes().keys() {
+                self.insert_tree(index_name.len() as u64);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add cost of applying a document transition.
+    fn document(
+        &mut self,
+        data_contract: &DataContract,
+        owner_id: Identifier,
+        transition: &DocumentTransition,
+    ) -> Result<(), Error> {
+        let document_type = data_contract
+            .document_type_for_name(transition.document_type_name())
+            .map_err(ProtocolError::from)?;
+        let indices = document_type.indexes().len();
+
+        match transition {
+            DocumentTransition::Create(create) => {
+                let document = Document::try_from_create_transition(
+                    create,
+                    owner_id,
+                    &BlockInfo::default(),
+                    &document_type,
+                    self.platform_version,
+                )?;
+                let document_len = self.serialized_len(&document, document_type)?;
+
+                self.insert(HASH_SIZE, document_len);
+                for _ in 0..indices {
+                    self.insert(HASH_SIZE, OPTIMIZED_DOCUMENT_REFERENCE.into());
+                }
+                if create.prefunded_voting_balance().is_some() {
+                    // contested document is stored in the vote poll until the contest is resolved
+                    self.insert(HASH_SIZE, document_len);
+                    self.insert(HASH_SIZE, CONTESTED_DOCUMENT_REFERENCE_SIZE.into());
+                }
+            }
+            DocumentTransition::Replace(replace) => {
+                let document = Document::try_from_replace_transition(
+                    replace,
+                    owner_id,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    &BlockInfo::default(),
+                    &document_type,
+                    self.platform_version,
+                )?;
+                let document_len = self.serialized_len(&document, document_type)?;
+
+                self.insert(HASH_SIZE, document_len);
+                self.reindex(indices);
+            }
+            DocumentTransition::Delete(_) => {
+                for _ in 0..=indices {
+                    self.remove();
+                }
+            }
+            DocumentTransition::Transfer(_)
+            | DocumentTransition::UpdatePrice(_)
+            | DocumentTransition::Purchase(_) => {
+                // stored document is not known, so its estimated size is used
+                let document_len = document_type.estimated_size(self.platform_version)?;
+
+                self.processing(KnownCostItem::FetchIdentityBalanceProcessingCost);
+                self.insert(HASH_SIZE, document_len.into());
+                self.reindex(indices);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add cost of moving document references in `indices` indices.
+    fn reindex(&mut self, indices: usize) {
+        for _ in 0..indices {
+            self.remove();
+            self.insert(HASH_SIZE, OPTIMIZED_DOCUMENT_REFERENCE.into());
+        }
+    }
+
+    /// Size of the document serialized the way Drive stores it.
+    fn serialized_len(
+        &self,
+        document: &Document,
+        document_type: DocumentTypeRef,
+    ) -> Result<u64, Error> {
+        Ok(document
+            .serialize(document_type, self.platform_version)?
+            .len() as u64)
+    }
+}
+
+/// Size of a stored item: its key, value, storage flags and the hash GroveDB keeps for it.
+fn item_size(key_len: u64, value_len: u64) -> u64 {
+    key_len
+        .saturating_add(value_len)
+        .saturating_add(STORAGE_FLAGS_SIZE.into())
+        .saturating_add(HASH_SIZE)
+}
+
+/// Size of the value encoded using platform bincode format.
+fn encoded_len<T: bincode::Encode>(value: &T) -> Result<u64, Error> {
+    let config = bincode::config::standard()
+        .with_big_endian()
+        .with_no_limit();
+
+    bincode::encode_to_vec(value, config)
+        .map(|bytes| bytes.len() as u64)
+        .map_err(|e| ProtocolError::EncodingError(e.to_string()).into())
+}
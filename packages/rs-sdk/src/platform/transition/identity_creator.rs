@@ -0,0 +1,493 @@
+//! Fund and create identities from Dash Core UTXOs.
+//!
+//! [IdentityCreator] runs the whole identity creation flow:
+//!
+//! 1. builds and signs an asset lock transaction spending provided UTXOs,
+//! 2. broadcasts it to Dash Core,
+//! 3. waits for an instant asset lock proof; if the instant lock doesn't arrive in time, falls back
+//!    to a chain asset lock proof,
+//! 4. creates the identity on Platform.
+//!
+//! Once the asset lock transaction is broadcast, the funds are locked and can only be claimed with
+//! the asset lock proof. To not lose them when the process is interrupted, the creator can persist
+//! its progress in a state file (see [IdentityCreator::with_state_file()]). When the state file
+//! exists, the flow is resumed from the last completed step instead of spending the UTXOs again.
+//!
+//! The state file contains no private keys; the same keys must be provided when resuming.
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use dapi_grpc::core::v0::{
+    GetBlockchainStatusRequest, GetBlockchainStatusResponse, GetTransactionRequest,
+    TransactionsWithProofsResponse,
+};
+use dapi_grpc::tonic::Streaming;
+use dpp::balances::credits::Duffs;
+use dpp::bincode;
+use dpp::dashcore::consensus::{deserialize, serialize};
+use dpp::dashcore::hashes::Hash;
+use dpp::dashcore::secp256k1::Secp256k1;
+use dpp::dashcore::{PrivateKey, Transaction};
+use dpp::identity::accessors::IdentityGettersV0;
+use dpp::identity::signer::Signer;
+use dpp::platform_value::string_encoding::Encoding;
+use dpp::prelude::{AssetLockProof, Identifier, Identity};
+use dpp::ProtocolError;
+use rs_dapi_client::{DapiRequestExecutor, RequestSettings};
+use tokio::time::timeout;
+
+use super::put_identity::PutIdentity;
+use crate::core::asset_lock::{build_asset_lock_transaction, Utxo, DEFAULT_FEE_PER_BYTE};
+use crate::platform::Fetch;
+use crate::{Error, Sdk};
+
+/// Version of the [IdentityCreatorState] serialization format.
+pub const IDENTITY_CREATOR_STATE_FORMAT_VERSION: u16 = 0;
+/// Default time to wait for the instant lock of the asset lock transaction.
+pub const DEFAULT_INSTANT_LOCK_TIMEOUT: Duration = Duration::from_secs(60);
+/// Default time to wait for the chain lock of the asset lock transaction.
+pub const DEFAULT_CHAIN_LOCK_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Progress of the identity creation, persisted after each step.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdentityCreatorState {
+    /// Asset lock transaction was built and signed, but not broadcast yet
+    TransactionSigned(Transaction),
+    /// Asset lock transaction was broadcast; funds are locked
+    TransactionBroadcast(Transaction),
+    /// Asset lock proof was received; the identity can be created
+    AssetLockProofReceived {
+        /// Asset lock transaction
+        transaction: Transaction,
+        /// Proof of the asset lock transaction
+        asset_lock_proof: AssetLockProof,
+    },
+    /// Identity was created
+    IdentityCreated {
+        /// Asset lock transaction
+        transaction: Transaction,
+        /// Identifier of the created identity
+        identity_id: Identifier,
+    },
+}
+
+impl IdentityCreatorState {
+    /// Asset lock transaction.
+    pub fn transaction(&self) -> &Transaction {
+        match self {
+            Self::TransactionSigned(transaction)
+            | Self::TransactionBroadcast(transaction)
+            | Self::AssetLockProofReceived { transaction, .. }
+            | Self::IdentityCreated { transaction, .. } => transaction,
+        }
+    }
+
+    /// Serialize the state using platform bincode format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let (tag, asset_lock_proof, identity_id) = match self {
+            Self::TransactionSigned(_) => (0u8, None, None),
+            Self::TransactionBroadcast(_) => (1, None, None),
+            Self::AssetLockProofReceived {
+                asset_lock_proof, ..
+            } => (2, Some(asset_lock_proof.clone()), None),
+            Self::IdentityCreated { identity_id, .. } => (3, None, Some(identity_id.to_buffer())),
+        };
+
+        let data = (
+            IDENTITY_CREATOR_STATE_FORMAT_VERSION,
+            tag,
+            serialize(self.transaction()),
+            asset_lock_proof,
+            identity_id,
+        );
+
+        bincode::encode_to_vec(data, bincode_config())
+            .map_err(|e| ProtocolError::EncodingError(e.to_string()).into())
+    }
+
+    /// Deserialize the state created with [IdentityCreatorState::to_bytes()].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        #[allow(clippy::type_complexity)]
+        let ((version, tag, transaction, asset_lock_proof, identity_id), _): (
+            (u16, u8, Vec<u8>, Option<AssetLockProof>, Option<[u8; 32]>),
+            usize,
+        ) = bincode::decode_from_slice(bytes, bincode_config())
+            .map_err(|e| ProtocolError::DecodingError(e.to_string()))?;
+
+        if version != IDENTITY_CREATOR_STATE_FORMAT_VERSION {
+            return Err(Error::Generic(format!(
+                "unsupported identity creator state format version {}",
+                version
+            )));
+        }
+
+        let transaction: Transaction =
+            deserialize(&transaction).map_err(|e| Error::CoreError(e.into()))?;
+
+        match (tag, asset_lock_proof, identity_id) {
+            (0, None, None) => Ok(Self::TransactionSigned(transaction)),
+            (1, None, None) => Ok(Self::TransactionBroadcast(transaction)),
+            (2, Some(asset_lock_proof), None) => Ok(Self::AssetLockProofReceived {
+                transaction,
+                asset_lock_proof,
+            }),
+            (3, None, Some(identity_id)) => Ok(Self::IdentityCreated {
+                transaction,
+                identity_id: Identifier::from(identity_id),
+            }),
+            (tag, _, _) => Err(Error::Generic(format!(
+                "invalid identity creator state with tag {}",
+                tag
+            ))),
+        }
+    }
+
+    /// Load the state from a file; returns `None` if the file doesn't exist.
+    pub fn load(path: &Path) -> Result<Option<Self>, Error> {
+        match std::fs::read(path) {
+            Ok(bytes) => Self::from_bytes(&bytes).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::Generic(format!(
+                "can't read identity creator state from {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    /// Save the state to a file.
+    ///
+    /// The state is written to a temporary file first and then renamed, so the file always
+    /// contains a complete state.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+
+        std::fs::write(&tmp_path, self.to_bytes()?)
+            .and_then(|_| std::fs::rename(&tmp_path, path))
+            .map_err(|e| {
+                Error::Generic(format!(
+                    "can't write identity creator state to {}: {}",
+                    path.display(),
+                    e
+                ))
+            })
+    }
+}
+
+/// Creates an identity funded from Dash Core UTXOs.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use dash_sdk::core::asset_lock::Utxo;
+/// use dash_sdk::platform::transition::identity_creator::IdentityCreator;
+/// # use dash_sdk::{Sdk, Error};
+/// # use dpp::dashcore::PrivateKey;
+/// # use dpp::identity::{Identity, signer::Signer};
+///
+/// # async fn example(sdk: &Sdk, identity: Identity, funding_key: PrivateKey, utxos: Vec<Utxo>, signer: impl Signer) -> Result<(), Error> {
+/// let identity = IdentityCreator::new(sdk, identity, funding_key, &signer)
+///     .with_utxos(utxos)
+///     .with_amount(100_000)
+///     .with_state_file("identity_creator.state")
+///     .create()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct IdentityCreator<'a, S: Signer> {
+    sdk: &'a Sdk,
+    identity: Identity,
+    funding_key: PrivateKey,
+    asset_lock_key: PrivateKey,
+    signer: &'a S,
+    utxos: Vec<Utxo>,
+    amount: Duffs,
+    fee_per_byte: Duffs,
+    state_file: Option<PathBuf>,
+    instant_lock_timeout: Duration,
+    chain_lock_timeout: Duration,
+}
+
+impl<'a, S: Signer> IdentityCreator<'a, S> {
+    /// Create new identity creator.
+    ///
+    /// `identity` contains public keys of the new identity, signed by `signer`.
+    /// UTXOs are spent with `funding_key`, which is also used as the asset lock key unless
+    /// [IdentityCreator::with_asset_lock_key()] is called.
+    pub fn new(sdk: &'a Sdk, identity: Identity, funding_key: PrivateKey, signer: &'a S) -> Self {
+        Self {
+            sdk,
+            identity,
+            funding_key,
+            asset_lock_key: funding_key,
+            signer,
+            utxos: Vec::new(),
+            amount: 0,
+            fee_per_byte: DEFAULT_FEE_PER_BYTE,
+            state_file: None,
+            instant_lock_timeout: DEFAULT_INSTANT_LOCK_TIMEOUT,
+            chain_lock_timeout: DEFAULT_CHAIN_LOCK_TIMEOUT,
+        }
+    }
+
+    /// UTXOs to spend; they must be P2PKH outputs of the funding key.
+    pub fn with_utxos(mut self, utxos: Vec<Utxo>) -> Self {
+        self.utxos = utxos;
+        self
+    }
+
+    /// Amount of duffs to lock and convert into identity credits.
+    pub fn with_amount(mut self, amount: Duffs) -> Self {
+        self.amount = amount;
+        self
+    }
+
+    /// Key used to sign the asset lock proof in the identity create transition.
+    pub fn with_asset_lock_key(mut self, asset_lock_key: PrivateKey) -> Self {
+        self.asset_lock_key = asset_lock_key;
+        self
+    }
+
+    /// Fee rate of the asset lock transaction, in duffs per byte.
+    pub fn with_fee_per_byte(mut self, fee_per_byte: Duffs) -> Self {
+        self.fee_per_byte = fee_per_byte;
+        self
+    }
+
+    /// File where progress is persisted, so that an interrupted flow can be resumed.
+    pub fn with_state_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.state_file = Some(path.into());
+        self
+    }
+
+    /// Time to wait for the instant lock before falling back to the chain lock.
+    pub fn with_instant_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.instant_lock_timeout = timeout;
+        self
+    }
+
+    /// Time to wait for the chain lock.
+    pub fn with_chain_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.chain_lock_timeout = timeout;
+        self
+    }
+
+    /// Create the identity, resuming from the state file if it exists.
+    ///
+    /// When resuming after the asset lock transaction was broadcast, the instant lock is no longer
+    /// available and the creator waits for the chain asset lock proof.
+    pub async fn create(&self) -> Result<Identity, Error> {
+        let mut state = match self.load_state()? {
+            Some(state) => {
+                tracing::info!(
+                    transaction_id = state.transaction().txid().to_string(),
+                    "resuming identity creation"
+                );
+                state
+            }
+            None => {
+                let state = IdentityCreatorState::TransactionSigned(self.build_transaction()?);
+                self.save_state(&state)?;
+                state
+            }
+        };
+
+        loop {
+            state = match state {
+                IdentityCreatorState::TransactionSigned(transaction) => {
+                    // start listening before broadcast to not miss the instant lock
+                    let stream = self.start_stream(&transaction).await?;
+                    self.broadcast(&transaction).await?;
+                    let state = IdentityCreatorState::TransactionBroadcast(transaction);
+                    self.save_state(&state)?;
+
+                    let asset_lock_proof = self
+                        .wait_for_asset_lock_proof(Some(stream), state.transaction())
+                        .await?;
+                    IdentityCreatorState::AssetLockProofReceived {
+                        transaction: state.transaction().clone(),
+                        asset_lock_proof,
+                    }
+                }
+                IdentityCreatorState::TransactionBroadcast(transaction) => {
+                    let asset_lock_proof =
+                        self.wait_for_asset_lock_proof(None, &transaction).await?;
+                    IdentityCreatorState::AssetLockProofReceived {
+                        transaction,
+                        asset_lock_proof,
+                    }
+                }
+                IdentityCreatorState::AssetLockProofReceived {
+                    transaction,
+                    asset_lock_proof,
+                } => {
+                    // identity might have been created before the state was saved
+                    let identity_id = asset_lock_proof.create_identifier()?;
+                    if let Some(identity) = Identity::fetch(self.sdk, identity_id).await? {
+                        self.save_state(&IdentityCreatorState::IdentityCreated {
+                            transaction,
+                            identity_id,
+                        })?;
+
+                        return Ok(identity);
+                    }
+
+                    let identity = self
+                        .identity
+                        .put_to_platform_and_wait_for_response(
+                            self.sdk,
+                            asset_lock_proof,
+                            &self.asset_lock_key,
+                            self.signer,
+                        )
+                        .await?;
+
+                    self.save_state(&IdentityCreatorState::IdentityCreated {
+                        transaction,
+                        identity_id: identity.id(),
+                    })?;
+
+                    return Ok(identity);
+                }
+                IdentityCreatorState::IdentityCreated { identity_id, .. } => {
+                    return Identity::fetch(self.sdk, identity_id)
+                        .await?
+                        .ok_or_else(|| {
+                            Error::MissingDependency(
+                                "Identity".to_string(),
+                                identity_id.to_string(Encoding::Base58),
+                            )
+                        });
+                }
+            };
+
+            self.save_state(&state)?;
+        }
+    }
+
+    fn build_transaction(&self) -> Result<Transaction, Error> {
+        let secp = Secp256k1::new();
+
+        build_asset_lock_transaction(
+            &self.utxos,
+            &self.funding_key,
+            &self.asset_lock_key.public_key(&secp),
+            self.amount,
+            self.fee_per_byte,
+        )
+    }
+
+    async fn start_stream(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Streaming<TransactionsWithProofsResponse>, Error> {
+        let GetBlockchainStatusResponse { chain, .. } = self
+            .sdk
+            .execute(GetBlockchainStatusRequest {}, RequestSettings::default())
+            .await?;
+        let best_block_hash = chain
+            .map(|chain| chain.best_block_hash)
+            .ok_or_else(|| Error::DapiClientError("missing `chain` field".to_string()))?;
+
+        // change output, credit output and the transaction itself, in case neither is matched
+        let secp = Secp256k1::new();
+        let elements = [
+            self.funding_key
+                .public_key(&secp)
+                .pubkey_hash()
+                .to_byte_array()
+                .to_vec(),
+            self.asset_lock_key
+                .public_key(&secp)
+                .pubkey_hash()
+                .to_byte_array()
+                .to_vec(),
+            transaction.txid().to_byte_array().to_vec(),
+        ];
+
+        self.sdk
+            .start_transactions_stream(best_block_hash, &elements)
+            .await
+    }
+
+    /// Broadcast the transaction; succeeds if the transaction is already known to Dash Core.
+    async fn broadcast(&self, transaction: &Transaction) -> Result<(), Error> {
+        let error = match self.sdk.broadcast_transaction(transaction).await {
+            Ok(_) => return Ok(()),
+            Err(e) => e,
+        };
+
+        let request = GetTransactionRequest {
+            id: transaction.txid().to_string(),
+        };
+        match self.sdk.execute(request, RequestSettings::default()).await {
+            Ok(_) => {
+                tracing::debug!(
+                    ?error,
+                    "asset lock transaction broadcast failed, but the transaction is already known"
+                );
+                Ok(())
+            }
+            Err(_) => Err(error),
+        }
+    }
+
+    async fn wait_for_asset_lock_proof(
+        &self,
+        stream: Option<Streaming<TransactionsWithProofsResponse>>,
+        transaction: &Transaction,
+    ) -> Result<AssetLockProof, Error> {
+        if let Some(stream) = stream {
+            match self
+                .sdk
+                .wait_for_asset_lock_proof_for_transaction(
+                    stream,
+                    transaction,
+                    Some(self.instant_lock_timeout),
+                )
+                .await
+            {
+                Ok(asset_lock_proof) => return Ok(asset_lock_proof),
+                Err(error) => tracing::warn!(
+                    ?error,
+                    "instant asset lock proof not received, falling back to chain asset lock proof"
+                ),
+            }
+        }
+
+        timeout(
+            self.chain_lock_timeout,
+            self.sdk.wait_for_chain_asset_lock_proof(transaction),
+        )
+        .await
+        .map_err(|_| {
+            Error::TimeoutReached(
+                self.chain_lock_timeout,
+                String::from("receiving chain asset lock proof"),
+            )
+        })?
+    }
+
+    fn load_state(&self) -> Result<Option<IdentityCreatorState>, Error> {
+        self.state_file
+            .as_deref()
+            .map(IdentityCreatorState::load)
+            .transpose()
+            .map(Option::flatten)
+    }
+
+    fn save_state(&self, state: &IdentityCreatorState) -> Result<(), Error> {
+        match &self.state_file {
+            Some(path) => state.save(path),
+            None => Ok(()),
+        }
+    }
+}
+
+fn bincode_config() -> impl bincode::config::Config {
+    bincode::config::standard()
+        .with_big_endian()
+        .with_no_limit()
+}
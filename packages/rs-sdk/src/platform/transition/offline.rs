@@ -0,0 +1,349 @@
+//! Offline signing of state transitions.
+//!
+//! Signing keys of cold-storage and air-gapped setups are not available to the machine connected
+//! to Dash Platform, so building, signing and broadcasting of a state transition are split into
+//! three steps:
+//!
+//! 1. Online: [PreparedTransition] fetches nonces and the data contract from Platform; they are used
+//!    to build the state transition using [PlaceholderSigner], and the result is exported with
+//!    [UnsignedTransition::to_bytes()].
+//! 2. Offline: [UnsignedTransition::from_bytes()] loads the unsigned transition and
+//!    [UnsignedTransition::sign()] signs its [signable bytes](UnsignedTransition::signable_bytes())
+//!    with a [Signer]; keys added by an identity update are signed with their own private keys, too.
+//!    The signed state transition is exported with [PlatformSerializable::serialize_to_bytes()].
+//! 3. Online: [UnsignedTransition::import_signed()] loads the signed state transition, ensuring it
+//!    matches the unsigned one and its signatures are valid, so that it can be broadcast with
+//!    [BroadcastStateTransition](super::broadcast::BroadcastStateTransition).
+//!
+//! All data is serialized using platform bincode format, so the offline step only needs `dpp`.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use dash_sdk::platform::transition::broadcast::BroadcastStateTransition;
+//! use dash_sdk::platform::transition::offline::{PlaceholderSigner, PreparedTransition, UnsignedTransition};
+//! use dpp::state_transition::identity_credit_transfer_transition::IdentityCreditTransferTransition;
+//! use dpp::state_transition::identity_credit_transfer_transition::methods::IdentityCreditTransferTransitionMethodsV0;
+//! # use dash_sdk::{Sdk, Error};
+//! # use dpp::identity::{Identity, IdentityPublicKey, signer::Signer};
+//! # use dpp::identity::accessors::IdentityGettersV0;
+//!
+//! # async fn example(sdk: &Sdk, identity: Identity, key: IdentityPublicKey, recipient: dpp::prelude::Identifier, signer: impl Signer) -> Result<(), Error> {
+//! // online: prepare and export unsigned transition
+//! let prepared = PreparedTransition::with_identity_nonce(sdk, identity.id(), None).await?;
+//! let transition = IdentityCreditTransferTransition::try_from_identity(
+//!     &identity,
+//!     Some(&key),
+//!     recipient,
+//!     1000,
+//!     prepared.user_fee_increase,
+//!     PlaceholderSigner,
+//!     prepared.nonce,
+//!     sdk.version(),
+//!     None,
+//! )?;
+//! let unsigned = UnsignedTransition::new(transition, key)?.to_bytes()?;
+//!
+//! // offline: sign
+//! let signed = UnsignedTransition::from_bytes(&unsigned)?.sign_to_bytes(&signer)?;
+//!
+//! // online: import and broadcast
+//! let state_transition = UnsignedTransition::from_bytes(&unsigned)?.import_signed(&signed)?;
+//! state_transition.broadcast(sdk).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [PlatformSerializable::serialize_to_bytes()]: dpp::serialization::PlatformSerializable::serialize_to_bytes
+use std::sync::Arc;
+
+use dpp::bincode;
+use dpp::data_contract::DataContract;
+use dpp::identity::identity_public_key::accessors::v0::IdentityPublicKeyGettersV0;
+use dpp::identity::signer::Signer;
+use dpp::identity::IdentityPublicKey;
+use dpp::native_bls::NativeBlsModule;
+use dpp::platform_value::string_encoding::Encoding;
+use dpp::platform_value::BinaryData;
+use dpp::prelude::{Identifier, IdentityNonce, UserFeeIncrease};
+use dpp::serialization::{
+    PlatformDeserializable, PlatformMessageSignable, PlatformSerializable, Signable,
+};
+use dpp::state_transition::identity_update_transition::accessors::IdentityUpdateTransitionAccessorsV0;
+use dpp::state_transition::public_key_in_creation::accessors::{
+    IdentityPublicKeyInCreationV0Getters, IdentityPublicKeyInCreationV0Setters,
+};
+use dpp::state_transition::StateTransition;
+use dpp::ProtocolError;
+
+use super::put_settings::PutSettings;
+use crate::platform::Fetch;
+use crate::{Error, Sdk};
+
+/// Version of the [UnsignedTransition] serialization format.
+pub const UNSIGNED_TRANSITION_FORMAT_VERSION: u16 = 0;
+
+/// Nonce and data contract fetched from Platform, needed to build a state transition offline.
+#[derive(Debug, Clone)]
+pub struct PreparedTransition {
+    /// Identity that owns the state transition
+    pub identity_id: Identifier,
+    /// Nonce to use in the state transition; it is already bumped
+    pub nonce: IdentityNonce,
+    /// Data contract the state transition refers to, if any
+    pub data_contract: Option<Arc<DataContract>>,
+    /// Fee multiplier to use in the state transition
+    pub user_fee_increase: UserFeeIncrease,
+}
+
+impl PreparedTransition {
+    /// Prepare a state transition that uses identity nonce.
+    ///
+    /// Use for data contract create, identity update, credit transfer and credit withdrawal
+    /// state transitions.
+    pub async fn with_identity_nonce(
+        sdk: &Sdk,
+        identity_id: Identifier,
+        settings: Option<PutSettings>,
+    ) -> Result<Self, Error> {
+        let nonce = sdk.get_identity_nonce(identity_id, true, settings).await?;
+
+        Ok(Self {
+            identity_id,
+            nonce,
+            data_contract: None,
+            user_fee_increase: settings
+                .and_then(|s| s.user_fee_increase)
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Prepare a state transition that uses identity contract nonce.
+    ///
+    /// Use for documents batch and data contract update state transitions.
+    pub async fn with_identity_contract_nonce(
+        sdk: &Sdk,
+        identity_id: Identifier,
+        contract_id: Identifier,
+        settings: Option<PutSettings>,
+    ) -> Result<Self, Error> {
+        let request_settings = settings.unwrap_or_default().request_settings;
+        let data_contract = DataContract::fetch_with_settings(sdk, contract_id, request_settings)
+            .await?
+            .ok_or_else(|| {
+                Error::MissingDependency(
+                    "DataContract".to_string(),
+                    contract_id.to_string(Encoding::Base58),
+                )
+            })?;
+        let nonce = sdk
+            .get_identity_contract_nonce(identity_id, contract_id, true, settings)
+            .await?;
+
+        Ok(Self {
+            identity_id,
+            nonce,
+            data_contract: Some(Arc::new(data_contract)),
+            user_fee_increase: settings
+                .and_then(|s| s.user_fee_increase)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Signer that produces empty signatures.
+///
+/// Use it to build state transitions with existing constructors before wrapping them
+/// in [UnsignedTransition]; the signature is created later by [UnsignedTransition::sign()].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlaceholderSigner;
+
+impl Signer for PlaceholderSigner {
+    fn sign(
+        &self,
+        _identity_public_key: &IdentityPublicKey,
+        _data: &[u8],
+    ) -> Result<BinaryData, ProtocolError> {
+        Ok(BinaryData::default())
+    }
+}
+
+/// State transition waiting to be signed offline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnsignedTransition {
+    state_transition: StateTransition,
+    identity_public_key: IdentityPublicKey,
+    signable_bytes: Vec<u8>,
+}
+
+impl UnsignedTransition {
+    /// Create an unsigned transition to be signed with `identity_public_key`.
+    ///
+    /// Any signature already present in the state transition is removed.
+    /// Identity create and top up transitions are signed with asset lock keys and are not supported.
+    pub fn new(
+        mut state_transition: StateTransition,
+        identity_public_key: IdentityPublicKey,
+    ) -> Result<Self, Error> {
+        if matches!(
+            state_transition,
+            StateTransition::IdentityCreate(_) | StateTransition::IdentityTopUp(_)
+        ) {
+            return Err(Error::Generic(format!(
+                "{} state transition can't be signed offline",
+                state_transition.name()
+            )));
+        }
+
+        state_transition.set_signature(BinaryData::default());
+        state_transition.set_signature_public_key_id(identity_public_key.id());
+        let signable_bytes = state_transition.signable_bytes()?;
+
+        Ok(Self {
+            state_transition,
+            identity_public_key,
+            signable_bytes,
+        })
+    }
+
+    /// Unsigned state transition.
+    pub fn state_transition(&self) -> &StateTransition {
+        &self.state_transition
+    }
+
+    /// Public key of the identity that must be used to sign the state transition.
+    pub fn identity_public_key(&self) -> &IdentityPublicKey {
+        &self.identity_public_key
+    }
+
+    /// Bytes to sign.
+    pub fn signable_bytes(&self) -> &[u8] {
+        &self.signable_bytes
+    }
+
+    /// Serialize unsigned transition using platform bincode format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let data = (
+            UNSIGNED_TRANSITION_FORMAT_VERSION,
+            self.state_transition.serialize_to_bytes()?,
+            self.identity_public_key.serialize_to_bytes()?,
+            &self.signable_bytes,
+        );
+
+        bincode::encode_to_vec(data, bincode_config())
+            .map_err(|e| ProtocolError::EncodingError(e.to_string()).into())
+    }
+
+    /// Deserialize unsigned transition created with [UnsignedTransition::to_bytes()].
+    ///
+    /// Returns an error if signable bytes don't match the state transition.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let ((version, state_transition, identity_public_key, signable_bytes), _): (
+            (u16, Vec<u8>, Vec<u8>, Vec<u8>),
+            usize,
+        ) = bincode::decode_from_slice(bytes, bincode_config())
+            .map_err(|e| ProtocolError::DecodingError(e.to_string()))?;
+
+        if version != UNSIGNED_TRANSITION_FORMAT_VERSION {
+            return Err(Error::Generic(format!(
+                "unsupported unsigned transition format version {}",
+                version
+            )));
+        }
+
+        let unsigned = Self::new(
+            StateTransition::deserialize_from_bytes(&state_transition)?,
+            IdentityPublicKey::deserialize_from_bytes(&identity_public_key)?,
+        )?;
+        if unsigned.signable_bytes != signable_bytes {
+            return Err(Error::Generic(
+                "signable bytes don't match unsigned state transition".to_string(),
+            ));
+        }
+
+        Ok(unsigned)
+    }
+
+    /// Sign the state transition.
+    ///
+    /// Keys added by an identity update must prove possession of their private keys, so the `signer`
+    /// must be able to sign with them, too.
+    pub fn sign<S: Signer>(&self, signer: &S) -> Result<StateTransition, Error> {
+        let mut state_transition = self.state_transition.clone();
+
+        if let StateTransition::IdentityUpdate(st) = &mut state_transition {
+            for key in st.public_keys_to_add_mut() {
+                if key.key_type().is_unique_key_type() {
+                    let signature =
+                        signer.sign(&IdentityPublicKey::from(&*key), &self.signable_bytes)?;
+                    key.set_signature(signature);
+                }
+            }
+        }
+
+        let signature = signer.sign(&self.identity_public_key, &self.signable_bytes)?;
+        state_transition.set_signature(signature);
+
+        Ok(state_transition)
+    }
+
+    /// Sign the state transition and serialize it using platform bincode format.
+    pub fn sign_to_bytes<S: Signer>(&self, signer: &S) -> Result<Vec<u8>, Error> {
+        Ok(self.sign(signer)?.serialize_to_bytes()?)
+    }
+
+    /// Import signed state transition serialized using platform bincode format.
+    ///
+    /// Returns an error if the signed state transition does not match this unsigned transition,
+    /// or any of its signatures is missing or invalid.
+    pub fn import_signed(&self, bytes: &[u8]) -> Result<StateTransition, Error> {
+        let state_transition = StateTransition::deserialize_from_bytes(bytes)?;
+
+        if state_transition.signable_bytes()? != self.signable_bytes {
+            return Err(Error::Generic(
+                "signed state transition does not match unsigned state transition".to_string(),
+            ));
+        }
+        if state_transition.signature_public_key_id() != Some(self.identity_public_key.id()) {
+            return Err(Error::Generic(format!(
+                "signed state transition must be signed with key {}",
+                self.identity_public_key.id()
+            )));
+        }
+        if state_transition.signature().is_empty() {
+            return Err(Error::Generic("state transition is not signed".to_string()));
+        }
+
+        state_transition
+            .verify_signature(&self.identity_public_key, &NativeBlsModule)
+            .map_err(|e| Error::Generic(format!("invalid signature of state transition: {}", e)))?;
+
+        if let StateTransition::IdentityUpdate(st) = &state_transition {
+            for key in st.public_keys_to_add() {
+                if !key.key_type().is_unique_key_type() {
+                    continue;
+                }
+                let result = self.signable_bytes.as_slice().verify_signature(
+                    key.key_type(),
+                    key.data().as_slice(),
+                    key.signature().as_slice(),
+                );
+                if !result.is_valid() {
+                    return Err(Error::Generic(format!(
+                        "invalid signature of added key {}: {:?}",
+                        key.id(),
+                        result.errors
+                    )));
+                }
+            }
+        }
+
+        Ok(state_transition)
+    }
+}
+
+fn bincode_config() -> impl bincode::config::Config {
+    bincode::config::standard()
+        .with_big_endian()
+        .with_no_limit()
+}
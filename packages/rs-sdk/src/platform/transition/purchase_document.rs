@@ -1,3 +1,4 @@
+use crate::platform::transition::broadcast::check_broadcast_error;
 use crate::platform::transition::broadcast_request::BroadcastRequestForStateTransition;
 use std::sync::Arc;
 
@@ -101,7 +102,8 @@ impl<S: Signer> PurchaseDocument<S> for Document {
         request
             .clone()
             .execute(sdk, settings.request_settings)
-            .await?;
+            .await
+            .map_err(Error::from_grpc_error)?;
 
         // response is empty for a broadcast, result comes from the stream wait for state transition result
 
@@ -116,7 +118,11 @@ impl<S: Signer> PurchaseDocument<S> for Document {
     ) -> Result<Document, Error> {
         let request = state_transition.wait_for_state_transition_result_request()?;
 
-        let response = request.execute(sdk, RequestSettings::default()).await?;
+        let response = request
+            .execute(sdk, RequestSettings::default())
+            .await
+            .map_err(Error::from_grpc_error)?;
+        check_broadcast_error(&response)?;
 
         let block_info = block_info_from_metadata(response.metadata()?)?;
 
@@ -1,3 +1,4 @@
+use crate::platform::transition::broadcast::check_broadcast_error;
 use crate::platform::transition::broadcast_identity::BroadcastRequestForNewIdentity;
 use crate::platform::transition::broadcast_request::BroadcastRequestForStateTransition;
 use crate::platform::Fetch;
@@ -56,7 +57,8 @@ impl<S: Signer> PutIdentity<S> for Identity {
         request
             .clone()
             .execute(sdk, RequestSettings::default())
-            .await?;
+            .await
+            .map_err(Error::from_grpc_error)?;
 
         // response is empty for a broadcast, result comes from the stream wait for state transition result
 
@@ -96,12 +98,16 @@ impl<S: Signer> PutIdentity<S> for Identity {
                     "identity was proved to not exist but was said to exist".to_string(),
                 ));
             }
-            Err(e) => return Err(e.into()),
+            Err(e) => return Err(Error::from_grpc_error(e)),
         }
 
         let request = state_transition.wait_for_state_transition_result_request()?;
 
-        let response = request.execute(sdk, RequestSettings::default()).await?;
+        let response = request
+            .execute(sdk, RequestSettings::default())
+            .await
+            .map_err(Error::from_grpc_error)?;
+        check_broadcast_error(&response)?;
 
         let block_info = block_info_from_metadata(response.metadata()?)?;
         let proof = response.proof_owned()?;
@@ -1,4 +1,5 @@
 use crate::platform::block_info_from_metadata::block_info_from_metadata;
+use crate::platform::transition::broadcast::check_broadcast_error;
 use crate::platform::transition::broadcast_request::BroadcastRequestForStateTransition;
 use crate::{Error, Sdk};
 use dapi_grpc::platform::VersionedGrpcResponse;
@@ -47,11 +48,16 @@ impl TopUpIdentity for Identity {
         request
             .clone()
             .execute(sdk, RequestSettings::default())
-            .await?;
+            .await
+            .map_err(Error::from_grpc_error)?;
 
         let request = state_transition.wait_for_state_transition_result_request()?;
 
-        let response = request.execute(sdk, RequestSettings::default()).await?;
+        let response = request
+            .execute(sdk, RequestSettings::default())
+            .await
+            .map_err(Error::from_grpc_error)?;
+        check_broadcast_error(&response)?;
 
         let block_info = block_info_from_metadata(response.metadata()?)?;
 
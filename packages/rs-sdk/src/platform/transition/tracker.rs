@@ -0,0 +1,754 @@
+//! Track state transitions until they are executed, rejected or expire.
+//!
+//! Broadcasting a state transition and waiting for its result are separate steps; when waiting fails
+//! (for example, times out), it's unknown whether the state transition was executed. [TransitionTracker]
+//! resolves this by polling Platform for proofs of the state changed by the transition
+//! (`getProofs`), verified with [Drive::verify_state_transition_was_executed_with_proof()], and for
+//! the result of the transition identified by its [TxId] (`waitForStateTransitionResult`).
+//!
+//! When the transition is rejected or expires, cached nonces of the owner identity are invalidated,
+//! so that next transitions use nonces fetched from Platform.
+//!
+//! [Drive::verify_state_transition_was_executed_with_proof()]: drive::drive::Drive::verify_state_transition_was_executed_with_proof
+use std::fmt::Debug;
+use std::time::Duration;
+
+use dapi_grpc::platform::v0::get_proofs_request::get_proofs_request_v0::{
+    document_request, identity_request, vote_status_request, ContractRequest, DocumentRequest,
+    IdentityRequest, VoteStatusRequest,
+};
+use dapi_grpc::platform::v0::get_proofs_request::{self, GetProofsRequestV0};
+use dapi_grpc::platform::v0::wait_for_state_transition_result_response::{
+    self, wait_for_state_transition_result_response_v0, WaitForStateTransitionResultResponseV0,
+};
+use dapi_grpc::platform::v0::{
+    GetProofsRequest, StateTransitionBroadcastError, WaitForStateTransitionResultResponse,
+};
+use dapi_grpc::platform::VersionedGrpcResponse;
+use dpp::data_contract::accessors::v0::DataContractV0Getters;
+use dpp::data_contract::document_type::accessors::DocumentTypeV0Getters;
+use dpp::identity::identity_nonce::{
+    IDENTITY_NONCE_VALUE_FILTER, IDENTITY_NONCE_VALUE_FILTER_MAX_BYTES,
+    MISSING_IDENTITY_REVISIONS_MAX_BYTES,
+};
+use dpp::identity::identity_public_key::accessors::v0::IdentityPublicKeyGettersV0;
+use dpp::prelude::{Identifier, IdentityNonce, UserFeeIncrease};
+use dpp::state_transition::data_contract_create_transition::accessors::DataContractCreateTransitionAccessorsV0;
+use dpp::state_transition::data_contract_update_transition::accessors::DataContractUpdateTransitionAccessorsV0;
+use dpp::state_transition::documents_batch_transition::accessors::DocumentsBatchTransitionAccessorsV0;
+use dpp::state_transition::documents_batch_transition::document_base_transition::v0::v0_methods::DocumentBaseTransitionV0Methods;
+use dpp::state_transition::documents_batch_transition::document_create_transition::v0::v0_methods::DocumentCreateTransitionV0Methods;
+use dpp::state_transition::documents_batch_transition::document_transition::{
+    DocumentTransition, DocumentTransitionV0Methods,
+};
+use dpp::state_transition::identity_create_transition::accessors::IdentityCreateTransitionAccessorsV0;
+use dpp::state_transition::identity_credit_transfer_transition::accessors::IdentityCreditTransferTransitionAccessorsV0;
+use dpp::state_transition::identity_credit_withdrawal_transition::accessors::IdentityCreditWithdrawalTransitionAccessorsV0;
+use dpp::state_transition::identity_topup_transition::accessors::IdentityTopUpTransitionAccessorsV0;
+use dpp::state_transition::identity_update_transition::accessors::IdentityUpdateTransitionAccessorsV0;
+use dpp::state_transition::masternode_vote_transition::accessors::MasternodeVoteTransitionAccessorsV0;
+use dpp::state_transition::proof_result::StateTransitionProofResult;
+use dpp::state_transition::public_key_in_creation::accessors::IdentityPublicKeyInCreationV0Getters;
+use dpp::state_transition::StateTransition;
+use dpp::voting::vote_polls::VotePoll;
+use dpp::voting::votes::resource_vote::accessors::v0::ResourceVoteGettersV0;
+use dpp::voting::votes::Vote;
+use drive_proof_verifier::error::ContextProviderError;
+use drive_proof_verifier::types::{IdentityContractNonceFetcher, IdentityNonceFetcher};
+use drive_proof_verifier::{ContextProvider, FromProof};
+use rs_dapi_client::{DapiRequest, RequestSettings};
+use tokio::time::Instant;
+
+use super::broadcast::BroadcastStateTransition;
+use super::broadcast_request::BroadcastRequestForStateTransition;
+use super::TxId;
+use crate::platform::Fetch;
+use crate::{Error, Sdk};
+
+/// Default interval between subsequent checks of the state transition status.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Default time after which a state transition that was not executed is considered expired.
+pub const DEFAULT_EXPIRY: Duration = Duration::from_secs(120);
+/// Default increase of `user_fee_increase` applied when rebroadcasting.
+pub const DEFAULT_FEE_INCREASE_STEP: UserFeeIncrease = 10;
+
+/// Configuration of [TransitionTracker].
+#[derive(Debug, Clone, Copy)]
+pub struct TrackerSettings {
+    /// Interval between subsequent checks of the state transition status
+    pub poll_interval: Duration,
+    /// Time after which a state transition that was not executed is rebroadcast or considered expired
+    pub expiry: Duration,
+    /// Max number of rebroadcasts; only used when the tracker was configured with
+    /// [TransitionTracker::with_rebroadcast()]
+    pub max_rebroadcasts: u32,
+    /// Increase of `user_fee_increase` applied on each rebroadcast
+    pub fee_increase_step: UserFeeIncrease,
+    /// Settings of requests sent to Platform
+    pub request_settings: RequestSettings,
+}
+
+impl Default for TrackerSettings {
+    fn default() -> Self {
+        Self {
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            expiry: DEFAULT_EXPIRY,
+            max_rebroadcasts: 0,
+            fee_increase_step: DEFAULT_FEE_INCREASE_STEP,
+            request_settings: RequestSettings::default(),
+        }
+    }
+}
+
+/// Final status of a tracked state transition.
+#[derive(Debug, Clone)]
+pub enum TransitionStatus {
+    /// State transition was executed; contains verified state after execution
+    Executed(StateTransitionProofResult),
+    /// State transition was rejected by Platform
+    Rejected {
+        /// Error code
+        code: u32,
+        /// Error message
+        message: String,
+        /// Serialized error details
+        data: Vec<u8>,
+    },
+    /// State transition was not executed in the configured time
+    Expired,
+}
+
+impl From<StateTransitionBroadcastError> for TransitionStatus {
+    fn from(error: StateTransitionBroadcastError) -> Self {
+        TransitionStatus::Rejected {
+            code: error.code,
+            message: error.message,
+            data: error.data,
+        }
+    }
+}
+
+/// Callback used to sign the state transition again, after its `user_fee_increase` was changed.
+pub type ResignFn = Box<dyn Fn(&mut StateTransition) -> Result<(), Error> + Send + Sync>;
+
+/// Nonce that must be consumed on Platform for the state transition to be executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NonceRequirement {
+    Identity {
+        identity_id: Identifier,
+        nonce: IdentityNonce,
+    },
+    IdentityContract {
+        identity_id: Identifier,
+        contract_id: Identifier,
+        nonce: IdentityNonce,
+    },
+}
+
+/// Tracks a state transition until it is executed, rejected or expires.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use dash_sdk::platform::transition::tracker::{TransitionStatus, TransitionTracker};
+/// # use dash_sdk::{Sdk, Error};
+/// # use dash_sdk::dpp::state_transition::StateTransition;
+///
+/// # async fn example(sdk: &Sdk, state_transition: StateTransition) -> Result<(), Error> {
+/// let mut tracker = TransitionTracker::new(sdk, state_transition)?;
+/// tracker.broadcast().await?;
+/// match tracker.wait().await? {
+///     TransitionStatus::Executed(result) => println!("executed: {:?}", result),
+///     TransitionStatus::Rejected { code, message, .. } => println!("rejected {}: {}", code, message),
+///     TransitionStatus::Expired => println!("expired"),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct TransitionTracker {
+    sdk: Sdk,
+    state_transition: StateTransition,
+    /// Hashes of all broadcast versions of the state transition, latest last
+    tx_ids: Vec<TxId>,
+    settings: TrackerSettings,
+    resign: Option<ResignFn>,
+}
+
+impl Debug for TransitionTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransitionTracker")
+            .field("state_transition", &self.state_transition)
+            .field("tx_ids", &self.tx_ids)
+            .field("settings", &self.settings)
+            .field("rebroadcast", &self.resign.is_some())
+            .finish()
+    }
+}
+
+impl TransitionTracker {
+    /// Create a tracker for a signed state transition.
+    pub fn new(sdk: &Sdk, state_transition: StateTransition) -> Result<Self, Error> {
+        let tx_id = TxId::try_from(&state_transition)?;
+
+        Ok(Self {
+            sdk: sdk.clone(),
+            state_transition,
+            tx_ids: vec![tx_id],
+            settings: TrackerSettings::default(),
+            resign: None,
+        })
+    }
+
+    /// Set tracker settings.
+    pub fn with_settings(mut self, settings: TrackerSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Enable rebroadcasting of expired state transitions.
+    ///
+    /// When the state transition is not executed within [TrackerSettings::expiry], its `user_fee_increase`
+    /// is increased by [TrackerSettings::fee_increase_step], it is signed again using `resign` and
+    /// broadcast, up to [TrackerSettings::max_rebroadcasts] times.
+    pub fn with_rebroadcast<F>(mut self, resign: F) -> Self
+    where
+        F: Fn(&mut StateTransition) -> Result<(), Error> + Send + Sync + 'static,
+    {
+        self.resign = Some(Box::new(resign));
+        self
+    }
+
+    /// Hash of the latest broadcast version of the state transition.
+    pub fn tx_id(&self) -> TxId {
+        *self.tx_ids.last().expect("at least one tx id")
+    }
+
+    /// Hashes of all broadcast versions of the state transition, latest last.
+    pub fn tx_ids(&self) -> &[TxId] {
+        &self.tx_ids
+    }
+
+    /// Latest version of the tracked state transition.
+    pub fn state_transition(&self) -> &StateTransition {
+        &self.state_transition
+    }
+
+    /// Broadcast the latest version of the state transition.
+    pub async fn broadcast(&self) -> Result<(), Error> {
+        self.state_transition.broadcast(&self.sdk).await
+    }
+
+    /// Wait until the state transition is executed, rejected or expires.
+    ///
+    /// The state transition must be already broadcast. On rejection or expiry, cached nonces
+    /// of the owner identity are invalidated and fetched again.
+    pub async fn wait(&mut self) -> Result<TransitionStatus, Error> {
+        let mut rebroadcasts = 0;
+
+        loop {
+            let deadline = Instant::now() + self.settings.expiry;
+
+            loop {
+                if let Some(status) = self.check().await? {
+                    if !matches!(status, TransitionStatus::Executed(_)) {
+                        self.resync_nonces().await;
+                    }
+                    return Ok(status);
+                }
+
+                if Instant::now() >= deadline {
+                    break;
+                }
+
+                tokio::select! {
+                    biased;
+                    _ = self.sdk.cancelled() => {
+                        return Err(Error::Cancelled(format!(
+                            "tracking of state transition {} cancelled",
+                            self.tx_id()
+                        )))
+                    }
+                    _ = tokio::time::sleep(self.settings.poll_interval) => {}
+                }
+            }
+
+            if self.resign.is_none() || rebroadcasts >= self.settings.max_rebroadcasts {
+                tracing::debug!(tx_id = %self.tx_id(), "state transition expired");
+                self.resync_nonces().await;
+                return Ok(TransitionStatus::Expired);
+            }
+
+            rebroadcasts += 1;
+            self.bump_fee_increase()?;
+            tracing::debug!(tx_id = %self.tx_id(), rebroadcasts, "rebroadcasting state transition");
+            if let Err(e) = self.broadcast().await {
+                self.resync_nonces().await;
+                return Err(e);
+            }
+        }
+    }
+
+    /// Check status of the state transition once.
+    ///
+    /// Returns `None` if the state transition was not executed nor rejected yet.
+    pub async fn check(&self) -> Result<Option<TransitionStatus>, Error> {
+        if let Some(result) = self.check_proofs().await? {
+            return Ok(Some(TransitionStatus::Executed(result)));
+        }
+
+        self.check_result().await
+    }
+
+    /// Check if Platform state proves that the state transition was executed.
+    async fn check_proofs(&self) -> Result<Option<StateTransitionProofResult>, Error> {
+        // Balance changes do not identify the transition that made them; execution of these
+        // transitions is only confirmed by their result
+        if matches!(
+            self.state_transition,
+            StateTransition::IdentityTopUp(_)
+                | StateTransition::IdentityCreditTransfer(_)
+                | StateTransition::IdentityCreditWithdrawal(_)
+        ) {
+            return Ok(None);
+        }
+
+        if let Some(requirement) = nonce_requirement(&self.state_transition) {
+            if !self.nonce_consumed(requirement).await? {
+                return Ok(None);
+            }
+        }
+
+        let request = self.proofs_request()?;
+        let response = request
+            .execute(&self.sdk, self.settings.request_settings)
+            .await?;
+
+        let metadata = response.metadata()?.clone();
+        let proof = response.proof_owned()?;
+        let response = WaitForStateTransitionResultResponse {
+            version: Some(wait_for_state_transition_result_response::Version::V0(
+                WaitForStateTransitionResultResponseV0 {
+                    result: Some(wait_for_state_transition_result_response_v0::Result::Proof(
+                        proof,
+                    )),
+                    metadata: Some(metadata),
+                },
+            )),
+        };
+
+        self.verify(response)
+    }
+
+    /// Check result of the state transition reported by Platform.
+    async fn check_result(&self) -> Result<Option<TransitionStatus>, Error> {
+        let request = self
+            .state_transition
+            .wait_for_state_transition_result_request()?;
+        let settings = self.settings.request_settings.override_by(RequestSettings {
+            timeout: Some(self.settings.poll_interval),
+            retries: Some(0),
+            ..Default::default()
+        });
+
+        let response = match request.execute(&self.sdk, settings).await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::trace!(tx_id = %self.tx_id(), error = ?e, "state transition result not available");
+                return Ok(None);
+            }
+        };
+
+        let wait_for_state_transition_result_response::Version::V0(ref v0) = response
+            .version
+            .as_ref()
+            .ok_or(drive_proof_verifier::Error::EmptyVersion)?;
+        if let Some(wait_for_state_transition_result_response_v0::Result::Error(error)) = &v0.result
+        {
+            return Ok(Some(error.clone().into()));
+        }
+
+        Ok(self.verify(response)?.map(TransitionStatus::Executed))
+    }
+
+    /// Verify that `response` proves execution of the state transition.
+    ///
+    /// Returns `None` if the proof is valid, but does not contain state expected after execution.
+    fn verify(
+        &self,
+        response: WaitForStateTransitionResultResponse,
+    ) -> Result<Option<StateTransitionProofResult>, Error> {
+        let result = self.verify_proof(response)?;
+
+        Ok(result.filter(|result| proves_execution(&self.state_transition, result)))
+    }
+
+    fn verify_proof(
+        &self,
+        response: WaitForStateTransitionResultResponse,
+    ) -> Result<Option<StateTransitionProofResult>, Error> {
+        let provider = self
+            .sdk
+            .context_provider()
+            .ok_or(ContextProviderError::Config(
+                "Context provider not initialized".to_string(),
+            ))?;
+
+        match StateTransitionProofResult::maybe_from_proof_with_metadata(
+            self.state_transition
+                .broadcast_request_for_state_transition()?,
+            response,
+            self.sdk.network,
+            self.sdk.version(),
+            &provider,
+        ) {
+            Ok((result, _, _)) => Ok(result),
+            // Grovedb proof does not match state expected after execution
+            Err(drive_proof_verifier::Error::DriveError { error }) => {
+                tracing::trace!(tx_id = %self.tx_id(), error, "state transition not executed yet");
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Check if Platform already consumed the nonce used by the state transition.
+    ///
+    /// Consumed nonce does not mean that the tracked transition was executed; it could be used by
+    /// another transition.
+    async fn nonce_consumed(&self, requirement: NonceRequirement) -> Result<bool, Error> {
+        let settings = self.settings.request_settings;
+        let (platform_nonce, nonce) = match requirement {
+            NonceRequirement::Identity { identity_id, nonce } => (
+                IdentityNonceFetcher::fetch_with_settings(&self.sdk, identity_id, settings)
+                    .await?
+                    .map(|fetcher| fetcher.0),
+                nonce,
+            ),
+            NonceRequirement::IdentityContract {
+                identity_id,
+                contract_id,
+                nonce,
+            } => (
+                IdentityContractNonceFetcher::fetch_with_settings(
+                    &self.sdk,
+                    (identity_id, contract_id),
+                    settings,
+                )
+                .await?
+                .map(|fetcher| fetcher.0),
+                nonce,
+            ),
+        };
+
+        Ok(platform_nonce.is_some_and(|platform_nonce| is_nonce_consumed(platform_nonce, nonce)))
+    }
+
+    /// Build a request for proofs of state changed by the state transition.
+    pub fn proofs_request(&self) -> Result<GetProofsRequest, Error> {
+        let mut request = GetProofsRequestV0 {
+            identities: vec![],
+            contracts: vec![],
+            documents: vec![],
+            votes: vec![],
+        };
+
+        let identity =
+            |identity_id: Identifier, request_type: identity_request::Type| IdentityRequest {
+                identity_id: identity_id.to_vec(),
+                request_type: request_type as i32,
+            };
+
+        match &self.state_transition {
+            StateTransition::DataContractCreate(st) => request.contracts.push(ContractRequest {
+                contract_id: st.data_contract().id().to_vec(),
+            }),
+            StateTransition::DataContractUpdate(st) => request.contracts.push(ContractRequest {
+                contract_id: st.data_contract().id().to_vec(),
+            }),
+            StateTransition::DocumentsBatch(st) => {
+                for transition in st.transitions() {
+                    let contract_id = transition.data_contract_id();
+                    let data_contract = self
+                        .sdk
+                        .context_provider()
+                        .ok_or(ContextProviderError::Config(
+                            "Context provider not initialized".to_string(),
+                        ))?
+                        .get_data_contract(&contract_id)?
+                        .ok_or_else(|| {
+                            Error::MissingDependency(
+                                "DataContract".to_string(),
+                                contract_id.to_string(
+                                    dpp::platform_value::string_encoding::Encoding::Base58,
+                                ),
+                            )
+                        })?;
+                    let document_type = data_contract
+                        .document_type_for_name(transition.document_type_name())
+                        .map_err(dpp::ProtocolError::from)?;
+
+                    let contested = matches!(
+                        transition,
+                        DocumentTransition::Create(create) if create.prefunded_voting_balance().is_some()
+                    );
+                    let contested_status = if contested {
+                        document_request::DocumentContestedStatus::Contested
+                    } else {
+                        document_request::DocumentContestedStatus::NotContested
+                    };
+
+                    request.documents.push(DocumentRequest {
+                        contract_id: contract_id.to_vec(),
+                        document_type: transition.document_type_name().clone(),
+                        document_type_keeps_history: document_type.documents_keep_history(),
+                        document_id: transition.base().id().to_vec(),
+                        document_contested_status: contested_status as i32,
+                    });
+                }
+            }
+            StateTransition::IdentityCreate(st) => request.identities.push(identity(
+                st.identity_id(),
+                identity_request::Type::FullIdentity,
+            )),
+            StateTransition::IdentityTopUp(st) => {
+                request
+                    .identities
+                    .push(identity(*st.identity_id(), identity_request::Type::Balance));
+                request.identities.push(identity(
+                    *st.identity_id(),
+                    identity_request::Type::Revision,
+                ));
+            }
+            StateTransition::IdentityCreditWithdrawal(st) => request
+                .identities
+                .push(identity(st.identity_id(), identity_request::Type::Balance)),
+            StateTransition::IdentityUpdate(st) => request
+                .identities
+                .push(identity(st.identity_id(), identity_request::Type::Keys)),
+            StateTransition::IdentityCreditTransfer(st) => {
+                request
+                    .identities
+                    .push(identity(st.identity_id(), identity_request::Type::Balance));
+                request
+                    .identities
+                    .push(identity(st.recipient_id(), identity_request::Type::Balance));
+            }
+            StateTransition::MasternodeVote(st) => match st.vote() {
+                Vote::ResourceVote(resource_vote) => match resource_vote.vote_poll() {
+                    VotePoll::ContestedDocumentResourceVotePoll(vote_poll) => {
+                        let config = dpp::bincode::config::standard()
+                            .with_big_endian()
+                            .with_no_limit();
+                        let index_values = vote_poll
+                            .index_values
+                            .iter()
+                            .map(|value| dpp::bincode::encode_to_vec(value, config))
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err(|e| {
+                                Error::Protocol(dpp::ProtocolError::EncodingError(e.to_string()))
+                            })?;
+
+                        request.votes.push(VoteStatusRequest {
+                            request_type: Some(
+                                vote_status_request::RequestType::ContestedResourceVoteStatusRequest(
+                                    vote_status_request::ContestedResourceVoteStatusRequest {
+                                        contract_id: vote_poll.contract_id.to_vec(),
+                                        document_type_name: vote_poll.document_type_name.clone(),
+                                        index_name: vote_poll.index_name.clone(),
+                                        index_values,
+                                        voter_identifier: st.pro_tx_hash().to_vec(),
+                                    },
+                                ),
+                            ),
+                        });
+                    }
+                },
+            },
+        }
+
+        Ok(GetProofsRequest {
+            version: Some(get_proofs_request::Version::V0(request)),
+        })
+    }
+
+    /// Increase `user_fee_increase` of the state transition and sign it again.
+    fn bump_fee_increase(&mut self) -> Result<(), Error> {
+        let resign = self
+            .resign
+            .as_ref()
+            .ok_or_else(|| Error::Config("rebroadcast is not configured".to_string()))?;
+
+        let mut state_transition = self.state_transition.clone();
+        state_transition.set_user_fee_increase(
+            state_transition
+                .user_fee_increase()
+                .saturating_add(self.settings.fee_increase_step),
+        );
+        resign(&mut state_transition)?;
+
+        self.tx_ids.push(TxId::try_from(&state_transition)?);
+        self.state_transition = state_transition;
+
+        Ok(())
+    }
+
+    /// Invalidate cached nonces of the owner identity and fetch the one used by the state transition again.
+    async fn resync_nonces(&self) {
+        let owner_id = self.state_transition.owner_id();
+        self.sdk.invalidate_identity_nonces(owner_id).await;
+
+        let result = match nonce_requirement(&self.state_transition) {
+            Some(NonceRequirement::Identity { identity_id, .. }) => self
+                .sdk
+                .get_identity_nonce(identity_id, false, None)
+                .await
+                .map(|_| ()),
+            Some(NonceRequirement::IdentityContract {
+                identity_id,
+                contract_id,
+                ..
+            }) => self
+                .sdk
+                .get_identity_contract_nonce(identity_id, contract_id, false, None)
+                .await
+                .map(|_| ()),
+            None => Ok(()),
+        };
+
+        if let Err(e) = result {
+            tracing::warn!(identity_id = %owner_id, error = ?e, "unable to resync identity nonces");
+        }
+    }
+}
+
+/// Check if `nonce` is consumed according to the identity nonce stored on Platform.
+///
+/// Lower bits of `platform_nonce` contain the latest consumed nonce; upper bits mark nonces below it
+/// that were not consumed yet. Nonces too far below the latest one can't be consumed anymore, so they
+/// are reported as consumed.
+fn is_nonce_consumed(platform_nonce: IdentityNonce, nonce: IdentityNonce) -> bool {
+    let latest = platform_nonce & IDENTITY_NONCE_VALUE_FILTER;
+    if nonce > latest {
+        return false;
+    }
+
+    let position_from_top = latest - nonce;
+    if position_from_top == 0 || position_from_top > MISSING_IDENTITY_REVISIONS_MAX_BYTES {
+        return true;
+    }
+
+    let missing_bit = 1 << (position_from_top - 1 + IDENTITY_NONCE_VALUE_FILTER_MAX_BYTES);
+    platform_nonce & missing_bit == 0
+}
+
+/// Check that the verified state contains changes made by the state transition.
+///
+/// Proofs of identity keys are not verified against the transition by Drive, so the keys are compared
+/// here.
+fn proves_execution(
+    state_transition: &StateTransition,
+    result: &StateTransitionProofResult,
+) -> bool {
+    match (state_transition, result) {
+        (
+            StateTransition::IdentityUpdate(st),
+            StateTransitionProofResult::VerifiedPartialIdentity(identity),
+        ) => {
+            let added = st.public_keys_to_add().iter().all(|key| {
+                identity
+                    .loaded_public_keys
+                    .get(&key.id())
+                    .is_some_and(|loaded| loaded.data() == key.data())
+            });
+            let disabled = st.public_key_ids_to_disable().iter().all(|key_id| {
+                identity
+                    .loaded_public_keys
+                    .get(key_id)
+                    .is_some_and(|loaded| loaded.is_disabled())
+            });
+
+            added && disabled
+        }
+        (StateTransition::IdentityUpdate(_), _) => false,
+        _ => true,
+    }
+}
+
+/// Nonce used by the state transition, if any.
+fn nonce_requirement(state_transition: &StateTransition) -> Option<NonceRequirement> {
+    let identity_id = state_transition.owner_id();
+
+    match state_transition {
+        StateTransition::DataContractCreate(st) => Some(NonceRequirement::Identity {
+            identity_id,
+            nonce: st.identity_nonce(),
+        }),
+        StateTransition::DataContractUpdate(st) => Some(NonceRequirement::IdentityContract {
+            identity_id,
+            contract_id: st.data_contract().id(),
+            nonce: st.identity_contract_nonce(),
+        }),
+        StateTransition::DocumentsBatch(st) => st
+            .transitions()
+            .iter()
+            .map(|transition| NonceRequirement::IdentityContract {
+                identity_id,
+                contract_id: transition.data_contract_id(),
+                nonce: transition.base().identity_contract_nonce(),
+            })
+            .max_by_key(|requirement| match requirement {
+                NonceRequirement::IdentityContract { nonce, .. } => *nonce,
+                NonceRequirement::Identity { nonce, .. } => *nonce,
+            }),
+        StateTransition::IdentityCreditWithdrawal(st) => Some(NonceRequirement::Identity {
+            identity_id,
+            nonce: st.nonce(),
+        }),
+        StateTransition::IdentityUpdate(st) => Some(NonceRequirement::Identity {
+            identity_id,
+            nonce: st.nonce(),
+        }),
+        StateTransition::IdentityCreditTransfer(st) => Some(NonceRequirement::Identity {
+            identity_id,
+            nonce: st.nonce(),
+        }),
+        StateTransition::MasternodeVote(st) => Some(NonceRequirement::Identity {
+            identity_id,
+            nonce: st.nonce(),
+        }),
+        StateTransition::IdentityCreate(_) | StateTransition::IdentityTopUp(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nonce_at_tip_is_consumed() {
+        assert!(is_nonce_consumed(5, 5));
+        assert!(is_nonce_consumed(5, 4));
+        assert!(!is_nonce_consumed(5, 6));
+    }
+
+    #[test]
+    fn missing_nonce_is_not_consumed() {
+        // nonce 5 consumed, nonces 3 and 4 missing
+        let platform_nonce = 5 | 1 << 40 | 1 << 41;
+
+        assert!(is_nonce_consumed(platform_nonce, 5));
+        assert!(!is_nonce_consumed(platform_nonce, 4));
+        assert!(!is_nonce_consumed(platform_nonce, 3));
+        assert!(is_nonce_consumed(platform_nonce, 2));
+    }
+
+    #[test]
+    fn nonce_too_far_in_past_is_consumed() {
+        let platform_nonce = 30 | 1 << 40;
+
+        assert!(is_nonce_consumed(
+            platform_nonce,
+            30 - MISSING_IDENTITY_REVISIONS_MAX_BYTES - 1
+        ));
+    }
+}
@@ -1,12 +1,33 @@
-use crate::Sdk;
+use crate::{Error, Sdk};
+use dpp::state_transition::StateTransition;
 
 /// State transition identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct TxId([u8; 32]);
 impl TxId {
     /// Checks if the state transition is confirmed
     pub fn is_confirmed(&self, _sdk: &Sdk) -> bool {
         todo!("Not implemented")
     }
+
+    /// Returns the state transition hash
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for TxId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl TryFrom<&StateTransition> for TxId {
+    type Error = Error;
+
+    fn try_from(state_transition: &StateTransition) -> Result<Self, Self::Error> {
+        Ok(TxId(state_transition.transaction_id()?))
+    }
 }
 
 impl From<TxId> for [u8; 32] {
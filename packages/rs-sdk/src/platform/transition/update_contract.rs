@@ -0,0 +1,183 @@
+use crate::platform::transition::broadcast::check_broadcast_error;
+use crate::platform::transition::broadcast_request::BroadcastRequestForStateTransition;
+use std::collections::BTreeMap;
+
+use crate::{Error, Sdk};
+
+use crate::platform::block_info_from_metadata::block_info_from_metadata;
+use crate::platform::transition::put_settings::PutSettings;
+use crate::platform::Fetch;
+use dapi_grpc::platform::VersionedGrpcResponse;
+use dpp::data_contract::accessors::v0::{DataContractV0Getters, DataContractV0Setters};
+use dpp::data_contract::validate_update::DataContractUpdateValidationMethodsV0;
+use dpp::data_contract::DataContract;
+use dpp::identity::identity_public_key::accessors::v0::IdentityPublicKeyGettersV0;
+use dpp::identity::signer::Signer;
+use dpp::identity::{IdentityPublicKey, PartialIdentity};
+use dpp::state_transition::data_contract_update_transition::methods::DataContractUpdateTransitionMethodsV0;
+use dpp::state_transition::data_contract_update_transition::DataContractUpdateTransition;
+use dpp::state_transition::proof_result::StateTransitionProofResult;
+use dpp::state_transition::StateTransition;
+use drive::drive::Drive;
+use drive_proof_verifier::error::ContextProviderError;
+use drive_proof_verifier::DataContractProvider;
+use rs_dapi_client::{DapiRequest, RequestSettings};
+
+#[async_trait::async_trait]
+/// A trait for updating an existing contract on platform
+pub trait UpdateContract<S: Signer> {
+    /// Updates a contract on platform
+    ///
+    /// The currently deployed version of the contract is fetched from platform, the version of
+    /// `self` is set to the next one and the update is validated locally with the same rules
+    /// Platform applies. [Error::InvalidStateTransition] is returned, without broadcasting
+    /// anything, when the update would be rejected.
+    ///
+    /// setting settings to `None` sets default connection behavior
+    async fn update_on_platform(
+        &self,
+        sdk: &Sdk,
+        identity_public_key: IdentityPublicKey,
+        signer: &S,
+        settings: Option<PutSettings>,
+    ) -> Result<StateTransition, Error>;
+
+    /// Waits for the response of a state transition after it has been broadcast
+    async fn wait_for_response(
+        &self,
+        sdk: &Sdk,
+        state_transition: StateTransition,
+    ) -> Result<DataContract, Error>;
+
+    /// Updates a contract on platform and waits for the confirmation proof
+    async fn update_on_platform_and_wait_for_response(
+        &self,
+        sdk: &Sdk,
+        identity_public_key: IdentityPublicKey,
+        signer: &S,
+    ) -> Result<DataContract, Error>;
+}
+
+#[async_trait::async_trait]
+impl<S: Signer> UpdateContract<S> for DataContract {
+    async fn update_on_platform(
+        &self,
+        sdk: &Sdk,
+        identity_public_key: IdentityPublicKey,
+        signer: &S,
+        settings: Option<PutSettings>,
+    ) -> Result<StateTransition, Error> {
+        let request_settings = settings.unwrap_or_default().request_settings;
+
+        let existing_data_contract =
+            DataContract::fetch_with_settings(sdk, self.id(), request_settings)
+                .await?
+                .ok_or(Error::MissingDependency(
+                    "DataContract".to_string(),
+                    format!("data contract {} not found", self.id()),
+                ))?;
+
+        let mut data_contract = self.clone();
+        data_contract.set_version(existing_data_contract.version() + 1);
+
+        let validation_result =
+            existing_data_contract.validate_update(&data_contract, sdk.version())?;
+
+        if !validation_result.is_valid() {
+            return Err(Error::InvalidStateTransition(validation_result.errors));
+        }
+
+        let new_identity_contract_nonce = sdk
+            .get_identity_contract_nonce(self.owner_id(), self.id(), true, settings)
+            .await?;
+
+        let key_id = identity_public_key.id();
+
+        let partial_identity = PartialIdentity {
+            id: self.owner_id(),
+            loaded_public_keys: BTreeMap::from([(key_id, identity_public_key)]),
+            balance: None,
+            revision: None,
+            not_found_public_keys: Default::default(),
+        };
+        let transition = DataContractUpdateTransition::new_from_data_contract(
+            data_contract,
+            &partial_identity,
+            key_id,
+            new_identity_contract_nonce,
+            settings
+                .unwrap_or_default()
+                .user_fee_increase
+                .unwrap_or_default(),
+            signer,
+            sdk.version(),
+            None,
+        )?;
+
+        let request = transition.broadcast_request_for_state_transition()?;
+
+        request
+            .clone()
+            .execute(sdk, request_settings)
+            .await
+            .map_err(Error::from_grpc_error)?;
+
+        // response is empty for a broadcast, result comes from the stream wait for state transition result
+
+        Ok(transition)
+    }
+
+    async fn wait_for_response(
+        &self,
+        sdk: &Sdk,
+        state_transition: StateTransition,
+    ) -> Result<DataContract, Error> {
+        let request = state_transition.wait_for_state_transition_result_request()?;
+
+        let response = request
+            .execute(sdk, RequestSettings::default())
+            .await
+            .map_err(Error::from_grpc_error)?;
+        check_broadcast_error(&response)?;
+
+        let block_info = block_info_from_metadata(response.metadata()?)?;
+
+        let proof = response.proof_owned()?;
+        let context_provider =
+            sdk.context_provider()
+                .ok_or(Error::from(ContextProviderError::Config(
+                    "Context provider not initialized".to_string(),
+                )))?;
+
+        let (_, result) = Drive::verify_state_transition_was_executed_with_proof(
+            &state_transition,
+            &block_info,
+            proof.grovedb_proof.as_slice(),
+            &context_provider.as_contract_lookup_fn(),
+            sdk.version(),
+        )?;
+
+        match result {
+            StateTransitionProofResult::VerifiedDataContract(data_contract) => Ok(data_contract),
+            _ => Err(Error::DapiClientError(
+                "proved something that was not a data contract".to_string(),
+            )),
+        }
+    }
+
+    async fn update_on_platform_and_wait_for_response(
+        &self,
+        sdk: &Sdk,
+        identity_public_key: IdentityPublicKey,
+        signer: &S,
+    ) -> Result<DataContract, Error> {
+        let state_transition = self
+            .update_on_platform(sdk, identity_public_key, signer, None)
+            .await?;
+
+        let data_contract =
+            <Self as UpdateContract<S>>::wait_for_response(self, sdk, state_transition).await?;
+
+        Ok(data_contract)
+    }
+}
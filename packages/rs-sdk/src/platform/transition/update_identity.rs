@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+
+use dapi_grpc::platform::VersionedGrpcResponse;
+use dpp::dashcore::{signer, PrivateKey};
+use dpp::identity::accessors::IdentityGettersV0;
+use dpp::identity::identity_public_key::accessors::v0::IdentityPublicKeyGettersV0;
+use dpp::identity::signer::Signer;
+use dpp::identity::{Identity, IdentityPublicKey, KeyID, KeyType, PartialIdentity, SecurityLevel};
+use dpp::platform_value::BinaryData;
+use dpp::state_transition::identity_update_transition::methods::IdentityUpdateTransitionMethodsV0;
+use dpp::state_transition::identity_update_transition::IdentityUpdateTransition;
+use dpp::state_transition::proof_result::StateTransitionProofResult;
+use dpp::ProtocolError;
+use drive::drive::Drive;
+use drive_proof_verifier::error::ContextProviderError;
+use drive_proof_verifier::DataContractProvider;
+use rs_dapi_client::DapiRequest;
+
+use crate::platform::block_info_from_metadata::block_info_from_metadata;
+use crate::platform::transition::broadcast::check_broadcast_error;
+use crate::platform::transition::broadcast_request::BroadcastRequestForStateTransition;
+use crate::platform::transition::put_settings::PutSettings;
+use crate::{Error, Sdk};
+
+#[async_trait::async_trait]
+/// A trait for updating the public keys of an identity on platform
+pub trait UpdateIdentity<S: Signer> {
+    /// Adds and disables public keys of an identity. Returns the proved identity keys
+    /// after the update.
+    ///
+    /// Every key in `add_public_keys` is accompanied by its private key, which is used to
+    /// sign the proof of possession required for unique key types.
+    /// The transition itself is signed with `master_public_key_id`; when it is `None`,
+    /// the first enabled MASTER key of the identity is used. An explicitly chosen key that is
+    /// not an enabled MASTER key of the identity is rejected before anything is sent.
+    async fn update_identity(
+        &self,
+        sdk: &Sdk,
+        add_public_keys: Vec<(IdentityPublicKey, PrivateKey)>,
+        disable_public_keys: Vec<KeyID>,
+        master_public_key_id: Option<KeyID>,
+        signer: &S,
+        settings: Option<PutSettings>,
+    ) -> Result<PartialIdentity, Error>;
+}
+
+#[async_trait::async_trait]
+impl<S: Signer> UpdateIdentity<S> for Identity {
+    async fn update_identity(
+        &self,
+        sdk: &Sdk,
+        add_public_keys: Vec<(IdentityPublicKey, PrivateKey)>,
+        disable_public_keys: Vec<KeyID>,
+        master_public_key_id: Option<KeyID>,
+        signer: &S,
+        settings: Option<PutSettings>,
+    ) -> Result<PartialIdentity, Error> {
+        let master_public_key_id = match master_public_key_id {
+            Some(key_id) => {
+                let key = self
+                    .public_keys()
+                    .get(&key_id)
+                    .ok_or(Error::Generic(format!(
+                        "signing key {} does not belong to identity {}",
+                        key_id,
+                        self.id()
+                    )))?;
+                // Platform only accepts identity updates signed with a master key
+                if key.security_level() != SecurityLevel::MASTER {
+                    return Err(Error::Generic(format!(
+                        "identity update must be signed with a master key, key {} is {:?}",
+                        key_id,
+                        key.security_level()
+                    )));
+                }
+                if key.is_disabled() {
+                    return Err(Error::Generic(format!(
+                        "cannot sign identity update with disabled key {}",
+                        key_id
+                    )));
+                }
+                key_id
+            }
+            None => self
+                .public_keys()
+                .values()
+                .find(|key| key.security_level() == SecurityLevel::MASTER && !key.is_disabled())
+                .map(|key| key.id())
+                .ok_or(Error::Generic(
+                    "identity has no enabled master key to sign the update".to_string(),
+                ))?,
+        };
+
+        if let Some(key_id) = disable_public_keys
+            .iter()
+            .find(|key_id| !self.public_keys().contains_key(key_id))
+        {
+            return Err(Error::Generic(format!(
+                "cannot disable key {} that does not belong to identity {}",
+                key_id,
+                self.id()
+            )));
+        }
+
+        let new_identity_nonce = sdk.get_identity_nonce(self.id(), true, settings).await?;
+        let settings = settings.unwrap_or_default();
+
+        // the transition carries the revision the identity will have once updated
+        let mut identity = self.clone();
+        identity.bump_revision();
+
+        let new_keys_signer = NewKeysSigner {
+            private_keys: add_public_keys
+                .iter()
+                .map(|(public_key, private_key)| (public_key.clone(), *private_key))
+                .collect(),
+            signer,
+        };
+
+        let state_transition = IdentityUpdateTransition::try_from_identity_with_signer(
+            &identity,
+            &master_public_key_id,
+            add_public_keys
+                .into_iter()
+                .map(|(public_key, _)| public_key)
+                .collect(),
+            disable_public_keys,
+            new_identity_nonce,
+            settings.user_fee_increase.unwrap_or_default(),
+            &new_keys_signer,
+            sdk.version(),
+            None,
+        )?;
+
+        let request = state_transition.broadcast_request_for_state_transition()?;
+
+        request
+            .clone()
+            .execute(sdk, settings.request_settings)
+            .await
+            .map_err(Error::from_grpc_error)?;
+
+        let request = state_transition.wait_for_state_transition_result_request()?;
+
+        let response = request
+            .execute(sdk, settings.request_settings)
+            .await
+            .map_err(Error::from_grpc_error)?;
+        check_broadcast_error(&response)?;
+
+        let block_info = block_info_from_metadata(response.metadata()?)?;
+
+        let proof = response.proof_owned()?;
+        let context_provider =
+            sdk.context_provider()
+                .ok_or(Error::from(ContextProviderError::Config(
+                    "Context provider not initialized".to_string(),
+                )))?;
+
+        let (_, result) = Drive::verify_state_transition_was_executed_with_proof(
+            &state_transition,
+            &block_info,
+            proof.grovedb_proof.as_slice(),
+            &context_provider.as_contract_lookup_fn(),
+            sdk.version(),
+        )?;
+
+        match result {
+            StateTransitionProofResult::VerifiedPartialIdentity(identity) => Ok(identity),
+            _ => Err(Error::DapiClientError("proved a non identity".to_string())),
+        }
+    }
+}
+
+/// Signer used to create identity update transitions.
+///
+/// Keys that are being added are signed with the private keys provided by the caller,
+/// everything else is delegated to the wrapped signer.
+struct NewKeysSigner<'a, S: Signer> {
+    private_keys: BTreeMap<IdentityPublicKey, PrivateKey>,
+    signer: &'a S,
+}
+
+impl<'a, S: Signer> Debug for NewKeysSigner<'a, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NewKeysSigner")
+            .field("new_keys", &self.private_keys.keys().collect::<Vec<_>>())
+            .field("signer", &self.signer)
+            .finish()
+    }
+}
+
+impl<'a, S: Signer> Signer for NewKeysSigner<'a, S> {
+    fn sign(
+        &self,
+        identity_public_key: &IdentityPublicKey,
+        data: &[u8],
+    ) -> Result<BinaryData, ProtocolError> {
+        match self.private_keys.get(identity_public_key) {
+            Some(private_key) if identity_public_key.key_type() == KeyType::ECDSA_SECP256K1 => {
+                let signature = signer::sign(data, &private_key.inner.secret_bytes())?;
+                Ok(signature.to_vec().into())
+            }
+            _ => self.signer.sign(identity_public_key, data),
+        }
+    }
+}
@@ -1,10 +1,13 @@
 use crate::platform::block_info_from_metadata::block_info_from_metadata;
 use crate::platform::query::VoteQuery;
+use crate::platform::transition::broadcast::check_broadcast_error;
 use crate::platform::transition::broadcast_request::BroadcastRequestForStateTransition;
 use crate::platform::transition::put_settings::PutSettings;
 use crate::platform::Fetch;
 use crate::{Error, Sdk};
 use dapi_grpc::platform::VersionedGrpcResponse;
+use dpp::consensus::state::state_error::StateError;
+use dpp::consensus::ConsensusError;
 use dpp::identifier::MasternodeIdentifiers;
 use dpp::identity::hash::IdentityPublicKeyHashMethodsV0;
 use dpp::identity::signer::Signer;
@@ -13,6 +16,7 @@ use dpp::prelude::Identifier;
 use dpp::state_transition::masternode_vote_transition::methods::MasternodeVoteTransitionMethodsV0;
 use dpp::state_transition::masternode_vote_transition::MasternodeVoteTransition;
 use dpp::state_transition::proof_result::StateTransitionProofResult;
+use dpp::state_transition::StateTransition;
 use dpp::voting::votes::resource_vote::accessors::v0::ResourceVoteGettersV0;
 use dpp::voting::votes::Vote;
 use drive::drive::Drive;
@@ -71,7 +75,10 @@ impl<S: Signer> PutVote<S> for Vote {
         )?;
         let request = masternode_vote_transition.broadcast_request_for_state_transition()?;
 
-        request.execute(sdk, settings.request_settings).await?;
+        request
+            .execute(sdk, settings.request_settings)
+            .await
+            .map_err(Error::from_grpc_error)?;
 
         Ok(())
     }
@@ -104,57 +111,91 @@ impl<S: Signer> PutVote<S> for Vote {
             sdk.version(),
             None,
         )?;
-        let request = masternode_vote_transition.broadcast_request_for_state_transition()?;
-
-        let response_result = request.execute(sdk, settings.request_settings).await;
-
-        match response_result {
-            Ok(_) => {}
-            //todo make this more reliable
-            Err(e) => {
-                return if e.to_string().contains("already exists") {
-                    let vote =
-                        Vote::fetch(sdk, VoteQuery::new(voter_pro_tx_hash, vote_poll_id)).await?;
-                    vote.ok_or(Error::DapiClientError(
-                        "vote was proved to not exist but was said to exist".to_string(),
-                    ))
-                } else {
-                    Err(e.into())
-                }
-            }
-        }
-
-        let request = masternode_vote_transition.wait_for_state_transition_result_request()?;
-        let response = request.execute(sdk, settings.request_settings).await?;
-
-        let block_info = block_info_from_metadata(response.metadata()?)?;
-        let proof = response.proof_owned()?;
-        let context_provider =
-            sdk.context_provider()
-                .ok_or(Error::from(ContextProviderError::Config(
-                    "Context provider not initialized".to_string(),
-                )))?;
 
-        let (_, result) = Drive::verify_state_transition_was_executed_with_proof(
+        broadcast_vote_and_wait(
+            sdk,
             &masternode_vote_transition,
-            &block_info,
-            proof.grovedb_proof.as_slice(),
-            &context_provider.as_contract_lookup_fn(),
-            sdk.version(),
-        )?;
-
-        //todo verify
+            voter_pro_tx_hash,
+            vote_poll_id,
+            settings,
+        )
+        .await
+    }
+}
 
-        match result {
-            StateTransitionProofResult::VerifiedMasternodeVote(vote) => Ok(vote),
-            _ => Err(Error::DapiClientError(
-                "proved something that was not a vote".to_string(),
-            )),
+/// Broadcast a masternode vote transition and wait for the proof of its execution.
+///
+/// If Platform reports that the vote already exists, the current vote of the masternode on the
+/// vote poll is fetched and returned instead.
+pub(crate) async fn broadcast_vote_and_wait(
+    sdk: &Sdk,
+    masternode_vote_transition: &StateTransition,
+    voter_pro_tx_hash: Identifier,
+    vote_poll_id: Identifier,
+    settings: PutSettings,
+) -> Result<Vote, Error> {
+    let request = masternode_vote_transition.broadcast_request_for_state_transition()?;
+
+    let response_result = request
+        .execute(sdk, settings.request_settings)
+        .await
+        .map_err(Error::from_grpc_error);
+
+    match response_result {
+        Ok(_) => {}
+        Err(e) if is_vote_already_present(&e) => {
+            let vote = Vote::fetch(sdk, VoteQuery::new(voter_pro_tx_hash, vote_poll_id)).await?;
+            return vote.ok_or(Error::DapiClientError(
+                "vote was proved to not exist but was said to exist".to_string(),
+            ));
         }
+        Err(e) => return Err(e),
+    }
+
+    let request = masternode_vote_transition.wait_for_state_transition_result_request()?;
+    let response = request
+        .execute(sdk, settings.request_settings)
+        .await
+        .map_err(Error::from_grpc_error)?;
+    check_broadcast_error(&response)?;
+
+    let block_info = block_info_from_metadata(response.metadata()?)?;
+    let proof = response.proof_owned()?;
+    let context_provider =
+        sdk.context_provider()
+            .ok_or(Error::from(ContextProviderError::Config(
+                "Context provider not initialized".to_string(),
+            )))?;
+
+    let (_, result) = Drive::verify_state_transition_was_executed_with_proof(
+        masternode_vote_transition,
+        &block_info,
+        proof.grovedb_proof.as_slice(),
+        &context_provider.as_contract_lookup_fn(),
+        sdk.version(),
+    )?;
+
+    match result {
+        StateTransitionProofResult::VerifiedMasternodeVote(vote) => Ok(vote),
+        _ => Err(Error::InvalidProvedResponse(
+            "proved something that was not a vote".to_string(),
+        )),
     }
 }
 
-fn get_voting_identity_id(
+/// Check if the vote was rejected because Platform already has it.
+fn is_vote_already_present(error: &Error) -> bool {
+    //todo make this more reliable
+    matches!(
+        error,
+        Error::StateTransitionConsensusError {
+            error: ConsensusError::StateError(StateError::MasternodeVoteAlreadyPresentError(_)),
+            ..
+        }
+    ) || error.to_string().contains("already exists")
+}
+
+pub(crate) fn get_voting_identity_id(
     voter_pro_tx_hash: Identifier,
     voting_public_key: &IdentityPublicKey,
 ) -> Result<Identifier, Error> {
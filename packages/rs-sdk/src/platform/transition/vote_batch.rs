@@ -0,0 +1,281 @@
+//! Cast masternode votes on many vote polls at once.
+//!
+//! [PutVote](super::vote::PutVote) submits a single [MasternodeVoteTransition]. [VoteBatch] takes a
+//! list of vote polls with choices, signs one transition per vote with the masternode VOTING key,
+//! and submits them concurrently.
+//!
+//! Every transition uses its own nonce of the voting identity. Nonces are allocated up front, in the
+//! order of the votes, and Platform accepts nonces ahead of the last executed one only up to
+//! [MAX_MISSING_IDENTITY_REVISIONS], so at most that many votes are in flight at a time.
+//!
+//! [VoteBatch::dry_run()] compares the votes with the current on-chain votes of the masternode
+//! without submitting anything. [VoteBatch::execute()] does the same comparison first and skips
+//! votes that don't change the current vote.
+use dpp::identity::identity_nonce::MAX_MISSING_IDENTITY_REVISIONS;
+use dpp::identity::signer::Signer;
+use dpp::identity::IdentityPublicKey;
+use dpp::prelude::{Identifier, IdentityNonce};
+use dpp::state_transition::masternode_vote_transition::methods::MasternodeVoteTransitionMethodsV0;
+use dpp::state_transition::masternode_vote_transition::MasternodeVoteTransition;
+use dpp::voting::vote_choices::resource_vote_choice::ResourceVoteChoice;
+use dpp::voting::vote_polls::VotePoll;
+use dpp::voting::votes::resource_vote::accessors::v0::ResourceVoteGettersV0;
+use dpp::voting::votes::resource_vote::v0::ResourceVoteV0;
+use dpp::voting::votes::resource_vote::ResourceVote;
+use dpp::voting::votes::Vote;
+use futures::{StreamExt, TryStreamExt};
+
+use super::put_settings::PutSettings;
+use super::vote::{broadcast_vote_and_wait, get_voting_identity_id};
+use crate::platform::query::VoteQuery;
+use crate::platform::Fetch;
+use crate::{Error, Sdk};
+
+/// Default number of votes submitted concurrently.
+pub const DEFAULT_MAX_VOTES_IN_FLIGHT: usize = MAX_MISSING_IDENTITY_REVISIONS as usize;
+
+/// Difference between a vote in the batch and the current vote of the masternode, as reported by
+/// [VoteBatch::dry_run()].
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoteChange {
+    /// Vote poll
+    pub vote_poll: VotePoll,
+    /// Current vote of the masternode, if it voted on this poll
+    pub current: Option<ResourceVoteChoice>,
+    /// Vote to cast
+    pub new: ResourceVoteChoice,
+}
+
+impl VoteChange {
+    /// Check if casting the vote changes the current vote of the masternode.
+    pub fn is_change(&self) -> bool {
+        self.current != Some(self.new)
+    }
+}
+
+/// Result of a single vote of the batch, as returned by [VoteBatch::execute()].
+#[derive(Debug)]
+pub struct VoteOutcome {
+    /// Vote poll
+    pub vote_poll: VotePoll,
+    /// Vote cast
+    pub choice: ResourceVoteChoice,
+    /// Nonce of the voting identity used by the vote transition; `None` if the vote was skipped
+    pub nonce: Option<IdentityNonce>,
+    /// Proved vote, or an error if the vote failed
+    pub result: Result<Vote, Error>,
+}
+
+impl VoteOutcome {
+    /// Check if the vote was skipped because the masternode already cast the same vote.
+    pub fn is_skipped(&self) -> bool {
+        self.nonce.is_none()
+    }
+}
+
+/// Builder of a batch of masternode votes.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// # use dash_sdk::platform::transition::vote_batch::VoteBatch;
+/// # async fn example<S: dpp::identity::signer::Signer>(
+/// #     sdk: &dash_sdk::Sdk,
+/// #     pro_tx_hash: dpp::prelude::Identifier,
+/// #     voting_key: &dpp::identity::IdentityPublicKey,
+/// #     signer: &S,
+/// #     votes: Vec<(dpp::voting::vote_polls::VotePoll, dpp::voting::vote_choices::resource_vote_choice::ResourceVoteChoice)>,
+/// # ) -> Result<(), dash_sdk::Error> {
+/// let batch = VoteBatch::new(sdk, pro_tx_hash, voting_key, signer).with_votes(votes);
+///
+/// for change in batch.dry_run().await?.iter().filter(|change| change.is_change()) {
+///     println!("{:?}: {:?} -> {:?}", change.vote_poll, change.current, change.new);
+/// }
+///
+/// for outcome in batch.execute().await? {
+///     if let Err(e) = outcome.result {
+///         eprintln!("vote on {:?} failed: {}", outcome.vote_poll, e);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct VoteBatch<'a, S: Signer> {
+    sdk: &'a Sdk,
+    voter_pro_tx_hash: Identifier,
+    voting_public_key: &'a IdentityPublicKey,
+    signer: &'a S,
+    votes: Vec<(VotePoll, ResourceVoteChoice)>,
+    settings: Option<PutSettings>,
+    max_in_flight: usize,
+}
+
+impl<'a, S: Signer> VoteBatch<'a, S> {
+    /// Create an empty batch of votes of the masternode `voter_pro_tx_hash`, signed with its
+    /// VOTING key.
+    pub fn new(
+        sdk: &'a Sdk,
+        voter_pro_tx_hash: Identifier,
+        voting_public_key: &'a IdentityPublicKey,
+        signer: &'a S,
+    ) -> Self {
+        Self {
+            sdk,
+            voter_pro_tx_hash,
+            voting_public_key,
+            signer,
+            votes: Vec::new(),
+            settings: None,
+            max_in_flight: DEFAULT_MAX_VOTES_IN_FLIGHT,
+        }
+    }
+
+    /// Add a vote on a single vote poll.
+    pub fn with_vote(mut self, vote_poll: VotePoll, choice: ResourceVoteChoice) -> Self {
+        self.votes.push((vote_poll, choice));
+        self
+    }
+
+    /// Add votes on many vote polls.
+    pub fn with_votes<I: IntoIterator<Item = (VotePoll, ResourceVoteChoice)>>(
+        mut self,
+        votes: I,
+    ) -> Self {
+        self.votes.extend(votes);
+        self
+    }
+
+    /// Settings used to fetch nonces and submit vote transitions.
+    pub fn with_settings(mut self, settings: PutSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Maximum number of votes submitted concurrently.
+    ///
+    /// Defaults to [DEFAULT_MAX_VOTES_IN_FLIGHT]; values above it can make Platform reject votes
+    /// with nonces too far ahead.
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// Votes in the batch, in the order they are submitted.
+    pub fn votes(&self) -> &[(VotePoll, ResourceVoteChoice)] {
+        &self.votes
+    }
+
+    /// Compare votes in the batch with current votes of the masternode, without submitting anything.
+    ///
+    /// Returns one [VoteChange] per vote, in the order of the batch.
+    pub async fn dry_run(&self) -> Result<Vec<VoteChange>, Error> {
+        futures::stream::iter(self.votes.iter().cloned())
+            .map(|(vote_poll, new)| async move {
+                let query = VoteQuery::new(self.voter_pro_tx_hash, vote_poll.unique_id()?);
+                let current = Vote::fetch(self.sdk, query)
+                    .await?
+                    .map(|Vote::ResourceVote(vote)| vote.resource_vote_choice());
+
+                Ok::<_, Error>(VoteChange {
+                    vote_poll,
+                    current,
+                    new,
+                })
+            })
+            .buffered(self.max_in_flight)
+            .try_collect()
+            .await
+    }
+
+    /// Sign and submit all votes, and wait for their results.
+    ///
+    /// Returns one [VoteOutcome] per vote, in the order of the batch. Votes equal to the current
+    /// votes of the masternode, as reported by [VoteBatch::dry_run()], are not submitted; they are
+    /// returned as skipped, with the current vote as the result. Failure of a single vote doesn't
+    /// stop the others; an error is returned only if current votes or nonces can't be retrieved, or
+    /// the votes can't be signed.
+    pub async fn execute(&self) -> Result<Vec<VoteOutcome>, Error> {
+        let voting_identity_id =
+            get_voting_identity_id(self.voter_pro_tx_hash, self.voting_public_key)?;
+        let settings = self.settings.unwrap_or_default();
+
+        let changes = self.dry_run().await?;
+
+        let mut votes = Vec::with_capacity(changes.len());
+        for change in changes {
+            let vote = Vote::ResourceVote(ResourceVote::V0(ResourceVoteV0 {
+                vote_poll: change.vote_poll.clone(),
+                resource_vote_choice: change.new,
+            }));
+            if !change.is_change() {
+                votes.push((change.vote_poll, change.new, vote, None));
+                continue;
+            }
+
+            let vote_poll_id = change.vote_poll.unique_id()?;
+            let nonce = self
+                .sdk
+                .get_identity_nonce(voting_identity_id, true, self.settings)
+                .await?;
+            let transition = MasternodeVoteTransition::try_from_vote_with_signer(
+                vote.clone(),
+                self.signer,
+                self.voter_pro_tx_hash,
+                self.voting_public_key,
+                nonce,
+                self.sdk.version(),
+                None,
+            )?;
+
+            votes.push((
+                change.vote_poll,
+                change.new,
+                vote,
+                Some((nonce, vote_poll_id, transition)),
+            ));
+        }
+
+        let outcomes: Vec<VoteOutcome> = futures::stream::iter(votes)
+            .map(|(vote_poll, choice, vote, transition)| async move {
+                let Some((nonce, vote_poll_id, transition)) = transition else {
+                    return VoteOutcome {
+                        vote_poll,
+                        choice,
+                        nonce: None,
+                        result: Ok(vote),
+                    };
+                };
+
+                let result = broadcast_vote_and_wait(
+                    self.sdk,
+                    &transition,
+                    self.voter_pro_tx_hash,
+                    vote_poll_id,
+                    settings,
+                )
+                .await;
+                if let Err(e) = &result {
+                    tracing::warn!(?vote_poll, ?choice, nonce, error = ?e, "masternode vote failed");
+                }
+
+                VoteOutcome {
+                    vote_poll,
+                    choice,
+                    nonce: Some(nonce),
+                    result,
+                }
+            })
+            .buffered(self.max_in_flight)
+            .collect()
+            .await;
+
+        // nonces of failed votes were not used, so cached nonce is ahead of Platform
+        if outcomes.iter().any(|outcome| outcome.result.is_err()) {
+            self.sdk
+                .invalidate_identity_nonces(voting_identity_id)
+                .await;
+        }
+
+        Ok(outcomes)
+    }
+}
@@ -12,6 +12,7 @@ use drive_proof_verifier::error::ContextProviderError;
 use drive_proof_verifier::DataContractProvider;
 
 use crate::platform::block_info_from_metadata::block_info_from_metadata;
+use crate::platform::transition::broadcast::check_broadcast_error;
 use crate::platform::transition::broadcast_request::BroadcastRequestForStateTransition;
 use crate::platform::transition::put_settings::PutSettings;
 use crate::{Error, Sdk};
@@ -68,11 +69,16 @@ impl WithdrawFromIdentity for Identity {
         request
             .clone()
             .execute(sdk, settings.unwrap_or_default().request_settings)
-            .await?;
+            .await
+            .map_err(Error::from_grpc_error)?;
 
         let request = state_transition.wait_for_state_transition_result_request()?;
 
-        let response = request.execute(sdk, RequestSettings::default()).await?;
+        let response = request
+            .execute(sdk, RequestSettings::default())
+            .await
+            .map_err(Error::from_grpc_error)?;
+        check_broadcast_error(&response)?;
 
         let block_info = block_info_from_metadata(response.metadata()?)?;
 
@@ -1,4 +1,5 @@
 //! Type-specific implementation for various dpp object types to make queries more convenient and intuitive.
+pub mod contested_resource_overview;
 pub mod epoch;
 pub mod identity;
 mod total_credits_in_platform;
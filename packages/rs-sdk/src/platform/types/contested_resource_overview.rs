@@ -0,0 +1,217 @@
+//! Overview of a contested resource vote poll.
+//!
+//! Masternodes vote on contested resources, like DPNS names requested by more than one identity.
+//! The state of a single vote poll is spread across several queries: contenders with their tallies,
+//! abstain and lock tallies, and the end date of the poll. [ContestedResourceOverview] combines them
+//! into one object, built only from proved responses.
+use dpp::block::block_info::BlockInfo;
+use dpp::prelude::{Identifier, TimestampMillis};
+use dpp::voting::contender_structs::ContenderWithSerializedDocument;
+use dpp::voting::vote_info_storage::contested_document_vote_poll_winner_info::ContestedDocumentVotePollWinnerInfo;
+use dpp::voting::vote_polls::contested_document_resource_vote_poll::ContestedDocumentResourceVotePoll;
+use dpp::voting::vote_polls::VotePoll;
+use drive::query::vote_poll_vote_state_query::{
+    ContestedDocumentVotePollDriveQuery, ContestedDocumentVotePollDriveQueryResultType,
+};
+use drive::query::VotePollsByEndDateDriveQuery;
+use drive_proof_verifier::types::Contenders;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use std::collections::BTreeMap;
+
+use crate::platform::{FetchMany, PaginatedQuery};
+use crate::{Error, Sdk};
+
+/// Max number of contenders and vote polls retrieved in a single request.
+pub const CONTESTED_RESOURCE_PAGE_LIMIT: u16 = 100;
+
+/// Status of a contested resource vote poll.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContestedResourceStatus {
+    /// Voting is in progress.
+    Active {
+        /// Contenders with the highest vote tally.
+        ///
+        /// More than one contender means a tie, which Platform resolves in favor of the oldest
+        /// contested document when the poll ends. Empty if there are no contenders.
+        leaders: Vec<Identifier>,
+        /// Lock votes outnumber votes for the leaders, so the resource would be locked if the poll
+        /// ended now.
+        locking: bool,
+    },
+    /// Voting has ended.
+    Finished {
+        /// Result of the vote poll.
+        winner: ContestedDocumentVotePollWinnerInfo,
+        /// Block in which the vote poll was decided.
+        block_info: BlockInfo,
+    },
+}
+
+/// Proof-verified state of a contested resource vote poll.
+///
+/// Use [ContestedResourceOverview::fetch()] to retrieve a single vote poll, or
+/// [ContestedResourceOverview::fetch_active_stream()] to iterate over all vote polls that are not
+/// yet finished.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContestedResourceOverview {
+    /// The vote poll.
+    pub vote_poll: ContestedDocumentResourceVotePoll,
+    /// Time when the vote poll ends.
+    ///
+    /// For finished vote polls, this is the time of the block in which the poll was decided.
+    /// `None` if the vote poll is not scheduled to end, e.g. because it doesn't exist.
+    pub end_time: Option<TimestampMillis>,
+    /// Contenders indexed by their identity IDs, with their serialized documents and vote tallies.
+    pub contenders: BTreeMap<Identifier, ContenderWithSerializedDocument>,
+    /// Tally of abstain votes.
+    pub abstain_vote_tally: u32,
+    /// Tally of lock votes.
+    pub lock_vote_tally: u32,
+    /// Current status of the vote poll.
+    pub status: ContestedResourceStatus,
+}
+
+impl ContestedResourceOverview {
+    /// Build an overview from the vote state of the poll and its end time.
+    pub fn new(
+        vote_poll: ContestedDocumentResourceVotePoll,
+        end_time: Option<TimestampMillis>,
+        vote_state: Contenders,
+    ) -> Self {
+        let abstain_vote_tally = vote_state.abstain_vote_tally.unwrap_or_default();
+        let lock_vote_tally = vote_state.lock_vote_tally.unwrap_or_default();
+
+        let (status, end_time) = match vote_state.winner {
+            Some((winner, block_info)) => (
+                ContestedResourceStatus::Finished { winner, block_info },
+                Some(block_info.time_ms),
+            ),
+            None => {
+                let highest_tally = vote_state
+                    .contenders
+                    .values()
+                    .map(|contender| contender.vote_tally().unwrap_or_default())
+                    .max();
+                let leaders = vote_state
+                    .contenders
+                    .iter()
+                    .filter(|(_, contender)| {
+                        Some(contender.vote_tally().unwrap_or_default()) == highest_tally
+                    })
+                    .map(|(id, _)| *id)
+                    .collect();
+                let locking = highest_tally.is_some_and(|tally| lock_vote_tally > tally);
+
+                (
+                    ContestedResourceStatus::Active { leaders, locking },
+                    end_time,
+                )
+            }
+        };
+
+        Self {
+            vote_poll,
+            end_time,
+            contenders: vote_state.contenders,
+            abstain_vote_tally,
+            lock_vote_tally,
+            status,
+        }
+    }
+
+    /// Fetch the overview of a single vote poll.
+    ///
+    /// Platform can't look up the end time of a vote poll directly, so for active polls it is found
+    /// by scanning vote polls in the order of their end dates; this takes one request per
+    /// [CONTESTED_RESOURCE_PAGE_LIMIT] vote polls scheduled to end before this one.
+    pub async fn fetch(
+        sdk: &Sdk,
+        vote_poll: ContestedDocumentResourceVotePoll,
+    ) -> Result<Self, Error> {
+        let vote_state = fetch_vote_state(sdk, vote_poll.clone()).await?;
+
+        let end_time = match vote_state.winner {
+            Some(_) => None,
+            None => {
+                let wanted = VotePoll::ContestedDocumentResourceVotePoll(vote_poll.clone());
+                VotePoll::fetch_many_stream(sdk, active_vote_polls_query(), None)
+                    .try_filter_map(|(end_time, poll)| {
+                        futures::future::ready(Ok((poll == wanted).then_some(end_time)))
+                    })
+                    .boxed()
+                    .try_next()
+                    .await?
+            }
+        };
+
+        Ok(Self::new(vote_poll, end_time, vote_state))
+    }
+
+    /// Iterate over overviews of all vote polls that are not finished yet, in the order of their
+    /// end times.
+    ///
+    /// Vote polls are retrieved page by page, and the vote state of each poll is fetched when it is
+    /// yielded.
+    ///
+    /// ## Parameters
+    ///
+    /// - `sdk`: An instance of [Sdk].
+    /// - `max_items`: Maximum number of vote polls to yield; `None` means no limit.
+    pub fn fetch_active_stream(
+        sdk: &Sdk,
+        max_items: Option<usize>,
+    ) -> BoxStream<'_, Result<Self, Error>> {
+        VotePoll::fetch_many_stream(sdk, active_vote_polls_query(), max_items)
+            .and_then(move |(end_time, poll)| async move {
+                let vote_poll = match poll {
+                    VotePoll::ContestedDocumentResourceVotePoll(vote_poll) => vote_poll,
+                };
+                let vote_state = fetch_vote_state(sdk, vote_poll.clone()).await?;
+
+                Ok(Self::new(vote_poll, Some(end_time), vote_state))
+            })
+            .boxed()
+    }
+}
+
+fn active_vote_polls_query() -> VotePollsByEndDateDriveQuery {
+    VotePollsByEndDateDriveQuery {
+        start_time: None,
+        end_time: None,
+        limit: Some(CONTESTED_RESOURCE_PAGE_LIMIT),
+        offset: None,
+        order_ascending: true,
+    }
+}
+
+/// Fetch all contenders of the vote poll, together with the abstain and lock tallies and the winner.
+async fn fetch_vote_state(
+    sdk: &Sdk,
+    vote_poll: ContestedDocumentResourceVotePoll,
+) -> Result<Contenders, Error> {
+    let mut next = Some(ContestedDocumentVotePollDriveQuery {
+        vote_poll,
+        result_type: ContestedDocumentVotePollDriveQueryResultType::DocumentsAndVoteTally,
+        offset: None,
+        limit: Some(CONTESTED_RESOURCE_PAGE_LIMIT),
+        start_at: None,
+        allow_include_locked_and_abstaining_vote_tally: true,
+    });
+
+    let mut vote_state = Contenders::default();
+    while let Some(query) = next {
+        let page = ContenderWithSerializedDocument::fetch_many(sdk, query.clone()).await?;
+
+        // tallies and the winner are returned with the first page only
+        vote_state.abstain_vote_tally = vote_state.abstain_vote_tally.or(page.abstain_vote_tally);
+        vote_state.lock_vote_tally = vote_state.lock_vote_tally.or(page.lock_vote_tally);
+        vote_state.winner = vote_state.winner.or(page.winner);
+
+        let (contenders, next_query) = query.next_page(page, sdk.version())?;
+        vote_state.contenders.extend(contenders);
+        next = next_query;
+    }
+
+    Ok(vote_state)
+}
@@ -0,0 +1,370 @@
+//! Watch Platform for state changes.
+//!
+//! Platform has no push mechanism for state changes, so [Sdk::watch()] polls it on a configured interval
+//! and compares subsequent snapshots of Platform state to detect new blocks, epochs and protocol
+//! version upgrades.
+//!
+//! [Sdk::watch_object()] builds on top of it to re-fetch (and verify) a single object only when
+//! a new block was produced.
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use dapi_grpc::platform::v0::ResponseMetadata;
+use dpp::block::epoch::EpochIndex;
+use dpp::block::extended_epoch_info::v0::ExtendedEpochInfoV0Getters;
+use dpp::block::extended_epoch_info::ExtendedEpochInfo;
+use dpp::prelude::{BlockHeight, CoreBlockHeight, TimestampMillis};
+use dpp::util::deserializer::ProtocolVersion;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+
+use crate::platform::fetch_current_no_parameters::FetchCurrent;
+use crate::platform::{Fetch, Query};
+use crate::{Error, Sdk};
+
+/// Default interval between subsequent polls of Platform state.
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Snapshot of Platform state, as reported in [ResponseMetadata].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlatformState {
+    /// Height of the latest Platform block
+    pub height: BlockHeight,
+    /// Latest core height known to Platform
+    pub core_chain_locked_height: CoreBlockHeight,
+    /// Current epoch
+    pub epoch: EpochIndex,
+    /// Time of the latest Platform block
+    pub time_ms: TimestampMillis,
+    /// Protocol version currently in use
+    pub protocol_version: ProtocolVersion,
+}
+
+impl From<&ResponseMetadata> for PlatformState {
+    fn from(metadata: &ResponseMetadata) -> Self {
+        Self {
+            height: metadata.height,
+            core_chain_locked_height: metadata.core_chain_locked_height,
+            epoch: metadata.epoch as EpochIndex,
+            time_ms: metadata.time_ms,
+            protocol_version: metadata.protocol_version,
+        }
+    }
+}
+
+impl PlatformState {
+    /// Build state from the proved current epoch and metadata of the response that proved it.
+    ///
+    /// Epoch and protocol version are taken from the proved epoch info, not from the metadata.
+    fn from_proved(epoch_info: &ExtendedEpochInfo, metadata: &ResponseMetadata) -> Self {
+        Self {
+            epoch: epoch_info.index(),
+            protocol_version: epoch_info.protocol_version(),
+            ..Self::from(metadata)
+        }
+    }
+}
+
+/// Event emitted by [Sdk::watch()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlatformEvent {
+    /// New block was produced.
+    ///
+    /// It is also emitted for the first observed state.
+    NewBlock(PlatformState),
+    /// New epoch has started.
+    NewEpoch {
+        /// Previously observed epoch
+        previous: EpochIndex,
+        /// Current state
+        state: PlatformState,
+    },
+    /// Protocol version in use has changed.
+    ProtocolVersionChanged {
+        /// Previously observed protocol version
+        previous: ProtocolVersion,
+        /// Current state
+        state: PlatformState,
+    },
+}
+
+impl PlatformEvent {
+    /// Platform state at the time the event was detected.
+    pub fn state(&self) -> &PlatformState {
+        match self {
+            PlatformEvent::NewBlock(state) => state,
+            PlatformEvent::NewEpoch { state, .. } => state,
+            PlatformEvent::ProtocolVersionChanged { state, .. } => state,
+        }
+    }
+}
+
+/// Compare two snapshots of Platform state and return events describing the difference.
+///
+/// Snapshots that are not newer than `previous` (eg. returned by a lagging node) produce no events.
+fn detect_events(previous: Option<&PlatformState>, current: PlatformState) -> Vec<PlatformEvent> {
+    let previous = match previous {
+        Some(previous) if current.height <= previous.height => return vec![],
+        Some(previous) => previous,
+        None => return vec![PlatformEvent::NewBlock(current)],
+    };
+
+    let mut events = vec![PlatformEvent::NewBlock(current)];
+    if current.epoch != previous.epoch {
+        events.push(PlatformEvent::NewEpoch {
+            previous: previous.epoch,
+            state: current,
+        });
+    }
+    if current.protocol_version != previous.protocol_version {
+        events.push(PlatformEvent::ProtocolVersionChanged {
+            previous: previous.protocol_version,
+            state: current,
+        });
+    }
+
+    events
+}
+
+struct WatchState {
+    sdk: Sdk,
+    interval: Duration,
+    last: Option<PlatformState>,
+    pending: VecDeque<PlatformEvent>,
+    polled: bool,
+}
+
+impl WatchState {
+    /// Wait for the next event, polling Platform as needed.
+    ///
+    /// Returns `None` when the Sdk was shut down.
+    async fn next_event(&mut self) -> Option<Result<PlatformEvent, Error>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+
+            // first poll is done immediately
+            if self.polled {
+                tokio::select! {
+                    biased;
+                    _ = self.sdk.cancelled() => return None,
+                    _ = tokio::time::sleep(self.interval) => {},
+                }
+            }
+
+            self.polled = true;
+
+            let current = tokio::select! {
+                biased;
+                _ = self.sdk.cancelled() => return None,
+                result = ExtendedEpochInfo::fetch_current_with_metadata(&self.sdk) => match result {
+                    Ok((epoch_info, metadata)) => PlatformState::from_proved(&epoch_info, &metadata),
+                    Err(e) => return Some(Err(e)),
+                },
+            };
+
+            let events = detect_events(self.last.as_ref(), current);
+            if !events.is_empty() {
+                self.last = Some(current);
+            }
+            self.pending.extend(events);
+        }
+    }
+}
+
+impl Sdk {
+    /// Watch Platform for state changes.
+    ///
+    /// Polls Platform every `interval` and emits [PlatformEvent]s when new block, new epoch or protocol
+    /// version change is detected. The first poll is done immediately and emits
+    /// [PlatformEvent::NewBlock] with the current state.
+    ///
+    /// Each poll fetches the current epoch with a proof. Epoch index and protocol version come from
+    /// the proved epoch info; block height, core chain locked height and time are taken from the
+    /// response metadata, which is not covered by the proof.
+    ///
+    /// Errors encountered while polling are yielded, but do not end the stream; it ends when the Sdk
+    /// is [shut down](Sdk::shutdown()).
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use dash_sdk::{Sdk, platform::watch::{PlatformEvent, DEFAULT_WATCH_INTERVAL}};
+    /// use futures::StreamExt;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let sdk = Sdk::new_mock();
+    /// let mut events = sdk.watch(DEFAULT_WATCH_INTERVAL);
+    /// sdk.shutdown();
+    /// while let Some(event) = events.next().await {
+    ///     if let Ok(PlatformEvent::NewEpoch { state, .. }) = event {
+    ///         println!("epoch {} started at height {}", state.epoch, state.height);
+    ///     }
+    /// }
+    /// # });
+    /// ```
+    pub fn watch(&self, interval: Duration) -> BoxStream<'static, Result<PlatformEvent, Error>> {
+        let state = WatchState {
+            sdk: self.clone(),
+            interval,
+            last: None,
+            pending: VecDeque::new(),
+            polled: false,
+        };
+
+        futures::stream::unfold(state, |mut state| async move {
+            state.next_event().await.map(|event| (event, state))
+        })
+        .boxed()
+    }
+
+    /// Watch a single object on Platform.
+    ///
+    /// Uses [Sdk::watch()] to detect new blocks and re-fetches the object, with proofs,
+    /// only when the height advances. Yields the object together with the state at which it was
+    /// fetched, for the first observation and whenever it changes.
+    ///
+    /// Errors encountered while polling or fetching are yielded, but do not end the stream.
+    pub fn watch_object<O, Q>(
+        &self,
+        query: Q,
+        interval: Duration,
+    ) -> BoxStream<'static, Result<(PlatformState, Option<O>), Error>>
+    where
+        O: Fetch + Clone + PartialEq + Send + 'static,
+        Q: Query<<O as Fetch>::Request> + 'static,
+    {
+        let sdk = self.clone();
+
+        self.watch(interval)
+            .filter_map(move |event| {
+                let sdk = sdk.clone();
+                let query = query.clone();
+                async move {
+                    match event {
+                        Ok(PlatformEvent::NewBlock(state)) => {
+                            Some(O::fetch(&sdk, query).await.map(|object| (state, object)))
+                        }
+                        Ok(_) => None,
+                        Err(e) => Some(Err(e)),
+                    }
+                }
+            })
+            .scan(None, |last: &mut Option<Option<O>>, result| {
+                let item = match result {
+                    Ok((state, object)) if last.as_ref() != Some(&object) => {
+                        let item = Some(Ok((state, object.clone())));
+                        *last = Some(object);
+                        item
+                    }
+                    Ok(_) => None,
+                    Err(e) => Some(Err(e)),
+                };
+                futures::future::ready(Some(item))
+            })
+            .filter_map(futures::future::ready)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpp::block::extended_epoch_info::v0::ExtendedEpochInfoV0;
+
+    fn state(
+        height: BlockHeight,
+        epoch: EpochIndex,
+        protocol_version: ProtocolVersion,
+    ) -> PlatformState {
+        PlatformState {
+            height,
+            core_chain_locked_height: 1000 + height as CoreBlockHeight,
+            epoch,
+            time_ms: height * 1000,
+            protocol_version,
+        }
+    }
+
+    #[test]
+    fn first_state_is_new_block() {
+        let current = state(10, 1, 1);
+
+        assert_eq!(
+            detect_events(None, current),
+            vec![PlatformEvent::NewBlock(current)]
+        );
+    }
+
+    #[test]
+    fn stale_state_produces_no_events() {
+        let previous = state(10, 1, 1);
+
+        assert!(detect_events(Some(&previous), state(10, 1, 1)).is_empty());
+        assert!(detect_events(Some(&previous), state(9, 2, 2)).is_empty());
+    }
+
+    #[test]
+    fn new_block_in_the_same_epoch() {
+        let current = state(11, 1, 1);
+
+        assert_eq!(
+            detect_events(Some(&state(10, 1, 1)), current),
+            vec![PlatformEvent::NewBlock(current)]
+        );
+    }
+
+    #[test]
+    fn new_epoch_and_protocol_version() {
+        let current = state(20, 2, 2);
+
+        assert_eq!(
+            detect_events(Some(&state(10, 1, 1)), current),
+            vec![
+                PlatformEvent::NewBlock(current),
+                PlatformEvent::NewEpoch {
+                    previous: 1,
+                    state: current,
+                },
+                PlatformEvent::ProtocolVersionChanged {
+                    previous: 1,
+                    state: current,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn state_from_proved_epoch_info() {
+        let metadata = ResponseMetadata {
+            height: 10,
+            core_chain_locked_height: 20,
+            epoch: 3,
+            time_ms: 30,
+            protocol_version: 4,
+            ..Default::default()
+        };
+        let epoch_info = ExtendedEpochInfo::V0(ExtendedEpochInfoV0 {
+            index: 5,
+            first_block_time: 0,
+            first_block_height: 0,
+            first_core_block_height: 0,
+            fee_multiplier_permille: 1000,
+            protocol_version: 6,
+        });
+
+        let state = PlatformState::from_proved(&epoch_info, &metadata);
+
+        assert_eq!(
+            state,
+            PlatformState {
+                height: 10,
+                core_chain_locked_height: 20,
+                epoch: 5,
+                time_ms: 30,
+                protocol_version: 6
+            }
+        );
+    }
+}
@@ -22,15 +22,19 @@ use drive_proof_verifier::MockContextProvider;
 use drive_proof_verifier::{ContextProvider, FromProof};
 pub use http::Uri;
 #[cfg(feature = "mocks")]
-use rs_dapi_client::mock::MockDapiClient;
+use rs_dapi_client::mock::{MockDapiClient, MockError};
+#[cfg(feature = "mocks")]
+use rs_dapi_client::session::ReplayDapiClient;
 pub use rs_dapi_client::AddressList;
 pub use rs_dapi_client::RequestSettings;
 use rs_dapi_client::{
     transport::{TransportClient, TransportRequest},
-    DapiClient, DapiClientError, DapiRequestExecutor,
+    DapiClient, DapiClientError, DapiRequestExecutor, DapiStream, DapiStreamExecutor,
+    DapiStreamRequest, ResponseVerifier,
 };
 use std::collections::btree_map::Entry;
 use std::fmt::Debug;
+use std::marker::PhantomData;
 #[cfg(feature = "mocks")]
 use std::num::NonZeroUsize;
 #[cfg(feature = "mocks")]
@@ -120,6 +124,12 @@ impl Debug for Sdk {
                 .field("mock", mock)
                 .field("proofs", &self.proofs)
                 .finish(),
+            #[cfg(feature = "mocks")]
+            SdkInstance::Replay { dapi, .. } => f
+                .debug_struct("Sdk")
+                .field("replay", dapi)
+                .field("proofs", &self.proofs)
+                .finish(),
         }
     }
 }
@@ -148,6 +158,15 @@ enum SdkInstance {
         /// Mock SDK implementation processing mock expectations and responses.
         mock: Arc<Mutex<MockDashPlatformSdk>>,
 
+        /// Platform version configured for this Sdk
+        version: &'static PlatformVersion,
+    },
+    /// SDK replaying a recorded DAPI session; proofs are verified like in [SdkInstance::Dapi]
+    #[cfg(feature = "mocks")]
+    Replay {
+        /// DAPI client serving responses from the recorded session.
+        dapi: Arc<ReplayDapiClient>,
+
         /// Platform version configured for this Sdk
         version: &'static PlatformVersion,
     },
@@ -173,32 +192,11 @@ impl Sdk {
     ///
     /// - `R`: Type of the request that was used to fetch the proof.
     /// - `O`: Type of the object to be retrieved from the proof.
-    pub(crate) async fn parse_proof<R, O: FromProof<R> + MockResponse>(
-        &self,
-        request: O::Request,
-        response: O::Response,
-    ) -> Result<Option<O>, drive_proof_verifier::Error>
-    where
-        O::Request: Mockable,
-    {
-        self.parse_proof_with_metadata(request, response)
-            .await
-            .map(|result| result.0)
-    }
-
-    /// Retrieve object `O` from proof contained in `request` (of type `R`) and `response`.
-    ///
-    /// This method is used to retrieve objects from proofs returned by Dash Platform.
-    ///
-    /// ## Generic Parameters
-    ///
-    /// - `R`: Type of the request that was used to fetch the proof.
-    /// - `O`: Type of the object to be retrieved from the proof.
-    pub(crate) async fn parse_proof_with_metadata<R, O: FromProof<R> + MockResponse>(
+    pub(crate) async fn parse_proof_with_metadata_and_proof<R, O: FromProof<R> + MockResponse>(
         &self,
         request: O::Request,
         response: O::Response,
-    ) -> Result<(Option<O>, ResponseMetadata), drive_proof_verifier::Error>
+    ) -> Result<(Option<O>, ResponseMetadata, Proof), drive_proof_verifier::Error>
     where
         O::Request: Mockable,
     {
@@ -214,54 +212,88 @@ impl Sdk {
                 self.network,
                 self.version(),
                 &provider,
-            )
-            .map(|(a, b, _)| (a, b)),
+            ),
             #[cfg(feature = "mocks")]
             SdkInstance::Mock { ref mock, .. } => {
                 let guard = mock.lock().await;
-                guard
-                    .parse_proof_with_metadata(request, response)
-                    .map(|(a, b, _)| (a, b))
+                guard.parse_proof_with_metadata(request, response)
             }
+            #[cfg(feature = "mocks")]
+            SdkInstance::Replay { .. } => O::maybe_from_proof_with_metadata(
+                request,
+                response,
+                self.network,
+                self.version(),
+                &provider,
+            ),
         }
     }
 
-    /// Retrieve object `O` from proof contained in `request` (of type `R`) and `response`.
+    /// Execute `request` and retrieve object `O` from the proof contained in the response.
     ///
-    /// This method is used to retrieve objects from proofs returned by Dash Platform.
-    ///
-    /// ## Generic Parameters
-    ///
-    /// - `R`: Type of the request that was used to fetch the proof.
-    /// - `O`: Type of the object to be retrieved from the proof.
-    pub(crate) async fn parse_proof_with_metadata_and_proof<R, O: FromProof<R> + MockResponse>(
+    /// When `settings` enable hedged or quorum reads (see [RequestSettings::fan_out]), proof of
+    /// the response of each DAPI node is verified separately, so that a single node returning an
+    /// invalid proof doesn't fail the request. Responses match for
+    /// [quorum](RequestSettings::quorum) when they prove the same object.
+    pub(crate) async fn execute_and_parse_proof<R, O>(
         &self,
-        request: O::Request,
-        response: O::Response,
-    ) -> Result<(Option<O>, ResponseMetadata, Proof), drive_proof_verifier::Error>
+        request: R,
+        settings: RequestSettings,
+    ) -> Result<(Option<O>, ResponseMetadata, Proof), Error>
     where
-        O::Request: Mockable,
+        R: TransportRequest + Mockable,
+        O: FromProof<R, Request = R, Response = R::Response> + MockResponse + PartialEq + Send,
     {
-        let provider = self
-            .context_provider
-            .as_ref()
-            .ok_or(drive_proof_verifier::Error::ContextProviderNotSet)?;
-
         match self.inner {
-            SdkInstance::Dapi { .. } => O::maybe_from_proof_with_metadata(
-                request,
-                response,
-                self.network,
-                self.version(),
-                &provider,
-            ),
+            SdkInstance::Dapi { ref dapi, .. } => {
+                let provider = self
+                    .context_provider
+                    .as_ref()
+                    .ok_or(drive_proof_verifier::Error::ContextProviderNotSet)?;
+
+                let verifier = ProofVerifier::<R, O> {
+                    request,
+                    network: self.network,
+                    version: self.version(),
+                    provider: &***provider,
+                    object: PhantomData,
+                };
+
+                Ok(dapi
+                    .execute_verified(verifier.request.clone(), settings, verifier)
+                    .await?)
+            }
             #[cfg(feature = "mocks")]
-            SdkInstance::Mock { ref mock, .. } => {
-                let guard = mock.lock().await;
-                guard.parse_proof_with_metadata(request, response)
+            SdkInstance::Mock { .. } | SdkInstance::Replay { .. } => {
+                let response = self.execute(request.clone(), settings).await?;
+
+                Ok(self
+                    .parse_proof_with_metadata_and_proof(request, response)
+                    .await?)
+            }
+        }
+    }
+
+    /// Replace DAPI addresses used by the SDK, keeping ban status and statistics of known ones.
+    ///
+    /// Addresses the SDK was built with are kept as a fallback; see
+    /// [DapiClient::update_addresses()].
+    ///
+    /// See [AddressDiscovery](crate::core::address_discovery::AddressDiscovery) to discover
+    /// addresses from the masternode list. Fails in mock mode.
+    pub fn update_addresses<I: IntoIterator<Item = Uri>>(&self, uris: I) -> Result<(), Error> {
+        match self.inner {
+            SdkInstance::Dapi { ref dapi, .. } => {
+                dapi.update_addresses(uris);
+                Ok(())
             }
+            #[cfg(feature = "mocks")]
+            SdkInstance::Mock { .. } | SdkInstance::Replay { .. } => Err(Error::Config(
+                "DAPI addresses can't be updated in mock mode".to_string(),
+            )),
         }
     }
+
     pub fn context_provider(&self) -> Option<impl ContextProvider> {
         self.context_provider.as_ref().map(Arc::clone)
     }
@@ -469,6 +501,25 @@ impl Sdk {
         }
     }
 
+    /// Removes cached identity nonce and all identity contract nonces of the given identity,
+    /// so that they are fetched from Platform on next use.
+    ///
+    /// Use it when a state transition failed or its outcome is unknown, as cached nonces could be
+    /// out of sync with Platform.
+    pub async fn invalidate_identity_nonces(&self, identity_id: Identifier) {
+        self.internal_cache
+            .identity_nonce_counter
+            .lock()
+            .await
+            .remove(&identity_id);
+
+        self.internal_cache
+            .identity_contract_nonce_counter
+            .lock()
+            .await
+            .retain(|(cached_identity_id, _), _| *cached_identity_id != identity_id);
+    }
+
     /// Return [Dash Platform version](PlatformVersion) information used by this SDK.
     ///
     ///
@@ -480,6 +531,8 @@ impl Sdk {
             SdkInstance::Dapi { version, .. } => version,
             #[cfg(feature = "mocks")]
             SdkInstance::Mock { version, .. } => version,
+            #[cfg(feature = "mocks")]
+            SdkInstance::Replay { version, .. } => version,
         }
     }
 
@@ -523,10 +576,69 @@ impl DapiRequestExecutor for Sdk {
                 let dapi_guard = dapi.lock().await;
                 dapi_guard.execute(request, settings).await
             }
+            #[cfg(feature = "mocks")]
+            SdkInstance::Replay { ref dapi, .. } => dapi.execute(request, settings).await,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl DapiStreamExecutor for Sdk {
+    async fn stream<R: DapiStreamRequest>(
+        &self,
+        request: R,
+        settings: RequestSettings,
+    ) -> Result<DapiStream<R>, DapiClientError<<R::Client as TransportClient>::Error>> {
+        match self.inner {
+            SdkInstance::Dapi { ref dapi, .. } => dapi.stream(request, settings).await,
+            #[cfg(feature = "mocks")]
+            SdkInstance::Mock { ref dapi, .. } => {
+                let dapi_guard = dapi.lock().await;
+                dapi_guard.stream(request, settings).await
+            }
+            #[cfg(feature = "mocks")]
+            SdkInstance::Replay { .. } => Err(MockError::MockExpectationNotFound(format!(
+                "streams are not recorded in DAPI sessions, unable to replay {:?}",
+                request
+            ))
+            .into()),
         }
     }
 }
 
+/// Verifies proofs of responses received from each DAPI node, see [Sdk::execute_and_parse_proof()].
+struct ProofVerifier<'a, R, O> {
+    request: R,
+    network: Network,
+    version: &'a PlatformVersion,
+    provider: &'a dyn ContextProvider,
+    object: PhantomData<fn() -> O>,
+}
+
+impl<R, O> ResponseVerifier<R> for ProofVerifier<'_, R, O>
+where
+    R: TransportRequest,
+    O: FromProof<R, Request = R, Response = R::Response> + PartialEq + Send,
+{
+    type Output = (Option<O>, ResponseMetadata, Proof);
+    type Error = drive_proof_verifier::Error;
+
+    fn verify(&self, response: R::Response) -> Result<Self::Output, Self::Error> {
+        O::maybe_from_proof_with_metadata(
+            self.request.clone(),
+            response,
+            self.network,
+            self.version,
+            self.provider,
+        )
+    }
+
+    /// Responses match when they prove the same object, even if nodes are at different heights.
+    fn matches(&self, (object, _, _): &Self::Output, (other, _, _): &Self::Output) -> bool {
+        object == other
+    }
+}
+
 /// Dash Platform SDK Builder, used to configure and [`SdkBuilder::build()`] the [Sdk].
 ///
 /// [SdkBuilder] implements a "builder" design pattern to allow configuration of the Sdk before it is instantiated.
@@ -574,6 +686,14 @@ pub struct SdkBuilder {
     #[cfg(feature = "mocks")]
     dump_dir: Option<PathBuf>,
 
+    /// recorder of all requests and responses, in the order they were executed
+    #[cfg(feature = "mocks")]
+    session_recorder: Option<rs_dapi_client::session::SessionRecorder>,
+
+    /// recorded session to replay instead of connecting to DAPI
+    #[cfg(feature = "mocks")]
+    replay: Option<Arc<ReplayDapiClient>>,
+
     /// Cancellation token; once cancelled, all pending requests should be aborted.
     pub(crate) cancel_token: CancellationToken,
 }
@@ -607,6 +727,10 @@ impl Default for SdkBuilder {
 
             #[cfg(feature = "mocks")]
             dump_dir: None,
+            #[cfg(feature = "mocks")]
+            session_recorder: None,
+            #[cfg(feature = "mocks")]
+            replay: None,
         }
     }
 }
@@ -733,6 +857,37 @@ impl SdkBuilder {
         self
     }
 
+    /// Record all requests and responses executed by the SDK, in the order they were executed.
+    ///
+    /// Recorded session can be saved with
+    /// [SessionRecorder::save()](rs_dapi_client::session::SessionRecorder::save()) and replayed with
+    /// [ReplayDapiClient](rs_dapi_client::session::ReplayDapiClient).
+    ///
+    /// Available only when `mocks` feature is enabled.
+    #[cfg(feature = "mocks")]
+    pub fn with_session_recorder(
+        mut self,
+        recorder: rs_dapi_client::session::SessionRecorder,
+    ) -> Self {
+        self.session_recorder = Some(recorder);
+        self
+    }
+
+    /// Serve requests from a recorded session instead of connecting to DAPI.
+    ///
+    /// Unlike [MockDashPlatformSdk] expectations, replayed responses are processed like responses of
+    /// real DAPI nodes, including proof verification, so quorum public keys must be available to
+    /// the [ContextProvider]. When no context provider is configured, quorum public keys are read
+    /// from [dump directory](SdkBuilder::with_dump_dir()). Use
+    /// [ReplayDapiClient::verify()] to check that the whole session was replayed.
+    ///
+    /// Available only when `mocks` feature is enabled.
+    #[cfg(feature = "mocks")]
+    pub fn with_replay(mut self, replay: Arc<ReplayDapiClient>) -> Self {
+        self.replay = Some(replay);
+        self
+    }
+
     /// Build the Sdk instance.
     ///
     /// This method will create the Sdk instance based on the configuration provided to the builder.
@@ -743,12 +898,38 @@ impl SdkBuilder {
     pub fn build(self) -> Result<Sdk, Error> {
         PlatformVersion::set_current(self.version);
 
+        #[cfg(feature = "mocks")]
+        if let Some(dapi) = self.replay {
+            let context_provider = self.context_provider.unwrap_or_else(|| {
+                let mut cp = MockContextProvider::new();
+                if let Some(ref dump_dir) = self.dump_dir {
+                    cp.quorum_keys_dir(Some(dump_dir.clone()));
+                }
+                Box::new(cp)
+            });
+
+            return Ok(Sdk {
+                network: self.network,
+                inner: SdkInstance::Replay {
+                    dapi,
+                    version: self.version,
+                },
+                proofs: self.proofs,
+                context_provider: Some(Arc::new(context_provider)),
+                cancel_token: self.cancel_token,
+                dump_dir: self.dump_dir,
+                internal_cache: Default::default(),
+            });
+        }
+
         let sdk= match self.addresses {
             // non-mock mode
             Some(addresses) => {
                 let dapi = DapiClient::new(addresses, self.settings);
                 #[cfg(feature = "mocks")]
-                let dapi = dapi.dump_dir(self.dump_dir.clone());
+                let dapi = dapi
+                    .dump_dir(self.dump_dir.clone())
+                    .record_session(self.session_recorder.clone());
 
                 #[allow(unused_mut)] // needs to be mutable for #[cfg(feature = "mocks")]
                 let mut sdk= Sdk{
@@ -864,3 +1045,57 @@ pub fn prettify_proof(proof: &Proof) -> String {
         proof.quorum_type,
     )
 }
+
+#[cfg(all(test, feature = "mocks"))]
+mod tests {
+    use super::*;
+    use dapi_grpc::platform::v0::GetProtocolVersionUpgradeStateRequest;
+    use drive_proof_verifier::types::ProtocolVersionUpgrades;
+
+    fn verifier(
+        provider: &MockContextProvider,
+    ) -> ProofVerifier<'_, GetProtocolVersionUpgradeStateRequest, ProtocolVersionUpgrades> {
+        ProofVerifier {
+            request: GetProtocolVersionUpgradeStateRequest::default(),
+            network: Network::Regtest,
+            version: PlatformVersion::latest(),
+            provider,
+            object: PhantomData,
+        }
+    }
+
+    fn output(
+        upgrades: ProtocolVersionUpgrades,
+        height: u64,
+    ) -> (Option<ProtocolVersionUpgrades>, ResponseMetadata, Proof) {
+        let metadata = ResponseMetadata {
+            height,
+            ..Default::default()
+        };
+
+        (Some(upgrades), metadata, Proof::default())
+    }
+
+    #[test]
+    fn equal_objects_match_regardless_of_insertion_order() {
+        let provider = MockContextProvider::new();
+        let verifier = verifier(&provider);
+
+        let mut upgrades = ProtocolVersionUpgrades::new();
+        upgrades.insert(1, Some(10));
+        upgrades.insert(2, Some(20));
+
+        let mut reversed = ProtocolVersionUpgrades::new();
+        reversed.insert(2, Some(20));
+        reversed.insert(1, Some(10));
+
+        // nodes at different heights prove the same object
+        assert!(verifier.matches(
+            &output(upgrades.clone(), 100),
+            &output(reversed.clone(), 101)
+        ));
+
+        upgrades.insert(1, Some(11));
+        assert!(!verifier.matches(&output(upgrades, 100), &output(reversed, 100)));
+    }
+}
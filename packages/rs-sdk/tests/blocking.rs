@@ -0,0 +1,54 @@
+//! Tests of the blocking SDK facade.
+use std::sync::Arc;
+
+use dash_sdk::{blocking, Error, Sdk};
+use dpp::identity::accessors::IdentityGettersV0;
+use dpp::identity::IdentityV0;
+use dpp::prelude::{Identifier, Identity};
+
+/// Given some identity, when I fetch it using blocking mock API, then I get the same identity
+#[test]
+fn test_blocking_fetch_identity() {
+    let runtime = Arc::new(tokio::runtime::Runtime::new().expect("create runtime"));
+    let mut sdk = Sdk::new_mock();
+
+    let expected: Identity = Identity::from(IdentityV0::default());
+    let query = expected.id();
+
+    runtime
+        .block_on(sdk.mock().expect_fetch(query, Some(expected.clone())))
+        .unwrap();
+
+    let sdk = blocking::Sdk::with_runtime(sdk, runtime);
+    let retrieved: Identity = sdk
+        .fetch(query)
+        .expect("fetch identity")
+        .expect("object should exist");
+
+    assert_eq!(retrieved, expected);
+}
+
+/// Given a blocking SDK that was shut down, when I fetch an identity, then I get a cancellation error
+#[test]
+fn test_blocking_fetch_after_shutdown() {
+    let sdk = blocking::Sdk::from_async(Sdk::new_mock()).expect("create blocking SDK");
+    sdk.shutdown();
+
+    let result: Result<Option<Identity>, _> = sdk.fetch(Identifier::random());
+
+    assert!(matches!(result, Err(Error::Cancelled(_))));
+}
+
+/// When I use blocking SDK from within an async runtime, then it fails instead of panicking
+#[tokio::test]
+async fn test_blocking_fetch_in_async_context() {
+    let sdk = blocking::Sdk::from_async(Sdk::new_mock()).expect("create blocking SDK");
+
+    let result: Result<Option<Identity>, _> = sdk.fetch(Identifier::random());
+    assert!(matches!(result, Err(Error::Generic(_))));
+
+    // dropping a runtime in async context panics
+    tokio::task::spawn_blocking(move || drop(sdk))
+        .await
+        .expect("drop blocking SDK");
+}
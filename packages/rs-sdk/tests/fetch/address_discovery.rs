@@ -0,0 +1,185 @@
+//! Tests of DAPI address discovery from the masternode list.
+use dash_sdk::core::address_discovery::AddressDiscovery;
+use dpp::dashcore::hashes::{sha256d, Hash};
+
+/// Evo masternode type
+const MN_TYPE_EVO: u16 = 1;
+/// Regular masternode type
+const MN_TYPE_REGULAR: u16 = 0;
+
+/// Serialize a masternode list entry.
+fn entry(pro_reg_tx_hash: u8, ip: [u8; 4], is_valid: bool, mn_type: u16) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend(2u16.to_le_bytes());
+    data.extend([pro_reg_tx_hash; 32]);
+    // confirmedHash
+    data.extend([0u8; 32]);
+    // IPv4-mapped IPv6 address, big-endian port
+    data.extend([0u8; 10]);
+    data.extend([0xff, 0xff]);
+    data.extend(ip);
+    data.extend(19999u16.to_be_bytes());
+    // pubKeyOperator, keyIDVoting
+    data.extend([0u8; 48 + 20]);
+    data.push(is_valid as u8);
+    data.extend(mn_type.to_le_bytes());
+    if mn_type == MN_TYPE_EVO {
+        data.extend(443u16.to_le_bytes());
+        // platformNodeID
+        data.extend([0u8; 20]);
+    }
+
+    data
+}
+
+/// Compute `merkleRootMNList` of serialized masternode list entries.
+fn merkle_root_mn_list(entries: &[&Vec<u8>]) -> [u8; 32] {
+    // entries are sorted by ProRegTx hash, and hashed without their version
+    let mut entries = entries.to_vec();
+    entries.sort_by_key(|entry| entry[2..34].to_vec());
+    let mut hashes: Vec<[u8; 32]> = entries
+        .iter()
+        .map(|entry| sha256d::Hash::hash(&entry[2..]).to_byte_array())
+        .collect();
+
+    while hashes.len() > 1 {
+        if hashes.len() % 2 == 1 {
+            hashes.push(*hashes.last().expect("not empty"));
+        }
+        hashes = hashes
+            .chunks(2)
+            .map(|pair| sha256d::Hash::hash(&[pair[0], pair[1]].concat()).to_byte_array())
+            .collect();
+    }
+
+    hashes.pop().unwrap_or_default()
+}
+
+/// Serialize a masternode list diff with a minimal coinbase transaction and no quorums.
+fn diff(
+    base_block_hash: [u8; 32],
+    deleted: &[u8],
+    entries: &[Vec<u8>],
+    merkle_root_mn_list: [u8; 32],
+) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend(base_block_hash);
+    data.extend([1u8; 32]);
+
+    // coinbase merkle tree: one transaction, one hash, one flag byte
+    data.extend(1u32.to_le_bytes());
+    data.push(1);
+    data.extend([2u8; 32]);
+    data.push(1);
+    data.push(1);
+
+    // coinbase transaction: version 3, type 5, one input, no outputs
+    data.extend(3u16.to_le_bytes());
+    data.extend(5u16.to_le_bytes());
+    data.push(1);
+    data.extend([0u8; 36]);
+    data.push(0);
+    data.extend([0xffu8; 4]);
+    data.push(0);
+    data.extend([0u8; 4]);
+    // payload: version 2, height, merkleRootMNList, merkleRootQuorums
+    data.push(2 + 4 + 32 + 32);
+    data.extend(2u16.to_le_bytes());
+    data.extend(1000u32.to_le_bytes());
+    data.extend(merkle_root_mn_list);
+    data.extend([0u8; 32]);
+
+    // diff version
+    data.extend(1u16.to_le_bytes());
+
+    data.push(deleted.len() as u8);
+    for pro_reg_tx_hash in deleted {
+        data.extend([*pro_reg_tx_hash; 32]);
+    }
+
+    data.push(entries.len() as u8);
+    for entry in entries {
+        data.extend(entry);
+    }
+
+    // deleted and new quorums
+    data.push(0);
+    data.push(0);
+
+    data
+}
+
+fn addresses(discovery: &AddressDiscovery) -> Vec<String> {
+    let mut addresses: Vec<String> = discovery
+        .addresses()
+        .iter()
+        .map(|uri| uri.to_string())
+        .collect();
+    addresses.sort();
+
+    addresses
+}
+
+/// Given a masternode list with evonodes, regular and banned masternodes, when I process it,
+/// then only valid evonodes are used as DAPI addresses, and later diffs update them.
+#[test]
+fn test_address_discovery_masternode_list() {
+    let mut discovery = AddressDiscovery::new();
+
+    let entries = [
+        entry(1, [10, 0, 0, 1], true, MN_TYPE_EVO),
+        entry(2, [10, 0, 0, 2], true, MN_TYPE_REGULAR),
+        entry(3, [10, 0, 0, 3], false, MN_TYPE_EVO),
+        entry(4, [10, 0, 0, 4], true, MN_TYPE_EVO),
+    ];
+    let root = merkle_root_mn_list(&entries.iter().collect::<Vec<_>>());
+    let full_list = diff([0u8; 32], &[], &entries, root);
+    discovery
+        .process_masternode_list_diff(&full_list)
+        .expect("full masternode list");
+
+    assert_eq!(
+        addresses(&discovery),
+        vec!["https://10.0.0.1:443/", "https://10.0.0.4:443/"]
+    );
+
+    let changed = [
+        entry(3, [10, 0, 0, 3], true, MN_TYPE_EVO),
+        entry(4, [10, 0, 0, 4], false, MN_TYPE_EVO),
+    ];
+    let root = merkle_root_mn_list(&[&entries[1], &changed[0], &changed[1]]);
+    let update = diff([1u8; 32], &[1], &changed, root);
+    discovery
+        .process_masternode_list_diff(&update)
+        .expect("masternode list diff");
+
+    assert_eq!(addresses(&discovery), vec!["https://10.0.0.3:443/"]);
+}
+
+/// Given a masternode list, when I process a diff that doesn't match the masternode list merkle root
+/// committed in its coinbase, then it's rejected and the current list is kept.
+#[test]
+fn test_address_discovery_rejects_unverified_list() {
+    let mut discovery = AddressDiscovery::new();
+
+    let entries = [entry(1, [10, 0, 0, 1], true, MN_TYPE_EVO)];
+    let root = merkle_root_mn_list(&entries.iter().collect::<Vec<_>>());
+    discovery
+        .process_masternode_list_diff(&diff([0u8; 32], &[], &entries, root))
+        .expect("full masternode list");
+
+    let forged = [entry(2, [10, 0, 0, 2], true, MN_TYPE_EVO)];
+    discovery
+        .process_masternode_list_diff(&diff([1u8; 32], &[], &forged, root))
+        .expect_err("entry not committed in coinbase");
+
+    assert_eq!(addresses(&discovery), vec!["https://10.0.0.1:443/"]);
+}
+
+/// When I process malformed masternode list diff, then an error is returned.
+#[test]
+fn test_address_discovery_rejects_invalid_data() {
+    AddressDiscovery::new()
+        .process_masternode_list_diff(&[0u8; 16])
+        .expect_err("truncated masternode list diff");
+}
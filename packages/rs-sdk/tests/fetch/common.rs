@@ -1,4 +1,17 @@
+use dapi_grpc::platform::v0::wait_for_state_transition_result_response::{
+    wait_for_state_transition_result_response_v0, WaitForStateTransitionResultResponseV0,
+};
+use dapi_grpc::platform::v0::{
+    wait_for_state_transition_result_response, BroadcastStateTransitionResponse, ResponseMetadata,
+    StateTransitionBroadcastError, WaitForStateTransitionResultResponse,
+};
+use dash_sdk::platform::transition::broadcast_request::BroadcastRequestForStateTransition;
 use dash_sdk::{mock::Mockable, platform::Query, Sdk};
+use dpp::identity::signer::Signer;
+use dpp::identity::IdentityPublicKey;
+use dpp::platform_value::BinaryData;
+use dpp::state_transition::StateTransition;
+use dpp::ProtocolError;
 use dpp::{data_contract::DataContractFactory, prelude::Identifier};
 use hex::ToHex;
 use rs_dapi_client::transport::TransportRequest;
@@ -76,6 +89,59 @@ pub fn mock_data_contract(
         .data_contract_owned()
 }
 
+/// Signer returning the same signature for any data.
+///
+/// Used to create deterministic state transitions in tests of mock API.
+#[derive(Debug)]
+pub struct MockSigner;
+
+impl Signer for MockSigner {
+    fn sign(
+        &self,
+        _identity_public_key: &IdentityPublicKey,
+        _data: &[u8],
+    ) -> Result<BinaryData, ProtocolError> {
+        Ok(BinaryData::new(vec![1; 65]))
+    }
+}
+
+/// Expect `state_transition` to be broadcast using mock API.
+///
+/// When `error` is set, it is returned as the result of waiting for the state transition.
+pub async fn expect_broadcast(
+    sdk: &mut Sdk,
+    state_transition: &StateTransition,
+    error: Option<StateTransitionBroadcastError>,
+) {
+    let request = state_transition
+        .broadcast_request_for_state_transition()
+        .expect("broadcast request");
+    sdk.mock()
+        .expect_request(&request, &Ok(BroadcastStateTransitionResponse::default()))
+        .await
+        .expect("expect broadcast");
+
+    if let Some(error) = error {
+        let request = state_transition
+            .wait_for_state_transition_result_request()
+            .expect("wait for result request");
+        let response = WaitForStateTransitionResultResponse {
+            version: Some(wait_for_state_transition_result_response::Version::V0(
+                WaitForStateTransitionResultResponseV0 {
+                    result: Some(wait_for_state_transition_result_response_v0::Result::Error(
+                        error,
+                    )),
+                    metadata: Some(ResponseMetadata::default()),
+                },
+            )),
+        };
+        sdk.mock()
+            .expect_request(&request, &Ok(response))
+            .await
+            .expect("expect wait for result");
+    }
+}
+
 /// Enable logging for tests
 pub fn setup_logs() {
     tracing_subscriber::fmt::fmt()
@@ -0,0 +1,62 @@
+use dapi_grpc::platform::v0::StateTransitionBroadcastError;
+use dash_sdk::Error;
+use dpp::consensus::state::identity::IdentityInsufficientBalanceError;
+use dpp::consensus::ConsensusError;
+use dpp::prelude::Identifier;
+use dpp::serialization::PlatformSerializable;
+
+fn cbor(value: ciborium::Value) -> Vec<u8> {
+    let mut buf = Vec::new();
+    ciborium::ser::into_writer(&value, &mut buf).expect("cbor encode");
+    buf
+}
+
+/// Given a state transition execution error returned by `waitForStateTransitionResult`,
+/// when I convert it to SDK error, then the consensus error is decoded together with its code.
+#[test]
+fn test_consensus_error_from_broadcast_error() {
+    let consensus_error: ConsensusError =
+        IdentityInsufficientBalanceError::new(Identifier::new([1u8; 32]), 10, 100).into();
+    let serialized = consensus_error.serialize_to_bytes().expect("serialize");
+
+    let error_data = cbor(ciborium::Value::Map(vec![(
+        "serializedError".into(),
+        ciborium::Value::Bytes(serialized),
+    )]));
+    let data = cbor(ciborium::Value::Map(vec![
+        ("code".into(), "40210".into()),
+        (
+            "drive-error-data-bin".into(),
+            ciborium::Value::Bytes(error_data),
+        ),
+    ]));
+
+    let error: Error = StateTransitionBroadcastError {
+        code: 40210,
+        message: "insufficient balance".to_string(),
+        data,
+    }
+    .into();
+
+    match error {
+        Error::StateTransitionConsensusError { code, error } => {
+            assert_eq!(code, 40210);
+            assert_eq!(error, consensus_error);
+        }
+        e => panic!("unexpected error: {:?}", e),
+    }
+}
+
+/// Given a state transition execution error without consensus error details,
+/// when I convert it to SDK error, then a generic error is returned.
+#[test]
+fn test_consensus_error_from_broadcast_error_without_details() {
+    let error: Error = StateTransitionBroadcastError {
+        code: 13,
+        message: "internal error".to_string(),
+        data: vec![],
+    }
+    .into();
+
+    assert!(matches!(error, Error::DapiClientError(_)), "{:?}", error);
+}
@@ -0,0 +1,105 @@
+//! Tests of [ContestedResourceOverview].
+use dash_sdk::platform::types::contested_resource_overview::{
+    ContestedResourceOverview, ContestedResourceStatus,
+};
+use dpp::block::block_info::BlockInfo;
+use dpp::identifier::Identifier;
+use dpp::platform_value::Value;
+use dpp::voting::contender_structs::{
+    ContenderWithSerializedDocument, ContenderWithSerializedDocumentV0,
+};
+use dpp::voting::vote_info_storage::contested_document_vote_poll_winner_info::ContestedDocumentVotePollWinnerInfo;
+use dpp::voting::vote_polls::contested_document_resource_vote_poll::ContestedDocumentResourceVotePoll;
+use drive_proof_verifier::types::Contenders;
+
+fn vote_poll() -> ContestedDocumentResourceVotePoll {
+    ContestedDocumentResourceVotePoll {
+        contract_id: Identifier::new([1; 32]),
+        document_type_name: "domain".to_string(),
+        index_name: "parentNameAndLabel".to_string(),
+        index_values: vec![
+            Value::Text("dash".to_string()),
+            Value::Text("a11ce".to_string()),
+        ],
+    }
+}
+
+fn vote_state(tallies: &[(u8, u32)], lock_vote_tally: u32) -> Contenders {
+    Contenders {
+        contenders: tallies
+            .iter()
+            .map(|(id, tally)| {
+                let identity_id = Identifier::new([*id; 32]);
+                let contender =
+                    ContenderWithSerializedDocument::V0(ContenderWithSerializedDocumentV0 {
+                        identity_id,
+                        serialized_document: None,
+                        vote_tally: Some(*tally),
+                    });
+                (identity_id, contender)
+            })
+            .collect(),
+        abstain_vote_tally: Some(3),
+        lock_vote_tally: Some(lock_vote_tally),
+        winner: None,
+    }
+}
+
+/// Given an active vote poll, when I build its overview,
+/// then the contender with the highest tally is the leader.
+#[test]
+fn test_contested_resource_overview_active_leader() {
+    let overview =
+        ContestedResourceOverview::new(vote_poll(), Some(1000), vote_state(&[(2, 5), (3, 7)], 1));
+
+    assert_eq!(overview.end_time, Some(1000));
+    assert_eq!(overview.contenders.len(), 2);
+    assert_eq!(overview.abstain_vote_tally, 3);
+    assert_eq!(overview.lock_vote_tally, 1);
+    assert_eq!(
+        overview.status,
+        ContestedResourceStatus::Active {
+            leaders: vec![Identifier::new([3; 32])],
+            locking: false,
+        }
+    );
+}
+
+/// Given an active vote poll with tied contenders outnumbered by lock votes, when I build its overview,
+/// then all tied contenders are leaders and the resource is being locked.
+#[test]
+fn test_contested_resource_overview_active_tie_locking() {
+    let overview =
+        ContestedResourceOverview::new(vote_poll(), Some(1000), vote_state(&[(2, 5), (3, 5)], 6));
+
+    assert_eq!(
+        overview.status,
+        ContestedResourceStatus::Active {
+            leaders: vec![Identifier::new([2; 32]), Identifier::new([3; 32])],
+            locking: true,
+        }
+    );
+}
+
+/// Given a finished vote poll, when I build its overview,
+/// then the status contains the winner and the end time is the time of the deciding block.
+#[test]
+fn test_contested_resource_overview_finished() {
+    let block_info = BlockInfo {
+        time_ms: 2000,
+        height: 10,
+        ..Default::default()
+    };
+    let winner = ContestedDocumentVotePollWinnerInfo::WonByIdentity(Identifier::new([3; 32]));
+
+    let mut state = vote_state(&[(2, 5), (3, 7)], 1);
+    state.winner = Some((winner, block_info));
+
+    let overview = ContestedResourceOverview::new(vote_poll(), None, state);
+
+    assert_eq!(overview.end_time, Some(2000));
+    assert_eq!(
+        overview.status,
+        ContestedResourceStatus::Finished { winner, block_info }
+    );
+}
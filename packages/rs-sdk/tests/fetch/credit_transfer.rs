@@ -0,0 +1,193 @@
+use super::common::{expect_broadcast, MockSigner};
+use dapi_grpc::platform::v0::wait_for_state_transition_result_response::{
+    wait_for_state_transition_result_response_v0, WaitForStateTransitionResultResponseV0,
+};
+use dapi_grpc::platform::v0::{
+    wait_for_state_transition_result_response, Proof, ResponseMetadata,
+    StateTransitionBroadcastError, WaitForStateTransitionResultResponse,
+};
+use dash_sdk::platform::transition::broadcast_request::BroadcastRequestForStateTransition;
+use dash_sdk::platform::transition::transfer::TransferToIdentity;
+use dash_sdk::{Error, Sdk};
+use dpp::block::block_info::BlockInfo;
+use dpp::identity::accessors::IdentityGettersV0;
+use dpp::identity::accessors::IdentitySettersV0;
+use dpp::identity::identity_public_key::accessors::v0::IdentityPublicKeyGettersV0;
+use dpp::identity::identity_public_key::v0::IdentityPublicKeyV0;
+use dpp::identity::{Identity, IdentityPublicKey, IdentityV0, KeyType, Purpose, SecurityLevel};
+use dpp::platform_value::BinaryData;
+use dpp::prelude::Identifier;
+use dpp::state_transition::identity_credit_transfer_transition::methods::IdentityCreditTransferTransitionMethodsV0;
+use dpp::state_transition::identity_credit_transfer_transition::IdentityCreditTransferTransition;
+use drive::util::test_helpers::setup::setup_drive_with_initial_state_structure;
+use drive_proof_verifier::types::IdentityNonceFetcher;
+
+fn identity_with_transfer_key() -> Identity {
+    let key: IdentityPublicKey = IdentityPublicKeyV0 {
+        id: 3,
+        purpose: Purpose::TRANSFER,
+        security_level: SecurityLevel::CRITICAL,
+        contract_bounds: None,
+        key_type: KeyType::ECDSA_SECP256K1,
+        read_only: false,
+        data: BinaryData::new(vec![2; 33]),
+        disabled_at: None,
+    }
+    .into();
+
+    IdentityV0 {
+        id: Identifier::new([1; 32]),
+        public_keys: [(key.id(), key)].into(),
+        balance: 10_000,
+        revision: 0,
+    }
+    .into()
+}
+
+/// Given an identity with a transfer key, when I transfer credits and Platform rejects the transfer,
+/// then a transfer signed with the transfer key and the next identity nonce is broadcast, and the
+/// rejection is returned.
+#[tokio::test]
+async fn test_transfer_credits_rejected() {
+    let mut sdk = Sdk::new_mock();
+    let identity = identity_with_transfer_key();
+    let recipient = Identifier::new([2; 32]);
+
+    sdk.mock()
+        .expect_fetch(identity.id(), Some(IdentityNonceFetcher(4)))
+        .await
+        .expect("expect nonce");
+
+    let expected = IdentityCreditTransferTransition::try_from_identity(
+        &identity,
+        None,
+        recipient,
+        1000,
+        0,
+        MockSigner,
+        5,
+        sdk.version(),
+        None,
+    )
+    .expect("transfer transition");
+    expect_broadcast(
+        &mut sdk,
+        &expected,
+        Some(StateTransitionBroadcastError {
+            code: 40210,
+            message: "insufficient balance".to_string(),
+            data: vec![],
+        }),
+    )
+    .await;
+
+    let error = identity
+        .transfer_credits(&sdk, recipient, 1000, None, MockSigner, None)
+        .await
+        .expect_err("transfer rejected");
+
+    assert!(
+        matches!(error, Error::DapiClientError(ref message) if message.contains("40210")),
+        "{:?}",
+        error
+    );
+}
+
+/// Given an identity with a transfer key, when I transfer credits and Platform executes the
+/// transfer, then the proved balances are returned: the sender's balance decreased by the amount
+/// and the fee, the recipient's balance increased by the amount, and the identity nonce advanced.
+#[tokio::test]
+async fn test_transfer_credits() {
+    const AMOUNT: u64 = 1000;
+    const FEE: u64 = 250;
+
+    let mut sdk = Sdk::new_mock();
+    let identity = identity_with_transfer_key();
+    let mut recipient: Identity = IdentityV0 {
+        id: Identifier::new([2; 32]),
+        public_keys: Default::default(),
+        balance: 500,
+        revision: 0,
+    }
+    .into();
+
+    sdk.mock()
+        .expect_fetch(identity.id(), Some(IdentityNonceFetcher(4)))
+        .await
+        .expect("expect nonce");
+
+    let expected = IdentityCreditTransferTransition::try_from_identity(
+        &identity,
+        None,
+        recipient.id(),
+        AMOUNT,
+        0,
+        MockSigner,
+        5,
+        sdk.version(),
+        None,
+    )
+    .expect("transfer transition");
+    expect_broadcast(&mut sdk, &expected, None).await;
+
+    // Platform state after the transfer was executed, and its proof
+    let drive = setup_drive_with_initial_state_structure();
+    let mut sender = identity.clone();
+    sender.set_balance(identity.balance() - AMOUNT - FEE);
+    recipient.set_balance(recipient.balance() + AMOUNT);
+    for executed in [sender, recipient.clone()] {
+        drive
+            .add_new_identity(
+                executed,
+                false,
+                &BlockInfo::default(),
+                true,
+                None,
+                sdk.version(),
+            )
+            .expect("add identity");
+    }
+    let grovedb_proof = drive
+        .prove_many_identity_balances(
+            &[identity.id().to_buffer(), recipient.id().to_buffer()],
+            None,
+            &sdk.version().drive,
+        )
+        .expect("prove balances");
+
+    let request = expected
+        .wait_for_state_transition_result_request()
+        .expect("wait for result request");
+    let response = WaitForStateTransitionResultResponse {
+        version: Some(wait_for_state_transition_result_response::Version::V0(
+            WaitForStateTransitionResultResponseV0 {
+                result: Some(wait_for_state_transition_result_response_v0::Result::Proof(
+                    Proof {
+                        grovedb_proof,
+                        ..Default::default()
+                    },
+                )),
+                metadata: Some(ResponseMetadata::default()),
+            },
+        )),
+    };
+    sdk.mock()
+        .expect_request(&request, &Ok(response))
+        .await
+        .expect("expect wait for result");
+
+    let (sender_balance, recipient_balance) = identity
+        .transfer_credits(&sdk, recipient.id(), AMOUNT, None, MockSigner, None)
+        .await
+        .expect("transfer credits");
+
+    assert_eq!(sender_balance, identity.balance() - AMOUNT - FEE);
+    assert_eq!(recipient_balance, 500 + AMOUNT);
+
+    // nonce used by the transfer is cached, so the next state transition uses the following one
+    let nonce = sdk
+        .get_identity_nonce(identity.id(), true, None)
+        .await
+        .expect("next nonce");
+    assert_eq!(nonce, 6);
+}
@@ -0,0 +1,31 @@
+use dash_sdk::platform::dashpay::Profile;
+
+/// Given profiles, when I validate them, then only profiles allowed by the contract are valid.
+#[test]
+fn test_dashpay_profile_validation() {
+    let profile = Profile {
+        display_name: Some("Alice".to_string()),
+        public_message: Some("Hello".to_string()),
+        ..Default::default()
+    };
+    assert!(profile.validate().is_ok());
+    assert_eq!(profile.to_properties().len(), 2);
+
+    let too_long = Profile {
+        display_name: Some("a".repeat(26)),
+        ..Default::default()
+    };
+    assert!(too_long.validate().is_err());
+
+    let empty = Profile {
+        public_message: Some(String::new()),
+        ..Default::default()
+    };
+    assert!(empty.validate().is_err());
+
+    let avatar_hash_without_url = Profile {
+        avatar_hash: Some([1u8; 32]),
+        ..Default::default()
+    };
+    assert!(avatar_hash_without_url.validate().is_err());
+}
@@ -0,0 +1,144 @@
+use super::common::{expect_broadcast, mock_data_contract, mock_document_type, MockSigner};
+use dapi_grpc::platform::v0::StateTransitionBroadcastError;
+use dash_sdk::platform::transition::document_batch::DocumentBatchBuilder;
+use dash_sdk::{Error, Sdk};
+use dpp::data_contract::accessors::v0::DataContractV0Getters;
+use dpp::data_contract::document_type::random_document::CreateRandomDocument;
+use dpp::data_contract::DataContract;
+use dpp::document::{Document, DocumentV0Setters};
+use dpp::identity::identity_public_key::v0::IdentityPublicKeyV0;
+use dpp::identity::{IdentityPublicKey, KeyType, Purpose, SecurityLevel};
+use dpp::platform_value::BinaryData;
+use dpp::prelude::Identifier;
+use dpp::state_transition::documents_batch_transition::accessors::DocumentsBatchTransitionAccessorsV0;
+use dpp::state_transition::documents_batch_transition::document_transition::DocumentTransitionV0Methods;
+use dpp::state_transition::StateTransition;
+use drive_proof_verifier::types::IdentityContractNonceFetcher;
+
+const DOCUMENT_TYPE_NAME: &str = "document_type_name";
+
+fn owner_key() -> IdentityPublicKey {
+    IdentityPublicKeyV0 {
+        id: 1,
+        purpose: Purpose::AUTHENTICATION,
+        security_level: SecurityLevel::HIGH,
+        contract_bounds: None,
+        key_type: KeyType::ECDSA_SECP256K1,
+        read_only: false,
+        data: BinaryData::new(vec![2; 33]),
+        disabled_at: None,
+    }
+    .into()
+}
+
+fn document(data_contract: &DataContract, owner_id: Identifier, seed: u64) -> Document {
+    let mut document = data_contract
+        .document_type_for_name(DOCUMENT_TYPE_NAME)
+        .expect("document type")
+        .random_document(Some(seed), dpp::version::PlatformVersion::latest())
+        .expect("random document");
+    document.set_owner_id(owner_id);
+    document.set_revision(Some(1));
+
+    document
+}
+
+/// Batch creating, replacing and deleting a document
+fn builder(data_contract: &DataContract, owner_id: Identifier) -> DocumentBatchBuilder {
+    DocumentBatchBuilder::new(data_contract.clone(), owner_id)
+        .create_document(
+            document(data_contract, owner_id, 1),
+            DOCUMENT_TYPE_NAME,
+            [1; 32],
+        )
+        .replace_document(document(data_contract, owner_id, 2), DOCUMENT_TYPE_NAME)
+        .delete_document(document(data_contract, owner_id, 3), DOCUMENT_TYPE_NAME)
+}
+
+async fn mock_sdk(data_contract: &DataContract, owner_id: Identifier) -> Sdk {
+    let mut sdk = Sdk::new_mock();
+    sdk.mock()
+        .expect_fetch(
+            (owner_id, data_contract.id()),
+            Some(IdentityContractNonceFetcher(4)),
+        )
+        .await
+        .expect("expect identity contract nonce");
+
+    sdk
+}
+
+/// Given a protocol version allowing one transition per batch, when I sign a batch of three
+/// actions, then I get three batch transitions with consecutive identity contract nonces, in the
+/// order of actions.
+#[tokio::test]
+async fn test_document_batch_split_by_protocol_limit() {
+    let owner_id = Identifier::new([1; 32]);
+    let data_contract = mock_data_contract(Some(&mock_document_type()));
+    let sdk = mock_sdk(&data_contract, owner_id).await;
+    assert_eq!(
+        sdk.version()
+            .system_limits
+            .max_transitions_in_documents_batch,
+        1
+    );
+
+    let state_transitions = builder(&data_contract, owner_id)
+        .sign(&sdk, &owner_key(), &MockSigner, None)
+        .await
+        .expect("sign batch");
+
+    assert_eq!(state_transitions.len(), 3);
+    for (state_transition, expected_nonce) in state_transitions.iter().zip(5..) {
+        let StateTransition::DocumentsBatch(batch) = state_transition else {
+            panic!("expected documents batch, got {:?}", state_transition);
+        };
+        assert_eq!(batch.transitions().len(), 1);
+        assert_eq!(
+            batch.transitions()[0].identity_contract_nonce(),
+            expected_nonce
+        );
+    }
+}
+
+/// Given a batch split into many batch transitions, when the first one is rejected by Platform,
+/// then the error is returned and the remaining ones are not broadcast.
+#[tokio::test]
+async fn test_document_batch_stops_on_rejection() {
+    let owner_id = Identifier::new([1; 32]);
+    let data_contract = mock_data_contract(Some(&mock_document_type()));
+
+    // signing is deterministic, so the same transitions are signed by the tested SDK
+    let expected = builder(&data_contract, owner_id)
+        .sign(
+            &mock_sdk(&data_contract, owner_id).await,
+            &owner_key(),
+            &MockSigner,
+            None,
+        )
+        .await
+        .expect("sign batch");
+
+    let mut sdk = mock_sdk(&data_contract, owner_id).await;
+    expect_broadcast(
+        &mut sdk,
+        &expected[0],
+        Some(StateTransitionBroadcastError {
+            code: 40500,
+            message: "document already exists".to_string(),
+            data: vec![],
+        }),
+    )
+    .await;
+
+    let error = builder(&data_contract, owner_id)
+        .broadcast_and_wait(&sdk, &owner_key(), &MockSigner, None)
+        .await
+        .expect_err("batch rejected");
+
+    assert!(
+        matches!(error, Error::DapiClientError(ref message) if message.contains("40500")),
+        "{:?}",
+        error
+    );
+}
@@ -0,0 +1,173 @@
+use super::common::{expect_broadcast, mock_document_type, MockSigner};
+use dash_sdk::platform::transition::delete_document::DeleteDocument;
+use dash_sdk::platform::transition::replace_document::ReplaceDocument;
+use dash_sdk::{Error, Sdk};
+use dpp::data_contract::document_type::accessors::DocumentTypeV0Getters;
+use dpp::data_contract::document_type::random_document::CreateRandomDocument;
+use dpp::data_contract::document_type::DocumentType;
+use dpp::document::{Document, DocumentV0Getters, DocumentV0Setters};
+use dpp::identity::identity_public_key::v0::IdentityPublicKeyV0;
+use dpp::identity::{IdentityPublicKey, KeyType, Purpose, SecurityLevel};
+use dpp::platform_value::{platform_value, BinaryData};
+use dpp::prelude::Identifier;
+use dpp::state_transition::documents_batch_transition::methods::v0::DocumentsBatchTransitionMethodsV0;
+use dpp::state_transition::documents_batch_transition::DocumentsBatchTransition;
+use dpp::version::PlatformVersion;
+use drive_proof_verifier::types::IdentityContractNonceFetcher;
+
+/// Document type whose documents can be replaced and deleted
+fn mutable_document_type() -> DocumentType {
+    let schema = platform_value!({
+        "type": "object",
+        "properties": {
+            "a": {
+                "type": "string",
+                "maxLength": 10,
+                "position": 0
+            }
+        },
+        "additionalProperties": false,
+    });
+
+    DocumentType::try_from_schema(
+        Identifier::new([7; 32]),
+        "note",
+        schema,
+        None,
+        false,
+        true,
+        true,
+        true,
+        &mut vec![],
+        PlatformVersion::latest(),
+    )
+    .expect("create document type")
+}
+
+fn document(document_type: &DocumentType) -> Document {
+    let mut document = document_type
+        .random_document(Some(1), PlatformVersion::latest())
+        .expect("random document");
+    document.set_revision(Some(1));
+
+    document
+}
+
+fn owner_key() -> IdentityPublicKey {
+    IdentityPublicKeyV0 {
+        id: 1,
+        purpose: Purpose::AUTHENTICATION,
+        security_level: SecurityLevel::HIGH,
+        contract_bounds: None,
+        key_type: KeyType::ECDSA_SECP256K1,
+        read_only: false,
+        data: BinaryData::new(vec![2; 33]),
+        disabled_at: None,
+    }
+    .into()
+}
+
+async fn expect_nonce(sdk: &mut Sdk, document: &Document, document_type: &DocumentType) {
+    sdk.mock()
+        .expect_fetch(
+            (document.owner_id(), document_type.data_contract_id()),
+            Some(IdentityContractNonceFetcher(4)),
+        )
+        .await
+        .expect("expect identity contract nonce");
+}
+
+/// Given a document of an immutable document type, when I replace or delete it, then the
+/// transition fails local validation and nothing is broadcast.
+#[tokio::test]
+async fn test_document_immutable_type() {
+    let sdk = Sdk::new_mock();
+    let document_type = mock_document_type();
+    let document = document(&document_type);
+
+    let error = document
+        .replace_on_platform(&sdk, document_type.clone(), owner_key(), &MockSigner, None)
+        .await
+        .expect_err("replace immutable document");
+    assert!(
+        matches!(error, Error::InvalidStateTransition(_)),
+        "{:?}",
+        error
+    );
+
+    let error = document
+        .delete_from_platform(&sdk, document_type, owner_key(), &MockSigner, None)
+        .await
+        .expect_err("delete document that can't be deleted");
+    assert!(
+        matches!(error, Error::InvalidStateTransition(_)),
+        "{:?}",
+        error
+    );
+}
+
+/// Given a document, when I replace it, then the document with the next revision is broadcast with
+/// the next identity contract nonce.
+#[tokio::test]
+async fn test_replace_document_broadcast() {
+    let mut sdk = Sdk::new_mock();
+    let document_type = mutable_document_type();
+    let document = document(&document_type);
+    expect_nonce(&mut sdk, &document, &document_type).await;
+
+    let mut replacement = document.clone();
+    replacement.set_revision(Some(2));
+    let expected = DocumentsBatchTransition::new_document_replacement_transition_from_document(
+        replacement,
+        document_type.as_ref(),
+        &owner_key(),
+        5,
+        0,
+        &MockSigner,
+        sdk.version(),
+        None,
+        None,
+        None,
+    )
+    .expect("replacement transition");
+    expect_broadcast(&mut sdk, &expected, None).await;
+
+    let state_transition = document
+        .replace_on_platform(&sdk, document_type, owner_key(), &MockSigner, None)
+        .await
+        .expect("broadcast replacement");
+
+    assert_eq!(state_transition, expected);
+}
+
+/// Given a document, when I delete it, then the deletion is broadcast with the next identity
+/// contract nonce.
+#[tokio::test]
+async fn test_delete_document_broadcast() {
+    let mut sdk = Sdk::new_mock();
+    let document_type = mutable_document_type();
+    let document = document(&document_type);
+    expect_nonce(&mut sdk, &document, &document_type).await;
+
+    let expected = DocumentsBatchTransition::new_document_deletion_transition_from_document(
+        document.clone(),
+        document_type.as_ref(),
+        &owner_key(),
+        5,
+        0,
+        &MockSigner,
+        sdk.version(),
+        None,
+        None,
+        None,
+    )
+    .expect("deletion transition");
+    expect_broadcast(&mut sdk, &expected, None).await;
+
+    let state_transition = document
+        .delete_from_platform(&sdk, document_type, owner_key(), &MockSigner, None)
+        .await
+        .expect("broadcast deletion");
+
+    assert_eq!(state_transition, expected);
+}
@@ -0,0 +1,62 @@
+use dash_sdk::platform::dpns::{
+    is_contested_label, is_valid_label, normalize_label, salted_domain_hash, split_name,
+    DASH_PARENT_DOMAIN,
+};
+use dpp::util::hash::hash_double;
+
+/// Given labels that look alike, when I normalize them, then I get the same homograph-safe label.
+#[test]
+fn test_dpns_normalize_label() {
+    assert_eq!(normalize_label("Alice"), "a11ce");
+    assert_eq!(normalize_label("a1ice"), "a11ce");
+    assert_eq!(normalize_label("ALlCE"), normalize_label("alice"));
+    assert_eq!(normalize_label("B0b-Oil"), "b0b-011");
+}
+
+/// Given labels, when I validate them, then only labels allowed by the DPNS contract are valid.
+#[test]
+fn test_dpns_label_validation() {
+    assert!(is_valid_label("alice"));
+    assert!(is_valid_label("Alice-2"));
+    assert!(is_valid_label(&"a".repeat(63)));
+
+    assert!(!is_valid_label("al"));
+    assert!(!is_valid_label(&"a".repeat(64)));
+    assert!(!is_valid_label("-alice"));
+    assert!(!is_valid_label("alice-"));
+    assert!(!is_valid_label("ali.ce"));
+    assert!(!is_valid_label("alicé"));
+}
+
+/// Given labels, when I check if they are contested, then short labels without digits other
+/// than 0 and 1 are contested.
+#[test]
+fn test_dpns_contested_label() {
+    assert!(is_contested_label("alice"));
+    assert!(is_contested_label("Bob-01"));
+    assert!(!is_contested_label("alice2"));
+    assert!(!is_contested_label(&"a".repeat(20)));
+}
+
+/// Given a salt and a label, when I compute the salted domain hash, then it matches the hash
+/// verified by the DPNS data trigger.
+#[test]
+fn test_dpns_salted_domain_hash() {
+    let salt = [5u8; 32];
+
+    let mut expected = salt.to_vec();
+    expected.extend_from_slice(b"a11ce.dash");
+
+    assert_eq!(
+        salted_domain_hash(&salt, "Alice", DASH_PARENT_DOMAIN),
+        hash_double(expected)
+    );
+}
+
+/// Given names with and without parent domain, when I split them, then names without parent
+/// domain belong to `dash`.
+#[test]
+fn test_dpns_split_name() {
+    assert_eq!(split_name("alice.dash"), ("alice", "dash"));
+    assert_eq!(split_name("alice"), ("alice", DASH_PARENT_DOMAIN));
+}
@@ -0,0 +1,80 @@
+use dash_sdk::platform::transition::estimate_fee::EstimateFee;
+use dash_sdk::Sdk;
+use dpp::fee::default_costs::KnownCostItem;
+use dpp::prelude::Identifier;
+use dpp::serialization::PlatformSerializable;
+use dpp::state_transition::identity_credit_transfer_transition::v0::IdentityCreditTransferTransitionV0;
+use dpp::state_transition::identity_credit_transfer_transition::IdentityCreditTransferTransition;
+use dpp::state_transition::StateTransition;
+
+fn transfer_transition(user_fee_increase: u16) -> StateTransition {
+    IdentityCreditTransferTransition::V0(IdentityCreditTransferTransitionV0 {
+        identity_id: Identifier::new([1u8; 32]),
+        recipient_id: Identifier::new([2u8; 32]),
+        amount: 1000,
+        nonce: 1,
+        user_fee_increase,
+        ..Default::default()
+    })
+    .into()
+}
+
+/// Given a credit transfer state transition, when I estimate its fee,
+/// then the min fee floor comes from the fee version and user fee increase raises the processing fee.
+#[test]
+fn test_estimate_fee_credit_transfer() {
+    let sdk = Sdk::new_mock();
+
+    let estimate = transfer_transition(0)
+        .estimate_fee(&sdk)
+        .expect("estimate fee");
+    assert_eq!(
+        estimate.min_fee,
+        sdk.version()
+            .fee_version
+            .state_transition_min_fees
+            .credit_transfer
+    );
+    assert!(estimate.processing_fee > 0);
+    assert_eq!(
+        estimate.total_fee(),
+        estimate.storage_fee + estimate.processing_fee
+    );
+    assert!(estimate.required_balance() >= estimate.min_fee);
+
+    let increased = transfer_transition(100)
+        .estimate_fee(&sdk)
+        .expect("estimate fee with user fee increase");
+    assert_eq!(increased.storage_fee, estimate.storage_fee);
+    assert_eq!(increased.processing_fee, estimate.processing_fee * 2);
+}
+
+/// Given a credit transfer state transition, when I estimate its fee, then it's equal to the fee
+/// calculated by hand from the fee version: no storage fee, as balances and nonce are only replaced,
+/// and processing of the state transition, signature, balance fetch and the three replaced items.
+#[test]
+fn test_estimate_fee_credit_transfer_known_fee() {
+    let sdk = Sdk::new_mock();
+    let fee_version = &sdk.version().fee_version;
+    let transition = transfer_transition(0);
+
+    let serialized_len = transition
+        .serialize_to_bytes()
+        .expect("serialize transition")
+        .len();
+    // key + value + storage flags + hash of sender balance, recipient balance and nonce
+    let replaced_bytes = 2 * (32 + 6 + 2 + 32) + (1 + 8 + 2 + 32);
+
+    let expected_processing_fee = KnownCostItem::SingleSHA256(serialized_len.div_ceil(64))
+        .lookup_cost(fee_version)
+        + fee_version.storage.non_storage_load_credit_per_byte * serialized_len as u64
+        + KnownCostItem::FetchSingleIdentityKeyProcessingCost.lookup_cost(fee_version)
+        + KnownCostItem::VerifySignatureEcdsaSecp256k1.lookup_cost(fee_version)
+        + KnownCostItem::FetchIdentityBalanceProcessingCost.lookup_cost(fee_version)
+        + replaced_bytes * fee_version.storage.storage_processing_credit_per_byte
+        + 3 * fee_version.storage.storage_seek_cost;
+
+    let estimate = transition.estimate_fee(&sdk).expect("estimate fee");
+    assert_eq!(estimate.storage_fee, 0);
+    assert_eq!(estimate.processing_fee, expected_processing_fee);
+}
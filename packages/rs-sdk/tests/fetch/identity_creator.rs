@@ -0,0 +1,227 @@
+use super::common::MockSigner;
+use dash_sdk::core::asset_lock::{build_asset_lock_transaction, Utxo, DUST_THRESHOLD};
+use dash_sdk::platform::transition::identity_creator::{IdentityCreator, IdentityCreatorState};
+use dash_sdk::Sdk;
+use dpp::dashcore::blockdata::script::Instruction;
+use dpp::dashcore::hashes::Hash;
+use dpp::dashcore::secp256k1::{ecdsa, Message, Secp256k1};
+use dpp::dashcore::sighash::{EcdsaSighashType, SighashCache};
+use dpp::dashcore::transaction::special_transaction::TransactionPayload;
+use dpp::dashcore::{Network, OutPoint, PrivateKey, ScriptBuf, Transaction, TxOut, Txid};
+use dpp::identity::accessors::IdentitySettersV0;
+use dpp::identity::state_transition::asset_lock_proof::chain::ChainAssetLockProof;
+use dpp::prelude::{AssetLockProof, Identifier, Identity};
+use dpp::version::PlatformVersion;
+
+fn private_key(seed: u8) -> PrivateKey {
+    PrivateKey::from_slice(&[seed; 32], Network::Testnet).expect("valid private key")
+}
+
+fn utxo(key: &PrivateKey, vout: u32, value: u64) -> Utxo {
+    let secp = Secp256k1::new();
+    Utxo {
+        out_point: OutPoint {
+            txid: Txid::from_byte_array([7u8; 32]),
+            vout,
+        },
+        tx_out: TxOut {
+            value,
+            script_pubkey: ScriptBuf::new_p2pkh(&key.public_key(&secp).pubkey_hash()),
+        },
+    }
+}
+
+fn asset_lock_transaction(amount: u64, utxo_values: &[u64]) -> Transaction {
+    let funding_key = private_key(1);
+    let asset_lock_key = private_key(2);
+    let utxos = utxo_values
+        .iter()
+        .enumerate()
+        .map(|(vout, value)| utxo(&funding_key, vout as u32, *value))
+        .collect::<Vec<_>>();
+
+    build_asset_lock_transaction(
+        &utxos,
+        &funding_key,
+        &asset_lock_key.public_key(&Secp256k1::new()),
+        amount,
+        1,
+    )
+    .expect("build asset lock transaction")
+}
+
+/// Given UTXOs worth more than the asset lock amount, when I build an asset lock transaction,
+/// then it locks the amount in a credit output, returns the change and pays a positive fee.
+#[test]
+fn test_asset_lock_transaction_with_change() {
+    let tx = asset_lock_transaction(100_000, &[60_000, 60_000]);
+
+    assert_eq!(tx.input.len(), 2);
+    assert!(tx.input.iter().all(|input| !input.script_sig.is_empty()));
+
+    assert_eq!(tx.output.len(), 2);
+    assert!(tx.output[0].script_pubkey.is_op_return());
+    assert_eq!(tx.output[0].value, 100_000);
+
+    let change = tx.output[1].value;
+    assert!(change >= DUST_THRESHOLD);
+    assert!(change < 20_000, "fee must be deducted from change");
+
+    let Some(TransactionPayload::AssetLockPayloadType(payload)) = &tx.special_transaction_payload
+    else {
+        panic!("asset lock payload expected");
+    };
+    assert_eq!(payload.credit_outputs.len(), 1);
+    assert_eq!(payload.credit_outputs[0].value, 100_000);
+}
+
+/// Given an asset lock transaction, when I check its inputs, then each input script pushes a
+/// `SIGHASH_ALL` signature of the legacy signature hash and the funding public key.
+#[test]
+fn test_asset_lock_transaction_signatures() {
+    let tx = asset_lock_transaction(100_000, &[60_000, 60_000]);
+    let secp = Secp256k1::new();
+    let funding_public_key = private_key(1).public_key(&secp);
+    let script_pubkey = ScriptBuf::new_p2pkh(&funding_public_key.pubkey_hash());
+    let sighash_cache = SighashCache::new(&tx);
+
+    for (index, input) in tx.input.iter().enumerate() {
+        let pushes = input
+            .script_sig
+            .instructions()
+            .map(|instruction| match instruction.expect("valid script") {
+                Instruction::PushBytes(bytes) => bytes.as_bytes().to_vec(),
+                Instruction::Op(op) => panic!("unexpected opcode {}", op),
+            })
+            .collect::<Vec<_>>();
+        let [signature, public_key] = pushes.as_slice() else {
+            panic!("signature and public key expected");
+        };
+        assert_eq!(public_key, &funding_public_key.to_bytes());
+
+        let (sighash_type, der) = signature.split_last().expect("signature");
+        assert_eq!(*sighash_type as u32, EcdsaSighashType::All.to_u32());
+
+        let sighash = sighash_cache
+            .legacy_signature_hash(index, &script_pubkey, EcdsaSighashType::All.to_u32())
+            .expect("signature hash");
+        let message = Message::from_slice(sighash.as_byte_array()).expect("message");
+        let signature = ecdsa::Signature::from_der(der).expect("DER signature");
+        secp.verify_ecdsa(&message, &signature, &funding_public_key.inner)
+            .expect("valid signature");
+    }
+}
+
+/// Given UTXOs that leave only dust after the asset lock amount and fee, when I build an asset lock
+/// transaction, then the dust is added to the fee instead of creating a change output.
+#[test]
+fn test_asset_lock_transaction_without_dust_change() {
+    let tx = asset_lock_transaction(100_000, &[100_500]);
+
+    assert_eq!(tx.output.len(), 1);
+}
+
+/// Given UTXOs worth less than the asset lock amount, when I build an asset lock transaction,
+/// then I get an error.
+#[test]
+fn test_asset_lock_transaction_insufficient_funds() {
+    let funding_key = private_key(1);
+    let result = build_asset_lock_transaction(
+        &[utxo(&funding_key, 0, 1_000)],
+        &funding_key,
+        &private_key(2).public_key(&Secp256k1::new()),
+        100_000,
+        1,
+    );
+
+    assert!(result.is_err());
+}
+
+/// Given identity creator state, when I serialize and deserialize it, then I get the same state.
+#[test]
+fn test_identity_creator_state_round_trip() {
+    let transaction = asset_lock_transaction(100_000, &[200_000]);
+
+    let states = [
+        IdentityCreatorState::TransactionSigned(transaction.clone()),
+        IdentityCreatorState::TransactionBroadcast(transaction.clone()),
+        IdentityCreatorState::AssetLockProofReceived {
+            transaction: transaction.clone(),
+            asset_lock_proof: AssetLockProof::Chain(ChainAssetLockProof {
+                core_chain_locked_height: 1000,
+                out_point: OutPoint {
+                    txid: transaction.txid(),
+                    vout: 0,
+                },
+            }),
+        },
+        IdentityCreatorState::IdentityCreated {
+            transaction: transaction.clone(),
+            identity_id: Identifier::new([3u8; 32]),
+        },
+    ];
+
+    for state in states {
+        let bytes = state.to_bytes().expect("serialize state");
+        let decoded = IdentityCreatorState::from_bytes(&bytes).expect("deserialize state");
+        assert_eq!(decoded, state);
+    }
+}
+
+/// Given a saved state with a received asset lock proof, when the identity already exists on
+/// Platform, then the creator returns it without broadcasting the identity create transition again.
+#[tokio::test]
+async fn test_identity_creator_resume_existing_identity() {
+    let mut sdk = Sdk::new_mock();
+    let transaction = asset_lock_transaction(100_000, &[200_000]);
+    let asset_lock_proof = AssetLockProof::Chain(ChainAssetLockProof {
+        core_chain_locked_height: 1000,
+        out_point: OutPoint {
+            txid: transaction.txid(),
+            vout: 0,
+        },
+    });
+    let identity_id = asset_lock_proof
+        .create_identifier()
+        .expect("identity identifier");
+
+    let state_file = std::env::temp_dir().join(format!(
+        "dash-sdk-identity-creator-{}.state",
+        Identifier::random().to_string(dpp::platform_value::string_encoding::Encoding::Hex)
+    ));
+    IdentityCreatorState::AssetLockProofReceived {
+        transaction: transaction.clone(),
+        asset_lock_proof,
+    }
+    .save(&state_file)
+    .expect("save state");
+
+    let mut identity =
+        Identity::random_identity(1, Some(3), PlatformVersion::latest()).expect("random identity");
+    identity.set_id(identity_id);
+    sdk.mock()
+        .expect_fetch(identity_id, Some(identity.clone()))
+        .await
+        .expect("expect identity");
+
+    // broadcast of the identity create transition is not expected, so it would fail the test
+    let created = IdentityCreator::new(&sdk, identity.clone(), private_key(1), &MockSigner)
+        .with_state_file(&state_file)
+        .create()
+        .await
+        .expect("resume identity creation");
+    assert_eq!(created, identity);
+
+    let state = IdentityCreatorState::load(&state_file)
+        .expect("load state")
+        .expect("state saved");
+    assert_eq!(
+        state,
+        IdentityCreatorState::IdentityCreated {
+            transaction,
+            identity_id,
+        }
+    );
+
+    std::fs::remove_file(state_file).ok();
+}
@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 
 use super::common::{mock_data_contract, mock_document_type};
+use dapi_grpc::platform::v0::get_documents_request::get_documents_request_v0::Start;
 use dash_sdk::{
     platform::{DocumentQuery, FetchMany},
     Sdk,
@@ -14,6 +15,7 @@ use dpp::{
     },
     document::{Document, DocumentV0Getters},
 };
+use futures::TryStreamExt;
 
 /// Given some data contract, document type and 1 document of this type, when I request multiple documents, I get that
 /// document.
@@ -59,3 +61,48 @@ async fn test_mock_document_fetch_many() {
     assert!(!retrieved.is_empty());
     assert_eq!(retrieved, expected);
 }
+
+/// Given some data contract and 2 documents returned in separate pages, when I stream documents with a limit of 2,
+/// I get both documents and no more pages are requested.
+#[tokio::test]
+async fn test_mock_document_fetch_many_stream() {
+    let mut sdk = Sdk::new_mock();
+    let document_type: DocumentType = mock_document_type();
+    let data_contract = mock_data_contract(Some(&document_type));
+
+    let doc1 = document_type
+        .random_document(None, sdk.version())
+        .expect("document 1 should be created");
+    let doc2 = document_type
+        .random_document(None, sdk.version())
+        .expect("document 2 should be created");
+
+    let mut query =
+        DocumentQuery::new(data_contract, document_type.name()).expect("create document query");
+    query.limit = 1;
+
+    let mut second_page_query = query.clone();
+    second_page_query.start = Some(Start::StartAfter(doc1.id().to_vec()));
+
+    sdk.mock()
+        .expect_fetch_many(
+            query.clone(),
+            Some(BTreeMap::from([(doc1.id(), Some(doc1.clone()))])),
+        )
+        .await
+        .unwrap();
+    sdk.mock()
+        .expect_fetch_many(
+            second_page_query,
+            Some(BTreeMap::from([(doc2.id(), Some(doc2.clone()))])),
+        )
+        .await
+        .unwrap();
+
+    let retrieved: Vec<Document> = Document::fetch_many_stream(&sdk, query, Some(2))
+        .try_collect()
+        .await
+        .expect("documents should be streamed");
+
+    assert_eq!(retrieved, vec![doc1, doc2]);
+}
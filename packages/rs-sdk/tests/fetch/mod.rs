@@ -5,22 +5,40 @@ compile_error!("tests require `mocks` feature to be enabled");
 #[cfg(not(any(feature = "network-testing", feature = "offline-testing")))]
 compile_error!("network-testing or offline-testing must be enabled for tests");
 
+mod address_discovery;
 #[cfg(feature = "mocks")]
 mod broadcast;
 mod common;
 mod config;
+mod consensus_error;
 mod contested_resource;
 mod contested_resource_identity_votes;
+mod contested_resource_overview;
 mod contested_resource_polls_by_ts;
 mod contested_resource_vote_state;
 mod contested_resource_voters;
+mod credit_transfer;
+mod dashpay;
 mod data_contract;
 mod document;
+mod document_batch;
+mod document_transitions;
+mod dpns;
 mod epoch;
+mod estimate_fee;
 mod identity;
 mod identity_contract_nonce;
+mod identity_creator;
 mod mock_fetch;
 mod mock_fetch_many;
+mod offline_signing;
+mod persistent_context_provider;
 mod prefunded_specialized_balance;
 mod protocol_version_vote_count;
 mod protocol_version_votes;
+mod session_replay;
+mod spv_context_provider;
+mod transition_tracker;
+mod update_contract;
+mod update_identity;
+mod vote_batch;
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use dash_sdk::platform::transition::offline::UnsignedTransition;
+use dash_sdk::Error;
+use dpp::dashcore::secp256k1::Secp256k1;
+use dpp::dashcore::{signer, Network, PrivateKey};
+use dpp::identity::identity_public_key::accessors::v0::IdentityPublicKeyGettersV0;
+use dpp::identity::identity_public_key::v0::IdentityPublicKeyV0;
+use dpp::identity::signer::Signer;
+use dpp::identity::{IdentityPublicKey, KeyID, KeyType, Purpose, SecurityLevel};
+use dpp::platform_value::BinaryData;
+use dpp::prelude::Identifier;
+use dpp::serialization::PlatformSerializable;
+use dpp::state_transition::identity_credit_transfer_transition::v0::IdentityCreditTransferTransitionV0;
+use dpp::state_transition::identity_credit_transfer_transition::IdentityCreditTransferTransition;
+use dpp::state_transition::identity_update_transition::accessors::IdentityUpdateTransitionAccessorsV0;
+use dpp::state_transition::identity_update_transition::v0::IdentityUpdateTransitionV0;
+use dpp::state_transition::identity_update_transition::IdentityUpdateTransition;
+use dpp::state_transition::public_key_in_creation::accessors::{
+    IdentityPublicKeyInCreationV0Getters, IdentityPublicKeyInCreationV0Setters,
+};
+use dpp::state_transition::StateTransition;
+use dpp::ProtocolError;
+
+/// Signer holding ECDSA private keys by key id.
+#[derive(Debug, Default)]
+struct KeySigner(BTreeMap<KeyID, PrivateKey>);
+
+impl KeySigner {
+    /// Add a private key derived from `seed` and return matching public key with `id`.
+    fn add_key(
+        &mut self,
+        id: KeyID,
+        purpose: Purpose,
+        security_level: SecurityLevel,
+        seed: u8,
+    ) -> IdentityPublicKey {
+        let private_key =
+            PrivateKey::from_slice(&[seed; 32], Network::Testnet).expect("private key");
+        let data = private_key.public_key(&Secp256k1::new()).to_bytes();
+        self.0.insert(id, private_key);
+
+        IdentityPublicKeyV0 {
+            id,
+            purpose,
+            security_level,
+            contract_bounds: None,
+            key_type: KeyType::ECDSA_SECP256K1,
+            read_only: false,
+            data: BinaryData::new(data),
+            disabled_at: None,
+        }
+        .into()
+    }
+}
+
+impl Signer for KeySigner {
+    fn sign(
+        &self,
+        identity_public_key: &IdentityPublicKey,
+        data: &[u8],
+    ) -> Result<BinaryData, ProtocolError> {
+        let private_key = self.0.get(&identity_public_key.id()).ok_or_else(|| {
+            ProtocolError::Generic(format!("unknown key {}", identity_public_key.id()))
+        })?;
+        let signature = signer::sign(data, &private_key.inner.secret_bytes())?;
+
+        Ok(signature.to_vec().into())
+    }
+}
+
+fn transfer_transition(amount: u64) -> StateTransition {
+    IdentityCreditTransferTransition::V0(IdentityCreditTransferTransitionV0 {
+        identity_id: Identifier::new([1u8; 32]),
+        recipient_id: Identifier::new([2u8; 32]),
+        amount,
+        nonce: 3,
+        ..Default::default()
+    })
+    .into()
+}
+
+/// Signer with a transfer key with id 2, and the key
+fn transfer_signer() -> (KeySigner, IdentityPublicKey) {
+    let mut signer = KeySigner::default();
+    let key = signer.add_key(2, Purpose::TRANSFER, SecurityLevel::CRITICAL, 2);
+
+    (signer, key)
+}
+
+/// Given an unsigned transition exported to bytes, when I sign it offline and import the signed transition,
+/// then I get a state transition signed over the exported signable bytes.
+#[test]
+fn test_offline_signing_round_trip() {
+    let (signer, key) = transfer_signer();
+    let unsigned = UnsignedTransition::new(transfer_transition(1000), key).expect("unsigned");
+    let exported = unsigned.to_bytes().expect("export unsigned");
+
+    // offline
+    let loaded = UnsignedTransition::from_bytes(&exported).expect("load unsigned");
+    assert_eq!(loaded, unsigned);
+    let signed = loaded.sign_to_bytes(&signer).expect("sign offline");
+
+    // online
+    let state_transition = unsigned.import_signed(&signed).expect("import signed");
+    let expected_signature = signer
+        .sign(unsigned.identity_public_key(), unsigned.signable_bytes())
+        .expect("sign");
+    assert_eq!(state_transition.signature(), &expected_signature);
+    assert_eq!(state_transition.signature_public_key_id(), Some(2));
+}
+
+/// Given an unsigned transition, when I import a signed transition that differs from it or is not signed,
+/// then an error is returned.
+#[test]
+fn test_offline_signing_rejects_mismatched_transition() {
+    let (signer, key) = transfer_signer();
+    let unsigned =
+        UnsignedTransition::new(transfer_transition(1000), key.clone()).expect("unsigned");
+
+    let other = UnsignedTransition::new(transfer_transition(2000), key)
+        .expect("other unsigned")
+        .sign_to_bytes(&signer)
+        .expect("sign other");
+    let result = unsigned.import_signed(&other);
+    assert!(matches!(result, Err(Error::Generic(_))), "{:?}", result);
+
+    let not_signed = unsigned
+        .state_transition()
+        .serialize_to_bytes()
+        .expect("serialize");
+    let result = unsigned.import_signed(&not_signed);
+    assert!(matches!(result, Err(Error::Generic(_))), "{:?}", result);
+}
+
+/// Given an unsigned transition, when I import a transition signed with another private key, then an
+/// error is returned.
+#[test]
+fn test_offline_signing_rejects_invalid_signature() {
+    let (_, key) = transfer_signer();
+    let unsigned = UnsignedTransition::new(transfer_transition(1000), key).expect("unsigned");
+
+    let mut other_signer = KeySigner::default();
+    other_signer.add_key(2, Purpose::TRANSFER, SecurityLevel::CRITICAL, 3);
+    let signed = unsigned
+        .sign_to_bytes(&other_signer)
+        .expect("sign with other key");
+
+    let result = unsigned.import_signed(&signed);
+    assert!(matches!(result, Err(Error::Generic(_))), "{:?}", result);
+}
+
+/// Given an unsigned identity update adding a key, when I sign it offline, then the added key is signed
+/// with its own private key, and a transition missing that signature is rejected on import.
+#[test]
+fn test_offline_signing_identity_update_signs_added_keys() {
+    let mut signer = KeySigner::default();
+    let master_key = signer.add_key(0, Purpose::AUTHENTICATION, SecurityLevel::MASTER, 4);
+    let new_key = signer.add_key(5, Purpose::AUTHENTICATION, SecurityLevel::HIGH, 5);
+
+    let state_transition: StateTransition =
+        IdentityUpdateTransition::V0(IdentityUpdateTransitionV0 {
+            identity_id: Identifier::new([1u8; 32]),
+            revision: 2,
+            nonce: 3,
+            add_public_keys: vec![new_key.into()],
+            ..Default::default()
+        })
+        .into();
+    let unsigned = UnsignedTransition::new(state_transition, master_key).expect("unsigned");
+
+    let signed = unsigned.sign(&signer).expect("sign offline");
+    let StateTransition::IdentityUpdate(update) = &signed else {
+        panic!("expected identity update, got {:?}", signed);
+    };
+    assert!(!update.public_keys_to_add()[0].signature().is_empty());
+    unsigned
+        .import_signed(&signed.serialize_to_bytes().expect("serialize"))
+        .expect("import signed");
+
+    let mut missing_key_signature = signed;
+    if let StateTransition::IdentityUpdate(update) = &mut missing_key_signature {
+        update.public_keys_to_add_mut()[0].set_signature(BinaryData::default());
+    }
+    let result = unsigned.import_signed(
+        &missing_key_signature
+            .serialize_to_bytes()
+            .expect("serialize"),
+    );
+    assert!(matches!(result, Err(Error::Generic(_))), "{:?}", result);
+}
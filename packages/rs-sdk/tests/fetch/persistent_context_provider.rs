@@ -0,0 +1,133 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use super::common::{mock_data_contract, mock_document_type};
+use dash_sdk::{context_provider::PersistentContextProvider, platform::MockContextProvider};
+use dpp::{
+    data_contract::accessors::v0::{DataContractV0Getters, DataContractV0Setters},
+    prelude::{CoreBlockHeight, DataContract, Identifier},
+    version::PlatformVersion,
+};
+use drive_proof_verifier::{error::ContextProviderError, ContextProvider};
+
+/// Context provider that always returns the same data contract.
+struct StaticContextProvider(Arc<DataContract>);
+
+impl ContextProvider for StaticContextProvider {
+    fn get_quorum_public_key(
+        &self,
+        _quorum_type: u32,
+        _quorum_hash: [u8; 32],
+        _core_chain_locked_height: u32,
+    ) -> Result<[u8; 48], ContextProviderError> {
+        Err(ContextProviderError::Config(
+            "quorum public keys not available".to_string(),
+        ))
+    }
+
+    fn get_data_contract(
+        &self,
+        _id: &Identifier,
+    ) -> Result<Option<Arc<DataContract>>, ContextProviderError> {
+        Ok(Some(Arc::clone(&self.0)))
+    }
+
+    fn get_platform_activation_height(&self) -> Result<CoreBlockHeight, ContextProviderError> {
+        Ok(0)
+    }
+}
+
+fn temp_cache_dir() -> std::path::PathBuf {
+    std::env::temp_dir().join(format!(
+        "dash-sdk-context-cache-{}",
+        Identifier::random().to_string(dpp::platform_value::string_encoding::Encoding::Hex)
+    ))
+}
+
+/// Given a data contract stored by one instance of the provider, when I create another instance using the same
+/// directory, then the data contract is served from disk without asking the wrapped provider.
+#[test]
+fn test_persistent_context_provider_warm_start() {
+    let dir = temp_cache_dir();
+    let data_contract = mock_data_contract(Some(&mock_document_type()));
+
+    let provider =
+        PersistentContextProvider::new(MockContextProvider::new(), &dir, PlatformVersion::latest())
+            .expect("create provider");
+    provider
+        .put_data_contract(data_contract.clone())
+        .expect("store data contract");
+
+    // MockContextProvider without dump dir fails on every request, so everything must come from the cache
+    let restarted =
+        PersistentContextProvider::new(MockContextProvider::new(), &dir, PlatformVersion::latest())
+            .expect("create provider");
+    assert_eq!(restarted.warm_start().expect("warm start"), 1);
+
+    let retrieved = restarted
+        .get_data_contract(&data_contract.id())
+        .expect("get data contract")
+        .expect("data contract should be cached");
+    assert_eq!(retrieved.as_ref(), &data_contract);
+
+    restarted.invalidate_data_contract(&data_contract.id());
+    restarted
+        .get_data_contract(&data_contract.id())
+        .expect_err("invalidated data contract should be requested from the wrapped provider");
+
+    std::fs::remove_dir_all(dir).expect("remove cache dir");
+}
+
+/// Given a data contract cached more than an hour ago, when the contract was updated since then and
+/// I request it using default settings, then the new version is retrieved from the wrapped provider
+/// and cached.
+#[test]
+fn test_persistent_context_provider_refreshes_updated_data_contract() {
+    let dir = temp_cache_dir();
+    let data_contract = mock_data_contract(Some(&mock_document_type()));
+    let mut updated = data_contract.clone();
+    updated.increment_version();
+
+    let provider =
+        PersistentContextProvider::new(MockContextProvider::new(), &dir, PlatformVersion::latest())
+            .expect("create provider");
+    provider
+        .put_data_contract(data_contract.clone())
+        .expect("store data contract");
+
+    // age the cached file beyond the default time-to-live
+    let stored_at = SystemTime::now() - Duration::from_secs(2 * 3600);
+    for entry in std::fs::read_dir(&dir).expect("read cache dir") {
+        std::fs::File::options()
+            .write(true)
+            .open(entry.expect("cache dir entry").path())
+            .and_then(|file| file.set_modified(stored_at))
+            .expect("set modification time");
+    }
+
+    let restarted = PersistentContextProvider::new(
+        StaticContextProvider(Arc::new(updated.clone())),
+        &dir,
+        PlatformVersion::latest(),
+    )
+    .expect("create provider");
+
+    let retrieved = restarted
+        .get_data_contract(&data_contract.id())
+        .expect("get data contract")
+        .expect("data contract should be found");
+    assert_eq!(retrieved.version(), data_contract.version() + 1);
+    assert_eq!(retrieved.as_ref(), &updated);
+
+    // the new version is cached, so the expired one is not served after a restart
+    let restarted =
+        PersistentContextProvider::new(MockContextProvider::new(), &dir, PlatformVersion::latest())
+            .expect("create provider");
+    let retrieved = restarted
+        .get_data_contract(&data_contract.id())
+        .expect("get data contract")
+        .expect("data contract should be cached");
+    assert_eq!(retrieved.as_ref(), &updated);
+
+    std::fs::remove_dir_all(dir).expect("remove cache dir");
+}
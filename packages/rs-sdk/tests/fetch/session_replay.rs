@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use dapi_grpc::platform::v0::GetIdentityRequest;
+use dash_sdk::platform::Fetch;
+use dash_sdk::SdkBuilder;
+use dpp::identity::accessors::IdentityGettersV0;
+use dpp::prelude::Identity;
+use rs_dapi_client::session::{ReplayDapiClient, SessionEntry};
+use rs_dapi_client::DumpData;
+
+use super::{common::setup_logs, config::Config};
+
+/// Given a session recorded from the response of `test_identity_read`, when I replay it and fetch
+/// the identity, then the proof is verified and the identity is returned.
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_session_replay_fetch_identity() {
+    setup_logs();
+
+    let cfg = Config::new();
+    let id = cfg.existing_identity_id;
+    let vectors = cfg.dump_dir.join("test_identity_read");
+
+    let dump = std::fs::read_dir(&vectors)
+        .expect("read test vectors")
+        .map(|entry| entry.expect("test vector").path())
+        .find(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("msg_IdentityRequest"))
+        })
+        .expect("identity request test vector");
+    let (request, response) = DumpData::<GetIdentityRequest>::load(dump)
+        .expect("load test vector")
+        .deserialize();
+
+    let entry = SessionEntry::new(&request, &response).expect("session entry");
+    let replay = Arc::new(ReplayDapiClient::new(vec![entry]));
+
+    let sdk = SdkBuilder::new_mock()
+        .with_replay(Arc::clone(&replay))
+        .with_dump_dir(&vectors)
+        .build()
+        .expect("build replaying sdk");
+
+    let identity = Identity::fetch(&sdk, id)
+        .await
+        .expect("fetch identity")
+        .expect("found identity");
+    assert_eq!(identity.id(), id);
+
+    replay.verify().expect("whole session replayed");
+}
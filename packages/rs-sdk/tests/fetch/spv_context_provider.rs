@@ -0,0 +1,145 @@
+use dash_sdk::context_provider::{SpvCheckpoint, SpvContextProvider};
+use dpp::bls_signatures::PrivateKey;
+use dpp::dashcore::block::Header;
+use dpp::dashcore::blockdata::constants::genesis_block;
+use dpp::dashcore::consensus::serialize;
+use dpp::dashcore::hashes::{sha256d, Hash, HashEngine};
+use dpp::dashcore::Network;
+use drive_proof_verifier::ContextProvider;
+
+/// LLMQ type signing chain locks on regtest
+const LLMQ_TEST: u8 = 100;
+const QUORUM_HASH: [u8; 32] = [5; 32];
+
+fn quorum_key(seed: u8) -> PrivateKey {
+    let mut key = [0u8; 32];
+    key[31] = seed;
+    PrivateKey::from_bytes(&key, false).expect("bls private key")
+}
+
+/// Regtest genesis block as a checkpoint, with one chain lock quorum signing with `quorum_key(1)`
+fn checkpoint() -> SpvCheckpoint {
+    let public_key = quorum_key(1)
+        .g1_element()
+        .expect("bls public key")
+        .to_bytes();
+
+    SpvCheckpoint {
+        height: 0,
+        header: genesis_block(Network::Regtest).header,
+        chain_lock_quorums: vec![(QUORUM_HASH, public_key)],
+    }
+}
+
+/// Child of `parent` with valid or invalid proof of work, as requested
+fn child_header(parent: &Header, valid_pow: bool) -> Header {
+    let mut header = *parent;
+    header.prev_blockhash = parent.block_hash();
+    header.time += 1;
+
+    for nonce in 0.. {
+        header.nonce = nonce;
+        if header.validate_pow(header.target()).is_ok() == valid_pow {
+            return header;
+        }
+    }
+    unreachable!("nonce space exhausted")
+}
+
+/// Serialized chain lock of the block, signed by the quorum as described in DIP-8
+fn chain_lock(height: u32, header: &Header, signer: &PrivateKey) -> Vec<u8> {
+    let block_hash = header.block_hash().to_byte_array();
+
+    let mut engine = sha256d::Hash::engine();
+    engine.input(&[5]);
+    engine.input(b"clsig");
+    engine.input(&height.to_le_bytes());
+    let request_id = sha256d::Hash::from_engine(engine);
+
+    let mut engine = sha256d::Hash::engine();
+    engine.input(&[LLMQ_TEST]);
+    engine.input(&QUORUM_HASH);
+    engine.input(request_id.as_byte_array());
+    engine.input(&block_hash);
+    let message_digest = sha256d::Hash::from_engine(engine);
+
+    let mut chain_lock = height.to_le_bytes().to_vec();
+    chain_lock.extend_from_slice(&block_hash);
+    chain_lock.extend_from_slice(&signer.sign(message_digest.as_byte_array()).to_bytes());
+
+    chain_lock
+}
+
+/// Given an SPV context provider that was not synced yet, when I request a quorum public key,
+/// then an error is returned instead of an unverified key.
+#[test]
+fn test_spv_context_provider_not_synced() {
+    let provider = SpvContextProvider::new(Network::Regtest, checkpoint());
+
+    assert_eq!(provider.chain_locked_height(), 0);
+    provider
+        .get_quorum_public_key(1, [1u8; 32], 1000)
+        .expect_err("quorum public key should not be available");
+    provider
+        .get_platform_activation_height()
+        .expect_err("platform activation height is not configured");
+
+    let provider = provider.with_platform_activation_height(1000);
+    assert_eq!(
+        provider
+            .get_platform_activation_height()
+            .expect("activation height"),
+        1000
+    );
+}
+
+/// Given an SPV context provider, when I feed it with malformed data or headers not connected to the
+/// checkpoint, then errors are returned and no chain lock is accepted.
+#[test]
+fn test_spv_context_provider_rejects_invalid_data() {
+    let provider = SpvContextProvider::new(Network::Regtest, checkpoint());
+
+    provider
+        .process_masternode_list_diff(&[0u8; 16])
+        .expect_err("truncated masternode list diff");
+    provider
+        .process_block_headers(&[vec![0u8; 80]])
+        .expect_err("header does not connect to the checkpoint");
+    provider
+        .process_chain_lock(&[0u8; 10])
+        .expect_err("truncated chain lock");
+
+    assert_eq!(provider.chain_locked_height(), 0);
+}
+
+/// Given a header following the checkpoint, when its proof of work is invalid, then it is rejected.
+#[test]
+fn test_spv_context_provider_rejects_invalid_pow() {
+    let provider = SpvContextProvider::new(Network::Regtest, checkpoint());
+    let header = child_header(&checkpoint().header, false);
+
+    provider
+        .process_block_headers(&[serialize(&header)])
+        .expect_err("invalid proof of work");
+}
+
+/// Given headers following the checkpoint, when I receive a chain lock signed by an unknown key, then
+/// it is rejected, and a following chain lock signed by the checkpoint quorum is accepted.
+#[test]
+fn test_spv_context_provider_verifies_chain_lock_with_checkpoint_quorum() {
+    let provider = SpvContextProvider::new(Network::Regtest, checkpoint());
+    let header = child_header(&checkpoint().header, true);
+    provider
+        .process_block_headers(&[serialize(&header)])
+        .expect("valid header");
+
+    provider
+        .process_chain_lock(&chain_lock(1, &header, &quorum_key(2)))
+        .expect_err("chain lock signed by untrusted key");
+    assert_eq!(provider.chain_locked_height(), 0);
+
+    provider
+        .process_chain_lock(&chain_lock(1, &header, &quorum_key(1)))
+        .expect("chain lock signed by checkpoint quorum");
+    assert_eq!(provider.chain_locked_height(), 1);
+}
@@ -0,0 +1,113 @@
+use dapi_grpc::platform::v0::get_proofs_request;
+use dapi_grpc::platform::v0::get_proofs_request::get_proofs_request_v0::identity_request;
+use dash_sdk::platform::transition::tracker::TransitionTracker;
+use dash_sdk::platform::transition::TxId;
+use dash_sdk::Sdk;
+use dpp::prelude::Identifier;
+use dpp::state_transition::identity_credit_transfer_transition::v0::IdentityCreditTransferTransitionV0;
+use dpp::state_transition::identity_credit_transfer_transition::IdentityCreditTransferTransition;
+use dpp::state_transition::identity_update_transition::v0::IdentityUpdateTransitionV0;
+use dpp::state_transition::identity_update_transition::IdentityUpdateTransition;
+use dpp::state_transition::StateTransition;
+use drive_proof_verifier::types::IdentityNonceFetcher;
+
+fn credit_transfer(nonce: u64) -> StateTransition {
+    IdentityCreditTransferTransition::V0(IdentityCreditTransferTransitionV0 {
+        identity_id: Identifier::from_bytes(&[1u8; 32]).expect("identifier"),
+        recipient_id: Identifier::from_bytes(&[2u8; 32]).expect("identifier"),
+        amount: 1000,
+        nonce,
+        ..Default::default()
+    })
+    .into()
+}
+
+fn identity_update(nonce: u64) -> StateTransition {
+    IdentityUpdateTransition::V0(IdentityUpdateTransitionV0 {
+        identity_id: Identifier::from_bytes(&[1u8; 32]).expect("identifier"),
+        revision: 2,
+        nonce,
+        disable_public_keys: vec![1],
+        ..Default::default()
+    })
+    .into()
+}
+
+/// Mock SDK that returns `platform_nonce` as the identity nonce of identity `[1; 32]`
+async fn mock_sdk(platform_nonce: u64) -> Sdk {
+    let mut sdk = Sdk::new_mock();
+    sdk.mock()
+        .expect_fetch(
+            Identifier::from_bytes(&[1u8; 32]).expect("identifier"),
+            Some(IdentityNonceFetcher(platform_nonce)),
+        )
+        .await
+        .expect("expect identity nonce");
+
+    sdk
+}
+
+/// Given a credit transfer state transition, when I track it,
+/// then the tracker requests proofs of balances of both the sender and the recipient.
+#[test]
+fn test_transition_tracker_credit_transfer_proofs_request() {
+    let sdk = Sdk::new_mock();
+
+    let sender = Identifier::from_bytes(&[1u8; 32]).expect("identifier");
+    let recipient = Identifier::from_bytes(&[2u8; 32]).expect("identifier");
+    let state_transition: StateTransition =
+        IdentityCreditTransferTransition::V0(IdentityCreditTransferTransitionV0 {
+            identity_id: sender,
+            recipient_id: recipient,
+            amount: 1000,
+            nonce: 1,
+            ..Default::default()
+        })
+        .into();
+
+    let tracker = TransitionTracker::new(&sdk, state_transition.clone()).expect("create tracker");
+    assert_eq!(
+        tracker.tx_id(),
+        TxId::try_from(&state_transition).expect("tx id")
+    );
+
+    let request = tracker.proofs_request().expect("proofs request");
+    let Some(get_proofs_request::Version::V0(v0)) = request.version else {
+        panic!("unexpected request version");
+    };
+
+    assert!(v0.contracts.is_empty());
+    assert!(v0.documents.is_empty());
+    assert!(v0.votes.is_empty());
+    assert_eq!(v0.identities.len(), 2);
+    for (request, identity_id) in v0.identities.iter().zip([sender, recipient]) {
+        assert_eq!(request.identity_id, identity_id.to_vec());
+        assert_eq!(request.request_type, identity_request::Type::Balance as i32);
+    }
+}
+
+/// Given an identity update which nonce is marked as missing in the identity nonce stored on
+/// Platform, when I check its status, then it is not reported as executed and no proofs are requested.
+#[tokio::test]
+async fn test_transition_tracker_dropped_transition() {
+    // nonce 5 consumed, nonce 4 missing
+    let sdk = mock_sdk(5 | 1 << 40).await;
+
+    let tracker = TransitionTracker::new(&sdk, identity_update(4)).expect("create tracker");
+
+    let status = tracker.check().await.expect("check status");
+    assert!(status.is_none(), "{:?}", status);
+}
+
+/// Given a credit transfer which nonce was consumed, when I check its status and its result is not
+/// available, then it is not reported as executed, as the nonce could be consumed by another
+/// transition.
+#[tokio::test]
+async fn test_transition_tracker_replaced_transition() {
+    let sdk = mock_sdk(5).await;
+
+    let tracker = TransitionTracker::new(&sdk, credit_transfer(5)).expect("create tracker");
+
+    let status = tracker.check().await.expect("check status");
+    assert!(status.is_none(), "{:?}", status);
+}
@@ -0,0 +1,101 @@
+use super::common::{expect_broadcast, mock_data_contract, mock_document_type, MockSigner};
+use dash_sdk::platform::transition::update_contract::UpdateContract;
+use dash_sdk::{Error, Sdk};
+use dpp::data_contract::accessors::v0::{DataContractV0Getters, DataContractV0Setters};
+use dpp::identity::identity_public_key::v0::IdentityPublicKeyV0;
+use dpp::identity::{IdentityPublicKey, KeyType, PartialIdentity, Purpose, SecurityLevel};
+use dpp::platform_value::BinaryData;
+use dpp::prelude::Identifier;
+use dpp::state_transition::data_contract_update_transition::methods::DataContractUpdateTransitionMethodsV0;
+use dpp::state_transition::data_contract_update_transition::DataContractUpdateTransition;
+use drive_proof_verifier::types::IdentityContractNonceFetcher;
+
+fn owner_key() -> IdentityPublicKey {
+    IdentityPublicKeyV0 {
+        id: 1,
+        purpose: Purpose::AUTHENTICATION,
+        security_level: SecurityLevel::CRITICAL,
+        contract_bounds: None,
+        key_type: KeyType::ECDSA_SECP256K1,
+        read_only: false,
+        data: BinaryData::new(vec![2; 33]),
+        disabled_at: None,
+    }
+    .into()
+}
+
+/// Given a deployed data contract, when I update it with a contract owned by another identity,
+/// then the update fails local validation and nothing is broadcast.
+#[tokio::test]
+async fn test_update_contract_invalid_update() {
+    let mut sdk = Sdk::new_mock();
+    let existing = mock_data_contract(Some(&mock_document_type()));
+
+    sdk.mock()
+        .expect_fetch(existing.id(), Some(existing.clone()))
+        .await
+        .expect("expect data contract");
+
+    let mut updated = existing.clone();
+    updated.set_owner_id(Identifier::new([9; 32]));
+
+    let error = updated
+        .update_on_platform(&sdk, owner_key(), &MockSigner, None)
+        .await
+        .expect_err("invalid update");
+
+    assert!(
+        matches!(error, Error::InvalidStateTransition(ref errors) if errors.len() == 1),
+        "{:?}",
+        error
+    );
+}
+
+/// Given a deployed data contract, when I update it, then the next version of the contract is
+/// broadcast with the next identity contract nonce.
+#[tokio::test]
+async fn test_update_contract_broadcast() {
+    let mut sdk = Sdk::new_mock();
+    let existing = mock_data_contract(Some(&mock_document_type()));
+
+    sdk.mock()
+        .expect_fetch(existing.id(), Some(existing.clone()))
+        .await
+        .expect("expect data contract")
+        .expect_fetch(
+            (existing.owner_id(), existing.id()),
+            Some(IdentityContractNonceFetcher(4)),
+        )
+        .await
+        .expect("expect identity contract nonce");
+
+    let mut next_version = existing.clone();
+    next_version.set_version(existing.version() + 1);
+    let key = owner_key();
+    let expected = DataContractUpdateTransition::new_from_data_contract(
+        next_version,
+        &PartialIdentity {
+            id: existing.owner_id(),
+            loaded_public_keys: [(1, key.clone())].into(),
+            balance: None,
+            revision: None,
+            not_found_public_keys: Default::default(),
+        },
+        1,
+        5,
+        0,
+        &MockSigner,
+        sdk.version(),
+        None,
+    )
+    .expect("data contract update transition");
+    expect_broadcast(&mut sdk, &expected, None).await;
+
+    // version of the updated contract is set by the SDK
+    let state_transition = existing
+        .update_on_platform(&sdk, key, &MockSigner, None)
+        .await
+        .expect("broadcast update");
+
+    assert_eq!(state_transition, expected);
+}
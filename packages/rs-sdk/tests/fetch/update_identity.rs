@@ -0,0 +1,141 @@
+use super::common::{expect_broadcast, MockSigner};
+use dapi_grpc::platform::v0::StateTransitionBroadcastError;
+use dash_sdk::platform::transition::update_identity::UpdateIdentity;
+use dash_sdk::{Error, Sdk};
+use dpp::identity::accessors::{IdentityGettersV0, IdentitySettersV0};
+use dpp::identity::identity_public_key::v0::IdentityPublicKeyV0;
+use dpp::identity::{
+    Identity, IdentityPublicKey, IdentityV0, KeyID, KeyType, Purpose, SecurityLevel,
+};
+use dpp::platform_value::BinaryData;
+use dpp::prelude::Identifier;
+use dpp::state_transition::identity_update_transition::methods::IdentityUpdateTransitionMethodsV0;
+use dpp::state_transition::identity_update_transition::IdentityUpdateTransition;
+use drive_proof_verifier::types::IdentityNonceFetcher;
+
+const MASTER_KEY_ID: KeyID = 0;
+const HIGH_KEY_ID: KeyID = 1;
+const DISABLED_MASTER_KEY_ID: KeyID = 2;
+
+fn key(id: KeyID, security_level: SecurityLevel, disabled: bool) -> IdentityPublicKey {
+    IdentityPublicKeyV0 {
+        id,
+        purpose: Purpose::AUTHENTICATION,
+        security_level,
+        contract_bounds: None,
+        key_type: KeyType::ECDSA_SECP256K1,
+        read_only: false,
+        data: BinaryData::new(vec![id as u8 + 2; 33]),
+        disabled_at: disabled.then_some(1),
+    }
+    .into()
+}
+
+fn identity() -> Identity {
+    IdentityV0 {
+        id: Identifier::new([1; 32]),
+        public_keys: [
+            (
+                MASTER_KEY_ID,
+                key(MASTER_KEY_ID, SecurityLevel::MASTER, false),
+            ),
+            (HIGH_KEY_ID, key(HIGH_KEY_ID, SecurityLevel::HIGH, false)),
+            (
+                DISABLED_MASTER_KEY_ID,
+                key(DISABLED_MASTER_KEY_ID, SecurityLevel::MASTER, true),
+            ),
+        ]
+        .into(),
+        balance: 10_000,
+        revision: 1,
+    }
+    .into()
+}
+
+/// Given an identity, when I sign its update with a key that is not an enabled master key,
+/// then the update is rejected before anything is sent to Platform.
+#[tokio::test]
+async fn test_update_identity_requires_master_key() {
+    let sdk = Sdk::new_mock();
+    let identity = identity();
+
+    for key_id in [HIGH_KEY_ID, DISABLED_MASTER_KEY_ID, 10] {
+        let error = identity
+            .update_identity(
+                &sdk,
+                vec![],
+                vec![HIGH_KEY_ID],
+                Some(key_id),
+                &MockSigner,
+                None,
+            )
+            .await
+            .expect_err("update with non-master key");
+
+        assert!(matches!(error, Error::Generic(_)), "{:?}", error);
+    }
+}
+
+/// Given an identity, when I disable a key that doesn't belong to it, then the update is rejected
+/// before anything is sent to Platform.
+#[tokio::test]
+async fn test_update_identity_disable_unknown_key() {
+    let sdk = Sdk::new_mock();
+
+    let error = identity()
+        .update_identity(&sdk, vec![], vec![10], None, &MockSigner, None)
+        .await
+        .expect_err("disable unknown key");
+
+    assert!(matches!(error, Error::Generic(_)), "{:?}", error);
+}
+
+/// Given an identity, when I disable one of its keys and Platform rejects the update, then the
+/// update with the next revision and identity nonce, signed with the master key, is broadcast, and
+/// the rejection is returned.
+#[tokio::test]
+async fn test_update_identity_rejected() {
+    let mut sdk = Sdk::new_mock();
+    let identity = identity();
+
+    sdk.mock()
+        .expect_fetch(identity.id(), Some(IdentityNonceFetcher(4)))
+        .await
+        .expect("expect nonce");
+
+    let mut updated = identity.clone();
+    updated.bump_revision();
+    let expected = IdentityUpdateTransition::try_from_identity_with_signer(
+        &updated,
+        &MASTER_KEY_ID,
+        vec![],
+        vec![HIGH_KEY_ID],
+        5,
+        0,
+        &MockSigner,
+        sdk.version(),
+        None,
+    )
+    .expect("identity update transition");
+    expect_broadcast(
+        &mut sdk,
+        &expected,
+        Some(StateTransitionBroadcastError {
+            code: 40100,
+            message: "invalid revision".to_string(),
+            data: vec![],
+        }),
+    )
+    .await;
+
+    let error = identity
+        .update_identity(&sdk, vec![], vec![HIGH_KEY_ID], None, &MockSigner, None)
+        .await
+        .expect_err("update rejected");
+
+    assert!(
+        matches!(error, Error::DapiClientError(ref message) if message.contains("40100")),
+        "{:?}",
+        error
+    );
+}
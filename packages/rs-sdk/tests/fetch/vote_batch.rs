@@ -0,0 +1,157 @@
+use dash_sdk::platform::transition::vote_batch::{VoteBatch, VoteChange};
+use dash_sdk::platform::VoteQuery;
+use dash_sdk::Sdk;
+use dpp::identity::identity_public_key::v0::IdentityPublicKeyV0;
+use dpp::identity::signer::Signer;
+use dpp::identity::{IdentityPublicKey, KeyType, Purpose, SecurityLevel};
+use dpp::platform_value::{BinaryData, Value};
+use dpp::prelude::Identifier;
+use dpp::voting::vote_choices::resource_vote_choice::ResourceVoteChoice;
+use dpp::voting::vote_polls::contested_document_resource_vote_poll::ContestedDocumentResourceVotePoll;
+use dpp::voting::vote_polls::VotePoll;
+use dpp::voting::votes::resource_vote::v0::ResourceVoteV0;
+use dpp::voting::votes::resource_vote::ResourceVote;
+use dpp::voting::votes::Vote;
+use dpp::ProtocolError;
+
+#[derive(Debug)]
+struct NoopSigner;
+
+impl Signer for NoopSigner {
+    fn sign(
+        &self,
+        _identity_public_key: &IdentityPublicKey,
+        _data: &[u8],
+    ) -> Result<BinaryData, ProtocolError> {
+        Ok(BinaryData::new(vec![0; 65]))
+    }
+}
+
+fn voting_key() -> IdentityPublicKey {
+    IdentityPublicKeyV0 {
+        id: 0,
+        purpose: Purpose::VOTING,
+        security_level: SecurityLevel::HIGH,
+        contract_bounds: None,
+        key_type: KeyType::ECDSA_HASH160,
+        read_only: false,
+        data: BinaryData::new(vec![4; 20]),
+        disabled_at: None,
+    }
+    .into()
+}
+
+fn vote_poll(label: &str) -> VotePoll {
+    VotePoll::ContestedDocumentResourceVotePoll(ContestedDocumentResourceVotePoll {
+        contract_id: Identifier::new([1; 32]),
+        document_type_name: "domain".to_string(),
+        index_name: "parentNameAndLabel".to_string(),
+        index_values: vec![
+            Value::Text("dash".to_string()),
+            Value::Text(label.to_string()),
+        ],
+    })
+}
+
+/// Given a vote equal to the current vote, when I check the change, then it's not a change.
+#[test]
+fn test_vote_change_is_change() {
+    let towards = ResourceVoteChoice::TowardsIdentity(Identifier::new([2; 32]));
+    let change = |current| VoteChange {
+        vote_poll: vote_poll("a11ce"),
+        current,
+        new: towards,
+    };
+
+    assert!(change(None).is_change());
+    assert!(change(Some(ResourceVoteChoice::Lock)).is_change());
+    assert!(!change(Some(towards)).is_change());
+}
+
+/// Given a masternode that voted on one of two polls, when I dry-run a batch of votes on both polls,
+/// then I get current votes of the masternode next to the new votes, in the order of the batch.
+#[tokio::test]
+async fn test_vote_batch_dry_run() {
+    let mut sdk = Sdk::new_mock();
+    let pro_tx_hash = Identifier::new([3; 32]);
+    let voted = vote_poll("a11ce");
+    let not_voted = vote_poll("b0b");
+
+    let current_vote = Vote::ResourceVote(ResourceVote::V0(ResourceVoteV0 {
+        vote_poll: voted.clone(),
+        resource_vote_choice: ResourceVoteChoice::Abstain,
+    }));
+    sdk.mock()
+        .expect_fetch(
+            VoteQuery::new(pro_tx_hash, voted.unique_id().unwrap()),
+            Some(current_vote),
+        )
+        .await
+        .unwrap()
+        .expect_fetch(
+            VoteQuery::new(pro_tx_hash, not_voted.unique_id().unwrap()),
+            None::<Vote>,
+        )
+        .await
+        .unwrap();
+
+    let key = voting_key();
+    let batch = VoteBatch::new(&sdk, pro_tx_hash, &key, &NoopSigner)
+        .with_vote(voted.clone(), ResourceVoteChoice::Lock)
+        .with_vote(not_voted.clone(), ResourceVoteChoice::Abstain);
+
+    let changes = batch.dry_run().await.expect("dry run");
+
+    assert_eq!(
+        changes,
+        vec![
+            VoteChange {
+                vote_poll: voted,
+                current: Some(ResourceVoteChoice::Abstain),
+                new: ResourceVoteChoice::Lock,
+            },
+            VoteChange {
+                vote_poll: not_voted,
+                current: None,
+                new: ResourceVoteChoice::Abstain,
+            },
+        ]
+    );
+}
+
+/// Given a masternode that already cast a vote, when I execute a batch with the same vote, then
+/// the vote is reported as skipped with the current vote, and nothing is signed or broadcast.
+#[tokio::test]
+async fn test_vote_batch_skips_unchanged_vote() {
+    let mut sdk = Sdk::new_mock();
+    let pro_tx_hash = Identifier::new([3; 32]);
+    let voted = vote_poll("a11ce");
+
+    let current_vote = Vote::ResourceVote(ResourceVote::V0(ResourceVoteV0 {
+        vote_poll: voted.clone(),
+        resource_vote_choice: ResourceVoteChoice::Abstain,
+    }));
+    // nonce and broadcast requests are not expected, so they would fail the vote
+    sdk.mock()
+        .expect_fetch(
+            VoteQuery::new(pro_tx_hash, voted.unique_id().unwrap()),
+            Some(current_vote.clone()),
+        )
+        .await
+        .unwrap();
+
+    let key = voting_key();
+    let outcomes = VoteBatch::new(&sdk, pro_tx_hash, &key, &NoopSigner)
+        .with_vote(voted.clone(), ResourceVoteChoice::Abstain)
+        .execute()
+        .await
+        .expect("execute batch");
+
+    assert_eq!(outcomes.len(), 1);
+    assert!(outcomes[0].is_skipped());
+    assert_eq!(outcomes[0].vote_poll, voted);
+    assert_eq!(
+        outcomes[0].result.as_ref().expect("skipped vote succeeds"),
+        &current_vote
+    );
+}